@@ -0,0 +1,21 @@
+//! Emits `GIT_SHA` (synth-1167) for `_health` to report alongside
+//! `CARGO_PKG_VERSION` — read at runtime via `option_env!("GIT_SHA")`, so a
+//! build without git on `PATH` or run outside a checkout (e.g. from a
+//! vendored source tarball) just omits it rather than failing the build.
+
+use std::process::Command;
+
+fn main() {
+    let sha = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok());
+
+    if let Some(sha) = sha {
+        println!("cargo:rustc-env=GIT_SHA={}", sha.trim());
+    }
+
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+}
@@ -0,0 +1,1011 @@
+//! Wire types for Sentinel host-function calls, shared by the sidecar host
+//! (`host_functions.rs`) and the WASM guest tools (via `tool_common`).
+//!
+//! Before this crate (synth-1187), each side spelled out the same JSON
+//! field names independently — `host_functions.rs` indexed a
+//! `serde_json::Value` by hand and `tool_common` built its own request
+//! structs, so a rename on one side silently drifted from the other until
+//! it broke at runtime. Defining the `Op` codes, the request/response
+//! structs, and the `host_call` error codes exactly once and importing them
+//! on both sides makes that drift a compile error instead.
+//!
+//! No `wasm`/`wasmtime` dependency here — this crate only knows about serde.
+
+use serde::{Deserialize, Serialize};
+
+/// Operation codes for host function dispatch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum Op {
+    ReadFile = 1,
+    WriteFile = 2,
+    ShellExec = 3,
+    HttpFetch = 4,
+    GetCredential = 5,
+    /// Allocate a transfer id for a chunked upload (synth-1185) — see
+    /// `tool_common::call_host_chunked`.
+    ChunkBegin = 6,
+    /// Append one base64-encoded chunk to a transfer opened with
+    /// `ChunkBegin` (synth-1185).
+    ChunkAppend = 7,
+    /// Finish a chunked upload, handing the assembled buffer to the real
+    /// operation named in the request's `op` field (synth-1185).
+    ChunkCommit = 8,
+    /// Drain one chunk of a spooled download, e.g. the `transfer_id` an
+    /// oversized `ReadFile` response returns (synth-1185) — see
+    /// `tool_common::drain_chunked_transfer`.
+    ChunkFetch = 9,
+    /// Blocks for a bounded number of milliseconds (synth-1193) — the
+    /// backoff step for `tool_common::call_host_with_retry`, so a guest
+    /// waits between attempts without busy-waiting and burning fuel.
+    Sleep = 10,
+    /// Reports a progress update for a long-running tool (synth-1195) — see
+    /// `tool_common::report_progress`. The host assigns ordering and caps
+    /// how many events it retains; see `Response::progress`.
+    Progress = 11,
+    /// Lists a directory's immediate entries (synth-1125), gated on
+    /// `Capability::ListDir`.
+    ListDir = 12,
+    /// Deletes a file (synth-1125), gated on `Capability::DeleteFile`.
+    DeleteFile = 13,
+    /// Reports a path's metadata without reading its content (synth-1125),
+    /// gated on `Capability::StatFile`.
+    StatFile = 14,
+    /// Reads one environment variable from the sidecar process (synth-1125),
+    /// gated on `Capability::ReadEnv`.
+    ReadEnv = 15,
+}
+
+impl Op {
+    /// Name used for `Metrics::record_host_call` (synth-1168) and error
+    /// messages — one spelling per op, shared instead of re-derived at each
+    /// call site.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Op::ReadFile => "read_file",
+            Op::WriteFile => "write_file",
+            Op::ShellExec => "shell_exec",
+            Op::HttpFetch => "http_fetch",
+            Op::GetCredential => "get_credential",
+            Op::ChunkBegin => "chunk_begin",
+            Op::ChunkAppend => "chunk_append",
+            Op::ChunkCommit => "chunk_commit",
+            Op::ChunkFetch => "chunk_fetch",
+            Op::Sleep => "sleep",
+            Op::Progress => "progress",
+            Op::ListDir => "list_dir",
+            Op::DeleteFile => "delete_file",
+            Op::StatFile => "stat_file",
+            Op::ReadEnv => "read_env",
+        }
+    }
+
+    /// Whether repeating this op with the same request is safe on its own,
+    /// without the caller opting in explicitly (synth-1193) — reads and
+    /// credential lookups are, writes and side-effecting ops aren't.
+    /// `HttpFetch` is conservatively treated as non-idempotent since its
+    /// method isn't known at the `Op` level (it could be a POST).
+    pub fn is_idempotent(&self) -> bool {
+        matches!(
+            self,
+            Op::ReadFile | Op::GetCredential | Op::ChunkFetch | Op::ListDir | Op::StatFile | Op::ReadEnv
+        )
+    }
+}
+
+/// Recovers an `Op` from the raw `i32` a guest passes to `host_call`. `Err`
+/// means the guest passed an op code no version of this protocol has ever
+/// defined.
+impl TryFrom<i32> for Op {
+    type Error = ();
+
+    fn try_from(value: i32) -> std::result::Result<Self, Self::Error> {
+        Ok(match value {
+            1 => Op::ReadFile,
+            2 => Op::WriteFile,
+            3 => Op::ShellExec,
+            4 => Op::HttpFetch,
+            5 => Op::GetCredential,
+            6 => Op::ChunkBegin,
+            7 => Op::ChunkAppend,
+            8 => Op::ChunkCommit,
+            9 => Op::ChunkFetch,
+            10 => Op::Sleep,
+            11 => Op::Progress,
+            12 => Op::ListDir,
+            13 => Op::DeleteFile,
+            14 => Op::StatFile,
+            15 => Op::ReadEnv,
+            _ => return Err(()),
+        })
+    }
+}
+
+/// The inverse of `TryFrom<i32> for Op` — recovers the wire code for an
+/// `Op` a caller already has in hand, e.g. `HostTransport::call`'s `op`
+/// argument (synth-1197). Never fails: every `Op` variant has a code.
+impl From<Op> for i32 {
+    fn from(op: Op) -> i32 {
+        op as i32
+    }
+}
+
+/// Codes below this are reserved for the `Op` variants defined above.
+/// Codes at or above it are deployment-specific custom ops (synth-1197),
+/// dispatched through a handler registered with
+/// `HostState::register_custom_op` instead of a hardcoded `Op` variant —
+/// this crate has no idea what they mean, only that `host_call_dispatch`
+/// shouldn't treat them as unknown.
+pub const CUSTOM_OP_RANGE_START: i32 = 1000;
+
+/// `host_call` return codes signalling an error instead of a response
+/// length. Mirrored in `host_call_dispatch`'s doc comment.
+pub mod error_codes {
+    pub const UNKNOWN_OP: i32 = -1;
+    pub const CAPABILITY_DENIED: i32 = -2;
+    pub const OPERATION_ERROR: i32 = -3;
+    pub const BUFFER_IO_ERROR: i32 = -4;
+    /// Response too large for the guest's IO buffer (synth-1186); the
+    /// needed size is written to the buffer as an 8-byte little-endian u64
+    /// in place of a response.
+    pub const RESPONSE_TOO_LARGE: i32 = -5;
+
+    /// True for a bare error code a guest should recognize on its own — one
+    /// of the constants above, or a `<= -1000` structured-payload code
+    /// (synth-1191). Anything else is a code neither side agreed on, which a
+    /// guest should reject explicitly rather than treat as a generic
+    /// transport failure.
+    pub fn is_recognized(code: i32) -> bool {
+        matches!(code, UNKNOWN_OP | CAPABILITY_DENIED | OPERATION_ERROR | BUFFER_IO_ERROR | RESPONSE_TOO_LARGE)
+            || code <= -1000
+    }
+}
+
+/// Error payload the host writes into a guest's IO buffer when a handler
+/// returns `Err` (synth-1188), in place of the bare message text it used to
+/// write there. `code` is a stable, machine-matchable string a tool can
+/// switch on; today every handler failure surfaces as `code:
+/// "operation_error"` since handlers don't yet classify their own errors,
+/// but the shape lets that improve without another wire-format change.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct ErrorPayload {
+    pub code: String,
+    pub message: String,
+}
+
+/// Request/response pairs for each `Op`, used by `host_functions.rs` to
+/// deserialize a request instead of indexing a `serde_json::Value`, and by
+/// `tool_common` (re-exported from there) to build one.
+pub mod messages {
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct ReadFileRequest {
+        pub path: String,
+        /// Forces spooling (synth-1204) — the response always carries a
+        /// `transfer_id` to drain via `Op::ChunkFetch` instead of inlining
+        /// `content`, even for a file well under the inline size limit.
+        /// Lets a caller read a file incrementally via successive
+        /// growing-offset `Op::ChunkFetch` calls instead of pulling the
+        /// whole thing into one IO_BUFFER round trip. Omitted from the
+        /// wire when `false` so an ordinary (non-streaming) request looks
+        /// exactly like it did before this field existed.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        pub stream: bool,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct ReadFileResponse {
+        /// Present when the file fit inline in one IO_BUFFER round trip.
+        /// `None` means the file was spooled — see `transfer_id`.
+        #[serde(default)]
+        pub content: Option<String>,
+        pub bytes: usize,
+        /// Present when the file was too large to inline (synth-1185) —
+        /// pass to `drain_chunked_transfer` to retrieve `content`.
+        #[serde(default)]
+        pub transfer_id: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct WriteFileRequest {
+        pub path: String,
+        pub content: String,
+        /// `"overwrite"` (default, omitted) truncates the file before
+        /// writing; `"append"` appends to it, creating the file if it
+        /// doesn't exist yet (synth-1210).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub mode: Option<String>,
+        /// Create missing parent directories before writing (synth-1210).
+        /// `None` keeps the pre-synth-1210 behavior of always creating them.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub create_dirs: Option<bool>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct WriteFileResponse {
+        pub written: usize,
+        /// The absolute path the host actually wrote to, after resolving
+        /// against the allowed roots (synth-1210).
+        pub path: String,
+        /// Whether a file already existed at `path` before this write
+        /// (synth-1210).
+        pub existed: bool,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct ShellExecRequest {
+        /// Shell command string, run via `sh -c` (or split into argv if the
+        /// host's policy disallows a shell). Mutually exclusive with
+        /// `program` (synth-1211).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub command: Option<String>,
+        /// Argv-mode program name, run directly with no shell involved
+        /// regardless of the host's `allow_sh_c` policy (synth-1211).
+        /// Mutually exclusive with `command`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub program: Option<String>,
+        /// Arguments passed to `program` (synth-1211). Ignored in `command`
+        /// mode.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub args: Vec<String>,
+        /// Working directory override (synth-1211). `None` keeps the host's
+        /// configured default.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub cwd: Option<String>,
+        /// Extra environment variables set on the child, on top of whatever
+        /// the host's `env_passthrough` policy already provides (synth-1211).
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        pub env: HashMap<String, String>,
+        /// Bytes written to the child's stdin before it's asked to exit
+        /// (synth-1211). `None` leaves stdin closed, as before synth-1211.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub stdin: Option<String>,
+        /// Per-request timeout override in milliseconds (synth-1211),
+        /// overriding the host's configured default when set.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub timeout_ms: Option<u64>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct ShellExecResponse {
+        pub stdout: String,
+        pub stderr: String,
+        pub exit_code: i32,
+        pub timed_out: bool,
+        /// Whether stdout or stderr was cut short to stay under
+        /// `max_output_bytes` (synth-1211).
+        pub truncated: bool,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct HttpFetchRequest {
+        pub url: String,
+        #[serde(default = "default_http_method")]
+        pub method: String,
+        #[serde(default)]
+        pub headers: HashMap<String, String>,
+        #[serde(default)]
+        pub body: Option<String>,
+        /// Follow HTTP redirects (synth-1206). `None` defers to the host's
+        /// own default rather than forcing one, same as `method`/`headers`
+        /// default to something reasonable when the caller doesn't care.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub follow_redirects: Option<bool>,
+        /// Per-request timeout override in milliseconds (synth-1206),
+        /// overriding the host's configured default when set.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub timeout_ms: Option<u64>,
+        /// Number of retries on a failed request (synth-1206).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub retries: Option<u32>,
+        /// How the response body should come back — e.g. `"utf8"` (default)
+        /// or `"base64"` for a body that isn't valid UTF-8 (synth-1206).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub response_encoding: Option<String>,
+        /// Write the response body straight to this path instead of
+        /// returning it inline (synth-1213), via the same capability check
+        /// and path validation as `Op::WriteFile`. When set, `HttpFetchResponse::body`
+        /// comes back empty and `saved_path`/`sha256`/`bytes` are populated
+        /// instead.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub save_to: Option<String>,
+    }
+
+    fn default_http_method() -> String {
+        "GET".to_string()
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct HttpFetchResponse {
+        pub status: u16,
+        pub body: String,
+        pub headers: HashMap<String, String>,
+        /// The URL the response actually came from, once redirects (if any)
+        /// were followed (synth-1206).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub final_url: Option<String>,
+        /// How many attempts the host made, including retries (synth-1206).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub attempts: Option<u32>,
+        /// Base64-encoded body, populated instead of `body` when
+        /// `response_encoding: "base64"` was requested (synth-1206).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub body_base64: Option<String>,
+        /// The absolute path the body was written to, when `save_to` was
+        /// given (synth-1213).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub saved_path: Option<String>,
+        /// Hex-encoded sha256 of the saved body (synth-1213).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub sha256: Option<String>,
+        /// Number of bytes written to `saved_path` (synth-1213).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub bytes: Option<u64>,
+    }
+
+    /// Header accompanying a binary-envelope `Op::WriteFile` call (synth-1203)
+    /// — everything `WriteFileRequest` has except `content`, which travels as
+    /// the envelope's raw payload instead of a JSON string.
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct WriteFileBinaryHeader {
+        pub path: String,
+        /// See `WriteFileRequest::mode` (synth-1210).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub mode: Option<String>,
+        /// See `WriteFileRequest::create_dirs` (synth-1210).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub create_dirs: Option<bool>,
+    }
+
+    /// Header accompanying a binary-envelope `Op::HttpFetch` call
+    /// (synth-1203) — everything `HttpFetchRequest` has except `body`, which
+    /// travels as the envelope's raw payload instead of a JSON string.
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct HttpFetchBinaryHeader {
+        pub url: String,
+        #[serde(default = "default_http_method")]
+        pub method: String,
+        #[serde(default)]
+        pub headers: HashMap<String, String>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct GetCredentialRequest {
+        pub name: String,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct GetCredentialResponse {
+        pub name: String,
+        pub value: String,
+    }
+
+    /// `Op::ChunkBegin` takes no fields — a transfer id is minted
+    /// unconditionally (synth-1185).
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct ChunkBeginRequest {}
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct ChunkBeginResponse {
+        pub transfer_id: String,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct ChunkAppendRequest {
+        pub transfer_id: String,
+        pub chunk_b64: String,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct ChunkAppendResponse {
+        pub received_bytes: usize,
+    }
+
+    /// `transfer_id` and `op` are common to every commit; `extra` carries
+    /// whatever fields the named `op`'s own request needs beyond the
+    /// assembled buffer itself (e.g. `write_file`'s `path`) — kept dynamic
+    /// since `ChunkCommit` can delegate to any future op's request shape,
+    /// not just the ones this crate already types.
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct ChunkCommitRequest {
+        pub transfer_id: String,
+        pub op: String,
+        #[serde(flatten)]
+        pub extra: HashMap<String, serde_json::Value>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct ChunkFetchRequest {
+        pub transfer_id: String,
+        pub offset: u64,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct ChunkFetchResponse {
+        pub chunk_b64: String,
+        pub eof: bool,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct SleepRequest {
+        pub millis: u64,
+    }
+
+    /// `Op::Sleep` has nothing to report back beyond "done".
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct SleepResponse {}
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct ProgressRequest {
+        pub message: String,
+        #[serde(default)]
+        pub percent: Option<u8>,
+        /// Free-form structured detail (e.g. `{"bytes_done": ..., "bytes_total": ...}`)
+        /// a caller wants alongside `message`. Opaque to the host beyond
+        /// leak-scanning it like the message.
+        #[serde(default)]
+        pub data: Option<serde_json::Value>,
+    }
+
+    /// `Op::Progress` has nothing to report back beyond "recorded".
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct ProgressResponse {}
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct ListDirRequest {
+        pub path: String,
+    }
+
+    /// One immediate child of a listed directory (synth-1125) — no recursion,
+    /// same as `std::fs::read_dir`.
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct DirEntry {
+        pub name: String,
+        pub is_dir: bool,
+        /// File size in bytes; `0` for a directory entry.
+        pub size: u64,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct ListDirResponse {
+        pub entries: Vec<DirEntry>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct DeleteFileRequest {
+        pub path: String,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct DeleteFileResponse {
+        /// The absolute path the host actually deleted, after resolving
+        /// against the allowed roots.
+        pub path: String,
+        /// Whether a file existed at `path` before this call.
+        pub existed: bool,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct StatFileRequest {
+        pub path: String,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct StatFileResponse {
+        pub path: String,
+        pub exists: bool,
+        pub is_dir: bool,
+        pub size: u64,
+        /// Milliseconds since the Unix epoch, when the filesystem reports one.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub modified_unix_ms: Option<u64>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct ReadEnvRequest {
+        pub name: String,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    pub struct ReadEnvResponse {
+        pub name: String,
+        /// `None` when the variable isn't set — distinct from an empty
+        /// string, which is a real (if unusual) value.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub value: Option<String>,
+    }
+}
+
+/// The host's view of one invocation, injected into the guest as JSON
+/// (synth-1202) rather than as an `Op` request/response pair, since a tool
+/// wants it up front rather than round-tripping a host call just to learn
+/// where it's allowed to touch the filesystem. See
+/// `tool_common::context()` on the guest side and `execute_wasm_sync`'s
+/// `SENTINEL_CONTEXT` env var on the host side.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ExecutionContext {
+    /// Directories `Op::ReadFile`/`Op::WriteFile` will accept a path under —
+    /// the same list `host_functions::validate_path` checks against.
+    pub allowed_paths: Vec<String>,
+    /// One of `allowed_paths`, set aside for a tool's own temporary files —
+    /// just a convention, not enforced separately from `allowed_paths`.
+    pub scratch_dir: String,
+    /// This invocation's request id, the same one the host tags its own
+    /// tracing spans with.
+    pub request_id: String,
+    /// The timeout actually in effect for this invocation, after ceiling
+    /// clamping — see `resolve_request_override`.
+    pub timeout_ms: u64,
+}
+
+/// A binary envelope for IO_BUFFER, alternative to a plain JSON request or
+/// response (synth-1203). Large payloads (a `WriteFile` write, an
+/// `HttpFetch` body) used to travel as a base64 string inside the JSON
+/// body, paying base64's ~1.34x expansion plus two JSON passes just to
+/// smuggle bytes through a text field. An envelope is `MAGIC`, then the
+/// header's length as a little-endian `u32`, then the header JSON, then the
+/// payload bytes verbatim — `host_call_dispatch` and `call_host_binary`
+/// (`tool_common`) are the only things that build/parse one.
+pub mod binary_envelope {
+    /// Prefix marking IO_BUFFER bytes as an envelope rather than plain JSON.
+    /// Distinct from `{`/`[`/`"`, the first byte of any JSON value we ever
+    /// send, so detecting one is a single length + prefix check.
+    pub const MAGIC: [u8; 4] = *b"SNB1";
+
+    /// Serializes `header`, then concatenates `MAGIC`, the header length,
+    /// the header bytes, and `payload` into one buffer ready to write into
+    /// IO_BUFFER.
+    pub fn encode(header: &serde_json::Value, payload: &[u8]) -> Vec<u8> {
+        let header_bytes = serde_json::to_vec(header).expect("serde_json::Value always serializes");
+        let mut out = Vec::with_capacity(MAGIC.len() + 4 + header_bytes.len() + payload.len());
+        out.extend_from_slice(&MAGIC);
+        out.extend_from_slice(&(header_bytes.len() as u32).to_le_bytes());
+        out.extend_from_slice(&header_bytes);
+        out.extend_from_slice(payload);
+        out
+    }
+
+    /// The inverse of [`encode`]. `None` means `bytes` isn't an envelope at
+    /// all (too short, or missing `MAGIC`) — the normal case for every op
+    /// that doesn't use one, so callers should fall back to parsing `bytes`
+    /// as plain JSON rather than treating this as an error. A malformed
+    /// envelope past the `MAGIC` check (e.g. a header length past the end
+    /// of `bytes`) is also `None`, since it's the same "not actually valid"
+    /// outcome from the caller's point of view.
+    pub fn decode(bytes: &[u8]) -> Option<(serde_json::Value, &[u8])> {
+        if bytes.len() < MAGIC.len() + 4 || bytes[..MAGIC.len()] != MAGIC {
+            return None;
+        }
+        let header_start = MAGIC.len() + 4;
+        let header_len = u32::from_le_bytes(bytes[MAGIC.len()..header_start].try_into().ok()?) as usize;
+        let header_end = header_start.checked_add(header_len)?;
+        if header_end > bytes.len() {
+            return None;
+        }
+        let header = serde_json::from_slice(&bytes[header_start..header_end]).ok()?;
+        Some((header, &bytes[header_end..]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::messages::*;
+    use super::*;
+    use std::collections::HashMap;
+
+    fn round_trip<T: serde::Serialize + for<'de> serde::Deserialize<'de> + PartialEq + std::fmt::Debug>(
+        value: T,
+    ) {
+        let bytes = serde_json::to_vec(&value).unwrap();
+        let back: T = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(value, back);
+    }
+
+    #[test]
+    fn op_try_from_round_trips_every_variant() {
+        for op in [
+            Op::ReadFile,
+            Op::WriteFile,
+            Op::ShellExec,
+            Op::HttpFetch,
+            Op::GetCredential,
+            Op::ChunkBegin,
+            Op::ChunkAppend,
+            Op::ChunkCommit,
+            Op::ChunkFetch,
+            Op::Sleep,
+            Op::Progress,
+            Op::ListDir,
+            Op::DeleteFile,
+            Op::StatFile,
+            Op::ReadEnv,
+        ] {
+            let code: i32 = op.into();
+            assert_eq!(code, op as i32);
+            assert_eq!(Op::try_from(code), Ok(op));
+            assert!(code < CUSTOM_OP_RANGE_START, "{op:?}'s code {code} collides with the custom op range");
+        }
+    }
+
+    #[test]
+    fn op_try_from_rejects_unknown_code() {
+        assert_eq!(Op::try_from(0), Err(()));
+        assert_eq!(Op::try_from(16), Err(()));
+    }
+
+    #[test]
+    fn op_try_from_rejects_a_code_in_the_custom_op_range() {
+        // `Op::try_from` only ever recovers a code this crate defines —
+        // codes >= CUSTOM_OP_RANGE_START are dispatched separately, through
+        // `HostState`'s custom op handler table, not through `Op` at all.
+        assert_eq!(Op::try_from(CUSTOM_OP_RANGE_START), Err(()));
+    }
+
+    #[test]
+    fn is_idempotent_matches_read_only_ops() {
+        assert!(Op::ReadFile.is_idempotent());
+        assert!(Op::GetCredential.is_idempotent());
+        assert!(Op::ChunkFetch.is_idempotent());
+        assert!(Op::ListDir.is_idempotent());
+        assert!(Op::StatFile.is_idempotent());
+        assert!(Op::ReadEnv.is_idempotent());
+        assert!(!Op::WriteFile.is_idempotent());
+        assert!(!Op::ShellExec.is_idempotent());
+        assert!(!Op::HttpFetch.is_idempotent());
+        assert!(!Op::Sleep.is_idempotent());
+        assert!(!Op::DeleteFile.is_idempotent());
+    }
+
+    #[test]
+    fn read_file_response_round_trips_inline_and_spooled() {
+        let bytes = serde_json::to_vec(&ReadFileResponse {
+            content: Some("hi".to_string()),
+            bytes: 2,
+            transfer_id: None,
+        })
+        .unwrap();
+        let back: ReadFileResponse = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(back.content.as_deref(), Some("hi"));
+        assert_eq!(back.transfer_id, None);
+
+        let bytes = serde_json::to_vec(&ReadFileResponse {
+            content: None,
+            bytes: 5_000_000,
+            transfer_id: Some("t1".to_string()),
+        })
+        .unwrap();
+        let back: ReadFileResponse = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(back.content, None);
+        assert_eq!(back.transfer_id.as_deref(), Some("t1"));
+    }
+
+    #[test]
+    fn read_file_request_missing_path_is_a_real_error() {
+        let err = serde_json::from_str::<ReadFileRequest>("{}").unwrap_err();
+        assert!(err.to_string().contains("path"));
+    }
+
+    #[test]
+    fn write_file_round_trips() {
+        round_trip(WriteFileRequest {
+            path: "/tmp/x".to_string(),
+            content: "data".to_string(),
+            mode: None,
+            create_dirs: None,
+        });
+        round_trip(WriteFileResponse { written: 4, path: "/tmp/x".to_string(), existed: false });
+    }
+
+    #[test]
+    fn write_file_request_without_mode_or_create_dirs_serializes_identically_to_before_synth_1210() {
+        let request =
+            WriteFileRequest { path: "/tmp/x".to_string(), content: "data".to_string(), mode: None, create_dirs: None };
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value, serde_json::json!({"path": "/tmp/x", "content": "data"}));
+    }
+
+    #[test]
+    fn write_file_request_carries_mode_and_create_dirs_when_given() {
+        round_trip(WriteFileRequest {
+            path: "/tmp/x".to_string(),
+            content: "data".to_string(),
+            mode: Some("append".to_string()),
+            create_dirs: Some(false),
+        });
+    }
+
+    #[test]
+    fn shell_exec_round_trips() {
+        round_trip(ShellExecRequest {
+            command: Some("echo hi".to_string()),
+            program: None,
+            args: Vec::new(),
+            cwd: None,
+            env: HashMap::new(),
+            stdin: None,
+            timeout_ms: None,
+        });
+        round_trip(ShellExecResponse {
+            stdout: "hi\n".to_string(),
+            stderr: String::new(),
+            exit_code: 0,
+            timed_out: false,
+            truncated: false,
+        });
+    }
+
+    #[test]
+    fn shell_exec_request_omits_new_fields_when_default() {
+        let request = ShellExecRequest {
+            command: Some("echo hi".to_string()),
+            program: None,
+            args: Vec::new(),
+            cwd: None,
+            env: HashMap::new(),
+            stdin: None,
+            timeout_ms: None,
+        };
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value, serde_json::json!({"command": "echo hi"}));
+    }
+
+    #[test]
+    fn shell_exec_request_carries_argv_mode_fields_when_given() {
+        round_trip(ShellExecRequest {
+            command: None,
+            program: Some("echo".to_string()),
+            args: vec!["hi".to_string()],
+            cwd: Some("/tmp".to_string()),
+            env: HashMap::from([("FOO".to_string(), "bar".to_string())]),
+            stdin: Some("input".to_string()),
+            timeout_ms: Some(5000),
+        });
+    }
+
+    #[test]
+    fn http_fetch_request_defaults_method_and_headers() {
+        let request: HttpFetchRequest = serde_json::from_str(r#"{"url": "https://example.com"}"#).unwrap();
+        assert_eq!(request.method, "GET");
+        assert!(request.headers.is_empty());
+        assert_eq!(request.body, None);
+        assert_eq!(request.follow_redirects, None);
+        assert_eq!(request.timeout_ms, None);
+        assert_eq!(request.retries, None);
+        assert_eq!(request.response_encoding, None);
+        assert_eq!(request.save_to, None);
+    }
+
+    #[test]
+    fn http_fetch_request_without_the_new_fields_serializes_identically_to_before_synth_1206() {
+        let request = HttpFetchRequest {
+            url: "https://example.com".to_string(),
+            method: "GET".to_string(),
+            headers: HashMap::new(),
+            body: None,
+            follow_redirects: None,
+            timeout_ms: None,
+            retries: None,
+            response_encoding: None,
+            save_to: None,
+        };
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value, serde_json::json!({"url": "https://example.com", "method": "GET", "headers": {}, "body": null}));
+    }
+
+    #[test]
+    fn http_fetch_round_trips() {
+        let mut headers = HashMap::new();
+        headers.insert("accept".to_string(), "application/json".to_string());
+        round_trip(HttpFetchRequest {
+            url: "https://example.com".to_string(),
+            method: "POST".to_string(),
+            headers: headers.clone(),
+            body: Some("{}".to_string()),
+            follow_redirects: Some(false),
+            timeout_ms: Some(5_000),
+            retries: Some(2),
+            response_encoding: Some("base64".to_string()),
+            save_to: Some("/workspace/out.bin".to_string()),
+        });
+        round_trip(HttpFetchResponse {
+            status: 200,
+            body: "ok".to_string(),
+            headers,
+            final_url: Some("https://example.com/".to_string()),
+            attempts: Some(1),
+            body_base64: None,
+            saved_path: None,
+            sha256: None,
+            bytes: None,
+        });
+    }
+
+    #[test]
+    fn http_fetch_request_omits_save_to_when_absent() {
+        let request = HttpFetchRequest {
+            url: "https://example.com".to_string(),
+            method: "GET".to_string(),
+            headers: HashMap::new(),
+            body: None,
+            follow_redirects: None,
+            timeout_ms: None,
+            retries: None,
+            response_encoding: None,
+            save_to: None,
+        };
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value, serde_json::json!({"url": "https://example.com", "method": "GET", "headers": {}, "body": null}));
+    }
+
+    #[test]
+    fn http_fetch_response_carries_saved_path_sha256_and_bytes_when_saved_to_disk() {
+        round_trip(HttpFetchResponse {
+            status: 200,
+            body: String::new(),
+            headers: HashMap::new(),
+            final_url: None,
+            attempts: None,
+            body_base64: None,
+            saved_path: Some("/workspace/out.bin".to_string()),
+            sha256: Some("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b85".to_string()),
+            bytes: Some(4096),
+        });
+    }
+
+    #[test]
+    fn get_credential_round_trips() {
+        round_trip(GetCredentialRequest { name: "api_key".to_string() });
+        round_trip(GetCredentialResponse { name: "api_key".to_string(), value: "secret".to_string() });
+    }
+
+    #[test]
+    fn chunk_begin_request_accepts_empty_object() {
+        let _: ChunkBeginRequest = serde_json::from_str("{}").unwrap();
+        round_trip(ChunkBeginResponse { transfer_id: "t1".to_string() });
+    }
+
+    #[test]
+    fn chunk_append_round_trips() {
+        round_trip(ChunkAppendRequest { transfer_id: "t1".to_string(), chunk_b64: "aGk=".to_string() });
+        round_trip(ChunkAppendResponse { received_bytes: 2 });
+    }
+
+    #[test]
+    fn chunk_commit_request_carries_extra_fields() {
+        let request: ChunkCommitRequest = serde_json::from_str(
+            r#"{"transfer_id": "t1", "op": "write_file", "path": "/tmp/x"}"#,
+        )
+        .unwrap();
+        assert_eq!(request.transfer_id, "t1");
+        assert_eq!(request.op, "write_file");
+        assert_eq!(request.extra.get("path").and_then(|v| v.as_str()), Some("/tmp/x"));
+    }
+
+    #[test]
+    fn chunk_commit_request_missing_op_is_a_real_error() {
+        let err = serde_json::from_str::<ChunkCommitRequest>(r#"{"transfer_id": "t1"}"#).unwrap_err();
+        assert!(err.to_string().contains("op"));
+    }
+
+    #[test]
+    fn chunk_fetch_round_trips() {
+        round_trip(ChunkFetchRequest { transfer_id: "t1".to_string(), offset: 512 });
+        round_trip(ChunkFetchResponse { chunk_b64: "aGk=".to_string(), eof: true });
+    }
+
+    #[test]
+    fn sleep_round_trips() {
+        round_trip(SleepRequest { millis: 50 });
+        round_trip(SleepResponse {});
+    }
+
+    #[test]
+    fn progress_request_defaults_percent_and_data() {
+        let request: ProgressRequest = serde_json::from_str(r#"{"message": "downloading"}"#).unwrap();
+        assert_eq!(request.percent, None);
+        assert_eq!(request.data, None);
+    }
+
+    #[test]
+    fn progress_round_trips() {
+        round_trip(ProgressRequest {
+            message: "downloading".to_string(),
+            percent: Some(42),
+            data: Some(serde_json::json!({"bytes_done": 1024})),
+        });
+        round_trip(ProgressResponse {});
+    }
+
+    #[test]
+    fn list_dir_round_trips() {
+        round_trip(ListDirRequest { path: "/workspace".to_string() });
+        round_trip(ListDirResponse {
+            entries: vec![
+                DirEntry { name: "file.txt".to_string(), is_dir: false, size: 42 },
+                DirEntry { name: "subdir".to_string(), is_dir: true, size: 0 },
+            ],
+        });
+    }
+
+    #[test]
+    fn delete_file_round_trips() {
+        round_trip(DeleteFileRequest { path: "/workspace/x".to_string() });
+        round_trip(DeleteFileResponse { path: "/workspace/x".to_string(), existed: true });
+    }
+
+    #[test]
+    fn stat_file_round_trips() {
+        round_trip(StatFileRequest { path: "/workspace/x".to_string() });
+        round_trip(StatFileResponse {
+            path: "/workspace/x".to_string(),
+            exists: true,
+            is_dir: false,
+            size: 128,
+            modified_unix_ms: Some(1_700_000_000_000),
+        });
+    }
+
+    #[test]
+    fn stat_file_response_omits_modified_unix_ms_when_absent() {
+        let response =
+            StatFileResponse { path: "/workspace/x".to_string(), exists: false, is_dir: false, size: 0, modified_unix_ms: None };
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(value, serde_json::json!({"path": "/workspace/x", "exists": false, "is_dir": false, "size": 0}));
+    }
+
+    #[test]
+    fn read_env_round_trips() {
+        round_trip(ReadEnvRequest { name: "PATH".to_string() });
+        round_trip(ReadEnvResponse { name: "PATH".to_string(), value: Some("/usr/bin".to_string()) });
+    }
+
+    #[test]
+    fn read_env_response_omits_value_when_unset() {
+        let response = ReadEnvResponse { name: "NOT_SET".to_string(), value: None };
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(value, serde_json::json!({"name": "NOT_SET"}));
+    }
+
+    #[test]
+    fn error_payload_round_trips() {
+        round_trip(ErrorPayload {
+            code: "operation_error".to_string(),
+            message: "path '/etc/x' is not under an allowed directory".to_string(),
+        });
+    }
+
+    #[test]
+    fn execution_context_round_trips() {
+        round_trip(ExecutionContext {
+            allowed_paths: vec!["/workspace".to_string()],
+            scratch_dir: "/workspace/.scratch".to_string(),
+            request_id: "req-1".to_string(),
+            timeout_ms: 30_000,
+        });
+    }
+
+    #[test]
+    fn binary_envelope_round_trips_header_and_payload() {
+        let header = serde_json::json!({"path": "/tmp/x"});
+        let payload = b"some raw bytes, not JSON at all \x00\x01\xff";
+        let encoded = binary_envelope::encode(&header, payload);
+
+        let (decoded_header, decoded_payload) = binary_envelope::decode(&encoded).unwrap();
+        assert_eq!(decoded_header, header);
+        assert_eq!(decoded_payload, payload);
+    }
+
+    #[test]
+    fn binary_envelope_decode_rejects_plain_json() {
+        assert!(binary_envelope::decode(br#"{"path": "/tmp/x"}"#).is_none());
+    }
+
+    #[test]
+    fn binary_envelope_decode_rejects_a_header_length_past_the_end() {
+        let mut bytes = binary_envelope::MAGIC.to_vec();
+        bytes.extend_from_slice(&1_000_000u32.to_le_bytes());
+        assert!(binary_envelope::decode(&bytes).is_none());
+    }
+}
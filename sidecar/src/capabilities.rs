@@ -3,6 +3,8 @@
 //! Deny-by-default: each tool execution gets an explicit set of capabilities.
 //! Host functions check capabilities before performing any privileged operation.
 
+use std::collections::{HashMap, HashSet};
+
 /// A capability that can be granted to a tool execution.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Capability {
@@ -15,6 +17,72 @@ pub enum Capability {
     /// declare it ahead of the implementation (BH3-129).
     InvokeTool,
     ShellExec,
+    /// Gates `Op::ListDir` (synth-1125). Kept separate from `ReadFile` so
+    /// operators can grant enumeration without content access.
+    ListDir,
+    /// Gates `Op::DeleteFile` (synth-1125). Kept separate from `WriteFile`
+    /// — deletion is a materially different risk than writing content.
+    DeleteFile,
+    /// Gates `Op::StatFile` (synth-1125, e.g. size/mtime without reading
+    /// content).
+    StatFile,
+    /// Gates `Op::ReadEnv` (synth-1125).
+    ReadEnv,
+    /// Umbrella grant covering every filesystem primitive (synth-1128):
+    /// implies `read_file`, `write_file`, `list_dir`, `delete_file`, and
+    /// `stat_file` (see [`Capability::implies`]). Not itself checked by any
+    /// host function — a tool.toml or request grants this instead of
+    /// enumerating every filesystem primitive by hand.
+    Filesystem,
+    /// Umbrella grant implying [`Self::Filesystem`], `shell_exec`, and
+    /// `read_env` (synth-1128) — the broadest non-scoped grant this sidecar
+    /// understands. Not itself checked by any host function.
+    Admin,
+}
+
+/// Coarse-grained risk tier returned by [`Capability::risk_level`]
+/// (synth-1129). Serializes lowercase (`"low"`, `"medium"`, `"high"`) for
+/// the `_capabilities` response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
+impl serde::Serialize for RiskLevel {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Serializes to (and deserializes from) the same string form as
+/// [`Capability::as_str`]/[`Capability::from_str`], so the wire
+/// representation matches what `tool.toml` and request JSON already use.
+/// Deserialization is strict: an unknown name is a hard error rather than a
+/// silently-dropped capability (synth-1120, consistent with synth-1119).
+impl serde::Serialize for Capability {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Capability {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Capability::from_str(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown capability '{s}'")))
+    }
 }
 
 impl Capability {
@@ -27,6 +95,12 @@ impl Capability {
             "use_credential" => Some(Self::UseCredential),
             "invoke_tool" => Some(Self::InvokeTool),
             "shell_exec" => Some(Self::ShellExec),
+            "list_dir" => Some(Self::ListDir),
+            "delete_file" => Some(Self::DeleteFile),
+            "stat_file" => Some(Self::StatFile),
+            "read_env" => Some(Self::ReadEnv),
+            "filesystem" => Some(Self::Filesystem),
+            "admin" => Some(Self::Admin),
             _ => None,
         }
     }
@@ -40,14 +114,279 @@ impl Capability {
             Self::UseCredential => "use_credential",
             Self::InvokeTool => "invoke_tool",
             Self::ShellExec => "shell_exec",
+            Self::ListDir => "list_dir",
+            Self::DeleteFile => "delete_file",
+            Self::StatFile => "stat_file",
+            Self::ReadEnv => "read_env",
+            Self::Filesystem => "filesystem",
+            Self::Admin => "admin",
+        }
+    }
+
+    /// Capabilities that a grant of `self` also implies, one level deep
+    /// (synth-1128) — e.g. `filesystem` implies every filesystem primitive,
+    /// and `admin` implies `filesystem` (and, transitively through it, the
+    /// same primitives) plus `shell_exec`/`read_env`. Expanded transitively,
+    /// with cycle protection, by [`CapabilitySet::grant_scoped`] so a
+    /// tool.toml or request can grant the umbrella capability instead of
+    /// enumerating every primitive it covers. Most capabilities imply
+    /// nothing.
+    pub fn implies(&self) -> &'static [Capability] {
+        match self {
+            Self::Filesystem => {
+                &[Self::ReadFile, Self::WriteFile, Self::ListDir, Self::DeleteFile, Self::StatFile]
+            }
+            Self::Admin => &[Self::Filesystem, Self::ShellExec, Self::ReadEnv],
+            _ => &[],
+        }
+    }
+
+    /// Human-readable, one-sentence explanation of what granting this
+    /// capability allows (synth-1129) — rendered on a consent screen
+    /// alongside [`Self::risk_level`] via the `_capabilities` request, so
+    /// UIs stay in sync with the binary instead of hardcoding this text.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::ReadFile => "Read files under the workspace or an explicitly granted path.",
+            Self::WriteFile => "Create or overwrite files under the workspace or an explicitly granted path.",
+            Self::HttpRequest => "Make outbound HTTP requests to an explicitly granted host.",
+            Self::UseCredential => "Read the value of a named credential passed with the request.",
+            Self::InvokeTool => "Invoke another registered tool from within this tool (not yet implemented).",
+            Self::ShellExec => "Run arbitrary shell commands — dangerous, equivalent to full host access via the shell.",
+            Self::ListDir => "List directory contents under the workspace or an explicitly granted path.",
+            Self::DeleteFile => "Delete files under the workspace or an explicitly granted path.",
+            Self::StatFile => "Read file metadata (size, modified time) without reading its content.",
+            Self::ReadEnv => "Read environment variables from the sidecar process.",
+            Self::Filesystem => "Read, write, list, stat, and delete files — implies every filesystem primitive.",
+            Self::Admin => "Unrestricted access — implies every filesystem primitive plus shell_exec and read_env.",
+        }
+    }
+
+    /// Coarse-grained risk tier for a consent screen (synth-1129): `Low` for
+    /// read-only/metadata operations, `Medium` for anything that mutates
+    /// state or reaches the network, `High` for arbitrary code execution or
+    /// umbrella grants that imply it.
+    pub fn risk_level(&self) -> RiskLevel {
+        match self {
+            Self::ReadFile | Self::ListDir | Self::StatFile | Self::ReadEnv => RiskLevel::Low,
+            Self::WriteFile
+            | Self::HttpRequest
+            | Self::UseCredential
+            | Self::InvokeTool
+            | Self::DeleteFile => RiskLevel::Medium,
+            Self::ShellExec | Self::Filesystem | Self::Admin => RiskLevel::High,
+        }
+    }
+
+    /// Every known capability name, in declaration order. Used to build a
+    /// permissive default (e.g. [`crate::config::SidecarConfig::default_capability_ceiling`])
+    /// without hardcoding the name list a second time.
+    pub fn all_names() -> &'static [&'static str] {
+        &[
+            "read_file",
+            "write_file",
+            "http_request",
+            "use_credential",
+            "invoke_tool",
+            "shell_exec",
+            "list_dir",
+            "delete_file",
+            "stat_file",
+            "read_env",
+            "filesystem",
+            "admin",
+        ]
+    }
+
+    /// Every known capability, in declaration order — the enum-typed
+    /// counterpart to [`Self::all_names`], used by the exhaustive
+    /// from_str/as_str round-trip test below. Kept as a plain literal (not
+    /// derived) so it must be updated by hand alongside a new variant.
+    #[cfg(test)]
+    fn all() -> &'static [Capability] {
+        &[
+            Self::ReadFile,
+            Self::WriteFile,
+            Self::HttpRequest,
+            Self::UseCredential,
+            Self::InvokeTool,
+            Self::ShellExec,
+            Self::ListDir,
+            Self::DeleteFile,
+            Self::StatFile,
+            Self::ReadEnv,
+            Self::Filesystem,
+            Self::Admin,
+        ]
+    }
+}
+
+/// A capability grant, optionally narrowed to a single path or host
+/// (synth-1118), e.g. `"read_file:/workspace/project-a"` or
+/// `"http_request:api.github.com"`, and/or budgeted to a maximum number of
+/// uses (synth-1123), e.g. `"use_credential#1"` or
+/// `"read_file:/workspace/project-a#5"`. `scope: None` is an unscoped grant
+/// and keeps the pre-synth-1118 meaning: unrestricted within the existing
+/// allowlists. `max_uses: None` is an unlimited grant, the pre-synth-1123
+/// meaning.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ScopedCapability {
+    pub capability: Capability,
+    pub scope: Option<String>,
+    pub max_uses: Option<u32>,
+}
+
+/// Same wire form as [`ScopedCapability::from_str`]: `"name"`, `"name:scope"`,
+/// `"name#max_uses"`, or `"name:scope#max_uses"`. Deserialization is strict,
+/// same as [`Capability`].
+impl serde::Serialize for ScopedCapability {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut rendered = match &self.scope {
+            Some(scope) => format!("{}:{}", self.capability.as_str(), scope),
+            None => self.capability.as_str().to_string(),
+        };
+        if let Some(max_uses) = self.max_uses {
+            rendered.push('#');
+            rendered.push_str(&max_uses.to_string());
         }
+        serializer.serialize_str(&rendered)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for ScopedCapability {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        ScopedCapability::from_str(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown capability '{s}'")))
     }
 }
 
+impl ScopedCapability {
+    /// Parse `"name"`, `"name:scope"`, `"name#max_uses"`, or
+    /// `"name:scope#max_uses"`. The usage budget, if present, is taken from
+    /// everything after the *last* `#` (so a scope value can't accidentally
+    /// be mistaken for one); the scope is everything between the first `:`
+    /// and that point, so host/path values containing colons (e.g.
+    /// `[::1]:8080`) survive intact. A `#` suffix that isn't a valid
+    /// non-negative integer makes the whole grant fail to parse.
+    pub fn from_str(s: &str) -> Option<Self> {
+        let (base, max_uses) = match s.rsplit_once('#') {
+            Some((base, count)) => (base, Some(count.parse::<u32>().ok()?)),
+            None => (s, None),
+        };
+        let (capability, scope) = match base.split_once(':') {
+            Some((name, scope)) => (Capability::from_str(name)?, Some(scope.to_string())),
+            None => (Capability::from_str(base)?, None),
+        };
+        Some(Self { capability, scope, max_uses })
+    }
+
+    /// Does this grant cover `resource` (a concrete path or host)? An
+    /// unscoped grant covers anything; a scoped grant only covers `resource`
+    /// itself or, for paths, anything under it — this only ever narrows the
+    /// existing allowlist checks, never widens them.
+    fn covers(&self, resource: &str) -> bool {
+        match &self.scope {
+            None => true,
+            Some(scope) => {
+                resource == scope.as_str()
+                    || resource.starts_with(&format!("{}/", scope.trim_end_matches('/')))
+            }
+        }
+    }
+}
+
+/// Error returned by [`CapabilitySet::from_strings_strict`] when one or more
+/// capability names fail to parse. Carries every offending name, not just
+/// the first, so the caller can report a complete diagnostic in one pass —
+/// same shape as `prepare_credential_values`'s excluded-name reporting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownCapability {
+    pub names: Vec<String>,
+}
+
+impl std::fmt::Display for UnknownCapability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown capability name(s): {}", self.names.join(", "))
+    }
+}
+
+impl std::error::Error for UnknownCapability {}
+
 /// A set of capabilities granted for a single tool execution.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct CapabilitySet {
-    caps: std::collections::HashSet<Capability>,
+    caps: Vec<ScopedCapability>,
+    /// Explicitly denied capabilities (synth-1127), overriding any grant in
+    /// `caps` — deny always wins over grant, checked by [`Self::has`] and
+    /// [`Self::has_scoped`]. Not part of the wire form: denials come from
+    /// `Request.denied_capabilities`, a separate field from the grant list.
+    denied: HashSet<Capability>,
+}
+
+/// Two sets are equal if they grant the same scoped capabilities (order and
+/// duplicates ignored, like a true set) and deny the same capabilities
+/// (synth-1131).
+impl PartialEq for CapabilitySet {
+    fn eq(&self, other: &Self) -> bool {
+        self.caps_set() == other.caps_set() && self.denied == other.denied
+    }
+}
+
+/// Serializes as a plain list of `ScopedCapability` strings.
+impl serde::Serialize for CapabilitySet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.caps.serialize(serializer)
+    }
+}
+
+/// Deserializes from a list of capability strings, same shape as the
+/// pre-synth-1120 `Vec<String>` wire format. Strict, like
+/// [`CapabilitySet::from_strings_strict`]: an unknown name fails the whole
+/// deserialization (and, transitively, the enclosing `Request`), rather than
+/// being dropped and surfacing later as a confusing capability-denied error.
+impl<'de> serde::Deserialize<'de> for CapabilitySet {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let caps = Vec::<ScopedCapability>::deserialize(deserializer)?;
+        Ok(caps.into_iter().collect())
+    }
+}
+
+impl FromIterator<ScopedCapability> for CapabilitySet {
+    fn from_iter<I: IntoIterator<Item = ScopedCapability>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for scoped in iter {
+            set.grant_scoped(scoped);
+        }
+        set
+    }
+}
+
+impl IntoIterator for CapabilitySet {
+    type Item = ScopedCapability;
+    type IntoIter = std::vec::IntoIter<ScopedCapability>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.caps.into_iter()
+    }
+}
+
+/// Renders a sorted, comma-separated list (each entry `name` or
+/// `name:scope`), so the output is stable regardless of grant order —
+/// suitable for logs and diagnostics.
+impl std::fmt::Display for CapabilitySet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut rendered: Vec<String> = self
+            .caps
+            .iter()
+            .map(|s| match &s.scope {
+                Some(scope) => format!("{}:{}", s.capability.as_str(), scope),
+                None => s.capability.as_str().to_string(),
+            })
+            .collect();
+        rendered.sort();
+        write!(f, "{}", rendered.join(", "))
+    }
 }
 
 impl CapabilitySet {
@@ -55,12 +394,13 @@ impl CapabilitySet {
         Self::default()
     }
 
-    /// Build a CapabilitySet from string names. Unknown names are logged and skipped.
+    /// Build a CapabilitySet from string names, each optionally suffixed
+    /// with `:scope` (synth-1118). Unknown names are logged and skipped.
     pub fn from_strings(names: &[String]) -> Self {
         let mut set = Self::new();
         for name in names {
-            if let Some(cap) = Capability::from_str(name) {
-                set.grant(cap);
+            if let Some(scoped) = ScopedCapability::from_str(name) {
+                set.grant_scoped(scoped);
             } else {
                 eprintln!("sidecar: warning: unknown capability '{}', skipping", name);
             }
@@ -68,24 +408,314 @@ impl CapabilitySet {
         set
     }
 
+    /// Like [`Self::from_strings`], but rejects unknown capability names
+    /// instead of silently dropping them (synth-1119) — a typo like
+    /// `"shell_execute"` should fail loudly at the request/tool.toml
+    /// boundary, not silently grant nothing and surface later as a
+    /// confusing "capability denied" from deep inside a host function.
+    /// Returns the built set alongside the names that parsed successfully.
+    pub fn from_strings_strict(names: &[String]) -> Result<(Self, Vec<String>), UnknownCapability> {
+        let mut set = Self::new();
+        let mut granted_names = Vec::new();
+        let mut unknown = Vec::new();
+        for name in names {
+            match ScopedCapability::from_str(name) {
+                Some(scoped) => {
+                    granted_names.push(name.clone());
+                    set.grant_scoped(scoped);
+                }
+                None => unknown.push(name.clone()),
+            }
+        }
+        if !unknown.is_empty() {
+            return Err(UnknownCapability { names: unknown });
+        }
+        Ok((set, granted_names))
+    }
+
+    /// Like [`Self::from_strings_strict`], but first expands any `@profile`
+    /// references against `profiles` (synth-1121), e.g. `@readonly` becomes
+    /// whatever capability strings `profiles["readonly"]` lists. A profile
+    /// member is not itself allowed to be a `@profile` reference — profiles
+    /// bundle concrete capabilities only, so nesting can't recurse. An
+    /// unknown profile name is reported the same way as an unknown
+    /// capability name.
+    pub fn from_strings_strict_with_profiles(
+        names: &[String],
+        profiles: &HashMap<String, Vec<String>>,
+    ) -> Result<(Self, Vec<String>), UnknownCapability> {
+        let mut expanded = Vec::new();
+        let mut unknown = Vec::new();
+        for name in names {
+            match name.strip_prefix('@') {
+                Some(profile_name) => match profiles.get(profile_name) {
+                    Some(members) => {
+                        for member in members {
+                            if member.starts_with('@') {
+                                unknown.push(format!(
+                                    "{name} (profile '{profile_name}' has nested reference '{member}', which is not allowed)"
+                                ));
+                            } else {
+                                expanded.push(member.clone());
+                            }
+                        }
+                    }
+                    None => unknown.push(name.clone()),
+                },
+                None => expanded.push(name.clone()),
+            }
+        }
+        if !unknown.is_empty() {
+            return Err(UnknownCapability { names: unknown });
+        }
+        Self::from_strings_strict(&expanded)
+    }
+
+    /// Grant an unscoped capability — unrestricted within the existing
+    /// allowlists, matching the pre-synth-1118 meaning of `grant`.
     pub fn grant(&mut self, cap: Capability) {
-        self.caps.insert(cap);
+        self.grant_scoped(ScopedCapability { capability: cap, scope: None, max_uses: None });
+    }
+
+    /// Grant `scoped`, then transitively expand any capabilities it implies
+    /// (synth-1128, see [`Capability::implies`]), inheriting the same scope
+    /// and usage budget as the top-level grant. Cycle-protected: a
+    /// capability already expanded once in this call is never re-expanded,
+    /// so a (currently nonexistent, but defensively handled) implication
+    /// cycle can't recurse forever.
+    pub fn grant_scoped(&mut self, scoped: ScopedCapability) {
+        self.grant_scoped_expanding(scoped, &mut HashSet::new());
+    }
+
+    fn grant_scoped_expanding(&mut self, scoped: ScopedCapability, seen: &mut HashSet<Capability>) {
+        if !seen.insert(scoped.capability.clone()) {
+            return;
+        }
+        let implied = scoped.capability.implies();
+        self.caps.push(scoped.clone());
+        for cap in implied {
+            self.grant_scoped_expanding(
+                ScopedCapability {
+                    capability: cap.clone(),
+                    scope: scoped.scope.clone(),
+                    max_uses: scoped.max_uses,
+                },
+                seen,
+            );
+        }
     }
 
+    /// Is `cap` granted at all, regardless of scope? Scope narrowing for a
+    /// concrete resource is [`Self::has_scoped`]. An explicit denial
+    /// (synth-1127) always wins over a grant.
     pub fn has(&self, cap: &Capability) -> bool {
-        self.caps.contains(cap)
+        !self.denied.contains(cap) && self.caps.iter().any(|s| &s.capability == cap)
+    }
+
+    /// Is `cap` granted for `resource` (a concrete, already-resolved path or
+    /// host)? An unscoped grant covers any resource; a scoped grant only
+    /// covers matches to [`ScopedCapability::covers`]. Host handlers should
+    /// use this instead of [`Self::has`] wherever a concrete path/host is
+    /// available, so scoped grants actually narrow access. An explicit
+    /// denial (synth-1127) always wins over a grant.
+    pub fn has_scoped(&self, cap: &Capability, resource: &str) -> bool {
+        !self.denied.contains(cap)
+            && self.caps.iter().any(|s| &s.capability == cap && s.covers(resource))
+    }
+
+    /// Explicitly deny `cap` (synth-1127), overriding any current or future
+    /// grant of it — [`Self::has`] and [`Self::has_scoped`] return false for
+    /// a denied capability regardless of what's in the grant list. Used to
+    /// run a normally-privileged tool in a degraded mode without editing its
+    /// grant list. Mirrors [`Self::grant_scoped`]: denying an umbrella
+    /// capability (e.g. `admin`) also transitively denies everything it
+    /// implies (`shell_exec`, `read_file`, ...), so `capabilities: ["admin"],
+    /// denied_capabilities: ["admin"]` actually blocks the primitives admin
+    /// expanded into at grant time, not just the literal `admin` entry.
+    pub fn deny(&mut self, cap: Capability) {
+        self.deny_expanding(cap, &mut HashSet::new());
+    }
+
+    fn deny_expanding(&mut self, cap: Capability, seen: &mut HashSet<Capability>) {
+        if !seen.insert(cap.clone()) {
+            return;
+        }
+        let implied = cap.implies();
+        self.denied.insert(cap);
+        for implied_cap in implied {
+            self.deny_expanding(implied_cap.clone(), seen);
+        }
+    }
+
+    /// Is `cap` explicitly denied?
+    pub fn is_denied(&self, cap: &Capability) -> bool {
+        self.denied.contains(cap)
+    }
+
+    /// Which of `required` are explicitly denied (synth-1127), as opposed to
+    /// merely not granted — lets a caller give a more specific error than
+    /// [`Self::missing_from`] alone would.
+    pub fn denied_from(&self, required: &[Capability]) -> Vec<Capability> {
+        required.iter().filter(|cap| self.is_denied(cap)).cloned().collect()
+    }
+
+    /// The scoped grant that covers `cap` for `resource` (or, if `resource`
+    /// is `None`, any grant of `cap` regardless of scope). Used by
+    /// [`crate::host_functions::HostState`] to look up a grant's usage
+    /// budget when metering consumption (synth-1123).
+    pub fn matching(&self, cap: &Capability, resource: Option<&str>) -> Option<&ScopedCapability> {
+        self.caps
+            .iter()
+            .find(|s| &s.capability == cap && resource.map_or(true, |r| s.covers(r)))
     }
 
     /// Check that all required capabilities are granted.
     pub fn requires_all(&self, required: &[Capability]) -> bool {
         required.iter().all(|cap| self.has(cap))
     }
+
+    /// Which of `required` are not granted, in the order given (synth-1122).
+    /// Lets a caller report every missing capability in one response instead
+    /// of one round trip per capability.
+    pub fn missing_from(&self, required: &[Capability]) -> Vec<Capability> {
+        required.iter().filter(|cap| !self.has(cap)).cloned().collect()
+    }
+
+    /// All granted capabilities, discarding scope. Used where only the
+    /// capability *names* matter, e.g. a tool.toml's required-capability
+    /// list, which declares bare capabilities rather than scoped grants.
+    pub fn capabilities(&self) -> Vec<Capability> {
+        self.caps.iter().map(|s| s.capability.clone()).collect()
+    }
+
+    /// Borrowing iterator over the granted scoped capabilities.
+    pub fn iter(&self) -> impl Iterator<Item = &ScopedCapability> {
+        self.caps.iter()
+    }
+
+    /// True if no capabilities are granted.
+    pub fn is_empty(&self) -> bool {
+        self.caps.is_empty()
+    }
+
+    /// Deduplicated view of the granted scoped capabilities, used to give
+    /// [`Self`]'s set-algebra operations and [`PartialEq`] true set
+    /// semantics regardless of grant order or repeated grants.
+    fn caps_set(&self) -> HashSet<ScopedCapability> {
+        self.caps.iter().cloned().collect()
+    }
+
+    /// Number of distinct granted scoped capabilities (synth-1131) —
+    /// repeated grants of the same `(capability, scope, max_uses)` count
+    /// once.
+    pub fn len(&self) -> usize {
+        self.caps_set().len()
+    }
+
+    /// Grants present in either `self` or `other` (synth-1131). Denials are
+    /// the union of both sides' — a denial anywhere in the combination still
+    /// wins, consistent with [`Self::has`] always checking `denied` first.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut caps = self.caps.clone();
+        caps.extend(other.caps.iter().cloned());
+        Self { caps, denied: self.denied.union(&other.denied).cloned().collect() }
+    }
+
+    /// Grants present in both `self` and `other` (synth-1131). Denials are
+    /// still the union of both sides' — narrowing the grant set never
+    /// narrows a denial back into a grant.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let other_caps = other.caps_set();
+        let caps = self.caps.iter().filter(|c| other_caps.contains(*c)).cloned().collect();
+        Self { caps, denied: self.denied.union(&other.denied).cloned().collect() }
+    }
+
+    /// Grants present in `self` but not in `other` (synth-1131). Keeps only
+    /// `self`'s own denials — `other`'s denials say nothing about what
+    /// `self` disallows.
+    pub fn difference(&self, other: &Self) -> Self {
+        let other_caps = other.caps_set();
+        let caps = self.caps.iter().filter(|c| !other_caps.contains(*c)).cloned().collect();
+        Self { caps, denied: self.denied.clone() }
+    }
+
+    /// True if every scoped capability granted by `self` is also granted by
+    /// `other` (synth-1131). Denials are not considered — this is purely a
+    /// grant-set containment check, e.g. for verifying a narrowed allowlist
+    /// didn't accidentally widen anything.
+    pub fn is_subset_of(&self, other: &Self) -> bool {
+        self.caps_set().is_subset(&other.caps_set())
+    }
+}
+
+/// Parse a connection's maximum capability set (synth-1124) from raw names,
+/// e.g. `["read_file", "http_request"]`. Unlike [`CapabilitySet::from_strings_strict`],
+/// this is intentionally lenient about unknown names — a ceiling exists to
+/// make a connection *more* restrictive, so a typo should not tear down the
+/// connection; it just never appears in the allowed set.
+pub fn ceiling_from_names(names: &[String]) -> std::collections::HashSet<Capability> {
+    names.iter().filter_map(|n| Capability::from_str(n)).collect()
+}
+
+/// Splits raw capability grant strings (as seen on `Request.capabilities`)
+/// into those whose base capability is within `ceiling` and those that
+/// exceed it (synth-1124). A name that fails to parse as a
+/// [`ScopedCapability`] (e.g. an `@profile` reference) is left in `allowed`
+/// here — profile expansion and strict validation happen later in
+/// [`crate::sandbox::SandboxEngine::execute`], and the ceiling only ever
+/// narrows concrete grants, never blocks something it can't yet interpret.
+pub fn partition_by_ceiling(
+    names: &[String],
+    ceiling: &std::collections::HashSet<Capability>,
+) -> (Vec<String>, Vec<String>) {
+    let mut allowed = Vec::new();
+    let mut stripped = Vec::new();
+    for name in names {
+        match ScopedCapability::from_str(name) {
+            Some(scoped) if !ceiling.contains(&scoped.capability) => stripped.push(name.clone()),
+            _ => allowed.push(name.clone()),
+        }
+    }
+    (allowed, stripped)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_capability_from_str_as_str_round_trip_over_every_variant() {
+        // synth-1125: catches a variant added to `Capability::all()` without
+        // a matching `from_str`/`as_str` arm, or an `all_names()` entry that
+        // has drifted out of sync with the enum.
+        assert_eq!(Capability::all().len(), Capability::all_names().len());
+        for cap in Capability::all() {
+            assert_eq!(Capability::from_str(cap.as_str()).as_ref(), Some(cap));
+            assert!(Capability::all_names().contains(&cap.as_str()));
+        }
+    }
+
+    #[test]
+    fn test_every_capability_has_a_nonempty_description() {
+        for cap in Capability::all() {
+            assert!(!cap.description().is_empty(), "{cap:?} has an empty description");
+        }
+    }
+
+    #[test]
+    fn test_risk_level_serializes_lowercase() {
+        assert_eq!(serde_json::to_string(&RiskLevel::Low).unwrap(), "\"low\"");
+        assert_eq!(serde_json::to_string(&RiskLevel::Medium).unwrap(), "\"medium\"");
+        assert_eq!(serde_json::to_string(&RiskLevel::High).unwrap(), "\"high\"");
+    }
+
+    #[test]
+    fn test_shell_exec_and_admin_are_high_risk() {
+        assert_eq!(Capability::ShellExec.risk_level(), RiskLevel::High);
+        assert_eq!(Capability::Admin.risk_level(), RiskLevel::High);
+        assert_eq!(Capability::ReadFile.risk_level(), RiskLevel::Low);
+    }
+
     #[test]
     fn test_from_str() {
         assert_eq!(Capability::from_str("read_file"), Some(Capability::ReadFile));
@@ -111,4 +741,518 @@ mod tests {
         assert!(set.requires_all(&[Capability::ReadFile, Capability::WriteFile]));
         assert!(!set.requires_all(&[Capability::ReadFile, Capability::ShellExec]));
     }
+
+    #[test]
+    fn test_scoped_capability_parses_name_and_scope() {
+        let scoped = ScopedCapability::from_str("read_file:/workspace/project-a").unwrap();
+        assert_eq!(scoped.capability, Capability::ReadFile);
+        assert_eq!(scoped.scope.as_deref(), Some("/workspace/project-a"));
+    }
+
+    #[test]
+    fn test_scoped_capability_unscoped_name_has_no_scope() {
+        let scoped = ScopedCapability::from_str("read_file").unwrap();
+        assert_eq!(scoped.scope, None);
+    }
+
+    #[test]
+    fn test_scoped_capability_unknown_name_is_none() {
+        assert!(ScopedCapability::from_str("bogus:/tmp").is_none());
+    }
+
+    #[test]
+    fn test_has_scoped_matches_exact_and_nested_paths() {
+        let set = CapabilitySet::from_strings(&["read_file:/workspace/project-a".into()]);
+        assert!(set.has_scoped(&Capability::ReadFile, "/workspace/project-a"));
+        assert!(set.has_scoped(&Capability::ReadFile, "/workspace/project-a/src/main.rs"));
+        assert!(!set.has_scoped(&Capability::ReadFile, "/workspace/project-b"));
+        // Prevent a "/workspace/project-a-evil" sibling from matching, same
+        // boundary rule as validate_path's allowlist check.
+        assert!(!set.has_scoped(&Capability::ReadFile, "/workspace/project-a-evil"));
+    }
+
+    #[test]
+    fn test_has_scoped_matches_exact_host() {
+        let set = CapabilitySet::from_strings(&["http_request:api.github.com".into()]);
+        assert!(set.has_scoped(&Capability::HttpRequest, "api.github.com"));
+        assert!(!set.has_scoped(&Capability::HttpRequest, "evil.example.com"));
+    }
+
+    #[test]
+    fn test_has_scoped_unscoped_grant_covers_any_resource() {
+        let set = CapabilitySet::from_strings(&["read_file".into()]);
+        assert!(set.has_scoped(&Capability::ReadFile, "/anything/at/all"));
+    }
+
+    #[test]
+    fn test_has_scoped_denies_when_not_granted() {
+        let set = CapabilitySet::new();
+        assert!(!set.has_scoped(&Capability::ReadFile, "/workspace"));
+    }
+
+    #[test]
+    fn test_from_strings_strict_rejects_typo() {
+        let err = CapabilitySet::from_strings_strict(&["shell_execute".to_string()]).unwrap_err();
+        assert_eq!(err.names, vec!["shell_execute".to_string()]);
+    }
+
+    #[test]
+    fn test_from_strings_strict_collects_every_unknown_name() {
+        let err = CapabilitySet::from_strings_strict(&[
+            "read_file".to_string(),
+            "shell_execute".to_string(),
+            "http_reqeust".to_string(),
+        ])
+        .unwrap_err();
+        assert_eq!(err.names, vec!["shell_execute".to_string(), "http_reqeust".to_string()]);
+    }
+
+    #[test]
+    fn test_from_strings_strict_accepts_valid_and_scoped_names() {
+        let (set, granted) = CapabilitySet::from_strings_strict(&[
+            "read_file".to_string(),
+            "http_request:api.github.com".to_string(),
+        ])
+        .unwrap();
+        assert!(set.has(&Capability::ReadFile));
+        assert!(set.has_scoped(&Capability::HttpRequest, "api.github.com"));
+        assert_eq!(granted, vec!["read_file".to_string(), "http_request:api.github.com".to_string()]);
+    }
+
+    #[test]
+    fn test_missing_from_reports_every_ungranted_capability() {
+        let set = CapabilitySet::from_strings(&["read_file".into()]);
+        assert_eq!(
+            set.missing_from(&[Capability::ReadFile, Capability::WriteFile, Capability::HttpRequest]),
+            vec![Capability::WriteFile, Capability::HttpRequest]
+        );
+    }
+
+    #[test]
+    fn test_missing_from_empty_when_one_missing() {
+        let set = CapabilitySet::from_strings(&["read_file".into()]);
+        assert_eq!(
+            set.missing_from(&[Capability::ReadFile, Capability::WriteFile]),
+            vec![Capability::WriteFile]
+        );
+    }
+
+    #[test]
+    fn test_missing_from_empty_when_none_missing() {
+        let set = CapabilitySet::from_strings(&["read_file".into(), "write_file".into()]);
+        assert!(set.missing_from(&[Capability::ReadFile, Capability::WriteFile]).is_empty());
+    }
+
+    #[test]
+    fn test_from_strings_strict_with_profiles_expands_members() {
+        let mut profiles = HashMap::new();
+        profiles.insert("readonly".to_string(), vec!["read_file".to_string()]);
+        profiles.insert(
+            "web".to_string(),
+            vec!["http_request".to_string(), "use_credential".to_string()],
+        );
+
+        let (set, _) = CapabilitySet::from_strings_strict_with_profiles(
+            &["@readonly".to_string(), "@web".to_string()],
+            &profiles,
+        )
+        .unwrap();
+        assert!(set.has(&Capability::ReadFile));
+        assert!(set.has(&Capability::HttpRequest));
+        assert!(set.has(&Capability::UseCredential));
+    }
+
+    #[test]
+    fn test_from_strings_strict_with_profiles_mixes_profile_and_bare_names() {
+        let mut profiles = HashMap::new();
+        profiles.insert("readonly".to_string(), vec!["read_file".to_string()]);
+
+        let (set, _) = CapabilitySet::from_strings_strict_with_profiles(
+            &["@readonly".to_string(), "shell_exec".to_string()],
+            &profiles,
+        )
+        .unwrap();
+        assert!(set.has(&Capability::ReadFile));
+        assert!(set.has(&Capability::ShellExec));
+    }
+
+    #[test]
+    fn test_from_strings_strict_with_profiles_rejects_unknown_profile() {
+        let profiles = HashMap::new();
+        let err = CapabilitySet::from_strings_strict_with_profiles(
+            &["@nonexistent".to_string()],
+            &profiles,
+        )
+        .unwrap_err();
+        assert_eq!(err.names, vec!["@nonexistent".to_string()]);
+    }
+
+    #[test]
+    fn test_from_strings_strict_with_profiles_rejects_nested_profile_reference() {
+        let mut profiles = HashMap::new();
+        profiles.insert("outer".to_string(), vec!["@inner".to_string()]);
+        profiles.insert("inner".to_string(), vec!["read_file".to_string()]);
+
+        let result =
+            CapabilitySet::from_strings_strict_with_profiles(&["@outer".to_string()], &profiles);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_scoped_capability_parses_max_uses_suffix() {
+        let scoped = ScopedCapability::from_str("use_credential#1").unwrap();
+        assert_eq!(scoped.capability, Capability::UseCredential);
+        assert_eq!(scoped.scope, None);
+        assert_eq!(scoped.max_uses, Some(1));
+    }
+
+    #[test]
+    fn test_scoped_capability_parses_scope_and_max_uses() {
+        let scoped = ScopedCapability::from_str("read_file:/workspace/project-a#5").unwrap();
+        assert_eq!(scoped.scope.as_deref(), Some("/workspace/project-a"));
+        assert_eq!(scoped.max_uses, Some(5));
+    }
+
+    #[test]
+    fn test_scoped_capability_invalid_max_uses_suffix_fails_to_parse() {
+        assert!(ScopedCapability::from_str("use_credential#not-a-number").is_none());
+    }
+
+    #[test]
+    fn test_scoped_capability_serialize_round_trips_max_uses() {
+        let scoped = ScopedCapability::from_str("read_file:/workspace#5").unwrap();
+        let json = serde_json::to_string(&scoped).unwrap();
+        assert_eq!(json, "\"read_file:/workspace#5\"");
+        let back: ScopedCapability = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, scoped);
+    }
+
+    #[test]
+    fn test_matching_finds_scoped_grant_with_budget() {
+        let mut set = CapabilitySet::new();
+        set.grant_scoped(ScopedCapability::from_str("use_credential#1").unwrap());
+        let scoped = set.matching(&Capability::UseCredential, None).unwrap();
+        assert_eq!(scoped.max_uses, Some(1));
+    }
+
+    #[test]
+    fn test_capability_serde_round_trip() {
+        let json = serde_json::to_string(&Capability::ShellExec).unwrap();
+        assert_eq!(json, "\"shell_exec\"");
+        let back: Capability = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, Capability::ShellExec);
+    }
+
+    #[test]
+    fn test_capability_deserialize_rejects_unknown_name() {
+        let result: Result<Capability, _> = serde_json::from_str("\"shell_execute\"");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_capability_set_serde_round_trip() {
+        let mut set = CapabilitySet::new();
+        set.grant(Capability::ReadFile);
+        set.grant_scoped(ScopedCapability::from_str("http_request:api.github.com").unwrap());
+
+        let json = serde_json::to_string(&set).unwrap();
+        let back: CapabilitySet = serde_json::from_str(&json).unwrap();
+        assert!(back.has(&Capability::ReadFile));
+        assert!(back.has_scoped(&Capability::HttpRequest, "api.github.com"));
+    }
+
+    #[test]
+    fn test_capability_set_deserialize_rejects_unknown_name() {
+        let result: Result<CapabilitySet, _> = serde_json::from_str(r#"["read_file", "shell_execute"]"#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_capability_set_from_iterator_and_into_iterator() {
+        let scoped = vec![
+            ScopedCapability::from_str("write_file").unwrap(),
+            ScopedCapability::from_str("read_file:/workspace").unwrap(),
+        ];
+        let set: CapabilitySet = scoped.into_iter().collect();
+        assert!(set.has(&Capability::WriteFile));
+        assert!(set.has_scoped(&Capability::ReadFile, "/workspace"));
+
+        let collected: Vec<ScopedCapability> = set.into_iter().collect();
+        assert_eq!(collected.len(), 2);
+    }
+
+    #[test]
+    fn test_capability_set_iter_borrows() {
+        let set = CapabilitySet::from_strings(&["read_file".into(), "write_file".into()]);
+        assert_eq!(set.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_capability_set_display_is_sorted_regardless_of_grant_order() {
+        let mut a = CapabilitySet::new();
+        a.grant(Capability::ShellExec);
+        a.grant(Capability::ReadFile);
+        a.grant_scoped(ScopedCapability::from_str("http_request:api.github.com").unwrap());
+
+        let mut b = CapabilitySet::new();
+        b.grant_scoped(ScopedCapability::from_str("http_request:api.github.com").unwrap());
+        b.grant(Capability::ReadFile);
+        b.grant(Capability::ShellExec);
+
+        assert_eq!(a.to_string(), b.to_string());
+        assert_eq!(a.to_string(), "http_request:api.github.com, read_file, shell_exec");
+    }
+
+    #[test]
+    fn test_deny_overrides_existing_grant() {
+        let mut set = CapabilitySet::from_strings(&["shell_exec".into(), "read_file".into()]);
+        assert!(set.has(&Capability::ShellExec));
+        set.deny(Capability::ShellExec);
+        assert!(!set.has(&Capability::ShellExec));
+        assert!(!set.has_scoped(&Capability::ShellExec, "/bin/sh"));
+        // Unaffected capabilities are untouched.
+        assert!(set.has(&Capability::ReadFile));
+    }
+
+    #[test]
+    fn test_denied_from_distinguishes_denied_from_merely_missing() {
+        let mut set = CapabilitySet::from_strings(&["shell_exec".into()]);
+        set.deny(Capability::ShellExec);
+        let missing = set.missing_from(&[
+            Capability::ShellExec,
+            Capability::WriteFile,
+        ]);
+        assert_eq!(missing, vec![Capability::ShellExec, Capability::WriteFile]);
+        assert_eq!(set.denied_from(&missing), vec![Capability::ShellExec]);
+    }
+
+    #[test]
+    fn test_grant_admin_transitively_implies_filesystem_primitives() {
+        // admin -> filesystem -> {read_file, write_file, list_dir,
+        // delete_file, stat_file}, two levels deep.
+        let set = CapabilitySet::from_strings(&["admin".to_string()]);
+        assert!(set.has(&Capability::Admin));
+        assert!(set.has(&Capability::Filesystem));
+        assert!(set.has(&Capability::ReadFile));
+        assert!(set.has(&Capability::WriteFile));
+        assert!(set.has(&Capability::ListDir));
+        assert!(set.has(&Capability::DeleteFile));
+        assert!(set.has(&Capability::StatFile));
+        assert!(set.has(&Capability::ShellExec));
+        assert!(set.has(&Capability::ReadEnv));
+        // Not implied by admin or filesystem.
+        assert!(!set.has(&Capability::HttpRequest));
+    }
+
+    #[test]
+    fn test_grant_filesystem_implies_primitives_with_inherited_scope() {
+        let set = CapabilitySet::from_strings(&["filesystem:/workspace".to_string()]);
+        assert!(set.has_scoped(&Capability::ReadFile, "/workspace/a.txt"));
+        assert!(!set.has_scoped(&Capability::ReadFile, "/etc/passwd"));
+    }
+
+    #[test]
+    fn test_unknown_name_still_errors_before_implication_expansion() {
+        // synth-1128: expansion must not mask a strict-parse failure.
+        let err =
+            CapabilitySet::from_strings_strict(&["admin".to_string(), "bogus".to_string()])
+                .unwrap_err();
+        assert_eq!(err.names, vec!["bogus".to_string()]);
+    }
+
+    #[test]
+    fn test_deny_applies_after_implication_expansion() {
+        // Denying a primitive implied by a broad grant still blocks it —
+        // deny wins post-expansion, not just against the literal grant name.
+        let mut set = CapabilitySet::from_strings(&["admin".to_string()]);
+        set.deny(Capability::ShellExec);
+        assert!(!set.has(&Capability::ShellExec));
+        // Everything else admin implies is untouched.
+        assert!(set.has(&Capability::ReadFile));
+        assert!(set.has(&Capability::ReadEnv));
+    }
+
+    #[test]
+    fn test_deny_of_an_umbrella_capability_also_blocks_everything_it_implies() {
+        // Mirror of grant_scoped's own expansion: denying the umbrella
+        // itself, not one of its leaves, must still degrade the tool —
+        // capabilities: ["admin"], denied_capabilities: ["admin"] should
+        // block shell_exec/read_file/etc., not just the literal "admin".
+        let mut set = CapabilitySet::from_strings(&["admin".to_string()]);
+        set.deny(Capability::Admin);
+        assert!(!set.has(&Capability::Admin));
+        assert!(!set.has(&Capability::ShellExec));
+        assert!(!set.has(&Capability::ReadEnv));
+        assert!(!set.has(&Capability::Filesystem));
+        assert!(!set.has(&Capability::ReadFile));
+        assert!(!set.has(&Capability::WriteFile));
+        assert!(!set.has(&Capability::ListDir));
+        assert!(!set.has(&Capability::DeleteFile));
+        assert!(!set.has(&Capability::StatFile));
+
+        let mut set = CapabilitySet::from_strings(&["filesystem".to_string()]);
+        set.deny(Capability::Filesystem);
+        assert!(!set.has(&Capability::Filesystem));
+        assert!(!set.has(&Capability::ReadFile));
+        assert!(!set.has(&Capability::WriteFile));
+        assert!(!set.has(&Capability::ListDir));
+        assert!(!set.has(&Capability::DeleteFile));
+        assert!(!set.has(&Capability::StatFile));
+    }
+
+    #[test]
+    fn test_capability_set_partial_eq_ignores_grant_order_and_duplicates() {
+        let mut a = CapabilitySet::new();
+        a.grant(Capability::ReadFile);
+        a.grant(Capability::WriteFile);
+        let mut b = CapabilitySet::new();
+        b.grant(Capability::WriteFile);
+        b.grant(Capability::ReadFile);
+        b.grant(Capability::ReadFile);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_capability_set_partial_eq_distinguishes_denials() {
+        let a = CapabilitySet::from_strings(&["read_file".into()]);
+        let mut b = a.clone();
+        b.deny(Capability::ReadFile);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_len_counts_distinct_grants_ignoring_duplicate_pushes() {
+        let mut set = CapabilitySet::new();
+        set.grant(Capability::ReadFile);
+        set.grant(Capability::ReadFile);
+        assert_eq!(set.len(), 1);
+        set.grant(Capability::WriteFile);
+        assert_eq!(set.len(), 2);
+        assert!(!set.is_empty());
+        assert!(CapabilitySet::new().is_empty());
+    }
+
+    #[test]
+    fn test_set_algebra_laws_hold_over_all_subsets_of_three_capabilities() {
+        // Exhaustive over the small variant space (synth-1131): every one
+        // of the 8 subsets of {read_file, write_file, shell_exec} paired
+        // with every other, asserting the standard set-algebra laws.
+        let universe = [Capability::ReadFile, Capability::WriteFile, Capability::ShellExec];
+        let subsets: Vec<CapabilitySet> = (0u8..8)
+            .map(|mask| {
+                let mut set = CapabilitySet::new();
+                for (i, cap) in universe.iter().enumerate() {
+                    if mask & (1 << i) != 0 {
+                        set.grant(cap.clone());
+                    }
+                }
+                set
+            })
+            .collect();
+
+        for a in &subsets {
+            for b in &subsets {
+                assert_eq!(a.union(b), b.union(a), "union must be commutative");
+                assert_eq!(a.intersection(b), b.intersection(a), "intersection must be commutative");
+                assert_eq!(a.union(a), a.clone(), "union must be idempotent");
+                assert_eq!(a.intersection(a), a.clone(), "intersection must be idempotent");
+                assert!(a.intersection(b).is_subset_of(a), "A∩B ⊆ A");
+                assert!(a.intersection(b).is_subset_of(b), "A∩B ⊆ B");
+                assert!(a.is_subset_of(&a.union(b)), "A ⊆ A∪B");
+                assert!(b.is_subset_of(&a.union(b)), "B ⊆ A∪B");
+                let diff = a.difference(b);
+                assert!(diff.is_subset_of(a), "A\\B ⊆ A");
+                for cap in &universe {
+                    if b.has(cap) {
+                        assert!(!diff.has(cap), "A\\B must not contain anything in B");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_union_preserves_denials_from_both_operands() {
+        let mut a = CapabilitySet::from_strings(&["read_file".into()]);
+        a.deny(Capability::WriteFile);
+        let mut b = CapabilitySet::from_strings(&["write_file".into()]);
+        b.deny(Capability::ShellExec);
+
+        let u = a.union(&b);
+        assert!(u.has(&Capability::ReadFile));
+        // write_file is granted by b but denied by a — deny wins.
+        assert!(!u.has(&Capability::WriteFile));
+        assert!(!u.has(&Capability::ShellExec));
+    }
+
+    #[test]
+    fn test_intersection_also_preserves_denials_from_both_operands() {
+        let mut a =
+            CapabilitySet::from_strings(&["read_file".into(), "write_file".into()]);
+        a.deny(Capability::ShellExec);
+        let b = CapabilitySet::from_strings(&[
+            "read_file".into(),
+            "write_file".into(),
+            "shell_exec".into(),
+        ]);
+
+        let i = a.intersection(&b);
+        assert!(i.has(&Capability::ReadFile));
+        assert!(i.has(&Capability::WriteFile));
+        assert!(!i.has(&Capability::ShellExec));
+    }
+
+    #[test]
+    fn test_difference_keeps_only_the_left_operands_denials() {
+        let mut a = CapabilitySet::from_strings(&["read_file".into()]);
+        a.deny(Capability::WriteFile);
+        let mut b = CapabilitySet::new();
+        b.deny(Capability::ReadFile);
+
+        let d = a.difference(&b);
+        // b's denial of read_file has no bearing on a's own set.
+        assert!(d.has(&Capability::ReadFile));
+        assert!(!d.is_denied(&Capability::ReadFile));
+        // a's own denial persists.
+        assert!(d.is_denied(&Capability::WriteFile));
+    }
+
+    #[test]
+    fn test_ceiling_from_names_ignores_unknown_names() {
+        let ceiling = ceiling_from_names(&["read_file".to_string(), "bogus".to_string()]);
+        assert!(ceiling.contains(&Capability::ReadFile));
+        assert_eq!(ceiling.len(), 1);
+    }
+
+    #[test]
+    fn test_partition_by_ceiling_strips_names_outside_ceiling() {
+        let ceiling = ceiling_from_names(&["read_file".to_string()]);
+        let (allowed, stripped) = partition_by_ceiling(
+            &["read_file".to_string(), "shell_exec".to_string()],
+            &ceiling,
+        );
+        assert_eq!(allowed, vec!["read_file".to_string()]);
+        assert_eq!(stripped, vec!["shell_exec".to_string()]);
+    }
+
+    #[test]
+    fn test_partition_by_ceiling_respects_scoped_grants() {
+        let ceiling = ceiling_from_names(&["read_file".to_string()]);
+        let (allowed, stripped) = partition_by_ceiling(
+            &["read_file:/workspace".to_string(), "http_request:api.github.com".to_string()],
+            &ceiling,
+        );
+        assert_eq!(allowed, vec!["read_file:/workspace".to_string()]);
+        assert_eq!(stripped, vec!["http_request:api.github.com".to_string()]);
+    }
+
+    #[test]
+    fn test_partition_by_ceiling_leaves_unparsed_names_for_downstream_validation() {
+        // `@profile` references (synth-1121) parse later, against the
+        // server's profile map — the ceiling must not block them here.
+        let ceiling = ceiling_from_names(&["read_file".to_string()]);
+        let (allowed, stripped) = partition_by_ceiling(&["@readonly".to_string()], &ceiling);
+        assert_eq!(allowed, vec!["@readonly".to_string()]);
+        assert!(stripped.is_empty());
+    }
 }
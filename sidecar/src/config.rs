@@ -2,11 +2,18 @@
 //!
 //! All settings can be overridden via SENTINEL_SIDECAR_* environment variables.
 
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::credentials::CredentialProviderKind;
+use crate::leak_detector::RedactionStyle;
+use crate::sandbox::RequestOverridePolicy;
 
 /// Configuration for the sidecar's sandbox engine and host functions.
 /// O-009: All limits are configurable via SENTINEL_SIDECAR_* environment variables.
-#[derive(Clone)]
+#[derive(Clone, Serialize)]
 pub struct SidecarConfig {
     /// Maximum WASM memory in bytes (default: 64 MiB).
     pub max_memory_bytes: u64,
@@ -26,44 +33,1241 @@ pub struct SidecarConfig {
     pub shell_timeout_ms: u64,
     /// Maximum shell output size in bytes.
     pub shell_max_output_bytes: u64,
+    /// Command program names `handle_shell_exec` always allows (synth-1160),
+    /// checked against the command's first whitespace-separated token.
+    /// Empty (the default) means no restriction beyond `shell_denylist`.
+    pub shell_allowlist: Vec<String>,
+    /// Command program names `handle_shell_exec` always refuses
+    /// (synth-1160) — wins over `shell_allowlist` on a name in both, same
+    /// "deny always wins" convention as `Request.denied_capabilities`.
+    pub shell_denylist: Vec<String>,
+    /// Env var names copied from the sidecar process into the shell child's
+    /// environment (synth-1160). Empty (the default) preserves the
+    /// pre-synth-1160 behavior of inheriting the sidecar's entire
+    /// environment unrestricted; a non-empty list clears the child's
+    /// environment and passes through only the named vars.
+    pub shell_env_passthrough: Vec<String>,
+    /// Run commands via `sh -c` (synth-1160), allowing pipes, redirects, and
+    /// other shell metacharacters. `false` runs the command as a bare argv
+    /// (whitespace-split, no shell), closing off shell injection for tools
+    /// that never need shell features. Defaults to `true`, preserving
+    /// pre-synth-1160 behavior.
+    pub shell_allow_sh_c: bool,
+    /// Working directory for the shell child process (synth-1160). `None`
+    /// inherits the sidecar's own working directory, same as before this
+    /// setting existed.
+    pub shell_default_cwd: Option<PathBuf>,
     /// Maximum allowed timeout from requests in milliseconds (cap to prevent
     /// epoch ticker threads from running indefinitely with malicious values).
     pub max_timeout_ms: u64,
+    /// Ceiling on a request's `fuel` override (synth-1157) — a request
+    /// asking for more than this either gets clamped to it or is rejected
+    /// outright, per `request_override_policy`. A request that doesn't
+    /// override `fuel` always runs with `max_fuel`, unaffected by this.
+    pub max_request_fuel: u64,
+    /// Ceiling on a request's `http_timeout_ms` override (synth-1157), same
+    /// shape as `max_request_fuel` but for the `http_fetch` host call's
+    /// per-request timeout.
+    pub max_request_http_timeout_ms: u64,
+    /// How a request override that exceeds its ceiling (`max_timeout_ms`,
+    /// `max_request_fuel`, `max_request_http_timeout_ms`) is handled
+    /// (synth-1157): clamped down to the ceiling (the default, preserving
+    /// `timeout_ms`'s pre-existing behavior) or rejected outright.
+    pub request_override_policy: RequestOverridePolicy,
+    /// Interval, in milliseconds, between epoch ticks used to enforce WASM
+    /// execution timeouts (synth-1158). Was hardcoded to 500ms in
+    /// `run_wasm`, which put a floor under how tight a timeout could
+    /// actually be (a 200ms timeout still waited ~500ms for the first
+    /// tick) and ticked long executions far more often than needed.
+    /// Validated to 10-5000ms — below 10ms the ticker thread's own
+    /// overhead dominates, above 5000ms a timeout's actual resolution
+    /// becomes too coarse to be useful.
+    pub epoch_tick_ms: u64,
     /// Maximum WASM stdout capture buffer in bytes.
     pub stdout_max_bytes: usize,
     /// DNS resolution timeout in seconds (default: 5).
     pub dns_timeout_s: u64,
+    /// How Redact-action leak matches are rewritten in tool output.
+    pub redaction_style: RedactionStyle,
+    /// Path to append leak-alert JSON lines to, if external alerting is
+    /// enabled (synth-1115). `None` means no default hook is installed.
+    pub leak_log_path: Option<PathBuf>,
+    /// Enables the obfuscated-credential deep scan (synth-1117): re-checks
+    /// output with whitespace/hyphens stripped and reversed, catching
+    /// credentials split across characters. Off by default — it re-scans
+    /// output up to two extra times per invocation.
+    pub leak_deep_scan: bool,
+    /// Named capability bundles (synth-1121), loaded from an optional
+    /// `[profiles]` TOML file so operators can grant `@readonly` instead of
+    /// repeating `["read_file"]` on every request. Empty when unset.
+    pub capability_profiles: HashMap<String, Vec<String>>,
+    /// Capability ceiling applied to a connection until it sends a `hello`
+    /// (synth-1124), as raw capability names. Defaults to every known
+    /// capability so unmodified clients (which never send a `hello`) see no
+    /// behavior change; operators tighten this for sockets that only serve
+    /// low-trust clients.
+    pub default_capability_ceiling: Vec<String>,
+    /// Refuse to register tools that don't declare a `sha256` in their
+    /// tool.toml (synth-1134). Off by default so existing deployments
+    /// without hashes keep working; operators shipping tools through a
+    /// tamper-sensitive pipeline turn this on to make hashing mandatory.
+    pub require_tool_hash: bool,
+    /// Ed25519 public keys (synth-1141), decoded from
+    /// SENTINEL_SIDECAR_TOOL_SIGNING_KEYS, that a manifest's `signature`
+    /// field is checked against at load time. A manifest verifies as signed
+    /// if it validates against *any* key in this list, so keys can be
+    /// rotated by adding the new one before removing the old.
+    pub tool_signing_keys: Vec<Vec<u8>>,
+    /// Refuse to register tools that aren't signed by a known key
+    /// (synth-1141). Off by default, mirroring `require_tool_hash` — hash
+    /// pinning alone is enough for most deployments; this is for pipelines
+    /// that need to prove *who* published a tool, not just that its bytes
+    /// are unchanged.
+    pub require_signed_tools: bool,
+    /// Allow loading a tool's precompiled `cwasm` artifact via
+    /// `Module::deserialize_file` instead of compiling its `.wasm` from
+    /// source (synth-1143). Off by default since a mismatched Wasmtime
+    /// engine build silently falls back to compiling anyway — this only
+    /// matters as a startup-latency win once operators have verified their
+    /// `--precompile` output actually loads on the target engine.
+    pub allow_precompiled: bool,
+    /// Downgrade an `output_schema` (synth-1146) mismatch from a hard
+    /// `invalid_tool_output` error to a warning recorded on the response. Off
+    /// by default so a tool's declared output contract is actually
+    /// enforced; operators roll out a new/tightened `output_schema` with
+    /// this on to see would-be violations in `output_validation_warnings`
+    /// before flipping it back off.
+    pub output_schema_warn_only: bool,
+    /// Enables the `_metrics` meta-request (synth-1168), which renders
+    /// Prometheus text exposition format. Off by default — metric
+    /// collection itself always happens (cheap counters), only exposing
+    /// them over the socket is gated, since a low-trust client probing
+    /// `_metrics` would otherwise get tool-usage and error-rate data for
+    /// free with no capability required, matching `_health`/`_ready`.
+    pub metrics_enabled: bool,
+    /// Deployment-wide baseline of hostnames/glob patterns tools may fetch
+    /// from over `http_fetch` (synth-1151), applied whenever neither a
+    /// request nor its tool.toml supplies its own `http_allowlist` — see
+    /// `SandboxEngine::execute`'s merge order. Empty by default, matching the
+    /// existing "no allowlist anywhere = deny all" behavior.
+    pub http_allowlist: Vec<String>,
+    /// Allow plain `http://` fetches, not just `https://` (synth-1151).
+    /// Previously hardcoded to `false` in `execute_wasm_sync`; still off by
+    /// default since HTTP traffic is unencrypted and easy to intercept.
+    pub http_allow_http: bool,
+    /// Path of the Unix domain socket the sidecar listens on (synth-1154).
+    /// Moved here from a standalone env read in `main` so it validates and
+    /// hot-reload-reports like every other setting.
+    pub socket_path: PathBuf,
+    /// Permission bits applied to the socket file (synth-1154), e.g. `0o600`.
+    /// Defaults to owner-only, since any local user able to connect can run
+    /// shell commands through a permissive tool.
+    pub socket_mode: u32,
+    /// Name of the group to `chown` the socket file to after binding
+    /// (synth-1154), so a trusted group other than the socket owner can also
+    /// connect. `None` leaves the group unchanged (whatever the sidecar
+    /// process's primary group is).
+    pub socket_group: Option<String>,
+    /// Host-side credential source (synth-1155), merged with a request's
+    /// own `credentials` map by `SandboxEngine::execute` — see
+    /// `crate::credentials`. `None` (the default) keeps every credential
+    /// coming from the request, unchanged from before this existed.
+    pub credential_provider: CredentialProviderKind,
+    /// Env var prefix stripped to form the credential name when
+    /// `credential_provider = "env"`.
+    pub credential_env_prefix: String,
+    /// Secrets file path when `credential_provider = "file"`.
+    pub credential_file_path: Option<PathBuf>,
+    /// Command whose stdout (a JSON object of name -> value) is parsed
+    /// when `credential_provider = "exec"`.
+    pub credential_exec_command: Option<String>,
+    /// Reject any request that supplies its own inline `credentials`
+    /// outright, instead of merging them with the provider's (synth-1155),
+    /// for deployments where inline credentials must never transit the
+    /// socket.
+    pub credential_provider_only: bool,
+    /// Address (`host:port`) for an optional TCP listener (synth-1169),
+    /// alongside the Unix socket, for a controller that can't reach a local
+    /// Unix socket (e.g. running on a separate host). `None` (the default)
+    /// leaves TCP disabled.
+    pub tcp_addr: Option<String>,
+    /// Bearer token a TCP client's first line must present as
+    /// `{"auth": {"token": "..."}}` before any other message is accepted
+    /// (synth-1169). Unlike the Unix socket, a TCP listener has no
+    /// filesystem permissions to fall back on for access control, so
+    /// `validate` refuses to start with `tcp_addr` set unless this is too.
+    pub tcp_auth_token: Option<String>,
+    /// PEM certificate path for TLS on the TCP listener (synth-1169). Must
+    /// be set together with `tcp_tls_key_path`; when both are `None` (the
+    /// default) the TCP listener speaks plaintext.
+    pub tcp_tls_cert_path: Option<PathBuf>,
+    /// PEM private key path paired with `tcp_tls_cert_path` (synth-1169).
+    pub tcp_tls_key_path: Option<PathBuf>,
+    /// UIDs allowed to connect to the Unix socket (synth-1170), checked
+    /// against `SO_PEERCRED` on every accepted connection. Empty (the
+    /// default) accepts any peer, preserving pre-synth-1170 behavior — the
+    /// socket file's own permissions are the only gate.
+    pub allowed_uids: Vec<u32>,
+    /// Additional listener sockets (synth-1159), each with its own path,
+    /// file mode, default capability ceiling, and connection cap — e.g. a
+    /// second, more restricted socket for an internal debugging UI
+    /// alongside the trusted controller's. Empty by default, in which case
+    /// `main` synthesizes a single listener from `socket_path`/`socket_mode`/
+    /// `default_capability_ceiling` (unlimited connections), preserving
+    /// pre-synth-1159 behavior exactly. File-only: unlike every other list
+    /// field there's no natural single env var for "a list of tables", so
+    /// there's no `SENTINEL_SIDECAR_LISTENERS` override.
+    pub listeners: Vec<ListenerConfig>,
+    /// Preset bundle of resource-limit defaults (synth-1161): `"strict"`,
+    /// `"balanced"`, or `"permissive"` — see [`LimitsProfile`] and
+    /// [`profile_defaults`]. Only supplies a *default* for the fields it
+    /// covers; a field set explicitly in the file or via its own env var
+    /// still wins, same precedence as every other layer in `merge`.
+    /// `"balanced"` reproduces this struct's pre-synth-1161 hardcoded
+    /// defaults exactly, so an unset `profile` changes nothing.
+    pub profile: LimitsProfile,
+    /// Maximum number of tool executions running at once across the whole
+    /// process (synth-1164), enforced by a `tokio::sync::Semaphore` sized
+    /// from this value once at startup in `main` — restart required to
+    /// change it, same as `listeners`. Bounds memory/CPU pressure once
+    /// `main::handle_connection` stopped serializing a connection's requests
+    /// and started spawning each one into its own task.
+    pub max_concurrent_executions: u64,
+    /// Seconds `main` waits for in-flight connections to finish after a
+    /// SIGTERM/SIGINT before aborting them and exiting anyway (synth-1171).
+    /// Sized once at startup, same as `max_concurrent_executions` — a live
+    /// reload can't retroactively change how long a shutdown already in
+    /// progress will wait.
+    pub drain_timeout_secs: u64,
+    /// Maximum size, in bytes, of a single newline-delimited line
+    /// `main::handle_connection` will buffer before rejecting it as
+    /// `invalid_request` and closing the connection (synth-1172) — covers
+    /// the TCP auth line and every request/control line alike. Protects
+    /// against a client (or bug) that never sends a newline growing this
+    /// connection's read buffer without bound. Captured once per connection
+    /// from this snapshot at accept time, same as `max_concurrent_executions`.
+    pub max_request_bytes: u64,
+    /// Maximum number of connections open at once across every listener —
+    /// Unix and TCP alike (synth-1173). Unlike [`ListenerConfig::max_connections`],
+    /// which bounds one `[[listener]]` entry, this is a single process-wide
+    /// cap enforced in `main`'s accept loop against
+    /// `SandboxEngine::open_connections()`'s current value; an accept over
+    /// the cap gets one `connection_limit_reached` error line and is closed
+    /// without ever reaching `handle_connection`. `None` means unlimited,
+    /// matching `ListenerConfig::max_connections`'s own convention.
+    pub max_connections: Option<u64>,
+    /// Seconds a connection may sit with no request pipelined on it before
+    /// `main::handle_connection` closes it (synth-1173), default 5 minutes.
+    /// Implemented as a `tokio::time::sleep` raced against the next read in
+    /// the same `tokio::select!` as the shutdown notice; an execution still
+    /// running on the connection (`request_tasks` non-empty) inhibits the
+    /// close, so a slow tool never gets its response dropped out from under
+    /// it just because the client went quiet while waiting.
+    pub idle_timeout_secs: u64,
+    /// Reject a `Request` carrying a field this build doesn't recognize
+    /// (synth-1177) with `invalid_request` naming the unknown keys, instead
+    /// of silently ignoring them. Off by default — the lenient decode
+    /// [`protocol::Request`] documents stays the wire's normal behavior, so
+    /// dropping an old controller's now-removed field never breaks it. Turn
+    /// this on to catch a typo'd field name (`"capabilties"`) before it
+    /// silently runs with an empty capability list instead of the intended
+    /// one.
+    pub strict_requests: bool,
+    /// Maximum number of entries a `{"batch": [...]}"` envelope may carry
+    /// (synth-1183) before `main::handle_connection` rejects the whole
+    /// envelope with `batch_too_large` instead of running any of it. Read
+    /// fresh from the live config snapshot on every batch, same as
+    /// `strict_requests` above — a reload takes effect for the very next
+    /// batch, not just future connections.
+    pub max_batch_size: usize,
+    /// Maximum total size in bytes a single chunked transfer (synth-1185)
+    /// may spool in `HostState::transfers` before `Op::ChunkAppend` starts
+    /// rejecting further chunks. Bounds a guest's worst-case in-memory
+    /// footprint for an oversized `write_file`/`read_file` payload, the way
+    /// `max_memory_bytes` bounds WASM linear memory.
+    pub max_chunked_transfer_bytes: u64,
+    /// How long an idle chunked transfer (synth-1185) may sit in
+    /// `HostState::transfers` before `host_call_dispatch` prunes it as
+    /// abandoned — same lazy-prune-on-next-call shape as
+    /// `main::prune_expired_grants`, just scoped to one execution's
+    /// transfer table instead of a connection's capability grants.
+    pub chunk_transfer_ttl_ms: u64,
+    /// Where each field's effective value actually came from (synth-1156),
+    /// keyed by field name — populated by `merge` and read back by
+    /// `effective_settings`. Not itself a config value, so it's skipped by
+    /// `Serialize` and left out of `diff`/`validate`; a plain
+    /// `SidecarConfig::default()` (as used throughout the test suite) just
+    /// gets an empty map, which `effective_settings` treats as "default"
+    /// for every field.
+    #[serde(skip)]
+    pub field_sources: HashMap<&'static str, ConfigSource>,
 }
 
 impl SidecarConfig {
-    /// Load configuration from environment variables with defaults.
+    /// Load configuration from defaults, an optional TOML file, and
+    /// environment variables, in that precedence order (synth-1148). The
+    /// file is located via `SENTINEL_SIDECAR_CONFIG`; every other
+    /// `SENTINEL_SIDECAR_*` var still overrides the corresponding file value,
+    /// same as it overrides a default. A missing/unreadable/unparseable file
+    /// here only warns and falls back to defaults+env — `from_env` is meant
+    /// to be infallible, the same contract it already had before file
+    /// support existed. An operator who wants a bad file to be fatal should
+    /// pass `--config` explicitly (see `main`), which uses [`Self::from_file`]
+    /// and does propagate the error.
     pub fn from_env() -> Self {
+        let raw = match std::env::var("SENTINEL_SIDECAR_CONFIG") {
+            Ok(path) => {
+                let strict = env_bool("SENTINEL_SIDECAR_CONFIG_STRICT", false);
+                match read_raw_file_config(Path::new(&path), strict) {
+                    Ok(raw) => raw,
+                    Err(e) => {
+                        eprintln!("sidecar: warning: {e}");
+                        RawFileConfig::default()
+                    }
+                }
+            }
+            Err(_) => RawFileConfig::default(),
+        };
+        Self::merge(raw)
+    }
+
+    /// Parse a TOML config file at `path` and merge it over the defaults,
+    /// with environment variables still applied on top (synth-1148). Unlike
+    /// `from_env`, errors reading, parsing, or (under
+    /// `SENTINEL_SIDECAR_CONFIG_STRICT`) validating the file are returned
+    /// rather than swallowed — this is for the explicit `--config <path>`
+    /// startup flag, where an operator naming a file expects it to actually
+    /// be used.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let strict = env_bool("SENTINEL_SIDECAR_CONFIG_STRICT", false);
+        let raw = read_raw_file_config(path, strict)?;
+        Ok(Self::merge(raw))
+    }
+
+    /// Sanity-check this config before the sidecar starts listening
+    /// (synth-1149): a typo'd numeric env var, a `tool_dir` that doesn't
+    /// exist, or an `allowed_paths` entry that's relative or missing should
+    /// fail startup with an actionable message instead of quietly falling
+    /// back to a default or failing confusingly on the first request. Every
+    /// violation is collected rather than stopping at the first one, so an
+    /// operator fixing their config sees the whole list in one pass.
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+
+        // Distinguish "unset" (fine, uses the default) from "set but
+        // unparseable" (silently ignored by `resolve_u64` today, and a strong
+        // signal of a typo like "1,000,000" for a comma-free u64).
+        for key in NUMERIC_ENV_VARS {
+            if let Ok(value) = std::env::var(key) {
+                if value.parse::<u64>().is_err() {
+                    errors.push(ConfigError {
+                        field: key.to_string(),
+                        message: format!("'{value}' is not a valid non-negative integer"),
+                    });
+                }
+            }
+        }
+
+        if !self.tool_dir.is_dir() {
+            errors.push(ConfigError {
+                field: "tool_dir".to_string(),
+                message: format!("{} does not exist or is not a directory", self.tool_dir.display()),
+            });
+        }
+
+        for path in &self.allowed_paths {
+            if !Path::new(path).is_absolute() {
+                errors.push(ConfigError {
+                    field: "allowed_paths".to_string(),
+                    message: format!("'{path}' is not an absolute path"),
+                });
+                continue;
+            }
+            if std::fs::canonicalize(path).is_err() {
+                errors.push(ConfigError {
+                    field: "allowed_paths".to_string(),
+                    message: format!("'{path}' does not exist"),
+                });
+            }
+        }
+
+        if self.timeout_ms == 0 {
+            errors.push(ConfigError { field: "timeout_ms".to_string(), message: "must be greater than 0".to_string() });
+        }
+        if self.max_timeout_ms == 0 {
+            errors.push(ConfigError { field: "max_timeout_ms".to_string(), message: "must be greater than 0".to_string() });
+        }
+        if self.http_default_timeout_ms == 0 {
+            errors.push(ConfigError {
+                field: "http_default_timeout_ms".to_string(),
+                message: "must be greater than 0".to_string(),
+            });
+        }
+        if self.shell_timeout_ms == 0 {
+            errors.push(ConfigError { field: "shell_timeout_ms".to_string(), message: "must be greater than 0".to_string() });
+        }
+        if self.max_concurrent_executions == 0 {
+            errors.push(ConfigError {
+                field: "max_concurrent_executions".to_string(),
+                message: "must be greater than 0".to_string(),
+            });
+        }
+        if self.max_request_bytes == 0 {
+            errors.push(ConfigError {
+                field: "max_request_bytes".to_string(),
+                message: "must be greater than 0".to_string(),
+            });
+        }
+        if self.idle_timeout_secs == 0 {
+            errors.push(ConfigError {
+                field: "idle_timeout_secs".to_string(),
+                message: "must be greater than 0".to_string(),
+            });
+        }
+        if self.max_batch_size == 0 {
+            errors.push(ConfigError {
+                field: "max_batch_size".to_string(),
+                message: "must be greater than 0".to_string(),
+            });
+        }
+        if self.max_chunked_transfer_bytes == 0 {
+            errors.push(ConfigError {
+                field: "max_chunked_transfer_bytes".to_string(),
+                message: "must be greater than 0".to_string(),
+            });
+        }
+        if self.chunk_transfer_ttl_ms == 0 {
+            errors.push(ConfigError {
+                field: "chunk_transfer_ttl_ms".to_string(),
+                message: "must be greater than 0".to_string(),
+            });
+        }
+        // synth-1160: a name in both lists is always ambiguous to a reader —
+        // the denylist wins at runtime, but a config that sets both for the
+        // same name is almost certainly a mistake, not an intentional
+        // "denylist as the final word" pattern.
+        for name in &self.shell_allowlist {
+            if self.shell_denylist.contains(name) {
+                errors.push(ConfigError {
+                    field: "shell_denylist".to_string(),
+                    message: format!("'{name}' appears in both shell_allowlist and shell_denylist"),
+                });
+            }
+        }
+        if self.max_memory_bytes < MIN_MEMORY_BYTES {
+            errors.push(ConfigError {
+                field: "max_memory_bytes".to_string(),
+                message: format!("must be at least {MIN_MEMORY_BYTES} bytes (1 MiB)"),
+            });
+        }
+        if self.shell_max_output_bytes as u64 > MAX_SANE_OUTPUT_BYTES {
+            errors.push(ConfigError {
+                field: "shell_max_output_bytes".to_string(),
+                message: format!("{} exceeds the sane ceiling of {MAX_SANE_OUTPUT_BYTES} bytes", self.shell_max_output_bytes),
+            });
+        }
+        if self.stdout_max_bytes as u64 > MAX_SANE_OUTPUT_BYTES {
+            errors.push(ConfigError {
+                field: "stdout_max_bytes".to_string(),
+                message: format!("{} exceeds the sane ceiling of {MAX_SANE_OUTPUT_BYTES} bytes", self.stdout_max_bytes),
+            });
+        }
+
+        // synth-1157: a ceiling below the default it's meant to bound would
+        // reject/clamp every single request, including ones that never
+        // asked for an override.
+        if self.max_timeout_ms < self.timeout_ms {
+            errors.push(ConfigError {
+                field: "max_timeout_ms".to_string(),
+                message: format!("must be >= timeout_ms ({})", self.timeout_ms),
+            });
+        }
+        if self.max_request_fuel < self.max_fuel {
+            errors.push(ConfigError {
+                field: "max_request_fuel".to_string(),
+                message: format!("must be >= max_fuel ({})", self.max_fuel),
+            });
+        }
+        if self.max_request_http_timeout_ms < self.http_default_timeout_ms {
+            errors.push(ConfigError {
+                field: "max_request_http_timeout_ms".to_string(),
+                message: format!("must be >= http_default_timeout_ms ({})", self.http_default_timeout_ms),
+            });
+        }
+        if !(10..=5000).contains(&self.epoch_tick_ms) {
+            errors.push(ConfigError {
+                field: "epoch_tick_ms".to_string(),
+                message: "must be between 10 and 5000 (inclusive)".to_string(),
+            });
+        }
+
+        match self.socket_path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() && !parent.is_dir() => {
+                errors.push(ConfigError {
+                    field: "socket_path".to_string(),
+                    message: format!("parent directory {} does not exist", parent.display()),
+                });
+            }
+            _ => {}
+        }
+
+        // synth-1159: same check as `socket_path` above, applied to every
+        // configured `[[listener]]` entry.
+        for (i, listener) in self.listeners.iter().enumerate() {
+            match listener.path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() && !parent.is_dir() => {
+                    errors.push(ConfigError {
+                        field: format!("listener[{i}].path"),
+                        message: format!("parent directory {} does not exist", parent.display()),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        // synth-1169: the TCP listener has no filesystem permissions to fall
+        // back on for access control the way the Unix socket does, so it
+        // must never bind without a bearer token configured.
+        if self.tcp_addr.is_some() && self.tcp_auth_token.is_none() {
+            errors.push(ConfigError {
+                field: "tcp_auth_token".to_string(),
+                message: "must be set when tcp_addr is configured".to_string(),
+            });
+        }
+        if self.tcp_tls_cert_path.is_some() != self.tcp_tls_key_path.is_some() {
+            errors.push(ConfigError {
+                field: "tcp_tls_key_path".to_string(),
+                message: "tcp_tls_cert_path and tcp_tls_key_path must be set together".to_string(),
+            });
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// Defaults → file → env merge shared by [`Self::from_env`] and
+    /// [`Self::from_file`]. `raw` holds whatever the file layer supplied (or
+    /// nothing, if there was no file); each field falls back from
+    /// env → file → hardcoded default, in that order of preference. Also
+    /// records which layer actually won for each field into
+    /// `field_sources` (synth-1156), via the `resolve_*` helpers below —
+    /// each one mirrors an `env_*` helper's precedence exactly, just
+    /// returning where the value came from alongside it.
+    fn merge(raw: RawFileConfig) -> Self {
+        let defaults = Self::default();
+        let mut sources: HashMap<&'static str, ConfigSource> = HashMap::new();
+
+        // synth-1161: resolved first so the profile-covered fields below can
+        // fall back to its bundle instead of `defaults` directly. A field
+        // whose resolve still bottoms out at `ConfigSource::Default` is
+        // reclassified as `ConfigSource::Profile` only when `profile` itself
+        // came from the file or an env var — an unset `profile` leaves every
+        // covered field indistinguishable from before this existed.
+        let (profile_str, s) = resolve_string("SENTINEL_SIDECAR_PROFILE", raw.profile, String::new());
+        sources.insert("profile", s);
+        let profile = LimitsProfile::from_str_or_default(&profile_str);
+        let profile_was_set = s != ConfigSource::Default;
+        let limits = profile_defaults(profile);
+        let via_profile = |source: ConfigSource| {
+            if profile_was_set && source == ConfigSource::Default {
+                ConfigSource::Profile
+            } else {
+                source
+            }
+        };
+
+        let (max_memory_bytes, s) = resolve_u64("SENTINEL_SIDECAR_MAX_MEMORY_BYTES", raw.max_memory_bytes, limits.max_memory_bytes);
+        sources.insert("max_memory_bytes", via_profile(s));
+        let (max_fuel, s) = resolve_u64("SENTINEL_SIDECAR_MAX_FUEL", raw.max_fuel, limits.max_fuel);
+        sources.insert("max_fuel", via_profile(s));
+        let (timeout_ms, s) = resolve_u64("SENTINEL_SIDECAR_TIMEOUT_MS", raw.timeout_ms, limits.timeout_ms);
+        sources.insert("timeout_ms", via_profile(s));
+        let (tool_dir, s) = resolve_string("SENTINEL_SIDECAR_TOOL_DIR", raw.tool_dir, defaults.tool_dir.to_string_lossy().into_owned());
+        sources.insert("tool_dir", s);
+        let (allowed_paths, s) = resolve_list("SENTINEL_SIDECAR_ALLOWED_PATHS", raw.allowed_paths, defaults.allowed_paths);
+        sources.insert("allowed_paths", s);
+        let (http_default_timeout_ms, s) = resolve_u64(
+            "SENTINEL_SIDECAR_HTTP_TIMEOUT_MS",
+            raw.http_default_timeout_ms,
+            limits.http_default_timeout_ms,
+        );
+        sources.insert("http_default_timeout_ms", via_profile(s));
+        let (http_max_response_bytes, s) = resolve_u64(
+            "SENTINEL_SIDECAR_HTTP_MAX_RESPONSE_BYTES",
+            raw.http_max_response_bytes,
+            limits.http_max_response_bytes,
+        );
+        sources.insert("http_max_response_bytes", via_profile(s));
+        // synth-1160: the flat `shell_timeout_ms`/`shell_max_output_bytes`
+        // keys predate the `[shell]` section below and keep taking priority
+        // over it when both are set, so existing configs and env vars are
+        // completely unaffected by its addition.
+        let (shell_timeout_ms, s) = resolve_u64(
+            "SENTINEL_SIDECAR_SHELL_TIMEOUT_MS",
+            raw.shell_timeout_ms.or_else(|| raw.shell.as_ref().and_then(|s| s.timeout)),
+            limits.shell_timeout_ms,
+        );
+        sources.insert("shell_timeout_ms", via_profile(s));
+        let (shell_max_output_bytes, s) = resolve_u64(
+            "SENTINEL_SIDECAR_SHELL_MAX_OUTPUT_BYTES",
+            raw.shell_max_output_bytes.or_else(|| raw.shell.as_ref().and_then(|s| s.max_output)),
+            limits.shell_max_output_bytes,
+        );
+        sources.insert("shell_max_output_bytes", via_profile(s));
+        let (shell_allowlist, s) = resolve_list(
+            "SENTINEL_SIDECAR_SHELL_ALLOWLIST",
+            raw.shell.as_ref().and_then(|s| s.allowlist.clone()),
+            Vec::new(),
+        );
+        sources.insert("shell_allowlist", s);
+        let (shell_denylist, s) = resolve_list(
+            "SENTINEL_SIDECAR_SHELL_DENYLIST",
+            raw.shell.as_ref().and_then(|s| s.denylist.clone()),
+            Vec::new(),
+        );
+        sources.insert("shell_denylist", s);
+        let (shell_env_passthrough, s) = resolve_list(
+            "SENTINEL_SIDECAR_SHELL_ENV_PASSTHROUGH",
+            raw.shell.as_ref().and_then(|s| s.env_passthrough.clone()),
+            Vec::new(),
+        );
+        sources.insert("shell_env_passthrough", s);
+        let (shell_allow_sh_c, s) = resolve_bool(
+            "SENTINEL_SIDECAR_SHELL_ALLOW_SH_C",
+            raw.shell.as_ref().and_then(|s| s.allow_sh_c),
+            true,
+        );
+        sources.insert("shell_allow_sh_c", s);
+        let (shell_default_cwd_raw, s) = resolve_opt_string(
+            "SENTINEL_SIDECAR_SHELL_DEFAULT_CWD",
+            raw.shell.as_ref().and_then(|s| s.default_cwd.clone()),
+        );
+        sources.insert("shell_default_cwd", s);
+        let (max_timeout_ms, s) = resolve_u64("SENTINEL_SIDECAR_MAX_TIMEOUT_MS", raw.max_timeout_ms, defaults.max_timeout_ms);
+        sources.insert("max_timeout_ms", s);
+        let (max_request_fuel, s) =
+            resolve_u64("SENTINEL_SIDECAR_MAX_REQUEST_FUEL", raw.max_request_fuel, defaults.max_request_fuel);
+        sources.insert("max_request_fuel", s);
+        let (max_request_http_timeout_ms, s) = resolve_u64(
+            "SENTINEL_SIDECAR_MAX_REQUEST_HTTP_TIMEOUT_MS",
+            raw.max_request_http_timeout_ms,
+            defaults.max_request_http_timeout_ms,
+        );
+        sources.insert("max_request_http_timeout_ms", s);
+        let (request_override_policy_raw, s) =
+            resolve_opt_string("SENTINEL_SIDECAR_REQUEST_OVERRIDE_POLICY", raw.request_override_policy);
+        sources.insert("request_override_policy", s);
+        let (epoch_tick_ms, s) = resolve_u64("SENTINEL_SIDECAR_EPOCH_TICK_MS", raw.epoch_tick_ms, defaults.epoch_tick_ms);
+        sources.insert("epoch_tick_ms", s);
+        let (stdout_max_bytes, s) = resolve_u64(
+            "SENTINEL_SIDECAR_STDOUT_MAX_BYTES",
+            raw.stdout_max_bytes,
+            defaults.stdout_max_bytes as u64,
+        );
+        sources.insert("stdout_max_bytes", s);
+        let (dns_timeout_s, s) = resolve_u64("SENTINEL_SIDECAR_DNS_TIMEOUT_S", raw.dns_timeout_s, defaults.dns_timeout_s);
+        sources.insert("dns_timeout_s", s);
+        let (redaction_style_raw, s) = resolve_opt_string("SENTINEL_SIDECAR_REDACTION_STYLE", raw.redaction_style);
+        sources.insert("redaction_style", s);
+        let (leak_log_path_raw, s) = resolve_opt_string("SENTINEL_SIDECAR_LEAK_LOG", raw.leak_log_path);
+        sources.insert("leak_log_path", s);
+        let (leak_deep_scan, s) = resolve_bool("SENTINEL_SIDECAR_LEAK_DEEP_SCAN", raw.leak_deep_scan, limits.leak_deep_scan);
+        sources.insert("leak_deep_scan", via_profile(s));
+        let (profiles_file, s) = resolve_opt_string("SENTINEL_SIDECAR_PROFILES_FILE", raw.profiles_file);
+        sources.insert("capability_profiles", s);
+        let (default_capability_ceiling, s) = resolve_list(
+            "SENTINEL_SIDECAR_DEFAULT_CEILING",
+            raw.default_capability_ceiling,
+            default_capability_ceiling(),
+        );
+        sources.insert("default_capability_ceiling", s);
+        let (require_tool_hash, s) = resolve_bool("SENTINEL_SIDECAR_REQUIRE_TOOL_HASH", raw.require_tool_hash, false);
+        sources.insert("require_tool_hash", s);
+        let (tool_signing_keys_raw, s) = resolve_list("SENTINEL_SIDECAR_TOOL_SIGNING_KEYS", raw.tool_signing_keys, Vec::new());
+        sources.insert("tool_signing_keys", s);
+        let (require_signed_tools, s) = resolve_bool("SENTINEL_SIDECAR_REQUIRE_SIGNED_TOOLS", raw.require_signed_tools, false);
+        sources.insert("require_signed_tools", s);
+        let (allow_precompiled, s) = resolve_bool("SENTINEL_SIDECAR_ALLOW_PRECOMPILED", raw.allow_precompiled, false);
+        sources.insert("allow_precompiled", s);
+        let (output_schema_warn_only, s) =
+            resolve_bool("SENTINEL_SIDECAR_OUTPUT_SCHEMA_WARN_ONLY", raw.output_schema_warn_only, false);
+        sources.insert("output_schema_warn_only", s);
+        let (metrics_enabled, s) = resolve_bool("SENTINEL_SIDECAR_METRICS_ENABLED", raw.metrics_enabled, false);
+        sources.insert("metrics_enabled", s);
+        let (http_allowlist, s) = resolve_list("SENTINEL_SIDECAR_HTTP_ALLOWLIST", raw.http_allowlist, defaults.http_allowlist);
+        sources.insert("http_allowlist", s);
+        let (http_allow_http, s) = resolve_bool("SENTINEL_SIDECAR_HTTP_ALLOW_HTTP", raw.http_allow_http, false);
+        sources.insert("http_allow_http", s);
+        let (socket_path, s) = resolve_string("SENTINEL_SIDECAR_SOCKET", raw.socket_path, defaults.socket_path.to_string_lossy().into_owned());
+        sources.insert("socket_path", s);
+        let (socket_mode_raw, s) = resolve_opt_string("SENTINEL_SIDECAR_SOCKET_MODE", raw.socket_mode);
+        sources.insert("socket_mode", s);
+        let (socket_group, s) = resolve_opt_string("SENTINEL_SIDECAR_SOCKET_GROUP", raw.socket_group);
+        sources.insert("socket_group", s);
+        let (credential_provider_raw, s) = resolve_opt_string("SENTINEL_SIDECAR_CREDENTIAL_PROVIDER", raw.credential_provider);
+        sources.insert("credential_provider", s);
+        let (credential_env_prefix, s) = resolve_string(
+            "SENTINEL_SIDECAR_CREDENTIAL_ENV_PREFIX",
+            raw.credential_env_prefix,
+            defaults.credential_env_prefix,
+        );
+        sources.insert("credential_env_prefix", s);
+        let (credential_file_path_raw, s) =
+            resolve_opt_string("SENTINEL_SIDECAR_CREDENTIAL_FILE_PATH", raw.credential_file_path);
+        sources.insert("credential_file_path", s);
+        let (credential_exec_command, s) =
+            resolve_opt_string("SENTINEL_SIDECAR_CREDENTIAL_EXEC_COMMAND", raw.credential_exec_command);
+        sources.insert("credential_exec_command", s);
+        let (credential_provider_only, s) =
+            resolve_bool("SENTINEL_SIDECAR_CREDENTIAL_PROVIDER_ONLY", raw.credential_provider_only, false);
+        sources.insert("credential_provider_only", s);
+        let (tcp_addr, s) = resolve_opt_string("SENTINEL_SIDECAR_TCP_ADDR", raw.tcp_addr);
+        sources.insert("tcp_addr", s);
+        let (tcp_auth_token, s) = resolve_opt_string("SENTINEL_SIDECAR_TCP_AUTH_TOKEN", raw.tcp_auth_token);
+        sources.insert("tcp_auth_token", s);
+        let (tcp_tls_cert_path_raw, s) =
+            resolve_opt_string("SENTINEL_SIDECAR_TCP_TLS_CERT_PATH", raw.tcp_tls_cert_path);
+        sources.insert("tcp_tls_cert_path", s);
+        let (tcp_tls_key_path_raw, s) =
+            resolve_opt_string("SENTINEL_SIDECAR_TCP_TLS_KEY_PATH", raw.tcp_tls_key_path);
+        sources.insert("tcp_tls_key_path", s);
+        let (allowed_uids_raw, s) = resolve_list("SENTINEL_SIDECAR_ALLOWED_UIDS", raw.allowed_uids, Vec::new());
+        sources.insert("allowed_uids", s);
+        let (listeners, s) = resolve_listeners(raw.listener);
+        sources.insert("listeners", s);
+        let (max_concurrent_executions, s) = resolve_u64(
+            "SENTINEL_SIDECAR_MAX_CONCURRENT_EXECUTIONS",
+            raw.max_concurrent_executions,
+            defaults.max_concurrent_executions,
+        );
+        sources.insert("max_concurrent_executions", s);
+        let (drain_timeout_secs, s) = resolve_u64(
+            "SENTINEL_SIDECAR_DRAIN_TIMEOUT_SECS",
+            raw.drain_timeout_secs,
+            defaults.drain_timeout_secs,
+        );
+        sources.insert("drain_timeout_secs", s);
+        let (max_request_bytes, s) = resolve_u64(
+            "SENTINEL_SIDECAR_MAX_REQUEST_BYTES",
+            raw.max_request_bytes,
+            defaults.max_request_bytes,
+        );
+        sources.insert("max_request_bytes", s);
+        let (max_connections, s) = resolve_opt_u64("SENTINEL_SIDECAR_MAX_CONNECTIONS", raw.max_connections);
+        sources.insert("max_connections", s);
+        let (idle_timeout_secs, s) = resolve_u64(
+            "SENTINEL_SIDECAR_IDLE_TIMEOUT_SECS",
+            raw.idle_timeout_secs,
+            defaults.idle_timeout_secs,
+        );
+        sources.insert("idle_timeout_secs", s);
+        let (strict_requests, s) = resolve_bool("SENTINEL_SIDECAR_STRICT_REQUESTS", raw.strict_requests, false);
+        sources.insert("strict_requests", s);
+        let (max_batch_size, s) = resolve_u64(
+            "SENTINEL_SIDECAR_MAX_BATCH_SIZE",
+            raw.max_batch_size,
+            defaults.max_batch_size as u64,
+        );
+        sources.insert("max_batch_size", s);
+        let (max_chunked_transfer_bytes, s) = resolve_u64(
+            "SENTINEL_SIDECAR_MAX_CHUNKED_TRANSFER_BYTES",
+            raw.max_chunked_transfer_bytes,
+            defaults.max_chunked_transfer_bytes,
+        );
+        sources.insert("max_chunked_transfer_bytes", s);
+        let (chunk_transfer_ttl_ms, s) = resolve_u64(
+            "SENTINEL_SIDECAR_CHUNK_TRANSFER_TTL_MS",
+            raw.chunk_transfer_ttl_ms,
+            defaults.chunk_transfer_ttl_ms,
+        );
+        sources.insert("chunk_transfer_ttl_ms", s);
+
         Self {
-            max_memory_bytes: env_u64("SENTINEL_SIDECAR_MAX_MEMORY_BYTES", 64 * 1024 * 1024),
-            max_fuel: env_u64("SENTINEL_SIDECAR_MAX_FUEL", 1_000_000_000),
-            timeout_ms: env_u64("SENTINEL_SIDECAR_TIMEOUT_MS", 30_000),
-            tool_dir: PathBuf::from(
-                std::env::var("SENTINEL_SIDECAR_TOOL_DIR").unwrap_or_else(|_| "./wasm".into()),
-            ),
-            allowed_paths: std::env::var("SENTINEL_SIDECAR_ALLOWED_PATHS")
-                .unwrap_or_else(|_| "/workspace".into())
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .collect(),
-            http_default_timeout_ms: env_u64("SENTINEL_SIDECAR_HTTP_TIMEOUT_MS", 30_000),
-            http_max_response_bytes: env_u64(
-                "SENTINEL_SIDECAR_HTTP_MAX_RESPONSE_BYTES",
-                10 * 1024 * 1024,
-            ),
-            shell_timeout_ms: env_u64("SENTINEL_SIDECAR_SHELL_TIMEOUT_MS", 30_000),
-            shell_max_output_bytes: env_u64("SENTINEL_SIDECAR_SHELL_MAX_OUTPUT_BYTES", 1024 * 1024),
-            max_timeout_ms: env_u64("SENTINEL_SIDECAR_MAX_TIMEOUT_MS", 300_000),
-            stdout_max_bytes: env_u64("SENTINEL_SIDECAR_STDOUT_MAX_BYTES", 1024 * 1024) as usize,
-            dns_timeout_s: env_u64("SENTINEL_SIDECAR_DNS_TIMEOUT_S", 5),
+            max_memory_bytes,
+            max_fuel,
+            timeout_ms,
+            tool_dir: PathBuf::from(tool_dir),
+            allowed_paths,
+            http_default_timeout_ms,
+            http_max_response_bytes,
+            shell_timeout_ms,
+            shell_max_output_bytes,
+            shell_allowlist,
+            shell_denylist,
+            shell_env_passthrough,
+            shell_allow_sh_c,
+            shell_default_cwd: shell_default_cwd_raw.map(PathBuf::from),
+            max_timeout_ms,
+            max_request_fuel,
+            max_request_http_timeout_ms,
+            request_override_policy: request_override_policy_raw
+                .map(|v| RequestOverridePolicy::from_str_or_default(&v))
+                .unwrap_or_default(),
+            epoch_tick_ms,
+            stdout_max_bytes: stdout_max_bytes as usize,
+            dns_timeout_s,
+            redaction_style: redaction_style_raw.map(|v| RedactionStyle::from_str_or_default(&v)).unwrap_or_default(),
+            leak_log_path: leak_log_path_raw.map(PathBuf::from),
+            leak_deep_scan,
+            capability_profiles: load_profiles(profiles_file),
+            default_capability_ceiling,
+            require_tool_hash,
+            tool_signing_keys: load_signing_keys(tool_signing_keys_raw),
+            require_signed_tools,
+            allow_precompiled,
+            output_schema_warn_only,
+            metrics_enabled,
+            http_allowlist,
+            http_allow_http,
+            socket_path: PathBuf::from(socket_path),
+            socket_mode: socket_mode_raw.map(|v| parse_octal_mode(&v).unwrap_or(defaults.socket_mode)).unwrap_or(defaults.socket_mode),
+            socket_group,
+            credential_provider: credential_provider_raw
+                .map(|v| CredentialProviderKind::from_str_or_default(&v))
+                .unwrap_or_default(),
+            credential_env_prefix,
+            credential_file_path: credential_file_path_raw.map(PathBuf::from),
+            credential_exec_command,
+            credential_provider_only,
+            tcp_addr,
+            tcp_auth_token,
+            tcp_tls_cert_path: tcp_tls_cert_path_raw.map(PathBuf::from),
+            tcp_tls_key_path: tcp_tls_key_path_raw.map(PathBuf::from),
+            allowed_uids: parse_uids(allowed_uids_raw),
+            listeners,
+            profile,
+            max_concurrent_executions,
+            drain_timeout_secs,
+            max_request_bytes,
+            max_connections,
+            idle_timeout_secs,
+            strict_requests,
+            max_batch_size: max_batch_size as usize,
+            max_chunked_transfer_bytes,
+            chunk_transfer_ttl_ms,
+            field_sources: sources,
+        }
+    }
+
+    /// Fully-resolved settings as JSON, one entry per field, each annotated
+    /// with where its value came from (synth-1156) — `{"value": ...,
+    /// "source": "default" | "file" | "env"}`. Anything secret-shaped
+    /// (`MASKED_FIELDS`) has its value replaced with `"***"` rather than
+    /// omitted, so the source annotation is still visible for debugging
+    /// without leaking the value itself. Backs the `--print-config` CLI
+    /// flag and the detailed `_health` response.
+    pub fn effective_settings(&self) -> serde_json::Value {
+        let serialized = serde_json::to_value(self).expect("SidecarConfig always serializes");
+        let fields = serialized.as_object().expect("SidecarConfig serializes to a JSON object");
+
+        let mut out = serde_json::Map::new();
+        for (name, value) in fields {
+            let value = if MASKED_FIELDS.contains(&name.as_str()) { serde_json::json!("***") } else { value.clone() };
+            let source = self.field_sources.get(name.as_str()).copied().unwrap_or(ConfigSource::Default);
+            out.insert(name.clone(), serde_json::json!({ "value": value, "source": source }));
+        }
+        serde_json::Value::Object(out)
+    }
+
+    /// Reload from the same sources `from_env`/`from_file` would use
+    /// (synth-1152), reporting which fields actually changed relative to
+    /// `self` and how each one is classified — see [`ConfigReloadReport`].
+    /// Does not validate the result; the caller (`main`'s SIGHUP handler and
+    /// the `{"reload": "config"}` control message) runs [`Self::validate`]
+    /// on it before swapping it in, same as at startup.
+    pub fn reload(&self, path: Option<&Path>) -> anyhow::Result<(Self, ConfigReloadReport)> {
+        let new = match path {
+            Some(path) => Self::from_file(path)?,
+            None => Self::from_env(),
+        };
+        let report = self.diff(&new);
+        Ok((new, report))
+    }
+
+    /// Compare `self` against `other` field by field, classifying each
+    /// difference as reload-safe or restart-required per
+    /// [`RELOAD_SAFE_FIELDS`]/[`RESTART_REQUIRED_FIELDS`] (synth-1152).
+    fn diff(&self, other: &Self) -> ConfigReloadReport {
+        let mut changed = Vec::new();
+        if self.max_memory_bytes != other.max_memory_bytes {
+            changed.push("max_memory_bytes");
+        }
+        if self.max_fuel != other.max_fuel {
+            changed.push("max_fuel");
+        }
+        if self.timeout_ms != other.timeout_ms {
+            changed.push("timeout_ms");
+        }
+        if self.tool_dir != other.tool_dir {
+            changed.push("tool_dir");
+        }
+        if self.allowed_paths != other.allowed_paths {
+            changed.push("allowed_paths");
+        }
+        if self.http_default_timeout_ms != other.http_default_timeout_ms {
+            changed.push("http_default_timeout_ms");
+        }
+        if self.http_max_response_bytes != other.http_max_response_bytes {
+            changed.push("http_max_response_bytes");
+        }
+        if self.shell_timeout_ms != other.shell_timeout_ms {
+            changed.push("shell_timeout_ms");
+        }
+        if self.shell_max_output_bytes != other.shell_max_output_bytes {
+            changed.push("shell_max_output_bytes");
+        }
+        if self.shell_allowlist != other.shell_allowlist {
+            changed.push("shell_allowlist");
+        }
+        if self.shell_denylist != other.shell_denylist {
+            changed.push("shell_denylist");
+        }
+        if self.shell_env_passthrough != other.shell_env_passthrough {
+            changed.push("shell_env_passthrough");
+        }
+        if self.shell_allow_sh_c != other.shell_allow_sh_c {
+            changed.push("shell_allow_sh_c");
+        }
+        if self.shell_default_cwd != other.shell_default_cwd {
+            changed.push("shell_default_cwd");
+        }
+        if self.max_timeout_ms != other.max_timeout_ms {
+            changed.push("max_timeout_ms");
+        }
+        if self.max_request_fuel != other.max_request_fuel {
+            changed.push("max_request_fuel");
+        }
+        if self.max_request_http_timeout_ms != other.max_request_http_timeout_ms {
+            changed.push("max_request_http_timeout_ms");
+        }
+        if self.request_override_policy != other.request_override_policy {
+            changed.push("request_override_policy");
+        }
+        if self.epoch_tick_ms != other.epoch_tick_ms {
+            changed.push("epoch_tick_ms");
+        }
+        if self.stdout_max_bytes != other.stdout_max_bytes {
+            changed.push("stdout_max_bytes");
+        }
+        if self.dns_timeout_s != other.dns_timeout_s {
+            changed.push("dns_timeout_s");
+        }
+        if self.redaction_style != other.redaction_style {
+            changed.push("redaction_style");
+        }
+        if self.leak_log_path != other.leak_log_path {
+            changed.push("leak_log_path");
+        }
+        if self.leak_deep_scan != other.leak_deep_scan {
+            changed.push("leak_deep_scan");
+        }
+        if self.capability_profiles != other.capability_profiles {
+            changed.push("capability_profiles");
+        }
+        if self.default_capability_ceiling != other.default_capability_ceiling {
+            changed.push("default_capability_ceiling");
+        }
+        if self.require_tool_hash != other.require_tool_hash {
+            changed.push("require_tool_hash");
+        }
+        if self.tool_signing_keys != other.tool_signing_keys {
+            changed.push("tool_signing_keys");
+        }
+        if self.require_signed_tools != other.require_signed_tools {
+            changed.push("require_signed_tools");
+        }
+        if self.allow_precompiled != other.allow_precompiled {
+            changed.push("allow_precompiled");
+        }
+        if self.output_schema_warn_only != other.output_schema_warn_only {
+            changed.push("output_schema_warn_only");
+        }
+        if self.metrics_enabled != other.metrics_enabled {
+            changed.push("metrics_enabled");
+        }
+        if self.http_allowlist != other.http_allowlist {
+            changed.push("http_allowlist");
+        }
+        if self.http_allow_http != other.http_allow_http {
+            changed.push("http_allow_http");
+        }
+        if self.socket_path != other.socket_path {
+            changed.push("socket_path");
+        }
+        if self.socket_mode != other.socket_mode {
+            changed.push("socket_mode");
+        }
+        if self.socket_group != other.socket_group {
+            changed.push("socket_group");
         }
+        if self.credential_provider != other.credential_provider {
+            changed.push("credential_provider");
+        }
+        if self.credential_env_prefix != other.credential_env_prefix {
+            changed.push("credential_env_prefix");
+        }
+        if self.credential_file_path != other.credential_file_path {
+            changed.push("credential_file_path");
+        }
+        if self.credential_exec_command != other.credential_exec_command {
+            changed.push("credential_exec_command");
+        }
+        if self.credential_provider_only != other.credential_provider_only {
+            changed.push("credential_provider_only");
+        }
+        if self.tcp_addr != other.tcp_addr {
+            changed.push("tcp_addr");
+        }
+        if self.tcp_auth_token != other.tcp_auth_token {
+            changed.push("tcp_auth_token");
+        }
+        if self.tcp_tls_cert_path != other.tcp_tls_cert_path {
+            changed.push("tcp_tls_cert_path");
+        }
+        if self.tcp_tls_key_path != other.tcp_tls_key_path {
+            changed.push("tcp_tls_key_path");
+        }
+        if self.allowed_uids != other.allowed_uids {
+            changed.push("allowed_uids");
+        }
+        if self.listeners != other.listeners {
+            changed.push("listeners");
+        }
+        if self.max_concurrent_executions != other.max_concurrent_executions {
+            changed.push("max_concurrent_executions");
+        }
+        if self.drain_timeout_secs != other.drain_timeout_secs {
+            changed.push("drain_timeout_secs");
+        }
+        if self.max_request_bytes != other.max_request_bytes {
+            changed.push("max_request_bytes");
+        }
+        if self.max_connections != other.max_connections {
+            changed.push("max_connections");
+        }
+        if self.idle_timeout_secs != other.idle_timeout_secs {
+            changed.push("idle_timeout_secs");
+        }
+        if self.strict_requests != other.strict_requests {
+            changed.push("strict_requests");
+        }
+        if self.max_batch_size != other.max_batch_size {
+            changed.push("max_batch_size");
+        }
+        if self.max_chunked_transfer_bytes != other.max_chunked_transfer_bytes {
+            changed.push("max_chunked_transfer_bytes");
+        }
+        if self.chunk_transfer_ttl_ms != other.chunk_transfer_ttl_ms {
+            changed.push("chunk_transfer_ttl_ms");
+        }
+        if self.profile != other.profile {
+            changed.push("profile");
+        }
+
+        let mut report = ConfigReloadReport::default();
+        for field in changed {
+            if RESTART_REQUIRED_FIELDS.contains(&field) {
+                report.restart_required.push(field.to_string());
+            } else {
+                debug_assert!(RELOAD_SAFE_FIELDS.contains(&field), "unclassified config field: {field}");
+                report.applied.push(field.to_string());
+            }
+        }
+        report
     }
 }
 
+/// A single `[[listener]]` entry (synth-1159) — see
+/// [`SidecarConfig::listeners`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ListenerConfig {
+    /// Path of this listener's Unix domain socket.
+    pub path: PathBuf,
+    /// Permission bits applied to this listener's socket file, same
+    /// convention as [`SidecarConfig::socket_mode`].
+    pub mode: u32,
+    /// Capability ceiling applied to connections accepted on this listener
+    /// until they send a `hello`, same convention as
+    /// [`SidecarConfig::default_capability_ceiling`] but scoped to this
+    /// socket rather than the whole sidecar.
+    pub default_capability_ceiling: Vec<String>,
+    /// Maximum number of connections this listener accepts concurrently.
+    /// `None` means unlimited, matching the pre-synth-1159 single socket.
+    pub max_connections: Option<usize>,
+    /// Wire encoding connections on this listener start with (synth-1175),
+    /// before any `hello` line has a chance to negotiate it up or down — see
+    /// [`crate::protocol::PayloadEncoding`]. Defaults to `Json`, preserving
+    /// every pre-synth-1175 listener's behavior exactly.
+    pub encoding: crate::protocol::PayloadEncoding,
+}
+
+/// A single problem found by [`SidecarConfig::validate`] (synth-1149),
+/// mirroring the `(field/path, message)` shape of `registry::LoadIssue` for
+/// the same reason: enough structure to group or count without re-parsing
+/// `message`.
+#[derive(Debug, Clone)]
+pub struct ConfigError {
+    /// Name of the offending setting — an env var (e.g.
+    /// `SENTINEL_SIDECAR_MAX_FUEL`) for parse errors, or a `SidecarConfig`
+    /// field name (e.g. `tool_dir`) for everything else.
+    pub field: String,
+    /// Human-readable detail.
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Outcome of [`SidecarConfig::reload`] (synth-1152): which fields differed
+/// from the previous config and took effect immediately (`applied`), versus
+/// which differed but only take effect on the next process restart
+/// (`restart_required`) — see [`RELOAD_SAFE_FIELDS`]/[`RESTART_REQUIRED_FIELDS`].
+#[derive(Debug, Clone, Default)]
+pub struct ConfigReloadReport {
+    pub applied: Vec<String>,
+    pub restart_required: Vec<String>,
+}
+
+/// `SidecarConfig` fields a hot reload (SIGHUP or `{"reload": "config"}`,
+/// synth-1152) takes effect for immediately: every in-flight execution keeps
+/// the `Arc<SidecarConfig>` snapshot it started with (see
+/// `SandboxEngine::execute`), but the very next one picks up the new value.
+const RELOAD_SAFE_FIELDS: &[&str] = &[
+    "max_memory_bytes",
+    "max_fuel",
+    "timeout_ms",
+    "allowed_paths",
+    "http_default_timeout_ms",
+    "http_max_response_bytes",
+    "shell_timeout_ms",
+    "shell_max_output_bytes",
+    "shell_allowlist",
+    "shell_denylist",
+    "shell_env_passthrough",
+    "shell_allow_sh_c",
+    "shell_default_cwd",
+    "max_timeout_ms",
+    "max_request_fuel",
+    "max_request_http_timeout_ms",
+    "request_override_policy",
+    "epoch_tick_ms",
+    "stdout_max_bytes",
+    "dns_timeout_s",
+    "redaction_style",
+    "leak_log_path",
+    "leak_deep_scan",
+    "capability_profiles",
+    "default_capability_ceiling",
+    "output_schema_warn_only",
+    "metrics_enabled",
+    "http_allowlist",
+    "http_allow_http",
+    // synth-1177: read fresh from the live config snapshot on every request,
+    // same as `metrics_enabled` above.
+    "strict_requests",
+    // synth-1183: read fresh from the live config snapshot on every batch,
+    // same as `strict_requests` above.
+    "max_batch_size",
+    // synth-1185: read fresh into each execution's HostState, same as
+    // `max_memory_bytes` above.
+    "max_chunked_transfer_bytes",
+    "chunk_transfer_ttl_ms",
+    // synth-1161: only a default supplier for fields already listed above,
+    // none of which require a restart to change.
+    "profile",
+];
+
+/// `SidecarConfig` fields a hot reload reports as changed but cannot apply
+/// without a restart (synth-1152): `tool_dir` is only picked up by the
+/// registry's own independent reload (synth-1132), and the signing/hashing
+/// settings gate module loading itself, which already happened for every
+/// tool currently in the registry.
+const RESTART_REQUIRED_FIELDS: &[&str] = &[
+    "tool_dir",
+    "require_tool_hash",
+    "tool_signing_keys",
+    "require_signed_tools",
+    "allow_precompiled",
+    // synth-1154: the socket is bound once at startup; changing any of these
+    // only takes effect the next time the process starts and binds again.
+    "socket_path",
+    "socket_mode",
+    "socket_group",
+    // synth-1155: like `tool_dir`, the credential map is loaded/refreshed by
+    // its own independent mechanism (`crate::credentials::load`, triggered
+    // by SIGHUP or `{"reload": "credentials"}`), not automatically by a
+    // config reload — these fields only take effect the next time that
+    // runs.
+    "credential_provider",
+    "credential_env_prefix",
+    "credential_file_path",
+    "credential_exec_command",
+    "credential_provider_only",
+    // synth-1169: like the Unix socket above, the TCP listener is bound
+    // once at startup.
+    "tcp_addr",
+    "tcp_auth_token",
+    "tcp_tls_cert_path",
+    "tcp_tls_key_path",
+    // synth-1170: baked into the Unix listener's accept loop at startup,
+    // alongside its ceiling and connection semaphore, above.
+    "allowed_uids",
+    // synth-1159: listener sockets, like the single legacy socket above, are
+    // bound once at startup.
+    "listeners",
+    // synth-1164: the execution semaphore is sized from this value once at
+    // startup, same as the listeners it gates.
+    "max_concurrent_executions",
+    // synth-1171: only ever read once, by the shutdown drain that's already
+    // running by the time a reload could change it.
+    "drain_timeout_secs",
+    // synth-1172: captured once per connection at accept time, same as
+    // `max_concurrent_executions` above.
+    "max_request_bytes",
+    // synth-1173: `config.max_connections` is only ever read once, when
+    // `main`'s accept loop clones it into the closure it checks on every
+    // accept; a reload can't reach back and rebound a cap already captured
+    // there.
+    "max_connections",
+    // synth-1173: captured once per connection at accept time, same as
+    // `max_request_bytes` above.
+    "idle_timeout_secs",
+];
+
+/// Env vars `SidecarConfig::merge` parses as `u64` via `resolve_u64`, which
+/// treats "unset" and "unparseable" identically (falls back to the default).
+/// `validate` re-checks these directly against `std::env::var` to catch the
+/// unparseable case, which `resolve_u64` can't distinguish on its own.
+const NUMERIC_ENV_VARS: &[&str] = &[
+    "SENTINEL_SIDECAR_MAX_MEMORY_BYTES",
+    "SENTINEL_SIDECAR_MAX_FUEL",
+    "SENTINEL_SIDECAR_TIMEOUT_MS",
+    "SENTINEL_SIDECAR_HTTP_TIMEOUT_MS",
+    "SENTINEL_SIDECAR_HTTP_MAX_RESPONSE_BYTES",
+    "SENTINEL_SIDECAR_SHELL_TIMEOUT_MS",
+    "SENTINEL_SIDECAR_SHELL_MAX_OUTPUT_BYTES",
+    "SENTINEL_SIDECAR_MAX_TIMEOUT_MS",
+    "SENTINEL_SIDECAR_STDOUT_MAX_BYTES",
+    "SENTINEL_SIDECAR_DNS_TIMEOUT_S",
+    "SENTINEL_SIDECAR_MAX_CONCURRENT_EXECUTIONS",
+    "SENTINEL_SIDECAR_IDLE_TIMEOUT_SECS",
+    "SENTINEL_SIDECAR_MAX_BATCH_SIZE",
+    "SENTINEL_SIDECAR_MAX_CHUNKED_TRANSFER_BYTES",
+    "SENTINEL_SIDECAR_CHUNK_TRANSFER_TTL_MS",
+];
+
+/// Minimum sane `max_memory_bytes` (synth-1149) — Wasmtime's own minimum
+/// page size makes anything smaller unable to run real tool code anyway.
+const MIN_MEMORY_BYTES: u64 = 1024 * 1024;
+
+/// Upper sanity ceiling (synth-1149) for byte-sized output-buffering limits
+/// (`shell_max_output_bytes`, `stdout_max_bytes`) — well beyond any output a
+/// well-behaved tool would produce, so a value above this is almost always a
+/// units mistake (e.g. bytes vs. KiB) rather than an intentional setting.
+const MAX_SANE_OUTPUT_BYTES: u64 = 256 * 1024 * 1024;
+
 impl Default for SidecarConfig {
     fn default() -> Self {
         Self {
@@ -79,17 +1283,1530 @@ impl Default for SidecarConfig {
             http_max_response_bytes: 10 * 1024 * 1024,  // 10 MiB
             shell_timeout_ms: 30_000,
             shell_max_output_bytes: 1024 * 1024,         // 1 MiB
+            shell_allowlist: Vec::new(),
+            shell_denylist: Vec::new(),
+            shell_env_passthrough: Vec::new(),
+            shell_allow_sh_c: true,
+            shell_default_cwd: None,
             max_timeout_ms: 300_000,                      // 5 minutes
+            max_request_fuel: 1_000_000_000,              // same as max_fuel: no headroom until requests can override it
+            max_request_http_timeout_ms: 300_000,          // 5 minutes, matching max_timeout_ms's ratio over its default
+            request_override_policy: RequestOverridePolicy::Clamp,
+            epoch_tick_ms: 100,                            // was hardcoded 500ms in run_wasm
             stdout_max_bytes: 1024 * 1024,                // 1 MiB
             dns_timeout_s: 5,
+            redaction_style: RedactionStyle::default(),
+            leak_log_path: None,
+            leak_deep_scan: false,
+            capability_profiles: HashMap::new(),
+            default_capability_ceiling: default_capability_ceiling(),
+            require_tool_hash: false,
+            tool_signing_keys: Vec::new(),
+            require_signed_tools: false,
+            allow_precompiled: false,
+            output_schema_warn_only: false,
+            metrics_enabled: false,
+            http_allowlist: Vec::new(),
+            http_allow_http: false,
+            socket_path: PathBuf::from("/tmp/sentinel-sidecar.sock"),
+            socket_mode: 0o600,
+            socket_group: None,
+            credential_provider: CredentialProviderKind::None,
+            credential_env_prefix: "SENTINEL_CRED_".to_string(),
+            credential_file_path: None,
+            credential_exec_command: None,
+            credential_provider_only: false,
+            tcp_addr: None,
+            tcp_auth_token: None,
+            tcp_tls_cert_path: None,
+            tcp_tls_key_path: None,
+            allowed_uids: Vec::new(),
+            listeners: Vec::new(),
+            profile: LimitsProfile::default(),
+            max_concurrent_executions: 32,
+            drain_timeout_secs: 10,
+            max_request_bytes: 4 * 1024 * 1024,
+            max_connections: None,
+            idle_timeout_secs: 5 * 60, // 5 minutes
+            strict_requests: false,
+            max_batch_size: 32,
+            max_chunked_transfer_bytes: 8 * 1024 * 1024, // 8 MiB
+            chunk_transfer_ttl_ms: 30_000,               // 30 seconds
+            field_sources: HashMap::new(),
         }
     }
 }
 
-/// Read a u64 from an env var, falling back to a default.
-fn env_u64(key: &str, default: u64) -> u64 {
+/// Every known capability name — the permissive default connection ceiling
+/// (synth-1124), preserving pre-ceiling behavior for clients that never send
+/// a `hello`.
+fn default_capability_ceiling() -> Vec<String> {
+    crate::capabilities::Capability::all_names()
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Read a bool from an env var (`"true"`/`"1"`), falling back to a default.
+fn env_bool(key: &str, default: bool) -> bool {
     std::env::var(key)
         .ok()
-        .and_then(|v| v.parse().ok())
+        .map(|v| matches!(v.as_str(), "true" | "1"))
         .unwrap_or(default)
 }
+
+/// Which layer actually supplied a field's effective value (synth-1156) —
+/// surfaced via [`SidecarConfig::effective_settings`] for `--print-config`
+/// and the detailed `_health` response. `Profile` (synth-1161) is a variant
+/// of `Default`: it's what a `profile`-covered field falls back to when
+/// neither the file nor an env var set it directly, distinguished from
+/// plain `Default` only so `--print-config` can show that a preset, not the
+/// hardcoded fallback, actually produced the value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigSource {
+    Default,
+    File,
+    Env,
+    Profile,
+}
+
+/// Resource-limit preset selected via the `profile` config key or
+/// `SENTINEL_SIDECAR_PROFILE` (synth-1161) — see [`profile_defaults`] for
+/// the actual bundled values. Unrecognized/unset strings fall back to
+/// `Balanced`, same convention as
+/// [`crate::credentials::CredentialProviderKind::from_str_or_default`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LimitsProfile {
+    /// Tighter memory/fuel/timeouts and deep leak scanning, for
+    /// running untrusted or unaudited tools.
+    Strict,
+    /// This struct's pre-synth-1161 hardcoded defaults, unchanged.
+    #[default]
+    Balanced,
+    /// Looser limits for trusted, resource-hungry tools.
+    Permissive,
+}
+
+impl LimitsProfile {
+    /// Parse from a config/env string, case-insensitively.
+    pub fn from_str_or_default(s: &str) -> Self {
+        match s.to_ascii_lowercase().as_str() {
+            "strict" => Self::Strict,
+            "permissive" => Self::Permissive,
+            _ => Self::Balanced,
+        }
+    }
+}
+
+/// Fields covered by a [`LimitsProfile`] preset (synth-1161): memory, fuel,
+/// timeouts, HTTP limits, shell limits, and leak-detection strictness.
+/// `Balanced`'s values are exactly [`SidecarConfig::default`]'s pre-existing
+/// hardcoded numbers, so selecting it (or leaving `profile` unset) changes
+/// nothing.
+struct ProfileDefaults {
+    max_memory_bytes: u64,
+    max_fuel: u64,
+    timeout_ms: u64,
+    http_default_timeout_ms: u64,
+    http_max_response_bytes: u64,
+    shell_timeout_ms: u64,
+    shell_max_output_bytes: u64,
+    leak_deep_scan: bool,
+}
+
+fn profile_defaults(profile: LimitsProfile) -> ProfileDefaults {
+    match profile {
+        LimitsProfile::Strict => ProfileDefaults {
+            max_memory_bytes: 32 * 1024 * 1024,       // 32 MiB
+            max_fuel: 250_000_000,
+            timeout_ms: 10_000,
+            http_default_timeout_ms: 10_000,
+            http_max_response_bytes: 2 * 1024 * 1024,  // 2 MiB
+            shell_timeout_ms: 10_000,
+            shell_max_output_bytes: 256 * 1024,        // 256 KiB
+            leak_deep_scan: true,
+        },
+        LimitsProfile::Balanced => ProfileDefaults {
+            max_memory_bytes: 64 * 1024 * 1024,        // 64 MiB
+            max_fuel: 1_000_000_000,
+            timeout_ms: 30_000,
+            http_default_timeout_ms: 30_000,
+            http_max_response_bytes: 10 * 1024 * 1024, // 10 MiB
+            shell_timeout_ms: 30_000,
+            shell_max_output_bytes: 1024 * 1024,       // 1 MiB
+            leak_deep_scan: false,
+        },
+        LimitsProfile::Permissive => ProfileDefaults {
+            max_memory_bytes: 128 * 1024 * 1024,       // 128 MiB
+            max_fuel: 4_000_000_000,
+            timeout_ms: 60_000,
+            http_default_timeout_ms: 60_000,
+            http_max_response_bytes: 25 * 1024 * 1024, // 25 MiB
+            shell_timeout_ms: 60_000,
+            shell_max_output_bytes: 4 * 1024 * 1024,   // 4 MiB
+            leak_deep_scan: false,
+        },
+    }
+}
+
+/// Fields whose value is secret-shaped and must be masked in
+/// [`SidecarConfig::effective_settings`] output, even though their source is
+/// still worth knowing during debugging.
+const MASKED_FIELDS: &[&str] = &[
+    "credential_env_prefix",
+    "credential_file_path",
+    "credential_exec_command",
+    "tool_signing_keys",
+    "tcp_auth_token",
+];
+
+/// `resolve_*` mirror the `env_*` helpers above but also report which layer
+/// won, for [`SidecarConfig::merge`]'s `field_sources` bookkeeping.
+fn resolve_u64(key: &str, file: Option<u64>, default: u64) -> (u64, ConfigSource) {
+    if let Some(v) = std::env::var(key).ok().and_then(|v| v.parse().ok()) {
+        (v, ConfigSource::Env)
+    } else if let Some(v) = file {
+        (v, ConfigSource::File)
+    } else {
+        (default, ConfigSource::Default)
+    }
+}
+
+fn resolve_bool(key: &str, file: Option<bool>, default: bool) -> (bool, ConfigSource) {
+    if let Some(v) = std::env::var(key).ok().map(|v| matches!(v.as_str(), "true" | "1")) {
+        (v, ConfigSource::Env)
+    } else if let Some(v) = file {
+        (v, ConfigSource::File)
+    } else {
+        (default, ConfigSource::Default)
+    }
+}
+
+fn resolve_string(key: &str, file: Option<String>, default: String) -> (String, ConfigSource) {
+    if let Ok(v) = std::env::var(key) {
+        (v, ConfigSource::Env)
+    } else if let Some(v) = file {
+        (v, ConfigSource::File)
+    } else {
+        (default, ConfigSource::Default)
+    }
+}
+
+fn resolve_opt_string(key: &str, file: Option<String>) -> (Option<String>, ConfigSource) {
+    if let Ok(v) = std::env::var(key) {
+        (Some(v), ConfigSource::Env)
+    } else if file.is_some() {
+        (file, ConfigSource::File)
+    } else {
+        (None, ConfigSource::Default)
+    }
+}
+
+/// Like [`resolve_opt_string`], but for a `u64` field with no default value
+/// of its own — an unset env var falls through to the file layer, and an
+/// unset (or unparseable) file value leaves it `None` rather than a
+/// sentinel like `0`.
+fn resolve_opt_u64(key: &str, file: Option<u64>) -> (Option<u64>, ConfigSource) {
+    if let Some(v) = std::env::var(key).ok().and_then(|v| v.parse().ok()) {
+        (Some(v), ConfigSource::Env)
+    } else if file.is_some() {
+        (file, ConfigSource::File)
+    } else {
+        (None, ConfigSource::Default)
+    }
+}
+
+fn resolve_list(key: &str, file: Option<Vec<String>>, default: Vec<String>) -> (Vec<String>, ConfigSource) {
+    if let Some(v) = std::env::var(key).ok().map(|v| v.split(',').map(|s| s.trim().to_string()).collect()) {
+        (v, ConfigSource::Env)
+    } else if let Some(v) = file {
+        (v, ConfigSource::File)
+    } else {
+        (default, ConfigSource::Default)
+    }
+}
+
+/// Resolve `[[listener]]` entries (synth-1159) from the file layer. Unlike
+/// the other `resolve_*` helpers there's no env var to check — an
+/// array-of-tables has no single-value env-var equivalent — so this is
+/// purely "file present or not".
+fn resolve_listeners(file: Option<Vec<RawListenerConfig>>) -> (Vec<ListenerConfig>, ConfigSource) {
+    match file {
+        Some(entries) => {
+            let listeners = entries
+                .into_iter()
+                .map(|entry| ListenerConfig {
+                    path: PathBuf::from(entry.path),
+                    mode: entry.mode.and_then(|m| parse_octal_mode(&m)).unwrap_or(0o600),
+                    default_capability_ceiling: entry
+                        .default_capability_ceiling
+                        .unwrap_or_else(default_capability_ceiling),
+                    max_connections: entry.max_connections,
+                    encoding: entry
+                        .encoding
+                        .as_deref()
+                        .and_then(crate::protocol::PayloadEncoding::from_name)
+                        .unwrap_or_default(),
+                })
+                .collect();
+            (listeners, ConfigSource::File)
+        }
+        None => (Vec::new(), ConfigSource::Default),
+    }
+}
+
+/// Parse a socket file mode (synth-1154) given as an octal string, with or
+/// without the conventional leading `0`/`0o` (e.g. `"600"`, `"0600"`,
+/// `"0o600"` all mean owner read/write only). Returns `None` on anything
+/// else so the caller can warn and fall back to the default.
+fn parse_octal_mode(raw: &str) -> Option<u32> {
+    let trimmed = raw.trim().trim_start_matches("0o");
+    if trimmed.is_empty() {
+        return Some(0);
+    }
+    u32::from_str_radix(trimmed, 8).ok()
+}
+
+/// Decode a list of base64-encoded Ed25519 public keys (synth-1141) into raw
+/// key bytes. An entry that fails to base64-decode is dropped with a warning
+/// rather than failing startup — one malformed key shouldn't take down the
+/// sidecar when the rest of the list is usable.
+fn load_signing_keys(raw: Vec<String>) -> Vec<Vec<u8>> {
+    raw.iter()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .filter_map(|encoded| {
+            use base64::Engine;
+            match base64::engine::general_purpose::STANDARD.decode(encoded) {
+                Ok(bytes) => Some(bytes),
+                Err(e) => {
+                    eprintln!("sidecar: warning: failed to decode tool signing key: {e}");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Parse `SENTINEL_SIDECAR_ALLOWED_UIDS`/`allowed_uids` entries (synth-1170)
+/// into numeric UIDs, warning and skipping (rather than failing startup)
+/// on an entry that isn't a valid `u32` — same tolerance `load_signing_keys`
+/// gives a malformed entry in its own CSV-ish list.
+fn parse_uids(raw: Vec<String>) -> Vec<u32> {
+    raw.iter()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .filter_map(|s| match s.parse::<u32>() {
+            Ok(uid) => Some(uid),
+            Err(e) => {
+                eprintln!("sidecar: warning: failed to parse allowed uid '{s}': {e}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Load `[profiles]` capability bundles from a TOML file (synth-1121), e.g.:
+///
+/// ```toml
+/// [profiles]
+/// readonly = ["read_file"]
+/// web = ["http_request", "use_credential"]
+/// ```
+///
+/// A missing path, unreadable file, or parse error yields an empty profile
+/// map with a warning — profiles are an optional convenience, not something
+/// that should prevent the sidecar from starting.
+fn load_profiles(path: Option<String>) -> HashMap<String, Vec<String>> {
+    let Some(path) = path else {
+        return HashMap::new();
+    };
+    let content = match std::fs::read_to_string(&path) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("sidecar: warning: failed to read profiles file {path}: {e}");
+            return HashMap::new();
+        }
+    };
+
+    #[derive(serde::Deserialize)]
+    struct ProfilesFile {
+        #[serde(default)]
+        profiles: HashMap<String, Vec<String>>,
+    }
+
+    match toml::from_str::<ProfilesFile>(&content) {
+        Ok(parsed) => parsed.profiles,
+        Err(e) => {
+            eprintln!("sidecar: warning: failed to parse profiles file {path}: {e}");
+            HashMap::new()
+        }
+    }
+}
+
+/// Partially-specified config as parsed from a TOML file (synth-1148). Every
+/// field is optional so [`SidecarConfig::merge`] can tell "not set in the
+/// file" apart from "set to the same value as the default", which matters
+/// for the defaults → file → env precedence: an env var must be able to
+/// override a value the file left unset, and a value the file *did* set must
+/// win over the hardcoded default.
+#[derive(Debug, Default, serde::Deserialize)]
+struct RawFileConfig {
+    #[serde(default)]
+    max_memory_bytes: Option<u64>,
+    #[serde(default)]
+    max_fuel: Option<u64>,
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+    #[serde(default)]
+    tool_dir: Option<String>,
+    #[serde(default)]
+    allowed_paths: Option<Vec<String>>,
+    #[serde(default)]
+    http_default_timeout_ms: Option<u64>,
+    #[serde(default)]
+    http_max_response_bytes: Option<u64>,
+    #[serde(default)]
+    shell_timeout_ms: Option<u64>,
+    #[serde(default)]
+    shell_max_output_bytes: Option<u64>,
+    /// `[shell]` table (synth-1160) — see [`crate::host_functions::ShellPolicy`].
+    #[serde(default)]
+    shell: Option<RawShellConfig>,
+    #[serde(default)]
+    max_timeout_ms: Option<u64>,
+    #[serde(default)]
+    max_request_fuel: Option<u64>,
+    #[serde(default)]
+    max_request_http_timeout_ms: Option<u64>,
+    #[serde(default)]
+    request_override_policy: Option<String>,
+    #[serde(default)]
+    epoch_tick_ms: Option<u64>,
+    #[serde(default)]
+    stdout_max_bytes: Option<u64>,
+    #[serde(default)]
+    dns_timeout_s: Option<u64>,
+    #[serde(default)]
+    redaction_style: Option<String>,
+    #[serde(default)]
+    leak_log_path: Option<String>,
+    #[serde(default)]
+    leak_deep_scan: Option<bool>,
+    /// Path to the `[profiles]` file (synth-1121) — named `profiles_file`
+    /// rather than nesting the profiles inline, since the profiles file is
+    /// itself independently reloadable and has its own env var,
+    /// `SENTINEL_SIDECAR_PROFILES_FILE`.
+    #[serde(default)]
+    profiles_file: Option<String>,
+    #[serde(default)]
+    default_capability_ceiling: Option<Vec<String>>,
+    #[serde(default)]
+    require_tool_hash: Option<bool>,
+    #[serde(default)]
+    tool_signing_keys: Option<Vec<String>>,
+    #[serde(default)]
+    require_signed_tools: Option<bool>,
+    #[serde(default)]
+    allow_precompiled: Option<bool>,
+    #[serde(default)]
+    output_schema_warn_only: Option<bool>,
+    #[serde(default)]
+    metrics_enabled: Option<bool>,
+    #[serde(default)]
+    http_allowlist: Option<Vec<String>>,
+    #[serde(default)]
+    http_allow_http: Option<bool>,
+    #[serde(default)]
+    socket_path: Option<String>,
+    /// Octal permission string (e.g. `"0600"`), not a bare integer — TOML
+    /// has no octal literal and a decimal `600` would silently mean
+    /// something else (`0o1130`).
+    #[serde(default)]
+    socket_mode: Option<String>,
+    #[serde(default)]
+    socket_group: Option<String>,
+    #[serde(default)]
+    credential_provider: Option<String>,
+    #[serde(default)]
+    credential_env_prefix: Option<String>,
+    #[serde(default)]
+    credential_file_path: Option<String>,
+    #[serde(default)]
+    credential_exec_command: Option<String>,
+    #[serde(default)]
+    credential_provider_only: Option<bool>,
+    /// `host:port` for the optional TCP listener (synth-1169) — see
+    /// [`crate::config::SidecarConfig::tcp_addr`].
+    #[serde(default)]
+    tcp_addr: Option<String>,
+    #[serde(default)]
+    tcp_auth_token: Option<String>,
+    #[serde(default)]
+    tcp_tls_cert_path: Option<String>,
+    #[serde(default)]
+    tcp_tls_key_path: Option<String>,
+    /// UIDs allowed on the Unix socket (synth-1170) — see
+    /// [`crate::config::SidecarConfig::allowed_uids`].
+    #[serde(default)]
+    allowed_uids: Option<Vec<String>>,
+    /// `[[listener]]` array-of-tables (synth-1159) — see
+    /// [`crate::config::ListenerConfig`].
+    #[serde(default)]
+    listener: Option<Vec<RawListenerConfig>>,
+    /// `profile` key (synth-1161) — see [`crate::config::LimitsProfile`].
+    #[serde(default)]
+    profile: Option<String>,
+    /// `max_concurrent_executions` key (synth-1164) — see
+    /// [`crate::config::SidecarConfig::max_concurrent_executions`].
+    #[serde(default)]
+    max_concurrent_executions: Option<u64>,
+    /// `drain_timeout_secs` key (synth-1171) — see
+    /// [`crate::config::SidecarConfig::drain_timeout_secs`].
+    #[serde(default)]
+    drain_timeout_secs: Option<u64>,
+    /// `max_request_bytes` key (synth-1172) — see
+    /// [`crate::config::SidecarConfig::max_request_bytes`].
+    #[serde(default)]
+    max_request_bytes: Option<u64>,
+    /// `max_connections` key (synth-1173) — see
+    /// [`crate::config::SidecarConfig::max_connections`].
+    #[serde(default)]
+    max_connections: Option<u64>,
+    /// `idle_timeout_secs` key (synth-1173) — see
+    /// [`crate::config::SidecarConfig::idle_timeout_secs`].
+    #[serde(default)]
+    idle_timeout_secs: Option<u64>,
+    /// `strict_requests` key (synth-1177) — see
+    /// [`crate::config::SidecarConfig::strict_requests`].
+    #[serde(default)]
+    strict_requests: Option<bool>,
+    /// `max_batch_size` key (synth-1183) — see
+    /// [`crate::config::SidecarConfig::max_batch_size`].
+    #[serde(default)]
+    max_batch_size: Option<u64>,
+    /// `max_chunked_transfer_bytes` key (synth-1185) — see
+    /// [`crate::config::SidecarConfig::max_chunked_transfer_bytes`].
+    #[serde(default)]
+    max_chunked_transfer_bytes: Option<u64>,
+    /// `chunk_transfer_ttl_ms` key (synth-1185) — see
+    /// [`crate::config::SidecarConfig::chunk_transfer_ttl_ms`].
+    #[serde(default)]
+    chunk_transfer_ttl_ms: Option<u64>,
+}
+
+/// `[shell]` table as parsed from TOML (synth-1160), before defaults are
+/// applied by [`SidecarConfig::merge`]. `timeout`/`max_output` here are
+/// only consulted when the legacy flat `shell_timeout_ms`/
+/// `shell_max_output_bytes` keys are absent — see the comment in `merge`.
+#[derive(Debug, Default, serde::Deserialize)]
+struct RawShellConfig {
+    #[serde(default)]
+    timeout: Option<u64>,
+    #[serde(default)]
+    max_output: Option<u64>,
+    #[serde(default)]
+    allowlist: Option<Vec<String>>,
+    #[serde(default)]
+    denylist: Option<Vec<String>>,
+    #[serde(default)]
+    env_passthrough: Option<Vec<String>>,
+    #[serde(default)]
+    allow_sh_c: Option<bool>,
+    #[serde(default)]
+    default_cwd: Option<String>,
+}
+
+/// One `[[listener]]` table as parsed from TOML (synth-1159), before
+/// defaults are applied by [`resolve_listeners`]. `path` is required — a
+/// listener entry without one is a broken config, not a "use the default"
+/// case, so it's a bare `String` rather than `Option<String>` and a missing
+/// value fails parsing with serde's own "missing field" error.
+#[derive(Debug, serde::Deserialize)]
+struct RawListenerConfig {
+    path: String,
+    /// Octal permission string, same convention as [`RawFileConfig::socket_mode`].
+    #[serde(default)]
+    mode: Option<String>,
+    #[serde(default)]
+    default_capability_ceiling: Option<Vec<String>>,
+    #[serde(default)]
+    max_connections: Option<usize>,
+    /// `"json"` or `"msgpack"` (synth-1175) — see
+    /// [`crate::protocol::PayloadEncoding`]. An unrecognized value falls back
+    /// to `Json`, same convention as an unparseable `mode`.
+    #[serde(default)]
+    encoding: Option<String>,
+}
+
+impl RawFileConfig {
+    /// Every key this struct understands, for unknown-key detection in
+    /// [`read_raw_file_config`]. Kept as an explicit list (Rust has no
+    /// reflection to derive it) so a new field added above without a
+    /// matching entry here just gets treated as unknown — caught immediately
+    /// by the full-example-config test below.
+    const FIELD_NAMES: &'static [&'static str] = &[
+        "max_memory_bytes",
+        "max_fuel",
+        "timeout_ms",
+        "tool_dir",
+        "allowed_paths",
+        "http_default_timeout_ms",
+        "http_max_response_bytes",
+        "shell_timeout_ms",
+        "shell_max_output_bytes",
+        "max_timeout_ms",
+        "max_request_fuel",
+        "max_request_http_timeout_ms",
+        "request_override_policy",
+        "epoch_tick_ms",
+        "stdout_max_bytes",
+        "dns_timeout_s",
+        "redaction_style",
+        "leak_log_path",
+        "leak_deep_scan",
+        "profiles_file",
+        "default_capability_ceiling",
+        "require_tool_hash",
+        "tool_signing_keys",
+        "require_signed_tools",
+        "allow_precompiled",
+        "output_schema_warn_only",
+        "metrics_enabled",
+        "http_allowlist",
+        "http_allow_http",
+        "socket_path",
+        "socket_mode",
+        "socket_group",
+        "credential_provider",
+        "credential_env_prefix",
+        "credential_file_path",
+        "credential_exec_command",
+        "credential_provider_only",
+        "tcp_addr",
+        "tcp_auth_token",
+        "tcp_tls_cert_path",
+        "tcp_tls_key_path",
+        "allowed_uids",
+        "listener",
+        "shell",
+        "profile",
+        "max_concurrent_executions",
+        "drain_timeout_secs",
+        "max_request_bytes",
+        "max_connections",
+        "idle_timeout_secs",
+        "strict_requests",
+        "max_batch_size",
+        "max_chunked_transfer_bytes",
+        "chunk_transfer_ttl_ms",
+    ];
+}
+
+/// Read and parse `path` into a [`RawFileConfig`] (synth-1148). Every
+/// top-level key not in [`RawFileConfig::FIELD_NAMES`] — most likely a typo
+/// — is a warning, or under `strict` a hard error, since it would otherwise
+/// silently do nothing.
+fn read_raw_file_config(path: &Path, strict: bool) -> anyhow::Result<RawFileConfig> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("failed to read config file {}: {e}", path.display()))?;
+    let value: toml::Value = toml::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("failed to parse config file {}: {e}", path.display()))?;
+    if let Some(table) = value.as_table() {
+        for key in table.keys() {
+            if !RawFileConfig::FIELD_NAMES.contains(&key.as_str()) {
+                let message = format!("config file {} has unknown key '{key}'", path.display());
+                if strict {
+                    anyhow::bail!(message);
+                }
+                eprintln!("sidecar: warning: {message}");
+            }
+        }
+    }
+    value
+        .try_into()
+        .map_err(|e| anyhow::anyhow!("failed to parse config file {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // `std::env::set_var` affects the whole process, so tests that touch env
+    // vars serialize against this lock to avoid racing each other.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Writes `content` to `<tmp>/sentinel_test_config_<name>.toml`, matching
+    /// the `std::env::temp_dir()`-based fixture style the rest of the crate's
+    /// tests already use (see `registry.rs`), rather than pulling in a
+    /// dedicated tempfile crate just for this file.
+    fn write_temp_toml(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("sentinel_test_config_{name}.toml"));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn from_file_parses_a_full_example_config_end_to_end() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml(
+            "full_example",
+            r#"
+            max_memory_bytes = 33554432
+            max_fuel = 500000000
+            timeout_ms = 15000
+            tool_dir = "/opt/sentinel/tools"
+            allowed_paths = ["/data", "/tmp/sentinel"]
+            http_default_timeout_ms = 5000
+            http_max_response_bytes = 1048576
+            shell_timeout_ms = 5000
+            shell_max_output_bytes = 65536
+            max_timeout_ms = 60000
+            max_request_fuel = 2000000000
+            max_request_http_timeout_ms = 120000
+            request_override_policy = "reject"
+            epoch_tick_ms = 250
+            stdout_max_bytes = 65536
+            dns_timeout_s = 2
+            redaction_style = "mask"
+            leak_log_path = "/var/log/sentinel/leaks.jsonl"
+            leak_deep_scan = true
+            profiles_file = "/opt/sentinel/profiles.toml"
+            default_capability_ceiling = ["read_file"]
+            require_tool_hash = true
+            tool_signing_keys = []
+            require_signed_tools = false
+            allow_precompiled = true
+            output_schema_warn_only = true
+            metrics_enabled = true
+            http_allowlist = ["*.example.com"]
+            http_allow_http = true
+            socket_path = "/run/sentinel/sidecar.sock"
+            socket_mode = "0640"
+            socket_group = "sentinel"
+            credential_provider = "file"
+            credential_env_prefix = "CRED_"
+            credential_file_path = "/opt/sentinel/credentials.json"
+            credential_exec_command = "get-secrets"
+            credential_provider_only = true
+            tcp_addr = "127.0.0.1:7801"
+            tcp_auth_token = "s3cr3t"
+            tcp_tls_cert_path = "/opt/sentinel/tls/cert.pem"
+            tcp_tls_key_path = "/opt/sentinel/tls/key.pem"
+            allowed_uids = ["0", "1000"]
+            max_concurrent_executions = 8
+            drain_timeout_secs = 30
+            max_request_bytes = 1048576
+            max_connections = 512
+            idle_timeout_secs = 120
+            max_batch_size = 64
+            max_chunked_transfer_bytes = 16777216
+            chunk_transfer_ttl_ms = 45000
+
+            [[listener]]
+            path = "/run/sentinel/controller.sock"
+            mode = "0600"
+            default_capability_ceiling = ["read_file", "write_file", "shell_exec"]
+
+            [[listener]]
+            path = "/run/sentinel/debug-ui.sock"
+            mode = "0666"
+            default_capability_ceiling = ["read_file"]
+            max_connections = 4
+            encoding = "msgpack"
+
+            [shell]
+            allowlist = ["ls", "cat"]
+            denylist = ["rm"]
+            env_passthrough = ["PATH", "HOME"]
+            allow_sh_c = false
+            default_cwd = "/opt/sentinel/work"
+            "#,
+        );
+
+        let config = SidecarConfig::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(config.max_memory_bytes, 33554432);
+        assert_eq!(config.max_fuel, 500000000);
+        assert_eq!(config.timeout_ms, 15000);
+        assert_eq!(config.tool_dir, PathBuf::from("/opt/sentinel/tools"));
+        assert_eq!(config.allowed_paths, vec!["/data".to_string(), "/tmp/sentinel".to_string()]);
+        assert_eq!(config.http_default_timeout_ms, 5000);
+        assert_eq!(config.http_max_response_bytes, 1048576);
+        assert_eq!(config.shell_timeout_ms, 5000);
+        assert_eq!(config.shell_max_output_bytes, 65536);
+        assert_eq!(config.max_timeout_ms, 60000);
+        assert_eq!(config.max_request_fuel, 2000000000);
+        assert_eq!(config.max_request_http_timeout_ms, 120000);
+        assert_eq!(config.request_override_policy, RequestOverridePolicy::Reject);
+        assert_eq!(config.epoch_tick_ms, 250);
+        assert_eq!(config.stdout_max_bytes, 65536);
+        assert_eq!(config.dns_timeout_s, 2);
+        assert_eq!(config.leak_log_path, Some(PathBuf::from("/var/log/sentinel/leaks.jsonl")));
+        assert!(config.leak_deep_scan);
+        assert_eq!(config.default_capability_ceiling, vec!["read_file".to_string()]);
+        assert!(config.require_tool_hash);
+        assert!(!config.require_signed_tools);
+        assert!(config.allow_precompiled);
+        assert!(config.output_schema_warn_only);
+        assert!(config.metrics_enabled);
+        assert_eq!(config.http_allowlist, vec!["*.example.com".to_string()]);
+        assert!(config.http_allow_http);
+        assert_eq!(config.socket_path, PathBuf::from("/run/sentinel/sidecar.sock"));
+        assert_eq!(config.socket_mode, 0o640);
+        assert_eq!(config.socket_group, Some("sentinel".to_string()));
+        assert_eq!(config.credential_provider, CredentialProviderKind::File);
+        assert_eq!(config.credential_env_prefix, "CRED_".to_string());
+        assert_eq!(config.credential_file_path, Some(PathBuf::from("/opt/sentinel/credentials.json")));
+        assert_eq!(config.credential_exec_command, Some("get-secrets".to_string()));
+        assert!(config.credential_provider_only);
+        assert_eq!(config.tcp_addr, Some("127.0.0.1:7801".to_string()));
+        assert_eq!(config.tcp_auth_token, Some("s3cr3t".to_string()));
+        assert_eq!(config.tcp_tls_cert_path, Some(PathBuf::from("/opt/sentinel/tls/cert.pem")));
+        assert_eq!(config.tcp_tls_key_path, Some(PathBuf::from("/opt/sentinel/tls/key.pem")));
+        assert_eq!(config.allowed_uids, vec![0, 1000]);
+        assert_eq!(config.max_concurrent_executions, 8);
+        assert_eq!(config.drain_timeout_secs, 30);
+        assert_eq!(config.max_request_bytes, 1048576);
+        assert_eq!(config.max_connections, Some(512));
+        assert_eq!(config.idle_timeout_secs, 120);
+        assert_eq!(config.max_batch_size, 64);
+        assert_eq!(config.max_chunked_transfer_bytes, 16777216);
+        assert_eq!(config.chunk_transfer_ttl_ms, 45000);
+        assert_eq!(
+            config.listeners,
+            vec![
+                ListenerConfig {
+                    path: PathBuf::from("/run/sentinel/controller.sock"),
+                    mode: 0o600,
+                    default_capability_ceiling: vec![
+                        "read_file".to_string(),
+                        "write_file".to_string(),
+                        "shell_exec".to_string(),
+                    ],
+                    max_connections: None,
+                    encoding: crate::protocol::PayloadEncoding::Json,
+                },
+                ListenerConfig {
+                    path: PathBuf::from("/run/sentinel/debug-ui.sock"),
+                    mode: 0o666,
+                    default_capability_ceiling: vec!["read_file".to_string()],
+                    max_connections: Some(4),
+                    encoding: crate::protocol::PayloadEncoding::MsgPack,
+                },
+            ]
+        );
+        assert_eq!(config.shell_allowlist, vec!["ls".to_string(), "cat".to_string()]);
+        assert_eq!(config.shell_denylist, vec!["rm".to_string()]);
+        assert_eq!(
+            config.shell_env_passthrough,
+            vec!["PATH".to_string(), "HOME".to_string()]
+        );
+        assert!(!config.shell_allow_sh_c);
+        assert_eq!(config.shell_default_cwd, Some(PathBuf::from("/opt/sentinel/work")));
+    }
+
+    #[test]
+    fn listeners_are_empty_by_default_preserving_the_single_socket_behavior() {
+        assert!(SidecarConfig::default().listeners.is_empty());
+    }
+
+    #[test]
+    fn a_listener_missing_a_default_ceiling_falls_back_to_every_capability() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml(
+            "listener_default_ceiling",
+            r#"
+            [[listener]]
+            path = "/run/sentinel/plain.sock"
+            "#,
+        );
+        let config = SidecarConfig::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(config.listeners.len(), 1);
+        assert_eq!(config.listeners[0].mode, 0o600);
+        assert_eq!(config.listeners[0].max_connections, None);
+        assert_eq!(config.listeners[0].default_capability_ceiling, default_capability_ceiling());
+    }
+
+    #[test]
+    fn validate_rejects_a_listener_whose_parent_directory_does_not_exist() {
+        let config = SidecarConfig {
+            listeners: vec![ListenerConfig {
+                path: PathBuf::from("/no/such/dir/sentinel.sock"),
+                mode: 0o600,
+                default_capability_ceiling: default_capability_ceiling(),
+                max_connections: None,
+                encoding: crate::protocol::PayloadEncoding::Json,
+            }],
+            ..SidecarConfig::default()
+        };
+        let errors = config.validate().unwrap_err();
+        assert!(errors.iter().any(|e| e.field == "listener[0].path"));
+    }
+
+    #[test]
+    fn shell_policy_fields_default_to_the_pre_synth_1160_behavior() {
+        let config = SidecarConfig::default();
+        assert!(config.shell_allowlist.is_empty());
+        assert!(config.shell_denylist.is_empty());
+        assert!(config.shell_env_passthrough.is_empty());
+        assert!(config.shell_allow_sh_c);
+        assert!(config.shell_default_cwd.is_none());
+    }
+
+    #[test]
+    fn validate_rejects_a_name_in_both_shell_allowlist_and_shell_denylist() {
+        let config = SidecarConfig {
+            shell_allowlist: vec!["rm".to_string()],
+            shell_denylist: vec!["rm".to_string()],
+            ..SidecarConfig::default()
+        };
+        let errors = config.validate().unwrap_err();
+        assert!(errors.iter().any(|e| e.field == "shell_denylist"));
+    }
+
+    #[test]
+    fn legacy_flat_shell_timeout_ms_wins_over_the_shell_table() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml(
+            "shell_flat_wins_over_table",
+            r#"
+            shell_timeout_ms = 4242
+            [shell]
+            timeout = 9999
+            "#,
+        );
+        let config = SidecarConfig::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(config.shell_timeout_ms, 4242);
+    }
+
+    #[test]
+    fn shell_table_timeout_is_used_when_the_flat_key_is_absent() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml(
+            "shell_table_timeout_alone",
+            r#"
+            [shell]
+            timeout = 9999
+            "#,
+        );
+        let config = SidecarConfig::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(config.shell_timeout_ms, 9999);
+    }
+
+    #[test]
+    fn unset_profile_defaults_to_balanced_and_changes_nothing() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml("profile_unset", "");
+        let config = SidecarConfig::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(config.profile, LimitsProfile::Balanced);
+        assert_eq!(config.max_memory_bytes, profile_defaults(LimitsProfile::Balanced).max_memory_bytes);
+        assert_eq!(config.effective_settings()["max_memory_bytes"]["source"], serde_json::json!("default"));
+    }
+
+    #[test]
+    fn strict_profile_applies_its_bundled_defaults() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml("profile_strict", "profile = \"strict\"\n");
+        let config = SidecarConfig::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let expected = profile_defaults(LimitsProfile::Strict);
+        assert_eq!(config.profile, LimitsProfile::Strict);
+        assert_eq!(config.max_memory_bytes, expected.max_memory_bytes);
+        assert_eq!(config.max_fuel, expected.max_fuel);
+        assert_eq!(config.timeout_ms, expected.timeout_ms);
+        assert_eq!(config.http_default_timeout_ms, expected.http_default_timeout_ms);
+        assert_eq!(config.http_max_response_bytes, expected.http_max_response_bytes);
+        assert_eq!(config.shell_timeout_ms, expected.shell_timeout_ms);
+        assert_eq!(config.shell_max_output_bytes, expected.shell_max_output_bytes);
+        assert_eq!(config.leak_deep_scan, expected.leak_deep_scan);
+        assert_eq!(config.effective_settings()["max_memory_bytes"]["source"], serde_json::json!("profile"));
+    }
+
+    #[test]
+    fn permissive_profile_applies_its_bundled_defaults() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml("profile_permissive", "profile = \"permissive\"\n");
+        let config = SidecarConfig::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let expected = profile_defaults(LimitsProfile::Permissive);
+        assert_eq!(config.max_memory_bytes, expected.max_memory_bytes);
+        assert_eq!(config.max_fuel, expected.max_fuel);
+        assert_eq!(config.shell_max_output_bytes, expected.shell_max_output_bytes);
+    }
+
+    #[test]
+    fn a_field_set_explicitly_still_overrides_its_profile() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml(
+            "profile_explicit_override",
+            "profile = \"strict\"\nmax_memory_bytes = 99999999\n",
+        );
+        let config = SidecarConfig::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(config.max_memory_bytes, 99999999);
+        assert_eq!(config.effective_settings()["max_memory_bytes"]["source"], serde_json::json!("file"));
+        // Fields the file didn't touch still take the profile's bundle.
+        assert_eq!(config.max_fuel, profile_defaults(LimitsProfile::Strict).max_fuel);
+    }
+
+    #[test]
+    fn profile_env_var_overrides_file_value() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml("profile_env_override", "profile = \"strict\"\n");
+        std::env::set_var("SENTINEL_SIDECAR_PROFILE", "permissive");
+        let config = SidecarConfig::from_file(&path).unwrap();
+        std::env::remove_var("SENTINEL_SIDECAR_PROFILE");
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(config.profile, LimitsProfile::Permissive);
+    }
+
+    #[test]
+    fn http_allowlist_env_var_overrides_file_value() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml("http_allowlist_env_override", "http_allowlist = [\"file.example.com\"]\n");
+        std::env::set_var("SENTINEL_SIDECAR_HTTP_ALLOWLIST", "env.example.com,other.example.com");
+        let config = SidecarConfig::from_file(&path).unwrap();
+        std::env::remove_var("SENTINEL_SIDECAR_HTTP_ALLOWLIST");
+        std::fs::remove_file(&path).ok();
+        assert_eq!(config.http_allowlist, vec!["env.example.com".to_string(), "other.example.com".to_string()]);
+    }
+
+    #[test]
+    fn http_allowlist_defaults_to_empty() {
+        let config = SidecarConfig::default();
+        assert!(config.http_allowlist.is_empty());
+        assert!(!config.http_allow_http);
+    }
+
+    #[test]
+    fn from_file_reports_missing_file() {
+        let result = SidecarConfig::from_file(Path::new("/nonexistent/sentinel-config.toml"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn from_file_reports_unparseable_toml() {
+        let path = write_temp_toml("unparseable", "this is not valid toml {{{");
+        let result = SidecarConfig::from_file(&path);
+        std::fs::remove_file(&path).ok();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn from_file_warns_but_succeeds_on_unknown_key_by_default() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("SENTINEL_SIDECAR_CONFIG_STRICT");
+        let path = write_temp_toml("unknown_key_warn", "max_fuel = 123\ntotally_unknown_setting = true\n");
+        let config = SidecarConfig::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(config.max_fuel, 123);
+    }
+
+    #[test]
+    fn from_file_rejects_unknown_key_under_strict_mode() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("SENTINEL_SIDECAR_CONFIG_STRICT", "true");
+        let path = write_temp_toml("unknown_key_strict", "totally_unknown_setting = true\n");
+        let result = SidecarConfig::from_file(&path);
+        std::env::remove_var("SENTINEL_SIDECAR_CONFIG_STRICT");
+        std::fs::remove_file(&path).ok();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn env_var_overrides_file_value() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml("env_overrides_file", "max_fuel = 111\ntimeout_ms = 222\n");
+        std::env::set_var("SENTINEL_SIDECAR_MAX_FUEL", "999");
+        let config = SidecarConfig::from_file(&path).unwrap();
+        std::env::remove_var("SENTINEL_SIDECAR_MAX_FUEL");
+        std::fs::remove_file(&path).ok();
+
+        // Overridden by the env var...
+        assert_eq!(config.max_fuel, 999);
+        // ...but a field the env var didn't touch still comes from the file.
+        assert_eq!(config.timeout_ms, 222);
+    }
+
+    #[test]
+    fn file_value_overrides_hardcoded_default() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml("file_overrides_default", "shell_timeout_ms = 4242\n");
+        let config = SidecarConfig::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(config.shell_timeout_ms, 4242);
+        // A field left unset in the file still falls back to the default.
+        assert_eq!(config.timeout_ms, SidecarConfig::default().timeout_ms);
+    }
+
+    #[test]
+    fn from_env_without_config_var_set_uses_defaults() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("SENTINEL_SIDECAR_CONFIG");
+        let config = SidecarConfig::from_env();
+        assert_eq!(config.max_fuel, SidecarConfig::default().max_fuel);
+    }
+
+    #[test]
+    fn from_env_loads_file_named_by_config_env_var() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml("from_env_config_var", "max_fuel = 77\n");
+        std::env::set_var("SENTINEL_SIDECAR_CONFIG", &path);
+        let config = SidecarConfig::from_env();
+        std::env::remove_var("SENTINEL_SIDECAR_CONFIG");
+        std::fs::remove_file(&path).ok();
+        assert_eq!(config.max_fuel, 77);
+    }
+
+    /// A config guaranteed to pass `validate()`, for tests that only care
+    /// about one specific violation — the hardcoded default's `tool_dir`
+    /// (`./wasm`) and `allowed_paths` (`/workspace`) don't necessarily exist
+    /// wherever the test suite runs.
+    fn valid_config_with_tmp_tool_dir(name: &str) -> SidecarConfig {
+        let tool_dir = std::env::temp_dir().join(format!("sentinel_test_config_tool_dir_{name}"));
+        std::fs::create_dir_all(&tool_dir).unwrap();
+        SidecarConfig {
+            tool_dir,
+            allowed_paths: vec![std::env::temp_dir().to_string_lossy().into_owned()],
+            ..SidecarConfig::default()
+        }
+    }
+
+    #[test]
+    fn validate_passes_on_an_otherwise_default_config() {
+        let config = valid_config_with_tmp_tool_dir("passes");
+        let result = config.validate();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_unparseable_numeric_env_var() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("SENTINEL_SIDECAR_MAX_FUEL", "1,000,000");
+        let config = valid_config_with_tmp_tool_dir("bad_numeric_env");
+        let result = config.validate();
+        std::env::remove_var("SENTINEL_SIDECAR_MAX_FUEL");
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+
+        let errors = result.unwrap_err();
+        assert!(errors.iter().any(|e| e.field == "SENTINEL_SIDECAR_MAX_FUEL"));
+    }
+
+    #[test]
+    fn validate_accepts_unset_numeric_env_var() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("SENTINEL_SIDECAR_MAX_FUEL");
+        let config = valid_config_with_tmp_tool_dir("unset_numeric_env");
+        let result = config.validate();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_nonexistent_tool_dir() {
+        let config = SidecarConfig { tool_dir: PathBuf::from("/nonexistent/sentinel-tools"), ..SidecarConfig::default() };
+        let errors = config.validate().unwrap_err();
+        assert!(errors.iter().any(|e| e.field == "tool_dir"));
+    }
+
+    #[test]
+    fn validate_rejects_relative_allowed_path() {
+        let mut config = valid_config_with_tmp_tool_dir("relative_allowed_path");
+        config.allowed_paths = vec!["relative/dir".to_string()];
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "allowed_paths" && e.message.contains("absolute")));
+    }
+
+    #[test]
+    fn validate_rejects_nonexistent_allowed_path() {
+        let mut config = valid_config_with_tmp_tool_dir("missing_allowed_path");
+        config.allowed_paths = vec!["/nonexistent/sentinel-allowed-path".to_string()];
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "allowed_paths" && e.message.contains("does not exist")));
+    }
+
+    #[test]
+    fn validate_rejects_zero_timeout_ms() {
+        let mut config = valid_config_with_tmp_tool_dir("zero_timeout");
+        config.timeout_ms = 0;
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "timeout_ms"));
+    }
+
+    #[test]
+    fn validate_rejects_zero_max_timeout_ms() {
+        let mut config = valid_config_with_tmp_tool_dir("zero_max_timeout");
+        config.max_timeout_ms = 0;
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "max_timeout_ms"));
+    }
+
+    #[test]
+    fn validate_rejects_zero_max_concurrent_executions() {
+        let mut config = valid_config_with_tmp_tool_dir("zero_max_concurrent_executions");
+        config.max_concurrent_executions = 0;
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "max_concurrent_executions"));
+    }
+
+    #[test]
+    fn validate_rejects_zero_max_request_bytes() {
+        let mut config = valid_config_with_tmp_tool_dir("zero_max_request_bytes");
+        config.max_request_bytes = 0;
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "max_request_bytes"));
+    }
+
+    #[test]
+    fn validate_rejects_zero_idle_timeout_secs() {
+        let mut config = valid_config_with_tmp_tool_dir("zero_idle_timeout_secs");
+        config.idle_timeout_secs = 0;
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "idle_timeout_secs"));
+    }
+
+    #[test]
+    fn validate_rejects_zero_max_batch_size() {
+        let mut config = valid_config_with_tmp_tool_dir("zero_max_batch_size");
+        config.max_batch_size = 0;
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "max_batch_size"));
+    }
+
+    #[test]
+    fn validate_rejects_zero_max_chunked_transfer_bytes() {
+        let mut config = valid_config_with_tmp_tool_dir("zero_max_chunked_transfer_bytes");
+        config.max_chunked_transfer_bytes = 0;
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "max_chunked_transfer_bytes"));
+    }
+
+    #[test]
+    fn validate_rejects_zero_chunk_transfer_ttl_ms() {
+        let mut config = valid_config_with_tmp_tool_dir("zero_chunk_transfer_ttl_ms");
+        config.chunk_transfer_ttl_ms = 0;
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "chunk_transfer_ttl_ms"));
+    }
+
+    #[test]
+    fn validate_rejects_max_timeout_ms_below_timeout_ms() {
+        let mut config = valid_config_with_tmp_tool_dir("max_timeout_below_default");
+        config.timeout_ms = 10_000;
+        config.max_timeout_ms = 5_000;
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "max_timeout_ms"));
+    }
+
+    #[test]
+    fn validate_rejects_max_request_fuel_below_max_fuel() {
+        let mut config = valid_config_with_tmp_tool_dir("max_request_fuel_below_default");
+        config.max_fuel = 10_000;
+        config.max_request_fuel = 5_000;
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "max_request_fuel"));
+    }
+
+    #[test]
+    fn validate_rejects_max_request_http_timeout_ms_below_http_default_timeout_ms() {
+        let mut config = valid_config_with_tmp_tool_dir("max_request_http_timeout_below_default");
+        config.http_default_timeout_ms = 10_000;
+        config.max_request_http_timeout_ms = 5_000;
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "max_request_http_timeout_ms"));
+    }
+
+    #[test]
+    fn validate_rejects_zero_http_default_timeout_ms() {
+        let mut config = valid_config_with_tmp_tool_dir("zero_http_timeout");
+        config.http_default_timeout_ms = 0;
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "http_default_timeout_ms"));
+    }
+
+    #[test]
+    fn validate_rejects_zero_shell_timeout_ms() {
+        let mut config = valid_config_with_tmp_tool_dir("zero_shell_timeout");
+        config.shell_timeout_ms = 0;
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "shell_timeout_ms"));
+    }
+
+    #[test]
+    fn validate_rejects_memory_below_one_mebibyte() {
+        let mut config = valid_config_with_tmp_tool_dir("tiny_memory");
+        config.max_memory_bytes = 1024;
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "max_memory_bytes"));
+    }
+
+    #[test]
+    fn validate_rejects_shell_max_output_above_sane_ceiling() {
+        let mut config = valid_config_with_tmp_tool_dir("huge_shell_output");
+        config.shell_max_output_bytes = MAX_SANE_OUTPUT_BYTES + 1;
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "shell_max_output_bytes"));
+    }
+
+    #[test]
+    fn validate_rejects_stdout_max_bytes_above_sane_ceiling() {
+        let mut config = valid_config_with_tmp_tool_dir("huge_stdout");
+        config.stdout_max_bytes = (MAX_SANE_OUTPUT_BYTES + 1) as usize;
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "stdout_max_bytes"));
+    }
+
+    #[test]
+    fn validate_collects_every_violation_in_one_pass() {
+        let mut config = SidecarConfig { tool_dir: PathBuf::from("/nonexistent/sentinel-tools"), ..SidecarConfig::default() };
+        config.timeout_ms = 0;
+        config.max_memory_bytes = 0;
+        let errors = config.validate().unwrap_err();
+        assert!(errors.len() >= 3);
+    }
+
+    #[test]
+    fn reload_reports_a_changed_reload_safe_field_as_applied() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml("reload_safe", "shell_timeout_ms = 999999\n");
+
+        let base = SidecarConfig::default();
+        let (reloaded, report) = base.reload(Some(&path)).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(reloaded.shell_timeout_ms, 999999);
+        assert_eq!(report.applied, vec!["shell_timeout_ms".to_string()]);
+        assert!(report.restart_required.is_empty());
+    }
+
+    #[test]
+    fn reload_reports_a_changed_restart_required_field_separately() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let tmp = std::env::temp_dir().join("sentinel_test_config_reload_restart_required");
+        std::fs::create_dir_all(&tmp).unwrap();
+        let path = write_temp_toml(
+            "reload_restart_required",
+            &format!("tool_dir = \"{}\"\n", tmp.to_string_lossy()),
+        );
+
+        let base = SidecarConfig::default();
+        let (reloaded, report) = base.reload(Some(&path)).unwrap();
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_dir_all(&tmp).ok();
+
+        assert_eq!(reloaded.tool_dir, tmp);
+        assert!(report.applied.is_empty());
+        assert_eq!(report.restart_required, vec!["tool_dir".to_string()]);
+    }
+
+    #[test]
+    fn reload_reports_no_changes_when_nothing_differs() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let base = SidecarConfig::default();
+        let (_reloaded, report) = base.reload(None).unwrap();
+
+        assert!(report.applied.is_empty());
+        assert!(report.restart_required.is_empty());
+    }
+
+    #[test]
+    fn parse_octal_mode_accepts_common_forms() {
+        assert_eq!(parse_octal_mode("600"), Some(0o600));
+        assert_eq!(parse_octal_mode("0600"), Some(0o600));
+        assert_eq!(parse_octal_mode("0o600"), Some(0o600));
+        assert_eq!(parse_octal_mode("640"), Some(0o640));
+    }
+
+    #[test]
+    fn parse_octal_mode_rejects_non_octal_digits() {
+        assert_eq!(parse_octal_mode("0800"), None);
+        assert_eq!(parse_octal_mode("rw-------"), None);
+    }
+
+    #[test]
+    fn default_socket_mode_is_owner_only() {
+        assert_eq!(SidecarConfig::default().socket_mode, 0o600);
+    }
+
+    #[test]
+    fn socket_mode_env_var_overrides_default() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("SENTINEL_SIDECAR_SOCKET_MODE", "0640");
+        let config = SidecarConfig::from_env();
+        std::env::remove_var("SENTINEL_SIDECAR_SOCKET_MODE");
+        assert_eq!(config.socket_mode, 0o640);
+    }
+
+    #[test]
+    fn validate_rejects_socket_path_whose_parent_directory_is_missing() {
+        let mut config = valid_config_with_tmp_tool_dir("socket_missing_parent");
+        config.socket_path = PathBuf::from("/nonexistent/sentinel/dir/sidecar.sock");
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "socket_path"));
+    }
+
+    #[test]
+    fn validate_accepts_socket_path_whose_parent_directory_exists() {
+        let mut config = valid_config_with_tmp_tool_dir("socket_existing_parent");
+        config.socket_path = std::env::temp_dir().join("sentinel_test_config_socket.sock");
+        let result = config.validate();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_tcp_addr_without_a_token() {
+        let mut config = valid_config_with_tmp_tool_dir("tcp_addr_no_token");
+        config.tcp_addr = Some("127.0.0.1:7801".to_string());
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "tcp_auth_token"));
+    }
+
+    #[test]
+    fn validate_accepts_tcp_addr_with_a_token() {
+        let mut config = valid_config_with_tmp_tool_dir("tcp_addr_with_token");
+        config.tcp_addr = Some("127.0.0.1:7801".to_string());
+        config.tcp_auth_token = Some("s3cr3t".to_string());
+        let result = config.validate();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_tls_cert_path_without_a_key_path() {
+        let mut config = valid_config_with_tmp_tool_dir("tls_cert_no_key");
+        config.tcp_tls_cert_path = Some(PathBuf::from("/opt/sentinel/tls/cert.pem"));
+        let errors = config.validate().unwrap_err();
+        std::fs::remove_dir_all(&config.tool_dir).ok();
+        assert!(errors.iter().any(|e| e.field == "tcp_tls_key_path"));
+    }
+
+    #[test]
+    fn reload_reports_a_changed_socket_field_as_restart_required() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml("reload_socket_mode", "socket_mode = \"0640\"\n");
+        let base = SidecarConfig::default();
+        let (reloaded, report) = base.reload(Some(&path)).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(reloaded.socket_mode, 0o640);
+        assert!(report.applied.is_empty());
+        assert_eq!(report.restart_required, vec!["socket_mode".to_string()]);
+    }
+
+    #[test]
+    fn default_credential_provider_is_none() {
+        assert_eq!(SidecarConfig::default().credential_provider, CredentialProviderKind::None);
+    }
+
+    #[test]
+    fn credential_provider_env_var_overrides_default() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("SENTINEL_SIDECAR_CREDENTIAL_PROVIDER", "exec");
+        let config = SidecarConfig::from_env();
+        std::env::remove_var("SENTINEL_SIDECAR_CREDENTIAL_PROVIDER");
+        assert_eq!(config.credential_provider, CredentialProviderKind::Exec);
+    }
+
+    #[test]
+    fn reload_reports_a_changed_credential_field_as_restart_required() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml("reload_credential_provider", "credential_provider = \"env\"\n");
+        let base = SidecarConfig::default();
+        let (reloaded, report) = base.reload(Some(&path)).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(reloaded.credential_provider, CredentialProviderKind::Env);
+        assert!(report.applied.is_empty());
+        assert_eq!(report.restart_required, vec!["credential_provider".to_string()]);
+    }
+
+    #[test]
+    fn effective_settings_reports_default_source_for_an_untouched_field() {
+        let config = SidecarConfig::default();
+        let settings = config.effective_settings();
+        assert_eq!(settings["timeout_ms"]["value"], 30_000);
+        assert_eq!(settings["timeout_ms"]["source"], "default");
+    }
+
+    #[test]
+    fn effective_settings_reports_file_source_for_a_file_supplied_field() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml("effective_settings_file", "timeout_ms = 15000\n");
+        let config = SidecarConfig::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let settings = config.effective_settings();
+        assert_eq!(settings["timeout_ms"]["value"], 15_000);
+        assert_eq!(settings["timeout_ms"]["source"], "file");
+    }
+
+    #[test]
+    fn effective_settings_reports_env_source_and_wins_over_a_file_value() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let path = write_temp_toml("effective_settings_env", "timeout_ms = 15000\n");
+        std::env::set_var("SENTINEL_SIDECAR_TIMEOUT_MS", "9000");
+        let config = SidecarConfig::from_file(&path).unwrap();
+        std::env::remove_var("SENTINEL_SIDECAR_TIMEOUT_MS");
+        std::fs::remove_file(&path).ok();
+
+        let settings = config.effective_settings();
+        assert_eq!(settings["timeout_ms"]["value"], 9_000);
+        assert_eq!(settings["timeout_ms"]["source"], "env");
+    }
+
+    #[test]
+    fn effective_settings_masks_a_planted_secret_value_but_keeps_its_source() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("SENTINEL_SIDECAR_CREDENTIAL_EXEC_COMMAND", "get-secrets --api-key=super-secret");
+        let config = SidecarConfig::from_env();
+        std::env::remove_var("SENTINEL_SIDECAR_CREDENTIAL_EXEC_COMMAND");
+
+        let settings = config.effective_settings();
+        assert_eq!(settings["credential_exec_command"]["value"], "***");
+        assert_eq!(settings["credential_exec_command"]["source"], "env");
+        assert!(!settings.to_string().contains("super-secret"));
+    }
+
+    #[test]
+    fn parse_uids_skips_unparseable_entries() {
+        let uids = parse_uids(vec!["0".to_string(), "not-a-uid".to_string(), " 1000 ".to_string()]);
+        assert_eq!(uids, vec![0, 1000]);
+    }
+}
@@ -0,0 +1,256 @@
+//! Host-side credential provider (synth-1155).
+//!
+//! Credentials arriving inline in every `Request`'s `credentials` map
+//! transit the controller and the socket in plaintext and have to be
+//! retyped into every request. This module lets the sidecar itself load
+//! named credential values from a source configured once — an env var
+//! prefix, a secrets file, or an external command — so a request only
+//! needs to name which credentials it wants, or nothing at all.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+
+use crate::config::SidecarConfig;
+
+/// Where the sidecar's own credential values come from, set via
+/// `credential_provider` in config. `None` (the default) keeps today's
+/// behavior — every credential comes from the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CredentialProviderKind {
+    #[default]
+    None,
+    /// Process env vars prefixed with `credential_env_prefix`, stripped of
+    /// that prefix to form the credential name.
+    Env,
+    /// A JSON or TOML file (by extension) of credential name -> value.
+    File,
+    /// An external command whose stdout is parsed as a JSON object of
+    /// credential name -> value.
+    Exec,
+}
+
+impl CredentialProviderKind {
+    /// Parse from a config/env string, case-insensitively. Unrecognized
+    /// values fall back to the default (`None`), same convention as
+    /// [`crate::leak_detector::RedactionStyle::from_str_or_default`].
+    pub fn from_str_or_default(s: &str) -> Self {
+        match s.to_ascii_lowercase().as_str() {
+            "env" => Self::Env,
+            "file" => Self::File,
+            "exec" => Self::Exec,
+            _ => Self::None,
+        }
+    }
+}
+
+/// Load the credential map for `config`'s configured provider. Returns an
+/// empty map for [`CredentialProviderKind::None`], in which case
+/// [`merge`] reduces to exactly the request's own inline credentials —
+/// unchanged from before this provider existed.
+pub fn load(config: &SidecarConfig) -> Result<HashMap<String, String>> {
+    match config.credential_provider {
+        CredentialProviderKind::None => Ok(HashMap::new()),
+        CredentialProviderKind::Env => Ok(load_from_env(&config.credential_env_prefix)),
+        CredentialProviderKind::File => {
+            let path = config
+                .credential_file_path
+                .as_deref()
+                .context("credential_provider = \"file\" requires credential_file_path")?;
+            load_from_file(path)
+        }
+        CredentialProviderKind::Exec => {
+            let command = config
+                .credential_exec_command
+                .as_deref()
+                .context("credential_provider = \"exec\" requires credential_exec_command")?;
+            load_from_exec(command)
+        }
+    }
+}
+
+fn load_from_env(prefix: &str) -> HashMap<String, String> {
+    std::env::vars()
+        .filter_map(|(k, v)| k.strip_prefix(prefix).map(|name| (name.to_string(), v)))
+        .collect()
+}
+
+fn load_from_file(path: &Path) -> Result<HashMap<String, String>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read credentials file {}", path.display()))?;
+    if path.extension().and_then(|e| e.to_str()) == Some("toml") {
+        toml::from_str(&contents)
+            .with_context(|| format!("failed to parse credentials file {} as TOML", path.display()))
+    } else {
+        serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse credentials file {} as JSON", path.display()))
+    }
+}
+
+fn load_from_exec(command: &str) -> Result<HashMap<String, String>> {
+    let output = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .output()
+        .with_context(|| format!("failed to run credential exec command '{command}'"))?;
+    if !output.status.success() {
+        bail!("credential exec command '{command}' exited with {}", output.status);
+    }
+    let stdout = String::from_utf8(output.stdout)
+        .context("credential exec command produced non-UTF8 output")?;
+    serde_json::from_str(&stdout)
+        .context("credential exec command stdout must be a JSON object of name -> value")
+}
+
+/// Merge a request's inline credentials with the provider-loaded map. In
+/// the default merge mode the request's own entries win on a name
+/// collision, since a caller substituting its own value for one call is a
+/// normal, one-off case. When `provider_only` is set, any non-empty
+/// request `credentials` map is rejected outright instead — for
+/// deployments where inline credentials must never transit the socket.
+pub fn merge(
+    provider: &HashMap<String, String>,
+    request: &HashMap<String, String>,
+    provider_only: bool,
+) -> Result<HashMap<String, String>> {
+    if provider_only && !request.is_empty() {
+        bail!("inline request credentials are not accepted; this sidecar requires provider-supplied credentials");
+    }
+    let mut merged = provider.clone();
+    merged.extend(request.clone());
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // `std::env::set_var` affects the whole process, mirroring config.rs's
+    // ENV_LOCK convention for tests that touch it.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn config_with_provider(kind: CredentialProviderKind) -> SidecarConfig {
+        SidecarConfig {
+            credential_provider: kind,
+            ..SidecarConfig::default()
+        }
+    }
+
+    #[test]
+    fn credential_provider_kind_from_str_recognizes_each_variant() {
+        assert_eq!(CredentialProviderKind::from_str_or_default("env"), CredentialProviderKind::Env);
+        assert_eq!(CredentialProviderKind::from_str_or_default("FILE"), CredentialProviderKind::File);
+        assert_eq!(CredentialProviderKind::from_str_or_default("exec"), CredentialProviderKind::Exec);
+        assert_eq!(CredentialProviderKind::from_str_or_default("bogus"), CredentialProviderKind::None);
+    }
+
+    #[test]
+    fn none_provider_loads_an_empty_map() {
+        let config = config_with_provider(CredentialProviderKind::None);
+        let loaded = load(&config).unwrap();
+        assert!(loaded.is_empty());
+    }
+
+    #[test]
+    fn env_provider_strips_prefix_from_matching_vars() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("SENTINEL_CRED_TEST_API_KEY", "secret-123");
+        std::env::set_var("UNRELATED_VAR", "should-not-appear");
+
+        let mut config = config_with_provider(CredentialProviderKind::Env);
+        config.credential_env_prefix = "SENTINEL_CRED_TEST_".to_string();
+        let loaded = load(&config).unwrap();
+
+        std::env::remove_var("SENTINEL_CRED_TEST_API_KEY");
+        std::env::remove_var("UNRELATED_VAR");
+
+        assert_eq!(loaded.get("API_KEY"), Some(&"secret-123".to_string()));
+        assert!(!loaded.contains_key("UNRELATED_VAR"));
+    }
+
+    #[test]
+    fn file_provider_parses_json_secrets_file() {
+        let path = std::env::temp_dir().join("sentinel_test_credentials_file.json");
+        std::fs::write(&path, r#"{"api_key": "from-json-file"}"#).unwrap();
+
+        let mut config = config_with_provider(CredentialProviderKind::File);
+        config.credential_file_path = Some(path.clone());
+        let loaded = load(&config).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.get("api_key"), Some(&"from-json-file".to_string()));
+    }
+
+    #[test]
+    fn file_provider_parses_toml_secrets_file() {
+        let path = std::env::temp_dir().join("sentinel_test_credentials_file.toml");
+        std::fs::write(&path, "api_key = \"from-toml-file\"\n").unwrap();
+
+        let mut config = config_with_provider(CredentialProviderKind::File);
+        config.credential_file_path = Some(path.clone());
+        let loaded = load(&config).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.get("api_key"), Some(&"from-toml-file".to_string()));
+    }
+
+    #[test]
+    fn file_provider_without_a_path_fails() {
+        let config = config_with_provider(CredentialProviderKind::File);
+        assert!(load(&config).is_err());
+    }
+
+    #[test]
+    fn exec_provider_parses_stdout_as_json() {
+        let mut config = config_with_provider(CredentialProviderKind::Exec);
+        config.credential_exec_command =
+            Some(r#"printf '{"api_key": "from-exec-stub"}'"#.to_string());
+        let loaded = load(&config).unwrap();
+
+        assert_eq!(loaded.get("api_key"), Some(&"from-exec-stub".to_string()));
+    }
+
+    #[test]
+    fn exec_provider_surfaces_a_nonzero_exit_as_an_error() {
+        let mut config = config_with_provider(CredentialProviderKind::Exec);
+        config.credential_exec_command = Some("exit 1".to_string());
+        assert!(load(&config).is_err());
+    }
+
+    #[test]
+    fn merge_lets_request_credentials_win_by_default() {
+        let mut provider = HashMap::new();
+        provider.insert("api_key".to_string(), "provider-value".to_string());
+        provider.insert("db_password".to_string(), "provider-db".to_string());
+        let mut request = HashMap::new();
+        request.insert("api_key".to_string(), "request-value".to_string());
+
+        let merged = merge(&provider, &request, false).unwrap();
+
+        assert_eq!(merged.get("api_key"), Some(&"request-value".to_string()));
+        assert_eq!(merged.get("db_password"), Some(&"provider-db".to_string()));
+    }
+
+    #[test]
+    fn merge_in_provider_only_mode_rejects_inline_credentials() {
+        let provider = HashMap::new();
+        let mut request = HashMap::new();
+        request.insert("api_key".to_string(), "request-value".to_string());
+
+        assert!(merge(&provider, &request, true).is_err());
+    }
+
+    #[test]
+    fn merge_in_provider_only_mode_allows_an_empty_request_map() {
+        let mut provider = HashMap::new();
+        provider.insert("api_key".to_string(), "provider-value".to_string());
+        let request = HashMap::new();
+
+        let merged = merge(&provider, &request, true).unwrap();
+        assert_eq!(merged.get("api_key"), Some(&"provider-value".to_string()));
+    }
+}
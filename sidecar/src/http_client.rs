@@ -4,6 +4,7 @@
 //! and provides a safe HTTP client that connects to resolved IPs
 //! to prevent DNS rebinding attacks.
 
+use base64::Engine;
 use std::fmt;
 use std::io::Read as _;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
@@ -241,10 +242,76 @@ impl Default for HttpConfig {
     }
 }
 
+/// One HTTP round trip's raw result: status, captured response headers, and
+/// body bytes (synth-1206) — shared by `request_once` and
+/// `request_with_retries`, which adds the attempt count on top.
+type RawResponse = (u16, std::collections::HashMap<String, String>, Vec<u8>);
+type RawResponseWithAttempts = (u16, std::collections::HashMap<String, String>, Vec<u8>, u32);
+
+/// Maximum redirect hops `fetch` will follow (synth-1206) before giving up
+/// — same order of magnitude as browsers/curl's defaults, high enough for
+/// any legitimate redirect chain, low enough to bound a malicious loop.
+const MAX_REDIRECTS: u32 = 10;
+
+/// How the response body should be returned to the caller (synth-1206).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseEncoding {
+    /// Lossy UTF-8 decode into `HttpResponse::body` (the pre-synth-1206
+    /// behavior, still the default).
+    Utf8,
+    /// Base64-encode the raw bytes into `HttpResponse::body_base64` instead,
+    /// for responses that aren't valid UTF-8.
+    Base64,
+}
+
+impl ResponseEncoding {
+    /// Parses the wire-format string (`HttpFetchRequest::response_encoding`).
+    pub fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value {
+            None | Some("utf8") => Ok(Self::Utf8),
+            Some("base64") => Ok(Self::Base64),
+            Some(other) => Err(format!("unsupported response_encoding: '{other}' (expected 'utf8' or 'base64')")),
+        }
+    }
+}
+
+/// Per-request options layered on top of `HttpConfig` (synth-1206) — these
+/// come from the caller on a per-call basis, unlike `HttpConfig`'s
+/// sidecar-wide defaults.
+pub struct FetchOptions {
+    /// Follow 3xx redirects, re-validating each hop through `validate_url`
+    /// exactly like the original request. Defaults to `true` when the
+    /// caller doesn't specify.
+    pub follow_redirects: bool,
+    /// Overrides `HttpConfig::timeout_ms` for this request only.
+    pub timeout_ms: Option<u64>,
+    /// Extra attempts on top of the first if the request fails at the
+    /// transport level (DNS/connect/timeout) — not on HTTP error statuses,
+    /// which are a valid response, not a failure to retry.
+    pub retries: u32,
+    pub response_encoding: ResponseEncoding,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            follow_redirects: true,
+            timeout_ms: None,
+            retries: 0,
+            response_encoding: ResponseEncoding::Utf8,
+        }
+    }
+}
+
 /// Perform an HTTP fetch with SSRF protection.
 ///
 /// Validates the URL, connects to the resolved IP (preventing DNS rebinding),
-/// and returns the response with size limits.
+/// and returns the response with size limits. Redirects (synth-1206) are
+/// followed manually rather than left to `ureq`: each hop's `Location` is
+/// re-validated through `validate_url` — fresh DNS resolution, allowlist
+/// check, and private-IP rejection — the same way the original URL was, so
+/// a redirect can't be used to steer the connection anywhere the caller
+/// couldn't have requested directly.
 pub fn fetch(
     url_str: &str,
     method: &str,
@@ -252,18 +319,104 @@ pub fn fetch(
     body: Option<&str>,
     allowlist: &[String],
     config: &HttpConfig,
+    options: &FetchOptions,
 ) -> Result<HttpResponse, String> {
-    let validated = validate_url(url_str, allowlist, config.allow_http, config.dns_timeout_s)
-        .map_err(|e| e.to_string())?;
+    let mut current_url = url_str.to_string();
+    let mut current_method = method.to_uppercase();
+    let mut current_body = body.map(|b| b.to_string());
+    let mut attempts = 0u32;
+
+    for hop in 0..=MAX_REDIRECTS {
+        let validated = validate_url(&current_url, allowlist, config.allow_http, config.dns_timeout_s)
+            .map_err(|e| e.to_string())?;
+
+        let (status, resp_headers, body_buf, attempt_count) =
+            request_with_retries(&validated, &current_method, headers, current_body.as_deref(), config, options)?;
+        attempts += attempt_count;
+
+        if options.follow_redirects && (300..400).contains(&status) && hop < MAX_REDIRECTS {
+            if let Some(location) = resp_headers.get("location") {
+                if let Ok(next_url) = validated.url.join(location) {
+                    // 301/302/303: browsers and most HTTP clients switch to a
+                    // bodyless GET, regardless of the original method — only
+                    // 307/308 are specified to preserve method and body.
+                    if !matches!(status, 307 | 308) {
+                        current_method = "GET".to_string();
+                        current_body = None;
+                    }
+                    current_url = next_url.to_string();
+                    continue;
+                }
+            }
+        }
+
+        let (body_result, body_base64) = match options.response_encoding {
+            ResponseEncoding::Utf8 => (String::from_utf8_lossy(&body_buf).to_string(), None),
+            ResponseEncoding::Base64 => (
+                String::new(),
+                Some(base64::engine::general_purpose::STANDARD.encode(&body_buf)),
+            ),
+        };
+
+        return Ok(HttpResponse {
+            status,
+            body: body_result,
+            headers: resp_headers,
+            final_url: validated.url.to_string(),
+            attempts,
+            body_base64,
+        });
+    }
+
+    Err(format!("too many redirects: exceeded {MAX_REDIRECTS} hops"))
+}
 
+/// Performs one logical HTTP request against an already-validated,
+/// DNS-pinned URL, retrying up to `options.retries` extra times on a
+/// transport-level failure (synth-1206). Returns the response status,
+/// captured headers, raw body bytes, and how many attempts it took.
+fn request_with_retries(
+    validated: &ValidatedUrl,
+    method: &str,
+    headers: &[(String, String)],
+    body: Option<&str>,
+    config: &HttpConfig,
+    options: &FetchOptions,
+) -> Result<RawResponseWithAttempts, String> {
+    let mut last_err = None;
+    for attempt in 1..=(options.retries + 1) {
+        match request_once(validated, method, headers, body, config, options) {
+            Ok((status, resp_headers, body_buf)) => return Ok((status, resp_headers, body_buf, attempt)),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| "HTTP request failed".to_string()))
+}
+
+/// A single request/response round trip against a pinned resolver — no
+/// retry, no redirect handling. Split out of `fetch` (synth-1206) so both
+/// the retry loop and the redirect loop can each call it once per attempt.
+fn request_once(
+    validated: &ValidatedUrl,
+    method: &str,
+    headers: &[(String, String)],
+    body: Option<&str>,
+    config: &HttpConfig,
+    options: &FetchOptions,
+) -> Result<RawResponse, String> {
     let port = validated.url.port_or_known_default().unwrap_or(443);
     let pinned = PinnedResolver {
         addr: SocketAddr::new(validated.resolved_ip, port),
     };
 
-    let timeout = std::time::Duration::from_millis(config.timeout_ms);
+    let timeout_ms = options.timeout_ms.unwrap_or(config.timeout_ms);
+    let timeout = std::time::Duration::from_millis(timeout_ms);
     let ureq_config = ureq::config::Config::builder()
         .timeout_global(Some(timeout))
+        // Redirects are followed manually by `fetch` so every hop gets its
+        // own `validate_url` SSRF check instead of `ureq` reconnecting to
+        // whatever `PinnedResolver` happens to still be pinned to.
+        .max_redirects(0)
         .build();
     let agent = ureq::Agent::with_parts(ureq_config, DefaultConnector::default(), pinned);
 
@@ -310,7 +463,7 @@ pub fn fetch(
 
             // Capture relevant response headers
             let mut resp_headers = std::collections::HashMap::new();
-            for name in ["content-type", "content-length", "content-encoding"] {
+            for name in ["content-type", "content-length", "content-encoding", "location"] {
                 if let Some(val) = resp.headers().get(name) {
                     if let Ok(s) = val.to_str() {
                         resp_headers.insert(name.to_string(), s.to_string());
@@ -338,13 +491,8 @@ pub fn fetch(
                 }
                 body_buf.extend_from_slice(&chunk[..n]);
             }
-            let body_result = String::from_utf8_lossy(&body_buf).to_string();
 
-            Ok(HttpResponse {
-                status,
-                body: body_result,
-                headers: resp_headers,
-            })
+            Ok((status, resp_headers, body_buf))
         }
         Err(e) => Err(format!("HTTP request failed: {e}")),
     }
@@ -355,6 +503,14 @@ pub struct HttpResponse {
     pub status: u16,
     pub body: String,
     pub headers: std::collections::HashMap<String, String>,
+    /// The URL the response actually came from, after following any
+    /// redirects (synth-1206).
+    pub final_url: String,
+    /// How many attempts the host made, including retries (synth-1206).
+    pub attempts: u32,
+    /// Base64-encoded body, populated instead of `body` when
+    /// `ResponseEncoding::Base64` was requested (synth-1206).
+    pub body_base64: Option<String>,
 }
 
 #[cfg(test)]
@@ -484,6 +640,32 @@ mod tests {
         assert_eq!(result[0], addr);
     }
 
+    #[test]
+    fn test_response_encoding_defaults_to_utf8() {
+        assert_eq!(ResponseEncoding::parse(None).unwrap(), ResponseEncoding::Utf8);
+        assert_eq!(ResponseEncoding::parse(Some("utf8")).unwrap(), ResponseEncoding::Utf8);
+    }
+
+    #[test]
+    fn test_response_encoding_accepts_base64() {
+        assert_eq!(ResponseEncoding::parse(Some("base64")).unwrap(), ResponseEncoding::Base64);
+    }
+
+    #[test]
+    fn test_response_encoding_rejects_unknown_value() {
+        let err = ResponseEncoding::parse(Some("gzip")).unwrap_err();
+        assert!(err.contains("gzip"), "error should name the rejected value: {err}");
+    }
+
+    #[test]
+    fn test_fetch_options_default_follows_redirects_with_no_retries() {
+        let options = FetchOptions::default();
+        assert!(options.follow_redirects);
+        assert_eq!(options.timeout_ms, None);
+        assert_eq!(options.retries, 0);
+        assert_eq!(options.response_encoding, ResponseEncoding::Utf8);
+    }
+
     #[test]
     fn test_validated_url_resolved_ip_is_populated() {
         // validate_url on localhost should fail (private IP), but it proves
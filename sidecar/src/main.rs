@@ -6,59 +6,142 @@
 
 mod capabilities;
 mod config;
+mod credentials;
 mod host_functions;
 mod http_client;
 mod leak_detector;
+mod logging;
+mod metrics;
+mod native_tools;
 mod protocol;
 mod registry;
 mod sandbox;
+mod scheduler;
+mod schema;
+mod tls;
 
 use std::collections::HashSet;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
 
-use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
 use tokio::net::UnixListener;
+use tracing::Instrument;
 
-use config::SidecarConfig;
+use capabilities::Capability;
+use config::{ConfigReloadReport, ListenerConfig, SidecarConfig};
 use leak_detector::LeakDetector;
-use protocol::{Request, Response};
+use protocol::{
+    AuthMessage, BatchMessage, ControlMessage, GrantMessage, HelloMessage, PayloadEncoding, ReloadMessage, Request,
+    Response,
+};
 use registry::ToolRegistry;
 use sandbox::SandboxEngine;
-
-/// How long to wait for in-flight connections to finish before force-stopping.
-const DRAIN_TIMEOUT_SECS: u64 = 10;
+use scheduler::PriorityScheduler;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let socket_path = std::env::var("SENTINEL_SIDECAR_SOCKET")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| PathBuf::from("/tmp/sentinel-sidecar.sock"));
+    // Install structured logging (synth-1153) before anything else so every
+    // subsequent line, including config/registry load failures, goes
+    // through it — `sign-tool`/`--precompile` below are unaffected since
+    // they exit before ever touching a connection or a request.
+    logging::SidecarSubscriber::init();
+
+    // synth-1141: `sentinel-sidecar sign-tool ...` is a standalone offline
+    // helper, not a sidecar listener — handled and exited before any socket
+    // or registry setup below.
+    let cli_args: Vec<String> = std::env::args().collect();
+    if let Some(code) = run_sign_tool_subcommand(&cli_args[1..]) {
+        std::process::exit(code);
+    }
+    // synth-1143: `sentinel-sidecar --precompile <dir>` emits a `.cwasm`
+    // artifact for every registered tool, then exits without starting a
+    // listener — same "standalone offline helper" shape as `sign-tool`.
+    if let Some(code) = run_precompile_subcommand(&cli_args[1..]) {
+        std::process::exit(code);
+    }
+
+    // Load configuration (synth-1148). `--config <path>` is authoritative and
+    // fails startup on a bad file, since an operator naming a file explicitly
+    // expects it to be used; without the flag, `from_env` still honors
+    // `SENTINEL_SIDECAR_CONFIG` but treats a bad file as non-fatal, same as
+    // its other optional-file settings (e.g. the profiles file). `config_path`
+    // is kept around so a later hot reload (synth-1152) re-reads the exact
+    // same source rather than guessing.
+    let config_path = config_flag_path(&cli_args[1..]);
+    let config = match &config_path {
+        Some(path) => match SidecarConfig::from_file(path) {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::error!(error = %e, "fatal: failed to load configuration");
+                std::process::exit(1);
+            }
+        },
+        None => SidecarConfig::from_env(),
+    };
+
+    // Fail fast on a bad config (synth-1149) — before touching the tool
+    // directory, the socket, or anything else that would otherwise fail
+    // later with a more confusing error. Every violation is reported at
+    // once so an operator doesn't have to fix-and-restart repeatedly.
+    if let Err(errors) = config.validate() {
+        for error in &errors {
+            tracing::error!(error = %error, "fatal: invalid configuration");
+        }
+        std::process::exit(1);
+    }
 
-    // Load configuration
-    let config = SidecarConfig::from_env();
+    // synth-1156: `--print-config` dumps the fully-resolved config as JSON,
+    // each field annotated with which layer supplied it and secrets masked,
+    // then exits — same "standalone helper that exits before touching the
+    // socket" shape as `sign-tool`/`--precompile` above.
+    if cli_args[1..].iter().any(|a| a == "--print-config") {
+        println!("{}", serde_json::to_string_pretty(&config.effective_settings())?);
+        return Ok(());
+    }
 
     // Build leak detector (Aho-Corasick automaton compiled once at startup,
     // shared immutably — no Mutex needed, U7/RACE-1)
-    let leak_detector = Arc::new(LeakDetector::new());
+    let mut leak_detector = LeakDetector::with_style(config.redaction_style);
+    if let Some(path) = &config.leak_log_path {
+        match leak_detector::file_log_hook(path.clone()) {
+            Ok(hook) => leak_detector = leak_detector.with_hook(hook),
+            Err(e) => tracing::warn!(
+                path = %path.display(),
+                error = %e,
+                "failed to open leak log"
+            ),
+        }
+    }
+    let leak_detector = Arc::new(leak_detector);
 
     // Create Wasmtime engine first — shared by registry (module compilation)
     // and sandbox (execution). Engine is Send + Sync.
-    let mut engine_config = wasmtime::Config::new();
-    engine_config.consume_fuel(true);
-    engine_config.epoch_interruption(true);
-    let wasm_engine = wasmtime::Engine::new(&engine_config)
+    let wasm_engine = wasmtime::Engine::new(&sandbox::wasm_engine_config())
         .expect("failed to create Wasmtime engine");
 
-    // Load tool registry with pre-compiled WASM modules
-    let registry = Arc::new(
-        ToolRegistry::load(&config.tool_dir, &wasm_engine)
-            .unwrap_or_else(|e| {
-                eprintln!("sidecar: warning: failed to load registry: {e}");
-                ToolRegistry::new()
-            })
-    );
-    eprintln!("sidecar: {} tool(s) registered", registry.len());
+    // Load tool registry with pre-compiled WASM modules. Wrapped in
+    // `RwLock<Arc<_>>` (synth-1132) so it can be hot-reloaded without
+    // restarting the sidecar: readers (connection handlers) clone the inner
+    // `Arc` under a brief read lock and keep using that snapshot for the
+    // rest of their execution, undisturbed by a later reload swapping in a
+    // fresh `Arc`.
+    let mut loaded_registry = ToolRegistry::load(
+        &config.tool_dir,
+        &wasm_engine,
+        config.require_tool_hash,
+        &config.tool_signing_keys,
+        config.require_signed_tools,
+        config.allow_precompiled,
+    )
+    .unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "failed to load registry");
+        ToolRegistry::new()
+    });
+    register_builtin_native_tools(&mut loaded_registry, &wasm_engine);
+    let registry: Arc<RwLock<Arc<ToolRegistry>>> = Arc::new(RwLock::new(Arc::new(loaded_registry)));
+    tracing::info!(count = registry.read().unwrap().len(), "tool(s) registered");
 
     // Shared child PID registry for shell process cleanup on shutdown (LEAK-1)
     let active_children: Arc<std::sync::Mutex<HashSet<u32>>> =
@@ -70,13 +153,61 @@ async fn main() -> anyhow::Result<()> {
             .expect("failed to create sandbox engine")
     );
 
-    // Remove stale socket file if it exists
-    if socket_path.exists() {
-        std::fs::remove_file(&socket_path)?;
-    }
+    // Bounds tool executions running at once across every connection
+    // (synth-1164), now that `handle_connection` spawns each request into
+    // its own task instead of running them strictly serially. Sized once at
+    // startup — `max_concurrent_executions` is restart-required. Priority-
+    // and aging-aware (synth-1178) rather than a flat `tokio::sync::Semaphore`,
+    // so `Request.priority` actually affects admission order once this pool
+    // is saturated.
+    let execution_semaphore = Arc::new(PriorityScheduler::new(
+        config.max_concurrent_executions as usize,
+    ));
 
-    let listener = UnixListener::bind(&socket_path)?;
-    eprintln!("sidecar: listening on {}", socket_path.display());
+    // Bind every configured listener (synth-1159) — a `[[listener]]` array
+    // lets a trusted controller socket and a more restricted debugging-UI
+    // socket coexist with different capability ceilings and connection
+    // caps. When none are configured, a single listener is synthesized from
+    // the legacy `socket_path`/`socket_mode`/`default_capability_ceiling`
+    // fields (unlimited connections), preserving pre-synth-1159 behavior
+    // exactly.
+    let listener_specs = effective_listeners(&config);
+    let active_listeners: Vec<ActiveListener> = listener_specs
+        .iter()
+        .map(|spec| bind_listener(spec, config.socket_group.as_deref(), &config.allowed_uids))
+        .collect::<std::io::Result<_>>()?;
+
+    // Optional TCP listener (synth-1169) — for a controller that can no
+    // longer reach the Unix socket (e.g. it moved to a separate host).
+    // `validate()` above already guarantees `tcp_auth_token` is set whenever
+    // `tcp_addr` is, and that the TLS cert/key paths are set together, so
+    // there's nothing left to check before binding except that the cert/key
+    // (if configured) actually parse — done here, fatally, same as every
+    // other startup-time config problem, rather than deferred to the first
+    // connection.
+    let tcp_tls_config = match (&config.tcp_tls_cert_path, &config.tcp_tls_key_path) {
+        (Some(cert_path), Some(key_path)) => match tls::load_server_config(cert_path, key_path) {
+            Ok(server_config) => Some(Arc::new(server_config)),
+            Err(e) => {
+                tracing::error!(error = %e, "fatal: failed to load TLS certificate/key for TCP listener");
+                std::process::exit(1);
+            }
+        },
+        _ => None,
+    };
+    let tcp_listener = match &config.tcp_addr {
+        Some(addr) => match tokio::net::TcpListener::bind(addr).await {
+            Ok(listener) => {
+                tracing::info!(addr, tls = tcp_tls_config.is_some(), "listening (TCP)");
+                Some(listener)
+            }
+            Err(e) => {
+                tracing::error!(addr, error = %e, "fatal: failed to bind TCP listener");
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
 
     // Shutdown signal handler — listens for both SIGINT and SIGTERM (SHUT-1)
     let shutdown = Arc::new(tokio::sync::Notify::new());
@@ -90,17 +221,84 @@ async fn main() -> anyhow::Result<()> {
 
             tokio::select! {
                 _ = tokio::signal::ctrl_c() => {
-                    eprintln!("sidecar: received SIGINT");
+                    tracing::info!("received SIGINT");
                 }
                 _ = sigterm.recv() => {
-                    eprintln!("sidecar: received SIGTERM");
+                    tracing::info!("received SIGTERM");
                 }
             }
             shutdown.notify_waiters();
         });
     }
 
-    // Accept loop with graceful shutdown and request draining (SHUT-2)
+    // Hot-reload the tool registry and the sidecar config on SIGHUP
+    // (synth-1132, synth-1152) — same effect as the `{"reload": "registry"}`
+    // and `{"reload": "config"}` control messages handled per-connection.
+    // `tool_dir`/signing settings here are always the ones from process
+    // startup, since those particular config fields require a restart to
+    // change (see `config::RESTART_REQUIRED_FIELDS`) — reloading config
+    // itself never rebinds them.
+    {
+        let registry = registry.clone();
+        let tool_dir = config.tool_dir.clone();
+        let wasm_engine = engine.engine().clone();
+        let require_tool_hash = config.require_tool_hash;
+        let tool_signing_keys = config.tool_signing_keys.clone();
+        let require_signed_tools = config.require_signed_tools;
+        let allow_precompiled = config.allow_precompiled;
+        let engine = engine.clone();
+        let config_path = config_path.clone();
+        tokio::spawn(async move {
+            let mut sighup = tokio::signal::unix::signal(
+                tokio::signal::unix::SignalKind::hangup(),
+            )
+            .expect("failed to register SIGHUP handler");
+            loop {
+                sighup.recv().await;
+                tracing::info!("received SIGHUP, reloading tool registry");
+                let result = reload_registry(
+                    &registry,
+                    &tool_dir,
+                    &wasm_engine,
+                    require_tool_hash,
+                    &tool_signing_keys,
+                    require_signed_tools,
+                    allow_precompiled,
+                );
+                log_reload_result(&result);
+
+                tracing::info!("received SIGHUP, reloading config");
+                let result = reload_sidecar_config(&engine, config_path.as_deref());
+                log_config_reload_result(&result);
+
+                tracing::info!("received SIGHUP, reloading credentials");
+                let result = reload_engine_credentials(&engine);
+                log_credentials_reload_result(&result);
+            }
+        });
+    }
+
+    // Accept loop with graceful shutdown and request draining (SHUT-2).
+    // Every listener (synth-1159) runs its own background accept loop —
+    // there's no `futures::select_all` available in this workspace to
+    // multiplex an arbitrary number of `UnixListener::accept()` futures
+    // directly — and forwards accepted connections, tagged with that
+    // listener's ceiling, into this shared channel. The rest of the loop
+    // below (spawn into `tasks`, drain on shutdown) is unchanged from the
+    // single-socket version.
+    let (accepted_tx, mut accepted_rx) = tokio::sync::mpsc::channel::<AcceptedConnection>(64);
+    for active in active_listeners {
+        spawn_listener_accept_loop(active, accepted_tx.clone(), shutdown.clone());
+    }
+    if let Some(tcp_listener) = tcp_listener {
+        let ceiling = Arc::new(capabilities::ceiling_from_names(&config.default_capability_ceiling));
+        // `validate()` guarantees `tcp_auth_token` is `Some` whenever
+        // `tcp_addr` (and so `tcp_listener`) is.
+        let token: Arc<str> = Arc::from(config.tcp_auth_token.clone().unwrap_or_default());
+        spawn_tcp_accept_loop(tcp_listener, ceiling, token, tcp_tls_config, accepted_tx.clone(), shutdown.clone());
+    }
+    drop(accepted_tx);
+
     let mut tasks = tokio::task::JoinSet::new();
 
     // Signal readiness to the Python supervisor. start_sidecar() waits for
@@ -109,15 +307,86 @@ async fn main() -> anyhow::Result<()> {
 
     loop {
         tokio::select! {
-            result = listener.accept() => {
-                let (stream, _addr) = result?;
+            accepted = accepted_rx.recv() => {
+                let Some(mut accepted) = accepted else {
+                    // Every listener's accept loop has exited — nothing left
+                    // to ever receive from, so there's no point looping.
+                    break;
+                };
+
+                // synth-1173: process-wide connection cap, checked here
+                // rather than in each listener's own accept loop since it's
+                // a single counter shared across every listener (and the
+                // optional TCP one) rather than a per-listener limit like
+                // `ListenerConfig::max_connections`. An accept over the cap
+                // gets one error line and is dropped before ever reaching
+                // `handle_connection`.
+                let open_connections = engine.open_connections();
+                if let Some(max) = config.max_connections {
+                    if open_connections.load(std::sync::atomic::Ordering::Relaxed) >= max {
+                        tracing::warn!(max_connections = max, "rejected connection: max_connections reached");
+                        tokio::spawn(async move {
+                            let _permit = accepted.permit;
+                            let mut out = serde_json::to_string(&Response::error_with_code(
+                                "maximum connection count reached".to_string(),
+                                "connection_limit_reached",
+                            ))
+                            .unwrap_or_default();
+                            out.push('\n');
+                            let _ = accepted.stream.write_all(out.as_bytes()).await;
+                        });
+                        continue;
+                    }
+                }
+                open_connections.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
                 let engine = engine.clone();
                 let registry = registry.clone();
                 let leak_detector = leak_detector.clone();
+                let tool_dir = config.tool_dir.clone();
+                let require_tool_hash = config.require_tool_hash;
+                let tool_signing_keys = config.tool_signing_keys.clone();
+                let require_signed_tools = config.require_signed_tools;
+                let allow_precompiled = config.allow_precompiled;
+                let config_path = config_path.clone();
+                let execution_semaphore = execution_semaphore.clone();
+                let max_request_bytes = config.max_request_bytes as usize;
+                let idle_timeout_secs = config.idle_timeout_secs;
+                let shutdown = shutdown.clone();
 
                 tasks.spawn(async move {
-                    if let Err(e) = handle_connection(stream, &engine, &registry, &leak_detector).await {
-                        eprintln!("sidecar: connection error: {e}");
+                    // Held for the connection's lifetime so a listener's
+                    // `max_connections` cap actually bounds concurrent
+                    // connections rather than just concurrent accepts.
+                    let _permit = accepted.permit;
+                    // Decrements the process-wide open-connection gauge
+                    // (synth-1173) no matter which way this task ends —
+                    // clean EOF, I/O error, or an abort during shutdown
+                    // drain.
+                    let _open_connections_guard = OpenConnectionGuard(open_connections);
+                    if let Err(e) = handle_connection(
+                        accepted.stream,
+                        &engine,
+                        &registry,
+                        &leak_detector,
+                        &accepted.ceiling,
+                        &tool_dir,
+                        require_tool_hash,
+                        &tool_signing_keys,
+                        require_signed_tools,
+                        allow_precompiled,
+                        config_path.as_deref(),
+                        &execution_semaphore,
+                        accepted.required_token.as_deref(),
+                        accepted.encoding,
+                        max_request_bytes,
+                        idle_timeout_secs,
+                        &shutdown,
+                    )
+                    .instrument(tracing::info_span!("connection", peer_uid = ?accepted.peer_uid))
+                    .await
+                    {
+                        tracing::warn!(error = %e, "connection error");
                     }
                 });
             }
@@ -133,18 +402,19 @@ async fn main() -> anyhow::Result<()> {
     // Drain: wait for in-flight connections to complete with timeout
     let inflight = tasks.len();
     if inflight > 0 {
-        eprintln!("sidecar: draining {inflight} in-flight connection(s)");
-        let drain_timeout = tokio::time::Duration::from_secs(DRAIN_TIMEOUT_SECS);
+        tracing::info!(count = inflight, "draining in-flight connection(s)");
+        let drain_timeout = tokio::time::Duration::from_secs(config.drain_timeout_secs);
         match tokio::time::timeout(drain_timeout, async {
             while tasks.join_next().await.is_some() {}
         })
         .await
         {
-            Ok(()) => eprintln!("sidecar: all connections drained"),
+            Ok(()) => tracing::info!("all connections drained"),
             Err(_) => {
-                eprintln!(
-                    "sidecar: drain timeout ({DRAIN_TIMEOUT_SECS}s), aborting {} remaining task(s)",
-                    tasks.len()
+                tracing::warn!(
+                    timeout_secs = config.drain_timeout_secs,
+                    remaining = tasks.len(),
+                    "drain timeout, aborting remaining task(s)"
                 );
                 tasks.abort_all();
             }
@@ -155,10 +425,7 @@ async fn main() -> anyhow::Result<()> {
     {
         let children = active_children.lock().unwrap_or_else(|e| e.into_inner());
         if !children.is_empty() {
-            eprintln!(
-                "sidecar: killing {} orphaned shell process(es)",
-                children.len()
-            );
+            tracing::info!(count = children.len(), "killing orphaned shell process(es)");
             #[cfg(unix)]
             for &pid in children.iter() {
                 // Kill the process group (same pattern as shell_exec timeout)
@@ -168,72 +435,1275 @@ async fn main() -> anyhow::Result<()> {
                     }
                     let result = kill(-(pid as i32), 9); // SIGKILL process group
                     if result != 0 {
-                        eprintln!(
-                            "sidecar: failed to kill child pgid {pid} (may have already exited)"
-                        );
+                        tracing::warn!(pgid = pid, "failed to kill child pgid (may have already exited)");
                     }
                 }
             }
         }
     }
 
-    // Clean up socket file
-    let _ = std::fs::remove_file(&socket_path);
-    eprintln!("sidecar: shutdown complete");
+    // Clean up every listener's socket file (synth-1159)
+    for spec in &listener_specs {
+        let _ = std::fs::remove_file(&spec.path);
+    }
+    tracing::info!("shutdown complete");
 
     Ok(())
 }
 
-/// Maximum request line length (2 MiB). Rejects oversized lines to prevent OOM.
-const MAX_REQUEST_LINE: usize = 2 * 1024 * 1024;
+/// Source of opaque per-connection ids (synth-1166), handed out once per
+/// accepted connection and used only to scope `cancel` control messages to
+/// same-connection requests unless the canceller is admin-capable — never
+/// exposed to clients.
+static NEXT_CONNECTION_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+/// Decrements a shared open-connections counter on drop (synth-1173) —
+/// held by a connection's spawned task for its whole lifetime, via
+/// `SandboxEngine::open_connections`, so `_health`/`_metrics`'s gauge comes
+/// back down whether the task ends via a clean EOF, a propagated I/O error,
+/// or being aborted outright during the shutdown drain's timeout.
+struct OpenConnectionGuard(Arc<std::sync::atomic::AtomicU64>);
+
+impl Drop for OpenConnectionGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, std::sync::atomic::Ordering::Relaxed);
+    }
+}
 
 /// Handle a single client connection — read newline-delimited JSON requests,
 /// process each one via the sandbox engine, and write back JSON responses.
-async fn handle_connection(
-    stream: tokio::net::UnixStream,
-    engine: &SandboxEngine,
-    registry: &ToolRegistry,
-    leak_detector: &LeakDetector,
-) -> std::io::Result<()> {
-    let (reader, mut writer) = stream.into_split();
+///
+/// The first line may optionally be a `{"hello": {...}}` handshake
+/// (synth-1124) declaring the maximum capability set this connection may
+/// ever grant. Until (or absent) a hello, `default_ceiling` applies. Every
+/// request's capabilities are intersected with the active ceiling before
+/// execution; anything outside it fails the request with the stripped names
+/// named in the error, rather than silently narrowing what the tool runs
+/// with.
+///
+/// Any line may also be a `{"grant": {...}}` control message (synth-1130)
+/// pre-authorizing a capability bundle for a bounded time; it is unioned
+/// into every subsequent `Request`'s capabilities until it expires, and is
+/// itself clamped to the active ceiling like any other grant.
+///
+/// Any line may also be a `{"reload": "registry"}` control message
+/// (synth-1132), which hot-reloads `tool_dir` and swaps it into `registry`
+/// — the same effect as SIGHUP, but scoped to this connection's caller
+/// getting the reload report back as a Response. `{"reload": "config"}`
+/// (synth-1152) does the same for `engine`'s config snapshot, and
+/// `{"reload": "credentials"}` (synth-1155) re-runs the configured
+/// credential provider and swaps its result into `engine`.
+///
+/// Generic over the stream type (synth-1169) so both the Unix listener and
+/// the optional TCP listener share this one implementation — `S` is
+/// whatever `tokio::io::split` can split, which is anything
+/// `AsyncRead + AsyncWrite`. When `required_token` is `Some` (only ever the
+/// case for a TCP-accepted connection — the Unix socket's authentication is
+/// its filesystem permissions), the very first line must be a matching
+/// `{"auth": {"token": "..."}}` message or the connection is rejected with
+/// `unauthorized` and closed without processing anything else.
+///
+/// `shutdown` (synth-1171) is the same process-wide `Notify` the accept
+/// loops watch; while idle between requests this connection races its next
+/// read against it and, the first time it fires, sends a one-line
+/// `{"event": "shutting_down"}` notice so a well-behaved client can start
+/// winding down on its own, rather than only discovering the sidecar is
+/// gone when the socket closes. The read loop itself keeps running — actual
+/// draining and the timeout are `main`'s job, not this connection's.
+///
+/// `max_request_bytes` (synth-1172) caps every line `read_bounded_line`
+/// reads — the auth line, and every request/control line in the main loop —
+/// so a client that never sends a newline can't grow this connection's
+/// buffer without bound; exceeding it is treated the same as any other
+/// malformed line: an `invalid_request` error and the connection is closed,
+/// since framing past an unterminated line can no longer be trusted.
+///
+/// `idle_timeout_secs` (synth-1173) closes the connection once this many
+/// seconds pass with no line read and no request of this connection's still
+/// running — a fresh deadline is armed at the top of every loop iteration,
+/// so back-to-back pipelined requests never trip it. It's skipped entirely
+/// while `request_tasks` is non-empty: a slow tool shouldn't lose its
+/// response just because the client went quiet waiting for it.
+///
+/// `encoding` (synth-1175) is this connection's starting wire encoding — see
+/// [`PayloadEncoding`] — mutable from here on: a `hello` line whose
+/// `encoding` field names a different one switches every message read or
+/// written afterward, including the `hello` ack itself. The mandatory TCP
+/// auth line above is always read as JSON regardless, since `required_token`
+/// (TCP) and a non-default `encoding` (only ever set per Unix listener)
+/// never occur on the same connection.
+/// Constant-time byte-slice comparison (synth-1169), used for the TCP
+/// bearer-token check below. `ring::constant_time::verify_slices_are_equal`
+/// is only a deprecated re-export in this `ring` version ("not intended for
+/// external use"), so this compares lengths up front (the token length isn't
+/// secret) and then accumulates the XOR of every byte pair instead of
+/// short-circuiting on the first mismatch.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter()
+        .zip(b.iter())
+        .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+        == 0
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn handle_connection<S>(
+    stream: S,
+    engine: &Arc<SandboxEngine>,
+    registry: &Arc<RwLock<Arc<ToolRegistry>>>,
+    leak_detector: &Arc<LeakDetector>,
+    default_ceiling: &HashSet<Capability>,
+    tool_dir: &std::path::Path,
+    require_tool_hash: bool,
+    tool_signing_keys: &[Vec<u8>],
+    require_signed_tools: bool,
+    allow_precompiled: bool,
+    config_path: Option<&Path>,
+    execution_semaphore: &Arc<PriorityScheduler>,
+    required_token: Option<&str>,
+    encoding: PayloadEncoding,
+    max_request_bytes: usize,
+    idle_timeout_secs: u64,
+    shutdown: &Arc<tokio::sync::Notify>,
+) -> std::io::Result<()>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + Unpin + 'static,
+{
+    // synth-1166: identifies this connection to the sandbox engine's
+    // in-flight tracker, so a `cancel` control message can be scoped to
+    // requests submitted on the same connection unless it's admin-capable.
+    let connection_id = NEXT_CONNECTION_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let (reader, writer) = tokio::io::split(stream);
+    let writer = Arc::new(tokio::sync::Mutex::new(writer));
     let mut buf_reader = BufReader::new(reader);
+    // synth-1175: mutable so a `hello` line can switch it partway through.
+    let mut encoding = encoding;
+
+    // synth-1169: the TCP listener's mandatory bearer-token check. Runs
+    // before the read loop below ever sees a `hello`/`grant`/`Request` line,
+    // and — unlike a failed `Request` parse — closes the connection outright
+    // rather than continuing to read further lines from a caller that never
+    // proved it holds the token. Always read as plain JSON (synth-1175): a
+    // non-default `encoding` is only ever configured per Unix listener,
+    // which never sets `required_token`.
+    if let Some(expected) = required_token {
+        let authorized = match read_bounded_line(&mut buf_reader, max_request_bytes).await {
+            Ok(Some(line)) => match serde_json::from_str::<AuthMessage>(&line) {
+                // Constant-time comparison: a bearer token is exactly the
+                // kind of secret a `==` early-exit-on-first-mismatch turns
+                // into a timing side channel for a network-exposed listener.
+                Ok(auth) => constant_time_eq(auth.auth.token.as_bytes(), expected.as_bytes()),
+                Err(_) => false,
+            },
+            _ => false,
+        };
+        if !authorized {
+            let err = Response::error_with_code(
+                "missing or invalid auth token".to_string(),
+                "unauthorized",
+            );
+            write_response(&writer, &err, encoding).await?;
+            return Ok(());
+        }
+        let ack = Response::success("auth acknowledged".to_string());
+        write_response(&writer, &ack, encoding).await?;
+    }
+    // Tool executions (synth-1164): each spawned into its own task, bounded
+    // by `execution_semaphore`, so one slow tool no longer blocks every other
+    // request pipelined on this connection. Joined after the read loop below
+    // exits, so a client disconnecting mid-flight doesn't drop responses
+    // that were already running. Admission order is priority-aware
+    // (synth-1178) — see `execution_semaphore`'s construction in `main()`.
+    let mut request_tasks = tokio::task::JoinSet::new();
+
+    let mut ceiling: Option<HashSet<Capability>> = None;
+    let mut first_line = true;
+    // Temporary capability grants (synth-1130): raw capability string paired
+    // with the instant it expires. Pruned lazily — only ever scanned, never
+    // proactively swept — on the next grant or request.
+    let mut temp_grants: Vec<(String, std::time::Instant)> = Vec::new();
+
+    // synth-1173: re-armed at the top of every loop iteration below, so the
+    // deadline always measures idleness since the last line (or shutdown
+    // notice) rather than since the connection was accepted.
+    let idle_timeout = tokio::time::Duration::from_secs(idle_timeout_secs);
+    let idle_sleep = tokio::time::sleep(idle_timeout);
+    tokio::pin!(idle_sleep);
 
     loop {
-        let response = match read_bounded_line(&mut buf_reader, MAX_REQUEST_LINE).await {
-            Ok(None) => break, // EOF
-            Ok(Some(line)) => match serde_json::from_str::<Request>(&line) {
-                Ok(req) => {
-                    eprintln!(
-                        "sidecar: [request_id={}] executing tool={}",
-                        req.request_id, req.tool_name
+        // Reap request tasks that finished since the last iteration
+        // (synth-1173) — `request_tasks` is otherwise only drained after this
+        // loop exits, which would leave `is_empty()` below permanently false
+        // on any connection that ever ran a request.
+        while request_tasks.try_join_next().is_some() {}
+        idle_sleep.as_mut().reset(tokio::time::Instant::now() + idle_timeout);
+
+        // synth-1171: races the next read against the process-wide shutdown
+        // signal so a connection sitting idle between requests still hears
+        // about a graceful shutdown — the read itself isn't cancelled, so a
+        // request that arrives in the same instant isn't dropped, but once
+        // notified, this fires at most once per connection (`notify_waiters`
+        // only wakes waiters registered before it's called, so a `Notify`
+        // already fired never fires a freshly-created `notified()` again).
+        //
+        // synth-1173: also races against `idle_sleep`, closing the
+        // connection once nothing has arrived for `idle_timeout_secs` —
+        // skipped (via the `if`) while `request_tasks` isn't empty, so an
+        // execution still running on this connection inhibits the close.
+        let bytes = tokio::select! {
+            result = read_frame(&mut buf_reader, encoding, max_request_bytes) => {
+                match result {
+                    Ok(None) => break, // EOF
+                    Ok(Some(bytes)) => bytes,
+                    Err(_) => {
+                        // Line/frame exceeds limit — send error and close connection
+                        let err = Response::error_with_code(
+                            format!("request too large (max {max_request_bytes} bytes)"),
+                            "invalid_request",
+                        );
+                        write_response(&writer, &err, encoding).await?;
+                        break;
+                    }
+                }
+            }
+            _ = shutdown.notified() => {
+                let out = encode_frame(&serde_json::json!({"event": "shutting_down"}), encoding);
+                let _ = writer.lock().await.write_all(&out).await;
+                continue;
+            }
+            () = &mut idle_sleep, if request_tasks.is_empty() => {
+                tracing::info!(idle_timeout_secs, "closing idle connection");
+                break;
+            }
+        };
+
+        if first_line {
+            first_line = false;
+            if let Ok(hello) = decode_message::<HelloMessage>(&bytes, encoding) {
+                // synth-1176: reject an incompatible hello before it can set
+                // a ceiling or negotiate an encoding — an old controller
+                // that never sends `protocol_version` at all is always let
+                // through.
+                if !protocol::protocol_version_supported(hello.hello.protocol_version) {
+                    let response = Response::error_with_code(
+                        format!(
+                            "unsupported protocol_version {}: this sidecar supports {}..={}",
+                            hello.hello.protocol_version.unwrap_or_default(),
+                            protocol::MIN_SUPPORTED_PROTOCOL_VERSION,
+                            protocol::MAX_SUPPORTED_PROTOCOL_VERSION,
+                        ),
+                        "unsupported_protocol",
+                    );
+                    write_response(&writer, &response, encoding).await?;
+                    continue;
+                }
+                let parsed = capabilities::ceiling_from_names(&hello.hello.max_capabilities);
+                tracing::info!(
+                    client = ?hello.hello.client,
+                    max_capabilities = %hello.hello.max_capabilities.join(", "),
+                    "connection hello"
+                );
+                ceiling = Some(parsed);
+                // synth-1175: switch encoding before the ack, so the ack
+                // itself goes out already using the negotiated one.
+                if let Some(requested) = hello.hello.encoding.as_deref().and_then(PayloadEncoding::from_name) {
+                    encoding = requested;
+                }
+                let mut ack = Response::success("hello acknowledged".to_string());
+                // synth-1176: advertise the supported range on every hello
+                // ack, so a controller can self-check compatibility even if
+                // it never sent its own `protocol_version`.
+                ack.data = Some(serde_json::json!({
+                    "protocol_version": {
+                        "min": protocol::MIN_SUPPORTED_PROTOCOL_VERSION,
+                        "max": protocol::MAX_SUPPORTED_PROTOCOL_VERSION,
+                    }
+                }));
+                write_response(&writer, &ack, encoding).await?;
+                continue;
+            }
+        }
+
+        if let Ok(reload) = decode_message::<ReloadMessage>(&bytes, encoding) {
+            let response = if reload.reload == "registry" {
+                let result = reload_registry(
+                    registry,
+                    tool_dir,
+                    engine.engine(),
+                    require_tool_hash,
+                    tool_signing_keys,
+                    require_signed_tools,
+                    allow_precompiled,
+                );
+                log_reload_result(&result);
+                reload_response(result)
+            } else if reload.reload == "config" {
+                let result = reload_sidecar_config(engine, config_path);
+                log_config_reload_result(&result);
+                config_reload_response(result)
+            } else if reload.reload == "credentials" {
+                let result = reload_engine_credentials(engine);
+                log_credentials_reload_result(&result);
+                credentials_reload_response(result)
+            } else {
+                Response::error_with_code(
+                    format!("unknown reload target: {}", reload.reload),
+                    "unknown_reload_target",
+                )
+            };
+            write_response(&writer, &response, encoding).await?;
+            continue;
+        }
+
+        if let Ok(grant) = decode_message::<GrantMessage>(&bytes, encoding) {
+            let active_ceiling = ceiling.as_ref().unwrap_or(default_ceiling);
+            let (allowed, stripped) =
+                capabilities::partition_by_ceiling(&grant.grant.capabilities, active_ceiling);
+            let expiry = std::time::Instant::now()
+                + std::time::Duration::from_millis(grant.grant.ttl_ms);
+            prune_expired_grants(&mut temp_grants, std::time::Instant::now());
+            for cap in &allowed {
+                temp_grants.push((cap.clone(), expiry));
+            }
+            let mut ack = Response::success("grant acknowledged".to_string());
+            ack.data = Some(serde_json::json!({ "granted": allowed, "stripped": stripped }));
+            write_response(&writer, &ack, encoding).await?;
+            continue;
+        }
+
+        if let Ok(control) = decode_message::<ControlMessage>(&bytes, encoding) {
+            let mut response = if control.control == "cancel" {
+                // synth-1166: cross-connection cancellation is scoped to
+                // connections whose active ceiling includes `admin` — the
+                // same umbrella capability that already gates the broadest
+                // non-scoped grants elsewhere (see `Capability::Admin`).
+                let active_ceiling = ceiling.as_ref().unwrap_or(default_ceiling);
+                let is_admin = active_ceiling.contains(&Capability::Admin);
+                let found = match engine.in_flight_owner(&control.request_id) {
+                    Some(owner) if owner == connection_id || is_admin => {
+                        engine.cancel(&control.request_id)
+                    }
+                    _ => false,
+                };
+                let mut ack = Response::success("cancel acknowledged".to_string());
+                ack.data = Some(serde_json::json!({ "event": "cancel_ack", "found": found }));
+                ack
+            } else if control.control == "reload" {
+                // synth-1182: a socket-native alternative to sending SIGHUP
+                // to a containerized sidecar. Gated to `admin`-capable
+                // connections — the same admin gate as cross-connection
+                // `cancel` above — since unlike the per-request `Request`
+                // path, a reload affects every connection sharing this
+                // engine and registry.
+                let active_ceiling = ceiling.as_ref().unwrap_or(default_ceiling);
+                if !active_ceiling.contains(&Capability::Admin) {
+                    Response::error_with_code(
+                        "control reload requires the admin capability".to_string(),
+                        "unauthorized",
+                    )
+                } else {
+                    admin_reload_response(
+                        control.target.as_deref().unwrap_or(""),
+                        registry,
+                        tool_dir,
+                        engine,
+                        require_tool_hash,
+                        tool_signing_keys,
+                        require_signed_tools,
+                        allow_precompiled,
+                        config_path,
+                    )
+                }
+            } else {
+                Response::error_with_code(
+                    format!("unknown control message: {}", control.control),
+                    "invalid_request",
+                )
+            };
+            response.request_id = control.request_id.clone();
+            write_response(&writer, &response, encoding).await?;
+            continue;
+        }
+
+        if let Ok(batch) = decode_message::<BatchMessage>(&bytes, encoding) {
+            let max_batch_size = engine.config().max_batch_size;
+            if batch.mode != "sequential" && batch.mode != "parallel" {
+                let mut response = Response::error_with_code(
+                    format!("unknown batch mode: {}", batch.mode),
+                    "invalid_request",
+                );
+                response.request_id = batch.request_id.clone();
+                write_response(&writer, &response, encoding).await?;
+            } else if batch.batch.len() > max_batch_size {
+                let mut response = Response::error_with_code(
+                    format!(
+                        "batch of {} entries exceeds max_batch_size {max_batch_size}",
+                        batch.batch.len()
+                    ),
+                    "batch_too_large",
+                );
+                response.request_id = batch.request_id.clone();
+                write_response(&writer, &response, encoding).await?;
+            } else {
+                // synth-1183: spawned like a single request above (synth-1164)
+                // so a slow batch can't stall this connection from reading
+                // ahead — the batch's own entries are resolved and executed
+                // entirely inside the spawned task, then written as one
+                // envelope response once every entry that's going to run has.
+                let engine = engine.clone();
+                let leak_detector = leak_detector.clone();
+                let writer = writer.clone();
+                let semaphore = execution_semaphore.clone();
+                let snapshot = registry.read().unwrap().clone();
+                let active_ceiling = ceiling.clone().unwrap_or_else(|| default_ceiling.clone());
+                let grants_snapshot = temp_grants.clone();
+                let batch_request_id = batch.request_id.clone();
+                request_tasks.spawn(async move {
+                    let responses = execute_batch_entries(
+                        batch.batch,
+                        &batch.mode,
+                        batch.continue_on_error,
+                        &engine,
+                        &snapshot,
+                        &leak_detector,
+                        connection_id,
+                        &semaphore,
+                        &active_ceiling,
+                        &grants_snapshot,
+                    )
+                    .await;
+                    let all_succeeded = responses.iter().all(|r| r.success);
+                    let data = serde_json::json!(responses);
+                    let mut envelope = if all_succeeded {
+                        Response::success_with_data("batch executed".to_string(), data)
+                    } else {
+                        let mut response = Response::error_with_code(
+                            "one or more batch entries failed".to_string(),
+                            "batch_partial_failure",
+                        );
+                        response.data = Some(data);
+                        response
+                    };
+                    envelope.request_id = batch_request_id;
+                    if let Err(e) = write_response(&writer, &envelope, encoding).await {
+                        tracing::warn!(error = %e, "failed to write batch response");
+                    }
+                });
+            }
+            continue;
+        }
+
+        match decode_request(&bytes, encoding, engine.config().strict_requests) {
+            Ok(mut req) => {
+                // synth-1176: reject an incompatible request before it ever
+                // reaches the capability ceiling or the sandbox — an old
+                // controller that never sends `protocol_version` at all is
+                // always let through.
+                if !protocol::protocol_version_supported(req.protocol_version) {
+                    let mut response = Response::error_with_code(
+                        format!(
+                            "unsupported protocol_version {}: this sidecar supports {}..={}",
+                            req.protocol_version.unwrap_or_default(),
+                            protocol::MIN_SUPPORTED_PROTOCOL_VERSION,
+                            protocol::MAX_SUPPORTED_PROTOCOL_VERSION,
+                        ),
+                        "unsupported_protocol",
                     );
-                    let resp = engine.execute(&req, registry, leak_detector).await;
-                    eprintln!(
-                        "sidecar: [request_id={}] tool={} success={}",
-                        req.request_id, req.tool_name, resp.success
+                    response.request_id = req.request_id.clone();
+                    write_response(&writer, &response, encoding).await?;
+                    continue;
+                }
+                // synth-1180: a request already past its caller-supplied
+                // usefulness window is rejected before it ever reaches the
+                // capability ceiling or the execution admission queue —
+                // nobody's waiting on the result, so don't spend a queue
+                // slot or a sandboxed execution finding that out.
+                if let Some(deadline_epoch_ms) = req.deadline_epoch_ms {
+                    if epoch_millis_now() > deadline_epoch_ms {
+                        let mut response = Response::error_with_code(
+                            "request deadline already passed before execution".to_string(),
+                            "deadline_exceeded",
+                        );
+                        response.request_id = req.request_id.clone();
+                        write_response(&writer, &response, encoding).await?;
+                        continue;
+                    }
+                }
+                prune_expired_grants(&mut temp_grants, std::time::Instant::now());
+                req.capabilities = merge_granted_capabilities(&req.capabilities, &temp_grants);
+                let active_ceiling = ceiling.as_ref().unwrap_or(default_ceiling);
+                let (_allowed, stripped) =
+                    capabilities::partition_by_ceiling(&req.capabilities, active_ceiling);
+                let request_span = tracing::info_span!(
+                    "request",
+                    request_id = %req.request_id,
+                    tool_name = %req.tool_name,
+                );
+                if !stripped.is_empty() {
+                    request_span.in_scope(|| {
+                        tracing::warn!(
+                            denied = %stripped.join(", "),
+                            "capability ceiling exceeded"
+                        );
+                    });
+                    let response = Response::error_with_code(
+                        format!(
+                            "capability ceiling exceeded: {} not permitted on this connection",
+                            stripped.join(", ")
+                        ),
+                        "capability_ceiling_exceeded",
                     );
+                    write_response(&writer, &response, encoding).await?;
+                } else {
+                    // synth-1164: spawned rather than awaited inline, so this
+                    // request's tool execution can't block the read loop from
+                    // picking up the next pipelined line — the semaphore
+                    // permit is acquired inside the task, after spawning, so
+                    // a full bench of in-flight executions still doesn't stop
+                    // this connection from reading ahead. Responses are
+                    // written as each task completes, so ordering across
+                    // requests on the same connection is no longer
+                    // guaranteed — a fast request queued behind a slow one
+                    // can now finish, and be written, first.
+                    let engine = engine.clone();
+                    let leak_detector = leak_detector.clone();
+                    let writer = writer.clone();
+                    let semaphore = execution_semaphore.clone();
+                    let priority = req.priority.unwrap_or(scheduler::DEFAULT_PRIORITY);
+                    let snapshot = registry.read().unwrap().clone();
+                    request_tasks.spawn(async move {
+                        let (_permit, queue_wait) = semaphore.acquire_owned(priority).await;
+                        request_span.in_scope(|| tracing::info!("executing tool"));
+                        let mut resp = engine
+                            .execute_cancellable(&req, &snapshot, &leak_detector, connection_id)
+                            .instrument(request_span.clone())
+                            .await;
+                        resp.request_id = req.request_id;
+                        resp.queue_wait_ms = Some(queue_wait.as_millis() as u64);
+                        request_span.in_scope(|| {
+                            tracing::info!(success = resp.success, "tool execution finished")
+                        });
+                        if let Err(e) = write_response(&writer, &resp, encoding).await {
+                            tracing::warn!(error = %e, "failed to write response");
+                        }
+                    });
+                }
+            }
+            Err(e) => {
+                let response =
+                    Response::error_with_code(format!("invalid request: {e}"), "invalid_request");
+                write_response(&writer, &response, encoding).await?;
+            }
+        };
+    }
+
+    // synth-1180: the client is gone (reader EOF, or the connection being
+    // torn down after a malformed frame) — cancel whatever this connection
+    // still has running rather than let it spin to completion (or its own
+    // timeout) for a response nobody will read. A no-op when the loop above
+    // exited via the idle-timeout branch, which only fires once
+    // `request_tasks` is already empty.
+    engine.cancel_connection(connection_id);
+
+    // Let in-flight request tasks finish and write their responses rather
+    // than dropping them when the client half-closes after pipelining its
+    // last request.
+    while request_tasks.join_next().await.is_some() {}
+
+    Ok(())
+}
+
+/// Serialize `response` and write it as one frame in `encoding` (synth-1175),
+/// serializing concurrent writers (synth-1164) through the shared `writer`
+/// mutex — control-message acks and spawned per-request tasks all funnel
+/// through here so two writes can never interleave their bytes.
+async fn write_response<W>(
+    writer: &Arc<tokio::sync::Mutex<W>>,
+    response: &Response,
+    encoding: PayloadEncoding,
+) -> std::io::Result<()>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    let out = encode_frame(response, encoding);
+    writer.lock().await.write_all(&out).await
+}
+
+/// Current wall-clock time as milliseconds since the Unix epoch, the same
+/// units as `Request.deadline_epoch_ms` (synth-1180). Clock skew between the
+/// controller and this process is a pre-existing risk of any absolute
+/// deadline, and not something the sidecar corrects for.
+fn epoch_millis_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Drop temporary grants (synth-1130) that expired at or before `now`. Split
+/// out from `handle_connection`'s I/O loop so the pruning logic can be unit
+/// tested without a socket or a real sleep.
+fn prune_expired_grants(grants: &mut Vec<(String, std::time::Instant)>, now: std::time::Instant) {
+    grants.retain(|(_, expiry)| *expiry > now);
+}
+
+/// Union a request's own capability names with the still-active temporary
+/// grants (synth-1130), without duplicating a name already present.
+fn merge_granted_capabilities(
+    request_caps: &[String],
+    active_grants: &[(String, std::time::Instant)],
+) -> Vec<String> {
+    let mut merged = request_caps.to_vec();
+    for (cap, _) in active_grants {
+        if !merged.contains(cap) {
+            merged.push(cap.clone());
+        }
+    }
+    merged
+}
+
+/// Admit one entry of a `{"batch": [...]}"` envelope (synth-1183): the same
+/// protocol-version, deadline, and capability-ceiling checks a lone
+/// [`Request`] passes through in `handle_connection` before it ever reaches
+/// the sandbox admission queue, just resolved per batch entry instead of per
+/// connection line. Returns the rejection [`Response`] (already carrying
+/// `req.request_id`) on the first failing check, or the request with its
+/// capabilities merged against `grants` ready to execute.
+#[allow(clippy::result_large_err)]
+fn admit_batch_entry(
+    mut req: Request,
+    active_ceiling: &HashSet<Capability>,
+    grants: &[(String, std::time::Instant)],
+) -> Result<Request, Response> {
+    if !protocol::protocol_version_supported(req.protocol_version) {
+        let mut response = Response::error_with_code(
+            format!(
+                "unsupported protocol_version {}: this sidecar supports {}..={}",
+                req.protocol_version.unwrap_or_default(),
+                protocol::MIN_SUPPORTED_PROTOCOL_VERSION,
+                protocol::MAX_SUPPORTED_PROTOCOL_VERSION,
+            ),
+            "unsupported_protocol",
+        );
+        response.request_id = req.request_id.clone();
+        return Err(response);
+    }
+    if let Some(deadline_epoch_ms) = req.deadline_epoch_ms {
+        if epoch_millis_now() > deadline_epoch_ms {
+            let mut response = Response::error_with_code(
+                "request deadline already passed before execution".to_string(),
+                "deadline_exceeded",
+            );
+            response.request_id = req.request_id.clone();
+            return Err(response);
+        }
+    }
+    req.capabilities = merge_granted_capabilities(&req.capabilities, grants);
+    let (_allowed, stripped) = capabilities::partition_by_ceiling(&req.capabilities, active_ceiling);
+    if !stripped.is_empty() {
+        let mut response = Response::error_with_code(
+            format!(
+                "capability ceiling exceeded: {} not permitted on this connection",
+                stripped.join(", ")
+            ),
+            "capability_ceiling_exceeded",
+        );
+        response.request_id = req.request_id.clone();
+        return Err(response);
+    }
+    Ok(req)
+}
+
+/// Execute every entry of a `{"batch": [...]}"` envelope (synth-1183).
+/// `"parallel"` runs every admitted entry concurrently, each bounded by the
+/// same `semaphore` as any other request; anything other than `"parallel"`
+/// (i.e. `"sequential"`, already validated by the caller) runs them in
+/// order and stops at the first entry whose `Response::success` is `false`
+/// unless `continue_on_error` is set — so the returned `Vec` may be shorter
+/// than `entries` was.
+#[allow(clippy::too_many_arguments)]
+async fn execute_batch_entries(
+    entries: Vec<Request>,
+    mode: &str,
+    continue_on_error: bool,
+    engine: &Arc<SandboxEngine>,
+    registry_snapshot: &Arc<ToolRegistry>,
+    leak_detector: &Arc<LeakDetector>,
+    connection_id: u64,
+    semaphore: &Arc<PriorityScheduler>,
+    active_ceiling: &HashSet<Capability>,
+    grants: &[(String, std::time::Instant)],
+) -> Vec<Response> {
+    if mode == "parallel" {
+        let total = entries.len();
+        let mut tasks = tokio::task::JoinSet::new();
+        for (idx, req) in entries.into_iter().enumerate() {
+            let admitted = admit_batch_entry(req, active_ceiling, grants);
+            let engine = engine.clone();
+            let registry_snapshot = registry_snapshot.clone();
+            let leak_detector = leak_detector.clone();
+            let semaphore = semaphore.clone();
+            tasks.spawn(async move {
+                let response = match admitted {
+                    Ok(req) => {
+                        let priority = req.priority.unwrap_or(scheduler::DEFAULT_PRIORITY);
+                        let (_permit, queue_wait) = semaphore.acquire_owned(priority).await;
+                        let mut resp = engine
+                            .execute_cancellable(&req, &registry_snapshot, &leak_detector, connection_id)
+                            .await;
+                        resp.request_id = req.request_id;
+                        resp.queue_wait_ms = Some(queue_wait.as_millis() as u64);
+                        resp
+                    }
+                    Err(response) => response,
+                };
+                (idx, response)
+            });
+        }
+        let mut ordered: Vec<Option<Response>> = (0..total).map(|_| None).collect();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok((idx, response)) = joined {
+                ordered[idx] = Some(response);
+            }
+        }
+        ordered.into_iter().flatten().collect()
+    } else {
+        let mut out = Vec::new();
+        for req in entries {
+            let response = match admit_batch_entry(req, active_ceiling, grants) {
+                Ok(req) => {
+                    let priority = req.priority.unwrap_or(scheduler::DEFAULT_PRIORITY);
+                    let (_permit, queue_wait) = semaphore.clone().acquire_owned(priority).await;
+                    let mut resp = engine
+                        .execute_cancellable(&req, registry_snapshot, leak_detector, connection_id)
+                        .await;
+                    resp.request_id = req.request_id;
+                    resp.queue_wait_ms = Some(queue_wait.as_millis() as u64);
                     resp
                 }
-                Err(e) => Response::error(format!("invalid request: {e}")),
-            },
-            Err(_) => {
-                // Line exceeds limit — send error and close connection
-                let err = Response::error("request too large (max 2 MiB)".to_string());
-                let mut out = serde_json::to_string(&err).unwrap_or_default();
-                out.push('\n');
-                writer.write_all(out.as_bytes()).await?;
+                Err(response) => response,
+            };
+            let failed = !response.success;
+            out.push(response);
+            if failed && !continue_on_error {
                 break;
             }
-        };
+        }
+        out
+    }
+}
+
+/// Anything `handle_connection` can speak newline-delimited JSON over
+/// (synth-1169) — a `UnixStream`, a plain `TcpStream`, or a TLS-terminated
+/// TCP stream (see `tls::TlsStream`). A marker rather than a real trait:
+/// every `AsyncRead + AsyncWrite + Send + Unpin` type already qualifies via
+/// the blanket impl below, so `Box<dyn AsyncStream>` is just a way to name
+/// "whichever one of those this connection turned out to be" once accepted.
+trait AsyncStream: tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + Unpin {}
+impl<T: tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + Unpin> AsyncStream for T {}
+
+/// A listener bound and ready to accept (synth-1159): the socket itself,
+/// its path (for logging and cleanup), the capability ceiling new
+/// connections on it start with, and an optional semaphore enforcing its
+/// `max_connections`. `allowed_uids` (synth-1170) is checked against each
+/// accepted connection's `SO_PEERCRED`; empty accepts any peer, same as
+/// before that check existed.
+struct ActiveListener {
+    listener: UnixListener,
+    path: PathBuf,
+    ceiling: Arc<HashSet<Capability>>,
+    semaphore: Option<Arc<tokio::sync::Semaphore>>,
+    allowed_uids: Vec<u32>,
+    /// Wire encoding new connections on this listener start with
+    /// (synth-1175) — see [`config::ListenerConfig::encoding`].
+    encoding: PayloadEncoding,
+}
+
+/// A connection accepted on one of possibly several listeners (synth-1159),
+/// tagged with that listener's ceiling and (if the listener has a
+/// `max_connections` cap) the permit reserving its slot — held by the
+/// caller for as long as the connection is alive. `stream` is boxed
+/// (synth-1169) since it may have come from the Unix listener or the
+/// optional TCP one, and `required_token` carries the TCP listener's bearer
+/// token through to `handle_connection` — always `None` for a Unix
+/// connection, whose authentication is the socket file's permissions.
+/// `peer_uid` (synth-1170) is the Unix peer's `SO_PEERCRED` uid, attached to
+/// the connection's tracing span; `None` for a TCP connection, which has no
+/// equivalent.
+struct AcceptedConnection {
+    stream: Box<dyn AsyncStream>,
+    ceiling: Arc<HashSet<Capability>>,
+    permit: Option<tokio::sync::OwnedSemaphorePermit>,
+    required_token: Option<Arc<str>>,
+    peer_uid: Option<u32>,
+    /// Wire encoding this connection starts with (synth-1175) — the owning
+    /// listener's [`ActiveListener::encoding`] for a Unix connection, always
+    /// `Json` for a TCP one. A `hello` line can still negotiate it up
+    /// regardless of this starting value.
+    encoding: PayloadEncoding,
+}
+
+/// The listeners `main` should actually bind (synth-1159): `config.listeners`
+/// verbatim, or — when none are configured — a single listener synthesized
+/// from the legacy `socket_path`/`socket_mode`/`default_capability_ceiling`
+/// fields with unlimited connections, preserving pre-synth-1159 behavior
+/// exactly for deployments that haven't adopted `[[listener]]` yet.
+fn effective_listeners(config: &SidecarConfig) -> Vec<ListenerConfig> {
+    if config.listeners.is_empty() {
+        vec![ListenerConfig {
+            path: config.socket_path.clone(),
+            mode: config.socket_mode,
+            default_capability_ceiling: config.default_capability_ceiling.clone(),
+            max_connections: None,
+            encoding: PayloadEncoding::Json,
+        }]
+    } else {
+        config.listeners.clone()
+    }
+}
 
-        let mut out = serde_json::to_string(&response).unwrap_or_default();
-        out.push('\n');
-        writer.write_all(out.as_bytes()).await?;
+/// Bind one listener socket (synth-1159): remove a stale socket file, bind
+/// with `mode` already in effect (see `bind_unix_socket`), `chown` to
+/// `socket_group` if configured, and log readiness. Shared by every
+/// `[[listener]]` entry (or the single synthesized one) — the same steps
+/// `main` previously ran once, inline, for its one socket.
+fn bind_listener(
+    spec: &ListenerConfig,
+    socket_group: Option<&str>,
+    allowed_uids: &[u32],
+) -> std::io::Result<ActiveListener> {
+    if spec.path.exists() {
+        std::fs::remove_file(&spec.path)?;
+    }
+    let listener = bind_unix_socket(&spec.path, spec.mode)?;
+    if let Err(e) = std::fs::set_permissions(&spec.path, std::fs::Permissions::from_mode(spec.mode)) {
+        tracing::warn!(path = %spec.path.display(), error = %e, "failed to set socket file mode");
+    }
+    if let Some(group) = socket_group {
+        if let Err(e) = chown_socket_group(&spec.path, group) {
+            tracing::warn!(path = %spec.path.display(), group, error = %e, "failed to chown socket file to group");
+        }
     }
+    tracing::info!(
+        path = %spec.path.display(),
+        mode = format_args!("{:o}", spec.mode),
+        max_connections = ?spec.max_connections,
+        "listening"
+    );
+    Ok(ActiveListener {
+        listener,
+        path: spec.path.clone(),
+        ceiling: Arc::new(capabilities::ceiling_from_names(&spec.default_capability_ceiling)),
+        semaphore: spec.max_connections.map(|n| Arc::new(tokio::sync::Semaphore::new(n))),
+        allowed_uids: allowed_uids.to_vec(),
+        encoding: spec.encoding,
+    })
+}
+
+/// Run `active`'s accept loop in the background (synth-1159), forwarding
+/// each accepted connection into `tx` tagged with `active`'s ceiling and
+/// (if capped) a permit reserving its slot. When `active` has a
+/// `max_connections` cap, the next accept blocks on acquiring a permit
+/// first — simple backpressure via the kernel's own accept backlog, rather
+/// than accepting past the cap and then rejecting. Each accepted connection's
+/// `SO_PEERCRED` uid (synth-1170) is read and, when `active.allowed_uids` is
+/// non-empty, checked against it — a peer outside the allowlist gets a single
+/// `unauthorized` error line and the connection is dropped without ever
+/// reaching `tx`. An empty allowlist accepts any peer (pre-synth-1170
+/// behavior), but the peer uid is still logged either way. Exits when
+/// `shutdown` fires or `tx`'s receiver is dropped.
+fn spawn_listener_accept_loop(
+    active: ActiveListener,
+    tx: tokio::sync::mpsc::Sender<AcceptedConnection>,
+    shutdown: Arc<tokio::sync::Notify>,
+) {
+    tokio::spawn(async move {
+        loop {
+            let permit = match &active.semaphore {
+                Some(semaphore) => match semaphore.clone().acquire_owned().await {
+                    Ok(permit) => Some(permit),
+                    Err(_) => break, // semaphore closed — nothing left to bound
+                },
+                None => None,
+            };
 
+            tokio::select! {
+                result = active.listener.accept() => {
+                    match result {
+                        Ok((mut stream, _addr)) => {
+                            let peer_uid = match stream.peer_cred() {
+                                Ok(cred) => Some(cred.uid()),
+                                Err(e) => {
+                                    tracing::warn!(path = %active.path.display(), error = %e, "failed to read peer credentials");
+                                    None
+                                }
+                            };
+                            if !active.allowed_uids.is_empty()
+                                && !peer_uid.is_some_and(|uid| active.allowed_uids.contains(&uid))
+                            {
+                                tracing::warn!(path = %active.path.display(), ?peer_uid, "rejected connection: peer uid not in allowed_uids");
+                                let mut out = serde_json::to_string(&Response::error_with_code(
+                                    "peer uid not authorized".to_string(),
+                                    "unauthorized",
+                                ))
+                                .unwrap_or_default();
+                                out.push('\n');
+                                let _ = stream.write_all(out.as_bytes()).await;
+                                continue;
+                            }
+                            tracing::info!(path = %active.path.display(), ?peer_uid, "accepted connection");
+                            let accepted = AcceptedConnection {
+                                stream: Box::new(stream),
+                                ceiling: active.ceiling.clone(),
+                                permit,
+                                required_token: None,
+                                peer_uid,
+                                encoding: active.encoding,
+                            };
+                            if tx.send(accepted).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => {
+                            tracing::warn!(path = %active.path.display(), error = %e, "accept failed");
+                        }
+                    }
+                }
+                _ = shutdown.notified() => {
+                    break;
+                }
+            }
+        }
+    });
+}
+
+/// Bind the Unix domain socket with `mode` already in effect the instant it
+/// appears on disk (synth-1154), rather than binding first and `chmod`ing
+/// after — the latter leaves a window where the socket exists with whatever
+/// permissive default the process umask would otherwise apply, during which
+/// any local user could connect. `bind()` for `AF_UNIX` sockets applies the
+/// calling process's umask to the socket file it creates, same as `open()`
+/// would, so narrowing it around the call is enough.
+fn bind_unix_socket(path: &Path, mode: u32) -> std::io::Result<UnixListener> {
+    let previous_umask = unsafe { libc::umask((!mode & 0o777) as libc::mode_t) };
+    let result = UnixListener::bind(path);
+    unsafe { libc::umask(previous_umask) };
+    result
+}
+
+/// `chown` the socket file's group to `group` (synth-1154), leaving its
+/// owner untouched. Only succeeds if the sidecar process is root or already
+/// a member of `group`, same as the `chown(1)` command; a failure here
+/// (unknown group, insufficient privilege) is logged as a warning by the
+/// caller rather than treated as fatal, since the socket is already usable
+/// by its owning user either way.
+fn chown_socket_group(path: &Path, group: &str) -> anyhow::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_group = CString::new(group)?;
+    let gid = unsafe {
+        let entry = libc::getgrnam(c_group.as_ptr());
+        if entry.is_null() {
+            anyhow::bail!("unknown group '{group}'");
+        }
+        (*entry).gr_gid
+    };
+    let c_path = CString::new(path.as_os_str().as_bytes())?;
+    let result = unsafe { libc::chown(c_path.as_ptr(), u32::MAX, gid) };
+    if result != 0 {
+        anyhow::bail!("chown failed: {}", std::io::Error::last_os_error());
+    }
     Ok(())
 }
 
+/// Run the optional TCP listener's accept loop in the background
+/// (synth-1169) — same shape as `spawn_listener_accept_loop`, tagging every
+/// accepted connection with `token` (so `handle_connection` demands it as
+/// the first line) and, when `tls_config` is set, handing the raw
+/// `TcpStream` to `tls::TlsStream::spawn_bridge` before boxing it, so the
+/// rest of the pipeline never has to know whether a given connection is
+/// plaintext or TLS-terminated. Exits when `shutdown` fires or `tx`'s
+/// receiver is dropped, same as the Unix accept loop.
+fn spawn_tcp_accept_loop(
+    listener: tokio::net::TcpListener,
+    ceiling: Arc<HashSet<Capability>>,
+    token: Arc<str>,
+    tls_config: Option<Arc<rustls::ServerConfig>>,
+    tx: tokio::sync::mpsc::Sender<AcceptedConnection>,
+    shutdown: Arc<tokio::sync::Notify>,
+) {
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                result = listener.accept() => {
+                    match result {
+                        Ok((stream, addr)) => {
+                            let stream: Box<dyn AsyncStream> = match &tls_config {
+                                Some(tls_config) => match stream.into_std().and_then(|std_stream| {
+                                    tls::TlsStream::spawn_bridge(std_stream, tls_config.clone())
+                                }) {
+                                    Ok(tls_stream) => Box::new(tls_stream),
+                                    Err(e) => {
+                                        tracing::warn!(peer = %addr, error = %e, "failed to start TLS on accepted connection");
+                                        continue;
+                                    }
+                                },
+                                None => Box::new(stream),
+                            };
+                            let accepted = AcceptedConnection {
+                                stream,
+                                ceiling: ceiling.clone(),
+                                permit: None,
+                                required_token: Some(token.clone()),
+                                peer_uid: None,
+                                encoding: PayloadEncoding::Json,
+                            };
+                            if tx.send(accepted).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => {
+                            tracing::warn!(error = %e, "TCP accept failed");
+                        }
+                    }
+                }
+                _ = shutdown.notified() => {
+                    break;
+                }
+            }
+        }
+    });
+}
+
+/// Rebuild `registry` from `tool_dir` and swap it in (synth-1132), shared by
+/// the SIGHUP handler and the `{"reload": "registry"}` control message. Any
+/// execution already holding a snapshot of the old `Arc<ToolRegistry>` keeps
+/// running against it — only the write lock's brief hold time is visible to
+/// concurrent readers.
+#[allow(clippy::too_many_arguments)]
+fn reload_registry(
+    registry: &Arc<RwLock<Arc<ToolRegistry>>>,
+    tool_dir: &std::path::Path,
+    engine: &wasmtime::Engine,
+    require_hash: bool,
+    signing_keys: &[Vec<u8>],
+    require_signed: bool,
+    allow_precompiled: bool,
+) -> anyhow::Result<registry::ReloadReport> {
+    let previous = registry.read().unwrap().clone();
+    let (mut new_registry, report) = ToolRegistry::reload(
+        tool_dir, engine, &previous, require_hash, signing_keys, require_signed, allow_precompiled,
+    )?;
+    // synth-1147: `ToolRegistry::reload` only knows about tool.toml files on
+    // disk, so a native tool registered at startup would otherwise vanish
+    // (and get misreported as "removed") on every hot reload.
+    register_builtin_native_tools(&mut new_registry, engine);
+    *registry.write().unwrap() = Arc::new(new_registry);
+    Ok(report)
+}
+
+/// Register every built-in native tool (synth-1147) — called once at
+/// startup and again after every registry reload, since a freshly
+/// loaded/reloaded [`ToolRegistry`] only contains what [`ToolRegistry::load`]
+/// found on disk.
+fn register_builtin_native_tools(registry: &mut ToolRegistry, engine: &wasmtime::Engine) {
+    if let Err(e) = registry.register_native(
+        engine,
+        "echo",
+        "Echoes its args back as output. Reference implementation of a native (non-WASM) tool.",
+        Vec::new(),
+        Arc::new(native_tools::EchoTool),
+    ) {
+        tracing::warn!(error = %e, "failed to register built-in native tool 'echo'");
+    }
+}
+
+/// Log the outcome of [`reload_registry`] at the same level of detail
+/// regardless of trigger (SIGHUP or control message).
+fn log_reload_result(result: &anyhow::Result<registry::ReloadReport>) {
+    match result {
+        Ok(report) => tracing::info!(
+            added = %report.added.join(", "),
+            removed = %report.removed.join(", "),
+            changed = %report.changed.join(", "),
+            errors = %report.errors.join("; "),
+            "registry reloaded"
+        ),
+        Err(e) => tracing::error!(error = %e, "registry reload failed"),
+    }
+}
+
+/// Turn a [`reload_registry`] outcome into the `{"reload": "registry"}`
+/// control message's [`Response`].
+fn reload_response(result: anyhow::Result<registry::ReloadReport>) -> Response {
+    match result {
+        Ok(report) => Response::success_with_data(
+            "registry reloaded".to_string(),
+            serde_json::json!({
+                "added": report.added,
+                "removed": report.removed,
+                "changed": report.changed,
+                "errors": report.errors,
+            }),
+        ),
+        Err(e) => Response::error_with_code(format!("reload failed: {e}"), "reload_failed"),
+    }
+}
+
+/// Reload `engine`'s config from `config_path` (or `SENTINEL_SIDECAR_CONFIG`/
+/// env if `None`, matching how it was loaded at startup) and, if it
+/// validates, swap it in (synth-1152). Shared by the SIGHUP handler and the
+/// `{"reload": "config"}` control message, same shape as [`reload_registry`].
+/// Rejects an invalid reload outright, leaving the previously active config
+/// (and any in-flight executions using it) untouched.
+fn reload_sidecar_config(
+    engine: &SandboxEngine,
+    config_path: Option<&Path>,
+) -> anyhow::Result<ConfigReloadReport> {
+    let current = engine.config();
+    let (new_config, report) = current.reload(config_path)?;
+    if let Err(errors) = new_config.validate() {
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        anyhow::bail!("reloaded config is invalid: {}", messages.join("; "));
+    }
+    engine.reload_config(new_config);
+    Ok(report)
+}
+
+/// Log the outcome of [`reload_sidecar_config`] at the same level of detail
+/// regardless of trigger (SIGHUP or control message).
+fn log_config_reload_result(result: &anyhow::Result<ConfigReloadReport>) {
+    match result {
+        Ok(report) => tracing::info!(
+            applied = %report.applied.join(", "),
+            restart_required = %report.restart_required.join(", "),
+            "config reloaded"
+        ),
+        Err(e) => tracing::error!(error = %e, "config reload failed"),
+    }
+}
+
+/// Turn a [`reload_sidecar_config`] outcome into the `{"reload": "config"}`
+/// control message's [`Response`].
+fn config_reload_response(result: anyhow::Result<ConfigReloadReport>) -> Response {
+    match result {
+        Ok(report) => Response::success_with_data(
+            "config reloaded".to_string(),
+            serde_json::json!({
+                "applied": report.applied,
+                "restart_required": report.restart_required,
+            }),
+        ),
+        Err(e) => Response::error_with_code(format!("reload failed: {e}"), "reload_failed"),
+    }
+}
+
+/// Re-run `engine`'s configured credential provider against its current
+/// config snapshot and swap the result in (synth-1155). Shared by the
+/// SIGHUP handler and the `{"reload": "credentials"}` control message,
+/// same shape as [`reload_sidecar_config`] — but independent of it, since a
+/// config reload changing an unrelated field shouldn't re-run an `exec`
+/// provider's command.
+fn reload_engine_credentials(engine: &SandboxEngine) -> anyhow::Result<()> {
+    engine.reload_credentials(&engine.config())
+}
+
+/// Log the outcome of [`reload_engine_credentials`] at the same level of
+/// detail regardless of trigger (SIGHUP or control message).
+fn log_credentials_reload_result(result: &anyhow::Result<()>) {
+    match result {
+        Ok(()) => tracing::info!("credentials reloaded"),
+        Err(e) => tracing::error!(error = %e, "credentials reload failed"),
+    }
+}
+
+/// Turn a [`reload_engine_credentials`] outcome into the
+/// `{"reload": "credentials"}` control message's [`Response`].
+fn credentials_reload_response(result: anyhow::Result<()>) -> Response {
+    match result {
+        Ok(()) => Response::success("credentials reloaded".to_string()),
+        Err(e) => Response::error_with_code(format!("reload failed: {e}"), "reload_failed"),
+    }
+}
+
+/// Dispatch an admin-gated `{"control": "reload", "target": "..."}"` control
+/// message (synth-1182) to the same reload machinery already shared by the
+/// SIGHUP handler and the `{"reload": "..."}` message, returning a
+/// structured summary as the Response's `data`. `"all"` reloads registry and
+/// config together, reporting both under one response rather than requiring
+/// two round trips. `"leak_patterns"` is accepted but is a no-op today — the
+/// leak detector's patterns are compiled into the binary, with no on-disk
+/// source to reload from — so the caller gets an honest acknowledgement
+/// rather than a fabricated summary or a confusing `unknown_reload_target`.
+#[allow(clippy::too_many_arguments)]
+fn admin_reload_response(
+    target: &str,
+    registry: &Arc<RwLock<Arc<ToolRegistry>>>,
+    tool_dir: &Path,
+    engine: &SandboxEngine,
+    require_tool_hash: bool,
+    tool_signing_keys: &[Vec<u8>],
+    require_signed_tools: bool,
+    allow_precompiled: bool,
+    config_path: Option<&Path>,
+) -> Response {
+    let reload_registry_now = || {
+        let result = reload_registry(
+            registry,
+            tool_dir,
+            engine.engine(),
+            require_tool_hash,
+            tool_signing_keys,
+            require_signed_tools,
+            allow_precompiled,
+        );
+        log_reload_result(&result);
+        result
+    };
+    let reload_config_now = || {
+        let result = reload_sidecar_config(engine, config_path);
+        log_config_reload_result(&result);
+        result
+    };
+
+    match target {
+        "registry" => reload_response(reload_registry_now()),
+        "config" => config_reload_response(reload_config_now()),
+        "leak_patterns" => Response::success_with_data(
+            "leak_patterns reload is a no-op: patterns are compiled in, not loaded from disk"
+                .to_string(),
+            serde_json::json!({ "reloaded": false }),
+        ),
+        "all" => {
+            let registry_result = reload_registry_now();
+            let registry_ok = registry_result.is_ok();
+            let config_result = reload_config_now();
+            let config_ok = config_result.is_ok();
+            let data = serde_json::json!({
+                "registry": reload_response(registry_result).data,
+                "config": config_reload_response(config_result).data,
+            });
+            if registry_ok && config_ok {
+                Response::success_with_data("all targets reloaded".to_string(), data)
+            } else {
+                let mut response = Response::error_with_code(
+                    "one or more reload targets failed".to_string(),
+                    "reload_failed",
+                );
+                response.data = Some(data);
+                response
+            }
+        }
+        _ => Response::error_with_code(
+            format!("unknown reload target: {target}"),
+            "unknown_reload_target",
+        ),
+    }
+}
+
 /// Read a newline-terminated line, rejecting lines that exceed `max_bytes`.
 /// Returns `Ok(None)` on EOF, `Ok(Some(line))` on success, `Err` on oversize.
 async fn read_bounded_line<R: AsyncBufRead + Unpin>(
@@ -266,3 +1736,1898 @@ async fn read_bounded_line<R: AsyncBufRead + Unpin>(
         }
     }
 }
+
+/// Read one MessagePack frame (synth-1175): a 4-byte big-endian length
+/// prefix followed by that many raw `rmp-serde`-encoded bytes. Returns
+/// `Ok(None)` on a clean EOF before any prefix bytes arrive, `Err` if the
+/// declared length exceeds `max_bytes` (mirroring `read_bounded_line`'s
+/// oversize rejection) or the stream ends mid-frame.
+async fn read_bounded_frame<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    max_bytes: usize,
+) -> std::io::Result<Option<Vec<u8>>> {
+    let mut len_buf = [0u8; 4];
+    match reader.read_exact(&mut len_buf).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u32::from_be_bytes(len_buf) as usize;
+    if len > max_bytes {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "request frame exceeds maximum length",
+        ));
+    }
+    let mut buf = vec![0u8; len];
+    reader.read_exact(&mut buf).await?;
+    Ok(Some(buf))
+}
+
+/// Read one protocol message as raw bytes (synth-1175), dispatching on the
+/// connection's active [`PayloadEncoding`] — newline-delimited JSON via
+/// [`read_bounded_line`], or a length-prefixed frame via
+/// [`read_bounded_frame`] for MessagePack.
+async fn read_frame<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    encoding: PayloadEncoding,
+    max_bytes: usize,
+) -> std::io::Result<Option<Vec<u8>>> {
+    match encoding {
+        PayloadEncoding::Json => Ok(read_bounded_line(reader, max_bytes).await?.map(String::into_bytes)),
+        PayloadEncoding::MsgPack => read_bounded_frame(reader, max_bytes).await,
+    }
+}
+
+/// Decode a protocol message from `bytes` per the connection's active
+/// [`PayloadEncoding`] (synth-1175).
+fn decode_message<T: serde::de::DeserializeOwned>(
+    bytes: &[u8],
+    encoding: PayloadEncoding,
+) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+    match encoding {
+        PayloadEncoding::Json => Ok(serde_json::from_slice(bytes)?),
+        PayloadEncoding::MsgPack => Ok(rmp_serde::from_slice(bytes)?),
+    }
+}
+
+/// Decode a `Request` per `encoding`, rejecting unknown fields when `strict`
+/// is set (synth-1177, driven by `SidecarConfig::strict_requests`) by
+/// decoding as [`protocol::StrictRequest`] instead of the normally-lenient
+/// [`Request`] — the error a `deny_unknown_fields` mismatch produces already
+/// names the offending key, so it flows into the same `invalid_request`
+/// branch as any other malformed request with no extra handling.
+fn decode_request(
+    bytes: &[u8],
+    encoding: PayloadEncoding,
+    strict: bool,
+) -> Result<Request, Box<dyn std::error::Error + Send + Sync>> {
+    if strict {
+        decode_message::<protocol::StrictRequest>(bytes, encoding).map(Request::from)
+    } else {
+        decode_message::<Request>(bytes, encoding)
+    }
+}
+
+/// Encode `value` for the wire per the connection's active
+/// [`PayloadEncoding`] (synth-1175): a JSON line terminated with `\n`, or a
+/// MessagePack frame (via `rmp_serde::to_vec_named`, so
+/// `#[serde(skip_serializing_if = ...)]` fields still round-trip by name
+/// rather than position) prefixed with its 4-byte big-endian length.
+fn encode_frame<T: serde::Serialize>(value: &T, encoding: PayloadEncoding) -> Vec<u8> {
+    match encoding {
+        PayloadEncoding::Json => {
+            let mut out = serde_json::to_string(value).unwrap_or_default();
+            out.push('\n');
+            out.into_bytes()
+        }
+        PayloadEncoding::MsgPack => {
+            let body = rmp_serde::to_vec_named(value).unwrap_or_default();
+            let mut out = Vec::with_capacity(4 + body.len());
+            out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+            out.extend_from_slice(&body);
+            out
+        }
+    }
+}
+
+/// Dispatch `sentinel-sidecar sign-tool <subcommand> ...` (synth-1141).
+/// Returns `None` when `args` isn't a `sign-tool` invocation at all, so the
+/// caller falls through to starting the sidecar as normal; `Some(code)` is
+/// the process exit code once the subcommand has run.
+fn run_sign_tool_subcommand(args: &[String]) -> Option<i32> {
+    let (cmd, rest) = args.split_first()?;
+    if cmd != "sign-tool" {
+        return None;
+    }
+    let Some((sub, rest)) = rest.split_first() else {
+        eprintln!("usage: sentinel-sidecar sign-tool <generate-key|sign> ...");
+        return Some(1);
+    };
+    Some(match sub.as_str() {
+        "generate-key" => sign_tool_generate_key(rest),
+        "sign" => sign_tool_sign(rest),
+        other => {
+            eprintln!("sidecar: unknown sign-tool subcommand '{other}' (expected 'generate-key' or 'sign')");
+            1
+        }
+    })
+}
+
+/// `sentinel-sidecar sign-tool generate-key <out.pk8>` — generates a new
+/// Ed25519 keypair, writes the PKCS#8 private key to `out.pk8`, and prints
+/// the base64 public key to add to SENTINEL_SIDECAR_TOOL_SIGNING_KEYS.
+fn sign_tool_generate_key(args: &[String]) -> i32 {
+    let [out_path] = args else {
+        eprintln!("usage: sentinel-sidecar sign-tool generate-key <out.pk8>");
+        return 1;
+    };
+
+    let rng = ring::rand::SystemRandom::new();
+    let pkcs8 = match ring::signature::Ed25519KeyPair::generate_pkcs8(&rng) {
+        Ok(pkcs8) => pkcs8,
+        Err(e) => {
+            eprintln!("sidecar: failed to generate keypair: {e}");
+            return 1;
+        }
+    };
+    if let Err(e) = std::fs::write(out_path, pkcs8.as_ref()) {
+        eprintln!("sidecar: failed to write {out_path}: {e}");
+        return 1;
+    }
+    let keypair = match ring::signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()) {
+        Ok(keypair) => keypair,
+        Err(e) => {
+            eprintln!("sidecar: failed to re-parse generated keypair: {e}");
+            return 1;
+        }
+    };
+    use ring::signature::KeyPair;
+    use base64::Engine;
+    let public_b64 = base64::engine::general_purpose::STANDARD.encode(keypair.public_key().as_ref());
+    println!("private key written to {out_path}");
+    println!("public key (add to SENTINEL_SIDECAR_TOOL_SIGNING_KEYS): {public_b64}");
+    0
+}
+
+/// `sentinel-sidecar sign-tool sign <key.pk8> <name> <version> <wasm-path>` —
+/// signs the same `(name, version, wasm sha256)` payload
+/// [`registry::ToolRegistry`] verifies at load, printing the base64
+/// signature to paste into that tool's `tool.toml` `signature` field.
+fn sign_tool_sign(args: &[String]) -> i32 {
+    let [key_path, name, version, wasm_path] = args else {
+        eprintln!("usage: sentinel-sidecar sign-tool sign <key.pk8> <name> <version> <wasm-path>");
+        return 1;
+    };
+
+    let pkcs8 = match std::fs::read(key_path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("sidecar: failed to read {key_path}: {e}");
+            return 1;
+        }
+    };
+    let keypair = match ring::signature::Ed25519KeyPair::from_pkcs8(&pkcs8) {
+        Ok(keypair) => keypair,
+        Err(e) => {
+            eprintln!("sidecar: failed to parse private key {key_path}: {e}");
+            return 1;
+        }
+    };
+    let wasm_bytes = match std::fs::read(wasm_path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("sidecar: failed to read {wasm_path}: {e}");
+            return 1;
+        }
+    };
+
+    let sha256 = registry::ToolRegistry::sha256_hex(&wasm_bytes);
+    let payload = registry::signing_payload(name, version, &sha256);
+    let signature = keypair.sign(&payload);
+    use base64::Engine;
+    let signature_b64 = base64::engine::general_purpose::STANDARD.encode(signature.as_ref());
+    println!("{signature_b64}");
+    0
+}
+
+/// Parse a `--config <path>` CLI flag (synth-1148), if present. Returns
+/// `None` when the flag is absent, in which case the caller falls back to
+/// `SidecarConfig::from_env` (which still honors `SENTINEL_SIDECAR_CONFIG`,
+/// just non-fatally).
+fn config_flag_path(args: &[String]) -> Option<PathBuf> {
+    let idx = args.iter().position(|a| a == "--config")?;
+    args.get(idx + 1).map(PathBuf::from)
+}
+
+/// Dispatch `sentinel-sidecar --precompile <dir>` (synth-1143). Returns
+/// `None` when `args` doesn't contain `--precompile`, so the caller falls
+/// through to starting the sidecar as normal.
+fn run_precompile_subcommand(args: &[String]) -> Option<i32> {
+    let idx = args.iter().position(|a| a == "--precompile")?;
+    Some(match args.get(idx + 1) {
+        Some(dir) => precompile_tools(dir),
+        None => {
+            eprintln!("usage: sentinel-sidecar --precompile <tool-dir>");
+            1
+        }
+    })
+}
+
+/// Load every tool.toml in `dir` and write a sibling `.cwasm` artifact for
+/// each one next to its `.wasm`, via `Module::serialize()`. Uses the exact
+/// same engine config as the sandbox (`sandbox::wasm_engine_config`), since a
+/// `.cwasm` only deserializes against the engine config it was produced
+/// with — precompiling with any other config would silently fail to help at
+/// load time and just fall back to compiling from source.
+fn precompile_tools(dir: &str) -> i32 {
+    let tool_dir = PathBuf::from(dir);
+    let engine = match wasmtime::Engine::new(&sandbox::wasm_engine_config()) {
+        Ok(engine) => engine,
+        Err(e) => {
+            eprintln!("sidecar: failed to create Wasmtime engine: {e}");
+            return 1;
+        }
+    };
+    // Never load an existing cwasm here — precompiling must always compile
+    // fresh from source, or a stale/foreign cwasm would just get echoed
+    // back out unchanged.
+    let registry = match ToolRegistry::load(&tool_dir, &engine, false, &[], false, false) {
+        Ok(registry) => registry,
+        Err(e) => {
+            eprintln!("sidecar: failed to load tool directory {}: {e}", tool_dir.display());
+            return 1;
+        }
+    };
+
+    let mut count = 0;
+    for meta in registry.tools() {
+        let cwasm_path = meta.wasm_path.with_extension("cwasm");
+        let bytes = match meta.module.serialize() {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                eprintln!("sidecar: failed to serialize module for '{}': {e}", meta.name);
+                continue;
+            }
+        };
+        if let Err(e) = std::fs::write(&cwasm_path, &bytes) {
+            eprintln!("sidecar: failed to write {}: {e}", cwasm_path.display());
+            continue;
+        }
+        println!("wrote {}", cwasm_path.display());
+        count += 1;
+    }
+    println!("precompiled {count} tool(s) from {}", tool_dir.display());
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    // `std::env::set_var` affects the whole process, mirroring config.rs's
+    // and credentials.rs's own ENV_LOCK convention for tests that touch it.
+    static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    fn ceiling(names: &[&str]) -> HashSet<Capability> {
+        capabilities::ceiling_from_names(&names.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    }
+
+    /// Spawn `handle_connection` over one half of a UnixStream pair, backed
+    /// by an empty registry (no real tool execution — these tests only
+    /// exercise the hello/ceiling handshake, not sandboxed WASM), and return
+    /// the other half for the test to drive.
+    async fn spawn_connection(default_ceiling: HashSet<Capability>) -> tokio::net::UnixStream {
+        spawn_connection_with_config(default_ceiling, SidecarConfig::default()).await
+    }
+
+    /// Like [`spawn_connection`], but with a caller-supplied config (synth-1177)
+    /// instead of `SidecarConfig::default()` — for tests exercising a
+    /// non-default setting like `strict_requests`.
+    async fn spawn_connection_with_config(
+        default_ceiling: HashSet<Capability>,
+        config: SidecarConfig,
+    ) -> tokio::net::UnixStream {
+        let (server, client) = tokio::net::UnixStream::pair().expect("unix stream pair");
+        let active_children = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        let engine = Arc::new(SandboxEngine::new(&config, active_children).expect("sandbox engine"));
+        let registry = Arc::new(RwLock::new(Arc::new(ToolRegistry::new())));
+        let leak_detector = Arc::new(LeakDetector::new());
+        let default_ceiling = Arc::new(default_ceiling);
+        let tool_dir = std::env::temp_dir().join("sentinel_test_main_empty_tool_dir");
+        let execution_semaphore = Arc::new(PriorityScheduler::new(16));
+
+        tokio::spawn(async move {
+            let _ = handle_connection(
+                server,
+                &engine,
+                &registry,
+                &leak_detector,
+                &default_ceiling,
+                &tool_dir,
+                false,
+                &[],
+                false,
+                false,
+                None,
+                &execution_semaphore,
+                None,
+                PayloadEncoding::Json,
+                4 * 1024 * 1024,
+                300,
+                &Arc::new(tokio::sync::Notify::new()),
+            )
+            .await;
+        });
+        client
+    }
+
+    async fn read_response_line<R: tokio::io::AsyncRead + Unpin>(stream: &mut R) -> serde_json::Value {
+        let mut buf = Vec::new();
+        let mut byte = [0u8; 1];
+        loop {
+            stream.read_exact(&mut byte).await.expect("read response line");
+            if byte[0] == b'\n' {
+                break;
+            }
+            buf.push(byte[0]);
+        }
+        serde_json::from_slice(&buf).expect("valid JSON response")
+    }
+
+    /// synth-1171: notifying a connection's `shutdown` handle while it's
+    /// sitting idle between requests sends a one-line `{"event":
+    /// "shutting_down"}` notice, but doesn't itself close the connection —
+    /// a request sent afterward is still served normally.
+    #[tokio::test]
+    async fn idle_connection_receives_shutting_down_notice() {
+        let (server, mut client) = tokio::net::UnixStream::pair().expect("unix stream pair");
+        let config = SidecarConfig::default();
+        let active_children = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        let engine = Arc::new(SandboxEngine::new(&config, active_children).expect("sandbox engine"));
+        let registry = Arc::new(RwLock::new(Arc::new(ToolRegistry::new())));
+        let leak_detector = Arc::new(LeakDetector::new());
+        let default_ceiling = Arc::new(ceiling(&["read_file"]));
+        let tool_dir = std::env::temp_dir().join("sentinel_test_main_shutdown_notice_tool_dir");
+        let execution_semaphore = Arc::new(PriorityScheduler::new(16));
+        let shutdown = Arc::new(tokio::sync::Notify::new());
+        let shutdown_for_connection = shutdown.clone();
+
+        tokio::spawn(async move {
+            let _ = handle_connection(
+                server,
+                &engine,
+                &registry,
+                &leak_detector,
+                &default_ceiling,
+                &tool_dir,
+                false,
+                &[],
+                false,
+                false,
+                None,
+                &execution_semaphore,
+                None,
+                PayloadEncoding::Json,
+                4 * 1024 * 1024,
+                300,
+                &shutdown_for_connection,
+            )
+            .await;
+        });
+
+        // Give the spawned task a chance to reach the read loop before
+        // notifying, so its `shutdown.notified()` future is already
+        // registered — `notify_waiters` only wakes waiters that exist at the
+        // moment it's called.
+        tokio::task::yield_now().await;
+        shutdown.notify_waiters();
+
+        let notice = read_response_line(&mut client).await;
+        assert_eq!(notice["event"], "shutting_down");
+
+        let request = serde_json::json!({
+            "request_id": "r1",
+            "tool_name": "_health",
+            "args": {},
+        });
+        client.write_all(request.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let resp = read_response_line(&mut client).await;
+        assert_ne!(resp["error_code"], "unauthorized");
+    }
+
+    /// synth-1172: a line longer than `max_request_bytes` with no newline in
+    /// sight gets an `invalid_request` error and the connection is closed,
+    /// rather than `read_bounded_line` buffering it without bound.
+    #[tokio::test]
+    async fn oversized_request_line_is_rejected_and_connection_closed() {
+        let (server, mut client) = tokio::net::UnixStream::pair().expect("unix stream pair");
+        let config = SidecarConfig::default();
+        let active_children = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        let engine = Arc::new(SandboxEngine::new(&config, active_children).expect("sandbox engine"));
+        let registry = Arc::new(RwLock::new(Arc::new(ToolRegistry::new())));
+        let leak_detector = Arc::new(LeakDetector::new());
+        let default_ceiling = Arc::new(ceiling(&["read_file"]));
+        let tool_dir = std::env::temp_dir().join("sentinel_test_main_oversized_request_tool_dir");
+        let execution_semaphore = Arc::new(PriorityScheduler::new(16));
+        let max_request_bytes = 64;
+
+        tokio::spawn(async move {
+            let _ = handle_connection(
+                server,
+                &engine,
+                &registry,
+                &leak_detector,
+                &default_ceiling,
+                &tool_dir,
+                false,
+                &[],
+                false,
+                false,
+                None,
+                &execution_semaphore,
+                None,
+                PayloadEncoding::Json,
+                max_request_bytes,
+                300,
+                &Arc::new(tokio::sync::Notify::new()),
+            )
+            .await;
+        });
+
+        // A pseudo-request well past `max_request_bytes` with no newline —
+        // `read_bounded_line` must reject it as soon as its buffer crosses
+        // the cap rather than reading indefinitely looking for one.
+        let oversized = vec![b'a'; max_request_bytes * 4];
+        client.write_all(&oversized).await.unwrap();
+
+        let resp = read_response_line(&mut client).await;
+        assert_eq!(resp["error_code"], "invalid_request");
+
+        // The connection is closed rather than kept open to read further,
+        // now-unsynchronized lines.
+        let mut byte = [0u8; 1];
+        assert_eq!(client.read(&mut byte).await.unwrap_or(0), 0);
+    }
+
+    /// synth-1173: a connection that never pipelines a request is closed once
+    /// `idle_timeout_secs` elapses with nothing read.
+    #[tokio::test]
+    async fn idle_connection_is_closed_after_timeout() {
+        let (server, mut client) = tokio::net::UnixStream::pair().expect("unix stream pair");
+        let config = SidecarConfig::default();
+        let active_children = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        let engine = Arc::new(SandboxEngine::new(&config, active_children).expect("sandbox engine"));
+        let registry = Arc::new(RwLock::new(Arc::new(ToolRegistry::new())));
+        let leak_detector = Arc::new(LeakDetector::new());
+        let default_ceiling = Arc::new(ceiling(&["read_file"]));
+        let tool_dir = std::env::temp_dir().join("sentinel_test_main_idle_timeout_tool_dir");
+        let execution_semaphore = Arc::new(PriorityScheduler::new(16));
+
+        tokio::spawn(async move {
+            let _ = handle_connection(
+                server,
+                &engine,
+                &registry,
+                &leak_detector,
+                &default_ceiling,
+                &tool_dir,
+                false,
+                &[],
+                false,
+                false,
+                None,
+                &execution_semaphore,
+                None,
+                PayloadEncoding::Json,
+                4 * 1024 * 1024,
+                1,
+                &Arc::new(tokio::sync::Notify::new()),
+            )
+            .await;
+        });
+
+        // Nothing is ever written on `client` — after a bit more than one
+        // idle-timeout period, the server side must have closed on its own.
+        let mut byte = [0u8; 1];
+        let read = tokio::time::timeout(std::time::Duration::from_secs(3), client.read(&mut byte))
+            .await
+            .expect("connection should close before the test's own timeout");
+        assert_eq!(read.unwrap_or(1), 0);
+    }
+
+    /// synth-1173: an execution still running on a connection inhibits the
+    /// idle close, even once nothing has been read for longer than
+    /// `idle_timeout_secs` — a slow tool shouldn't lose its response just
+    /// because the client went quiet waiting for it. The execution here is
+    /// held open by starving `execution_semaphore` of its only permit rather
+    /// than a genuinely slow tool, so the test doesn't need one to exist.
+    #[tokio::test]
+    async fn in_flight_execution_inhibits_idle_close() {
+        let (server, mut client) = tokio::net::UnixStream::pair().expect("unix stream pair");
+        let config = SidecarConfig::default();
+        let active_children = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        let engine = Arc::new(SandboxEngine::new(&config, active_children).expect("sandbox engine"));
+        let registry = Arc::new(RwLock::new(Arc::new(ToolRegistry::new())));
+        let leak_detector = Arc::new(LeakDetector::new());
+        let default_ceiling = Arc::new(ceiling(&["read_file"]));
+        let tool_dir = std::env::temp_dir().join("sentinel_test_main_idle_inhibit_tool_dir");
+        let execution_semaphore = Arc::new(PriorityScheduler::new(1));
+        let (held_permit, _) = execution_semaphore.clone().acquire_owned(scheduler::DEFAULT_PRIORITY).await;
+
+        tokio::spawn(async move {
+            let _ = handle_connection(
+                server,
+                &engine,
+                &registry,
+                &leak_detector,
+                &default_ceiling,
+                &tool_dir,
+                false,
+                &[],
+                false,
+                false,
+                None,
+                &execution_semaphore,
+                None,
+                PayloadEncoding::Json,
+                4 * 1024 * 1024,
+                1,
+                &Arc::new(tokio::sync::Notify::new()),
+            )
+            .await;
+        });
+
+        let request = serde_json::json!({
+            "request_id": "r1",
+            "tool_name": "_health",
+            "args": {},
+        });
+        client.write_all(request.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        // The spawned task is parked on `execution_semaphore.acquire_owned()`
+        // behind `held_permit`, so `request_tasks` stays non-empty for longer
+        // than `idle_timeout_secs` — the connection must still be open.
+        tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+        let mut byte = [0u8; 1];
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(50), client.read(&mut byte))
+                .await
+                .is_err(),
+            "connection closed while an execution was still in flight"
+        );
+
+        // Releasing the permit lets the execution (and so the idle-timeout
+        // exemption) finish; the response arrives normally.
+        drop(held_permit);
+        let resp = read_response_line(&mut client).await;
+        assert_eq!(resp["success"], serde_json::Value::Bool(true));
+    }
+
+    /// synth-1175: a connection started in `MsgPack` reads a length-prefixed
+    /// msgpack `Request` frame — with credentials and nested args, the two
+    /// shapes JSON would have to base64/string-encode — and writes back a
+    /// length-prefixed msgpack `Response` frame.
+    #[tokio::test]
+    async fn msgpack_encoded_request_round_trips_through_handle_connection() {
+        let (server, mut client) = tokio::net::UnixStream::pair().expect("unix stream pair");
+        let config = SidecarConfig::default();
+        let active_children = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        let engine = Arc::new(SandboxEngine::new(&config, active_children).expect("sandbox engine"));
+        let registry = Arc::new(RwLock::new(Arc::new(ToolRegistry::new())));
+        let leak_detector = Arc::new(LeakDetector::new());
+        let default_ceiling = Arc::new(ceiling(&["read_file"]));
+        let tool_dir = std::env::temp_dir().join("sentinel_test_main_msgpack_tool_dir");
+        let execution_semaphore = Arc::new(PriorityScheduler::new(16));
+
+        tokio::spawn(async move {
+            let _ = handle_connection(
+                server,
+                &engine,
+                &registry,
+                &leak_detector,
+                &default_ceiling,
+                &tool_dir,
+                false,
+                &[],
+                false,
+                false,
+                None,
+                &execution_semaphore,
+                None,
+                PayloadEncoding::MsgPack,
+                4 * 1024 * 1024,
+                300,
+                &Arc::new(tokio::sync::Notify::new()),
+            )
+            .await;
+        });
+
+        let request = Request {
+            request_id: "r1".to_string(),
+            tool_name: "_health".to_string(),
+            protocol_version: None,
+            tool_version: None,
+            args: serde_json::json!({"nested": {"a": [1, 2, 3]}}),
+            capabilities: vec!["read_file".to_string()],
+            denied_capabilities: Vec::new(),
+            timeout_ms: None,
+            fuel: None,
+            http_timeout_ms: None,
+            credentials: std::collections::HashMap::from([("api_key".to_string(), "s3cr3t".to_string())]),
+            http_allowlist: None,
+            priority: None,
+            deadline_epoch_ms: None,
+        };
+        client.write_all(&encode_frame(&request, PayloadEncoding::MsgPack)).await.unwrap();
+
+        let resp: Response = decode_message(&read_bounded_frame(&mut BufReader::new(&mut client), 4 * 1024 * 1024)
+            .await
+            .expect("read response frame")
+            .expect("response frame present"), PayloadEncoding::MsgPack)
+        .expect("valid msgpack response");
+        assert!(resp.success);
+        assert_eq!(resp.request_id, "r1");
+    }
+
+    #[tokio::test]
+    async fn hello_with_absent_protocol_version_is_accepted() {
+        // synth-1176: a caller predating this field never sends it at all —
+        // must still be let through, not rejected as version 0.
+        let mut client = spawn_connection(ceiling(&["read_file"])).await;
+        client.write_all(br#"{"hello": {"client": "old-controller"}}"#).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let ack = read_response_line(&mut client).await;
+        assert_eq!(ack["success"], true);
+        assert_eq!(ack["data"]["protocol_version"]["min"], protocol::MIN_SUPPORTED_PROTOCOL_VERSION);
+        assert_eq!(ack["data"]["protocol_version"]["max"], protocol::MAX_SUPPORTED_PROTOCOL_VERSION);
+    }
+
+    #[tokio::test]
+    async fn hello_with_supported_protocol_version_is_accepted() {
+        let mut client = spawn_connection(ceiling(&["read_file"])).await;
+        client
+            .write_all(
+                format!(
+                    r#"{{"hello": {{"protocol_version": {}}}}}"#,
+                    protocol::MAX_SUPPORTED_PROTOCOL_VERSION
+                )
+                .as_bytes(),
+            )
+            .await
+            .unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let ack = read_response_line(&mut client).await;
+        assert_eq!(ack["success"], true);
+    }
+
+    #[tokio::test]
+    async fn hello_with_too_old_protocol_version_is_rejected() {
+        let mut client = spawn_connection(ceiling(&["read_file"])).await;
+        client.write_all(br#"{"hello": {"protocol_version": 0}}"#).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let response = read_response_line(&mut client).await;
+        assert_eq!(response["success"], false);
+        assert_eq!(response["error_code"], "unsupported_protocol");
+    }
+
+    #[tokio::test]
+    async fn request_with_too_new_protocol_version_is_rejected() {
+        let mut client = spawn_connection(ceiling(&["read_file"])).await;
+        let request = serde_json::json!({
+            "request_id": "r1",
+            "tool_name": "_health",
+            "protocol_version": protocol::MAX_SUPPORTED_PROTOCOL_VERSION + 1,
+            "args": {},
+            "capabilities": [],
+        });
+        client.write_all(request.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let response = read_response_line(&mut client).await;
+        assert_eq!(response["success"], false);
+        assert_eq!(response["error_code"], "unsupported_protocol");
+        assert_eq!(response["request_id"], "r1");
+    }
+
+    #[tokio::test]
+    async fn request_with_absent_protocol_version_still_executes() {
+        let mut client = spawn_connection(ceiling(&["read_file"])).await;
+        let request = serde_json::json!({
+            "request_id": "r1",
+            "tool_name": "_health",
+            "args": {},
+            "capabilities": [],
+        });
+        client.write_all(request.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let response = read_response_line(&mut client).await;
+        assert_eq!(response["success"], true);
+    }
+
+    #[tokio::test]
+    async fn misspelled_capability_key_is_ignored_in_lenient_mode() {
+        // synth-1177: default mode. "capabilties" (missing the second `i`)
+        // is an unknown field, silently dropped by `Request`'s plain
+        // `Deserialize` — the request still runs, just with an empty
+        // (default) capability list rather than the caller's intent.
+        let mut client = spawn_connection(ceiling(&["read_file"])).await;
+        let request = serde_json::json!({
+            "request_id": "r1",
+            "tool_name": "_health",
+            "args": {},
+            "capabilties": ["read_file"],
+        });
+        client.write_all(request.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let response = read_response_line(&mut client).await;
+        assert_eq!(response["success"], true);
+    }
+
+    #[tokio::test]
+    async fn misspelled_capability_key_is_rejected_under_strict_requests() {
+        let config = SidecarConfig { strict_requests: true, ..SidecarConfig::default() };
+        let mut client = spawn_connection_with_config(ceiling(&["read_file"]), config).await;
+        let request = serde_json::json!({
+            "request_id": "r1",
+            "tool_name": "_health",
+            "args": {},
+            "capabilties": ["read_file"],
+        });
+        client.write_all(request.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let response = read_response_line(&mut client).await;
+        assert_eq!(response["success"], false);
+        assert_eq!(response["error_code"], "invalid_request");
+        assert!(
+            response["result"].as_str().unwrap().contains("capabilties"),
+            "error should name the unknown field: {response}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hello_ceiling_strips_disallowed_capability() {
+        let mut client = spawn_connection(ceiling(&[
+            "read_file", "write_file", "http_request", "use_credential", "invoke_tool", "shell_exec",
+        ]))
+        .await;
+
+        client
+            .write_all(br#"{"hello": {"max_capabilities": ["read_file"], "client": "low-trust"}}"#)
+            .await
+            .unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let ack = read_response_line(&mut client).await;
+        assert_eq!(ack["success"], true);
+
+        let request = serde_json::json!({
+            "request_id": "r1",
+            "tool_name": "shell_exec",
+            "args": {},
+            "capabilities": ["shell_exec"],
+        });
+        client.write_all(request.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let resp = read_response_line(&mut client).await;
+        assert_eq!(resp["success"], false);
+        assert_eq!(resp["error_code"], "capability_ceiling_exceeded");
+        assert!(resp["result"].as_str().unwrap().contains("shell_exec"));
+    }
+
+    #[tokio::test]
+    async fn test_requests_before_hello_use_configured_default_ceiling() {
+        let mut client = spawn_connection(ceiling(&["read_file"])).await;
+
+        let request = serde_json::json!({
+            "request_id": "r1",
+            "tool_name": "shell_exec",
+            "args": {},
+            "capabilities": ["shell_exec"],
+        });
+        client.write_all(request.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let resp = read_response_line(&mut client).await;
+        assert_eq!(resp["error_code"], "capability_ceiling_exceeded");
+    }
+
+    #[test]
+    fn test_prune_expired_grants_drops_only_expired_entries() {
+        let now = std::time::Instant::now();
+        let mut grants = vec![
+            ("shell_exec".to_string(), now + std::time::Duration::from_secs(10)),
+            ("read_file".to_string(), now - std::time::Duration::from_millis(1)),
+        ];
+        prune_expired_grants(&mut grants, now);
+        assert_eq!(grants, vec![("shell_exec".to_string(), now + std::time::Duration::from_secs(10))]);
+    }
+
+    #[test]
+    fn test_merge_granted_capabilities_unions_without_duplicates() {
+        let now = std::time::Instant::now();
+        let active = vec![
+            ("shell_exec".to_string(), now + std::time::Duration::from_secs(10)),
+            ("read_file".to_string(), now + std::time::Duration::from_secs(10)),
+        ];
+        let merged = merge_granted_capabilities(&["read_file".to_string()], &active);
+        assert_eq!(merged, vec!["read_file".to_string(), "shell_exec".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_grant_ack_reports_capabilities_granted_and_active() {
+        let mut client = spawn_connection(ceiling(&["read_file", "shell_exec"])).await;
+
+        client
+            .write_all(br#"{"grant": {"capabilities": ["shell_exec"], "ttl_ms": 60000}}"#)
+            .await
+            .unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let ack = read_response_line(&mut client).await;
+        assert_eq!(ack["success"], true);
+        assert_eq!(ack["data"]["granted"], serde_json::json!(["shell_exec"]));
+        assert_eq!(ack["data"]["stripped"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_grant_never_exceeds_connection_ceiling() {
+        let mut client = spawn_connection(ceiling(&["read_file"])).await;
+
+        client
+            .write_all(br#"{"grant": {"capabilities": ["shell_exec"], "ttl_ms": 60000}}"#)
+            .await
+            .unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let ack = read_response_line(&mut client).await;
+        assert_eq!(ack["data"]["granted"], serde_json::json!([]));
+        assert_eq!(ack["data"]["stripped"], serde_json::json!(["shell_exec"]));
+
+        let request = serde_json::json!({
+            "request_id": "r1",
+            "tool_name": "shell_exec",
+            "args": {},
+            "capabilities": [],
+        });
+        client.write_all(request.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let resp = read_response_line(&mut client).await;
+        // shell_exec was stripped from the grant at ack time, so it never
+        // reaches the merged capability list — the request has none at all
+        // and fails at tool lookup, not the ceiling check.
+        assert_ne!(resp["error_code"], "capability_ceiling_exceeded");
+    }
+
+    #[tokio::test]
+    async fn test_reload_registry_control_message_reports_empty_diff_on_empty_dir() {
+        let mut client = spawn_connection(ceiling(&["read_file"])).await;
+
+        client
+            .write_all(br#"{"reload": "registry"}"#)
+            .await
+            .unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let ack = read_response_line(&mut client).await;
+
+        // spawn_connection's tool_dir doesn't exist, so the reload succeeds
+        // against an empty directory and reports no changes at all.
+        assert_eq!(ack["success"], true);
+        assert_eq!(ack["data"]["added"], serde_json::json!([]));
+        assert_eq!(ack["data"]["removed"], serde_json::json!([]));
+        assert_eq!(ack["data"]["changed"], serde_json::json!([]));
+        assert_eq!(ack["data"]["errors"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_reload_unknown_target_is_rejected() {
+        let mut client = spawn_connection(ceiling(&["read_file"])).await;
+
+        client
+            .write_all(br#"{"reload": "bogus"}"#)
+            .await
+            .unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let ack = read_response_line(&mut client).await;
+
+        assert_eq!(ack["success"], false);
+        assert_eq!(ack["error_code"], "unknown_reload_target");
+    }
+
+    #[tokio::test]
+    async fn test_reload_config_control_message_applies_reload_safe_field_and_reports_it() {
+        let (server, mut client) = tokio::net::UnixStream::pair().expect("unix stream pair");
+        let config = SidecarConfig {
+            allowed_paths: vec![std::env::temp_dir().to_string_lossy().into_owned()],
+            ..SidecarConfig::default()
+        };
+        let active_children = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        let engine =
+            Arc::new(SandboxEngine::new(&config, active_children).expect("sandbox engine"));
+        let registry = Arc::new(RwLock::new(Arc::new(ToolRegistry::new())));
+        let leak_detector = Arc::new(LeakDetector::new());
+        let default_ceiling = Arc::new(ceiling(&["read_file"]));
+        let tool_dir = std::env::temp_dir().join("sentinel_test_main_reload_config_tool_dir");
+        let execution_semaphore = Arc::new(PriorityScheduler::new(16));
+        let config_path = std::env::temp_dir().join("sentinel_test_main_reload_config.toml");
+        std::fs::write(
+            &config_path,
+            format!(
+                "shell_timeout_ms = 999999\nallowed_paths = [\"{}\"]\n",
+                std::env::temp_dir().to_string_lossy(),
+            ),
+        )
+        .unwrap();
+
+        let conn_engine = engine.clone();
+        let conn_config_path = config_path.clone();
+        tokio::spawn(async move {
+            let _ = handle_connection(
+                server,
+                &conn_engine,
+                &registry,
+                &leak_detector,
+                &default_ceiling,
+                &tool_dir,
+                false,
+                &[],
+                false,
+                false,
+                Some(&conn_config_path),
+                &execution_semaphore,
+                None,
+                PayloadEncoding::Json,
+                4 * 1024 * 1024,
+                300,
+                &Arc::new(tokio::sync::Notify::new()),
+            )
+            .await;
+        });
+
+        client.write_all(br#"{"reload": "config"}"#).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let ack = read_response_line(&mut client).await;
+        std::fs::remove_file(&config_path).ok();
+
+        assert_eq!(ack["success"], true);
+        assert_eq!(ack["data"]["applied"], serde_json::json!(["shell_timeout_ms"]));
+        assert_eq!(ack["data"]["restart_required"], serde_json::json!([]));
+        // The engine's live snapshot reflects the reload immediately — the
+        // next execution to call `SandboxEngine::config()` sees the new
+        // limit, without needing a restart.
+        assert_eq!(engine.config().shell_timeout_ms, 999999);
+    }
+
+    #[tokio::test]
+    async fn test_reload_credentials_control_message_picks_up_a_changed_env_provider() {
+        // Held only around the env mutation itself, not across the awaits
+        // below — a std Mutex guard can't safely span an await point.
+        let guard = ENV_LOCK.lock().unwrap();
+        let (server, mut client) = tokio::net::UnixStream::pair().expect("unix stream pair");
+        let mut config = SidecarConfig {
+            allowed_paths: vec![std::env::temp_dir().to_string_lossy().into_owned()],
+            ..SidecarConfig::default()
+        };
+        config.credential_provider = credentials::CredentialProviderKind::Env;
+        config.credential_env_prefix = "SENTINEL_TEST_RELOAD_CRED_".to_string();
+        let active_children = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        let engine =
+            Arc::new(SandboxEngine::new(&config, active_children).expect("sandbox engine"));
+        let registry = Arc::new(RwLock::new(Arc::new(ToolRegistry::new())));
+        let leak_detector = Arc::new(LeakDetector::new());
+        let default_ceiling = Arc::new(ceiling(&["read_file"]));
+        let tool_dir = std::env::temp_dir().join("sentinel_test_main_reload_credentials_tool_dir");
+        let execution_semaphore = Arc::new(PriorityScheduler::new(16));
+
+        // Nothing is set at engine construction time, so the first snapshot
+        // is empty; setting the var and sending `{"reload": "credentials"}`
+        // should pick it up without restarting anything.
+        std::env::set_var("SENTINEL_TEST_RELOAD_CRED_API_KEY", "reloaded-value");
+        drop(guard);
+
+        let conn_engine = engine.clone();
+        tokio::spawn(async move {
+            let _ = handle_connection(
+                server,
+                &conn_engine,
+                &registry,
+                &leak_detector,
+                &default_ceiling,
+                &tool_dir,
+                false,
+                &[],
+                false,
+                false,
+                None,
+                &execution_semaphore,
+                None,
+                PayloadEncoding::Json,
+                4 * 1024 * 1024,
+                300,
+                &Arc::new(tokio::sync::Notify::new()),
+            )
+            .await;
+        });
+
+        client.write_all(br#"{"reload": "credentials"}"#).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let ack = read_response_line(&mut client).await;
+        std::env::remove_var("SENTINEL_TEST_RELOAD_CRED_API_KEY");
+
+        assert_eq!(ack["success"], true);
+        assert_eq!(engine.credentials().get("API_KEY"), Some(&"reloaded-value".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_capability_within_ceiling_reaches_execute() {
+        // No tool named this is registered, so a within-ceiling request
+        // fails downstream at tool lookup — proving the ceiling let it
+        // through rather than stripping it.
+        let mut client = spawn_connection(ceiling(&["read_file"])).await;
+
+        let request = serde_json::json!({
+            "request_id": "r1",
+            "tool_name": "file_read",
+            "args": {},
+            "capabilities": ["read_file"],
+        });
+        client.write_all(request.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let resp = read_response_line(&mut client).await;
+        assert_ne!(resp["error_code"], "capability_ceiling_exceeded");
+    }
+
+    /// Bind `spec` and drive its accept loop via `spawn_listener_accept_loop`
+    /// into a fresh channel, exactly like `main` does for every configured
+    /// listener (synth-1159), and return a connected client plus the bound
+    /// socket path (for cleanup). `allowed_uids` is forwarded to
+    /// `bind_listener` (synth-1170) — empty accepts any peer.
+    async fn connect_through_listener(
+        spec: &ListenerConfig,
+        allowed_uids: &[u32],
+    ) -> (tokio::net::UnixStream, PathBuf) {
+        let active = bind_listener(spec, None, allowed_uids).expect("bind listener");
+        let path = active.path.clone();
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<AcceptedConnection>(4);
+        let shutdown = Arc::new(tokio::sync::Notify::new());
+        spawn_listener_accept_loop(active, tx, shutdown);
+
+        let client = tokio::net::UnixStream::connect(&path).await.expect("connect to listener");
+
+        let accepted = rx.recv().await.expect("listener forwarded the accepted connection");
+        let config = SidecarConfig::default();
+        let active_children = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        let engine = Arc::new(SandboxEngine::new(&config, active_children).expect("sandbox engine"));
+        let registry = Arc::new(RwLock::new(Arc::new(ToolRegistry::new())));
+        let leak_detector = Arc::new(LeakDetector::new());
+        let tool_dir = std::env::temp_dir().join("sentinel_test_main_listener_tool_dir");
+        let execution_semaphore = Arc::new(PriorityScheduler::new(16));
+        tokio::spawn(async move {
+            let _permit = accepted.permit;
+            let _ = handle_connection(
+                accepted.stream,
+                &engine,
+                &registry,
+                &leak_detector,
+                &accepted.ceiling,
+                &tool_dir,
+                false,
+                &[],
+                false,
+                false,
+                None,
+                &execution_semaphore,
+                None,
+                PayloadEncoding::Json,
+                4 * 1024 * 1024,
+                300,
+                &Arc::new(tokio::sync::Notify::new()),
+            )
+            .await;
+        });
+
+        (client, path)
+    }
+
+    #[tokio::test]
+    async fn two_listeners_apply_their_own_capability_ceiling_independently() {
+        let trusted_spec = ListenerConfig {
+            path: std::env::temp_dir().join("sentinel_test_two_listeners_trusted.sock"),
+            mode: 0o600,
+            default_capability_ceiling: vec!["shell_exec".to_string()],
+            max_connections: None,
+            encoding: PayloadEncoding::Json,
+        };
+        let restricted_spec = ListenerConfig {
+            path: std::env::temp_dir().join("sentinel_test_two_listeners_restricted.sock"),
+            mode: 0o600,
+            default_capability_ceiling: vec!["read_file".to_string()],
+            max_connections: None,
+            encoding: PayloadEncoding::Json,
+        };
+        std::fs::remove_file(&trusted_spec.path).ok();
+        std::fs::remove_file(&restricted_spec.path).ok();
+
+        let (mut trusted_client, trusted_path) = connect_through_listener(&trusted_spec, &[]).await;
+        let (mut restricted_client, restricted_path) = connect_through_listener(&restricted_spec, &[]).await;
+
+        let request = serde_json::json!({
+            "request_id": "r1",
+            "tool_name": "shell_exec",
+            "args": {},
+            "capabilities": ["shell_exec"],
+        });
+
+        trusted_client.write_all(request.to_string().as_bytes()).await.unwrap();
+        trusted_client.write_all(b"\n").await.unwrap();
+        let trusted_resp = read_response_line(&mut trusted_client).await;
+
+        restricted_client.write_all(request.to_string().as_bytes()).await.unwrap();
+        restricted_client.write_all(b"\n").await.unwrap();
+        let restricted_resp = read_response_line(&mut restricted_client).await;
+
+        std::fs::remove_file(&trusted_path).ok();
+        std::fs::remove_file(&restricted_path).ok();
+
+        // No `shell_exec` tool is registered (empty registry), so on the
+        // trusted listener the request gets past the ceiling check and fails
+        // downstream at tool lookup instead — proving the ceiling let it
+        // through rather than stripping it.
+        assert_ne!(trusted_resp["error_code"], "capability_ceiling_exceeded");
+        assert_eq!(restricted_resp["error_code"], "capability_ceiling_exceeded");
+    }
+
+    /// synth-1170: connecting to a Unix listener as the current uid is let
+    /// through both when no allowlist is configured and when the current uid
+    /// is explicitly in it.
+    #[tokio::test]
+    async fn unix_listener_accepts_current_uid_when_allowlisted() {
+        let current_uid = unsafe { libc::getuid() };
+        let spec = ListenerConfig {
+            path: std::env::temp_dir().join("sentinel_test_peercred_allowed.sock"),
+            mode: 0o600,
+            default_capability_ceiling: Vec::new(),
+            max_connections: None,
+            encoding: PayloadEncoding::Json,
+        };
+        std::fs::remove_file(&spec.path).ok();
+
+        let (mut client, path) = connect_through_listener(&spec, &[current_uid]).await;
+
+        let request = serde_json::json!({"request_id": "r1", "tool_name": "_health", "args": {}});
+        client.write_all(request.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let resp = read_response_line(&mut client).await;
+
+        std::fs::remove_file(&path).ok();
+
+        assert_ne!(resp["error_code"], "unauthorized");
+    }
+
+    /// synth-1170: a peer whose uid isn't in a non-empty `allowed_uids` gets a
+    /// single `unauthorized` error line and never reaches `handle_connection`.
+    #[tokio::test]
+    async fn unix_listener_rejects_uid_absent_from_allowlist() {
+        let current_uid = unsafe { libc::getuid() };
+        let other_uid = if current_uid == 0 { 1 } else { 0 };
+        let spec = ListenerConfig {
+            path: std::env::temp_dir().join("sentinel_test_peercred_rejected.sock"),
+            mode: 0o600,
+            default_capability_ceiling: Vec::new(),
+            max_connections: None,
+            encoding: PayloadEncoding::Json,
+        };
+        std::fs::remove_file(&spec.path).ok();
+
+        let active = bind_listener(&spec, None, &[other_uid]).expect("bind listener");
+        let path = active.path.clone();
+        let (tx, _rx) = tokio::sync::mpsc::channel::<AcceptedConnection>(4);
+        let shutdown = Arc::new(tokio::sync::Notify::new());
+        spawn_listener_accept_loop(active, tx, shutdown);
+
+        let mut client = tokio::net::UnixStream::connect(&path).await.expect("connect to listener");
+        let resp = read_response_line(&mut client).await;
+
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(resp["error_code"], "unauthorized");
+    }
+
+    #[tokio::test]
+    async fn bind_unix_socket_creates_file_with_requested_mode() {
+        let path = std::env::temp_dir().join("sentinel_test_bind_unix_socket_mode.sock");
+        std::fs::remove_file(&path).ok();
+
+        let listener = bind_unix_socket(&path, 0o600).expect("bind unix socket");
+        let mode = std::fs::metadata(&path).expect("socket metadata").permissions().mode() & 0o777;
+        drop(listener);
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(mode, 0o600);
+    }
+
+    #[tokio::test]
+    async fn bind_unix_socket_honours_a_more_permissive_mode() {
+        let path = std::env::temp_dir().join("sentinel_test_bind_unix_socket_group_mode.sock");
+        std::fs::remove_file(&path).ok();
+
+        let listener = bind_unix_socket(&path, 0o660).expect("bind unix socket");
+        let mode = std::fs::metadata(&path).expect("socket metadata").permissions().mode() & 0o777;
+        drop(listener);
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(mode, 0o660);
+    }
+
+    #[tokio::test]
+    async fn chown_socket_group_rejects_unknown_group() {
+        let path = std::env::temp_dir().join("sentinel_test_chown_unknown_group.sock");
+        std::fs::remove_file(&path).ok();
+        let listener = bind_unix_socket(&path, 0o600).expect("bind unix socket");
+
+        let result = chown_socket_group(&path, "sentinel_test_nonexistent_group_xyz");
+
+        drop(listener);
+        std::fs::remove_file(&path).ok();
+        assert!(result.is_err());
+    }
+
+    /// A registry with a `slow` tool that spins until it hits its
+    /// `timeout_ms` and a `fast` native tool that returns immediately
+    /// (synth-1164) — for proving a request spawned after a slow one can
+    /// still finish (and get its response written) first. `slow` is a real
+    /// WASM module rather than a blocking `NativeTool`, deliberately: a
+    /// `NativeTool::run` executes in-place on the async runtime's own
+    /// worker threads (see `SandboxEngine::execute`), and a `NativeTool`
+    /// that parks one of those threads for any real duration can stall the
+    /// runtime's I/O driver along with it — genuinely slow work belongs in
+    /// WASM, which runs via `spawn_blocking` on a separate thread pool, the
+    /// same way it would for any real tool.
+    fn registry_with_slow_and_fast_tools(
+        engine: &wasmtime::Engine,
+        dir_name: &str,
+    ) -> (std::path::PathBuf, ToolRegistry) {
+        let tmp = std::env::temp_dir().join(dir_name);
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(
+            tmp.join("slow.wasm"),
+            r#"(module (func $start (export "_start") (loop $l br $l)))"#,
+        )
+        .unwrap();
+        std::fs::write(
+            tmp.join("slow.toml"),
+            r#"
+name = "slow"
+description = "spins until its timeout_ms fires"
+wasm = "slow.wasm"
+capabilities = []
+"#,
+        )
+        .unwrap();
+        let mut registry = ToolRegistry::load(&tmp, engine, false, &[], false, false).unwrap();
+        registry
+            .register_native(
+                engine,
+                "fast",
+                "test tool that returns immediately",
+                Vec::new(),
+                Arc::new(native_tools::EchoTool),
+            )
+            .unwrap();
+        (tmp, registry)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn pipelined_slow_then_fast_requests_reply_out_of_order() {
+        let (server, mut client) = tokio::net::UnixStream::pair().expect("unix stream pair");
+        let config = SidecarConfig::default();
+        let active_children = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        let engine = Arc::new(SandboxEngine::new(&config, active_children).expect("sandbox engine"));
+        let (tmp, test_registry) =
+            registry_with_slow_and_fast_tools(engine.engine(), "sentinel_test_main_pipelined_tool_dir");
+        let registry = Arc::new(RwLock::new(Arc::new(test_registry)));
+        let leak_detector = Arc::new(LeakDetector::new());
+        let default_ceiling = Arc::new(ceiling(&[]));
+        let tool_dir = std::env::temp_dir().join("sentinel_test_main_pipelined_tool_dir_unused");
+        let execution_semaphore = Arc::new(PriorityScheduler::new(16));
+
+        tokio::spawn(async move {
+            let _ = handle_connection(
+                server,
+                &engine,
+                &registry,
+                &leak_detector,
+                &default_ceiling,
+                &tool_dir,
+                false,
+                &[],
+                false,
+                false,
+                None,
+                &execution_semaphore,
+                None,
+                PayloadEncoding::Json,
+                4 * 1024 * 1024,
+                300,
+                &Arc::new(tokio::sync::Notify::new()),
+            )
+            .await;
+        });
+
+        let slow = serde_json::json!({
+            "request_id": "slow-1",
+            "tool_name": "slow",
+            "args": {},
+            "capabilities": [],
+            "timeout_ms": 2000,
+        });
+        let fast = serde_json::json!({
+            "request_id": "fast-1",
+            "tool_name": "fast",
+            "args": {},
+            "capabilities": [],
+        });
+        client.write_all(slow.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        client.write_all(fast.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let first = read_response_line(&mut client).await;
+        let second = read_response_line(&mut client).await;
+
+        std::fs::remove_dir_all(&tmp).ok();
+
+        assert_eq!(first["request_id"], "fast-1");
+        assert_eq!(second["request_id"], "slow-1");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn cancel_control_message_interrupts_a_slow_execution_on_the_same_connection() {
+        let (server, mut client) = tokio::net::UnixStream::pair().expect("unix stream pair");
+        let config = SidecarConfig::default();
+        let active_children = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        let engine = Arc::new(SandboxEngine::new(&config, active_children).expect("sandbox engine"));
+        let (tmp, test_registry) =
+            registry_with_slow_and_fast_tools(engine.engine(), "sentinel_test_main_cancel_tool_dir");
+        let registry = Arc::new(RwLock::new(Arc::new(test_registry)));
+        let leak_detector = Arc::new(LeakDetector::new());
+        let default_ceiling = Arc::new(ceiling(&[]));
+        let tool_dir = std::env::temp_dir().join("sentinel_test_main_cancel_tool_dir_unused");
+        let execution_semaphore = Arc::new(PriorityScheduler::new(16));
+
+        tokio::spawn(async move {
+            let _ = handle_connection(
+                server,
+                &engine,
+                &registry,
+                &leak_detector,
+                &default_ceiling,
+                &tool_dir,
+                false,
+                &[],
+                false,
+                false,
+                None,
+                &execution_semaphore,
+                None,
+                PayloadEncoding::Json,
+                4 * 1024 * 1024,
+                300,
+                &Arc::new(tokio::sync::Notify::new()),
+            )
+            .await;
+        });
+
+        let slow = serde_json::json!({
+            "request_id": "slow-1",
+            "tool_name": "slow",
+            "args": {},
+            "capabilities": [],
+            "timeout_ms": 10_000,
+        });
+        client.write_all(slow.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        // Give the request task a moment to be spawned and registered as
+        // in-flight before racing a cancel against it.
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        let cancel = serde_json::json!({ "control": "cancel", "request_id": "slow-1" });
+        client.write_all(cancel.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let ack = read_response_line(&mut client).await;
+        assert_eq!(ack["request_id"], "slow-1");
+        assert_eq!(ack["data"]["event"], "cancel_ack");
+        assert_eq!(ack["data"]["found"], true);
+
+        let cancelled_response = read_response_line(&mut client).await;
+
+        std::fs::remove_dir_all(&tmp).ok();
+
+        assert_eq!(cancelled_response["request_id"], "slow-1");
+        assert_eq!(cancelled_response["success"], false);
+        assert_eq!(cancelled_response["error_code"], "cancelled");
+    }
+
+    /// synth-1180: the client dropping its side of the connection mid-execution
+    /// — no `cancel` control message, just gone — must interrupt a still-running
+    /// execution the same way an explicit cancel would, rather than leaving it
+    /// to spin until its own `timeout_ms` fires.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn client_disconnect_cancels_in_flight_execution_on_that_connection() {
+        let (server, mut client) = tokio::net::UnixStream::pair().expect("unix stream pair");
+        let config = SidecarConfig::default();
+        let active_children = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        let engine = Arc::new(SandboxEngine::new(&config, active_children).expect("sandbox engine"));
+        let (tmp, test_registry) = registry_with_slow_and_fast_tools(
+            engine.engine(),
+            "sentinel_test_main_disconnect_cancel_tool_dir",
+        );
+        let registry = Arc::new(RwLock::new(Arc::new(test_registry)));
+        let leak_detector = Arc::new(LeakDetector::new());
+        let default_ceiling = Arc::new(ceiling(&[]));
+        let tool_dir = std::env::temp_dir().join("sentinel_test_main_disconnect_cancel_tool_dir_unused");
+        let execution_semaphore = Arc::new(PriorityScheduler::new(16));
+        let assertion_engine = engine.clone();
+
+        tokio::spawn(async move {
+            let _ = handle_connection(
+                server,
+                &engine,
+                &registry,
+                &leak_detector,
+                &default_ceiling,
+                &tool_dir,
+                false,
+                &[],
+                false,
+                false,
+                None,
+                &execution_semaphore,
+                None,
+                PayloadEncoding::Json,
+                4 * 1024 * 1024,
+                300,
+                &Arc::new(tokio::sync::Notify::new()),
+            )
+            .await;
+        });
+
+        let slow = serde_json::json!({
+            "request_id": "slow-1",
+            "tool_name": "slow",
+            "args": {},
+            "capabilities": [],
+            "timeout_ms": 10_000,
+        });
+        client.write_all(slow.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        // Give the request task a moment to be spawned and registered as
+        // in-flight before pulling the connection out from under it.
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        assert!(assertion_engine.in_flight_owner("slow-1").is_some(), "execution should be running");
+
+        drop(client);
+
+        let start = std::time::Instant::now();
+        while assertion_engine.in_flight_owner("slow-1").is_some() {
+            assert!(
+                start.elapsed() < std::time::Duration::from_secs(2),
+                "execution wasn't interrupted promptly after the client disconnected"
+            );
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[tokio::test]
+    async fn request_past_its_deadline_is_rejected_without_executing() {
+        let mut client = spawn_connection(ceiling(&[])).await;
+
+        let request = serde_json::json!({
+            "request_id": "r1",
+            "tool_name": "_health",
+            "args": {},
+            "deadline_epoch_ms": 1,
+        });
+        client.write_all(request.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let response = read_response_line(&mut client).await;
+        assert_eq!(response["request_id"], "r1");
+        assert_eq!(response["success"], false);
+        assert_eq!(response["error_code"], "deadline_exceeded");
+    }
+
+    #[tokio::test]
+    async fn request_with_future_deadline_executes_normally() {
+        let mut client = spawn_connection(ceiling(&[])).await;
+
+        let request = serde_json::json!({
+            "request_id": "r1",
+            "tool_name": "_health",
+            "args": {},
+            "deadline_epoch_ms": epoch_millis_now() + 60_000,
+        });
+        client.write_all(request.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let response = read_response_line(&mut client).await;
+        assert_eq!(response["success"], true);
+    }
+
+    #[tokio::test]
+    async fn cancel_control_message_for_unknown_request_id_reports_not_found() {
+        let mut client = spawn_connection(ceiling(&["read_file"])).await;
+
+        let cancel = serde_json::json!({ "control": "cancel", "request_id": "not-mine" });
+        client.write_all(cancel.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let ack = read_response_line(&mut client).await;
+        assert_eq!(ack["data"]["event"], "cancel_ack");
+        assert_eq!(ack["data"]["found"], false);
+    }
+
+    #[tokio::test]
+    async fn control_reload_without_admin_capability_is_rejected() {
+        let mut client = spawn_connection(ceiling(&["read_file"])).await;
+
+        let reload = serde_json::json!({
+            "control": "reload", "target": "registry", "request_id": "r1",
+        });
+        client.write_all(reload.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let ack = read_response_line(&mut client).await;
+        assert_eq!(ack["request_id"], "r1");
+        assert_eq!(ack["success"], false);
+        assert_eq!(ack["error_code"], "unauthorized");
+    }
+
+    #[tokio::test]
+    async fn control_reload_registry_picks_up_a_newly_added_tool() {
+        let tmp = std::env::temp_dir().join("sentinel_test_main_control_reload_registry");
+        std::fs::create_dir_all(&tmp).ok();
+        std::fs::remove_file(tmp.join("newtool.toml")).ok();
+        std::fs::remove_file(tmp.join("newtool.wasm")).ok();
+
+        let (server, mut client) = tokio::net::UnixStream::pair().expect("unix stream pair");
+        let config = SidecarConfig::default();
+        let active_children = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        let engine = Arc::new(SandboxEngine::new(&config, active_children).expect("sandbox engine"));
+        let registry = Arc::new(RwLock::new(Arc::new(ToolRegistry::new())));
+        let leak_detector = Arc::new(LeakDetector::new());
+        let default_ceiling: Arc<HashSet<Capability>> = Arc::new(ceiling(&["admin"]));
+        let execution_semaphore = Arc::new(PriorityScheduler::new(16));
+
+        let connection_tool_dir = tmp.clone();
+        tokio::spawn(async move {
+            let _ = handle_connection(
+                server, &engine, &registry, &leak_detector, &default_ceiling, &connection_tool_dir,
+                false, &[], false, false, None, &execution_semaphore, None,
+                PayloadEncoding::Json, 4 * 1024 * 1024, 300, &Arc::new(tokio::sync::Notify::new()),
+            ).await;
+        });
+
+        // Before the reload, the tool the registry hasn't seen yet is unknown.
+        let request = serde_json::json!({
+            "request_id": "r0", "tool_name": "newtool", "args": {}, "capabilities": [],
+        });
+        client.write_all(request.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let before = read_response_line(&mut client).await;
+        assert_eq!(before["error_code"], "unknown_tool");
+
+        // Drop a tool.toml (and a minimal, exports-free, but validly-encoded
+        // wasm module — same trick sandbox.rs's own tests use) into the
+        // directory the connection was configured with, then reload it over
+        // the socket instead of restarting the process.
+        std::fs::write(
+            tmp.join("newtool.wasm"),
+            [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00],
+        )
+        .unwrap();
+        std::fs::write(
+            tmp.join("newtool.toml"),
+            r#"
+name = "newtool"
+description = "a tool that only exists after a reload"
+wasm = "newtool.wasm"
+capabilities = []
+"#,
+        )
+        .unwrap();
+
+        let reload = serde_json::json!({
+            "control": "reload", "target": "registry", "request_id": "r1",
+        });
+        client.write_all(reload.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let ack = read_response_line(&mut client).await;
+        assert_eq!(ack["request_id"], "r1");
+        assert_eq!(ack["success"], true);
+        assert_eq!(ack["data"]["added"], serde_json::json!(["newtool"]));
+
+        // The tool is now known — a request for it gets past "unknown_tool"
+        // and reaches the WASM path, failing there instead (this module has
+        // no `_start` export) since there's no real wasm behind it.
+        let request = serde_json::json!({
+            "request_id": "r2", "tool_name": "newtool", "args": {}, "capabilities": [],
+        });
+        client.write_all(request.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+        let after = read_response_line(&mut client).await;
+        assert_ne!(after["error_code"], "unknown_tool");
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[tokio::test]
+    async fn control_reload_unknown_target_is_rejected() {
+        let mut client = spawn_connection(ceiling(&["admin"])).await;
+
+        let reload = serde_json::json!({
+            "control": "reload", "target": "bogus", "request_id": "r1",
+        });
+        client.write_all(reload.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let ack = read_response_line(&mut client).await;
+        assert_eq!(ack["success"], false);
+        assert_eq!(ack["error_code"], "unknown_reload_target");
+    }
+
+    #[tokio::test]
+    async fn control_reload_leak_patterns_is_an_honest_no_op() {
+        let mut client = spawn_connection(ceiling(&["admin"])).await;
+
+        let reload = serde_json::json!({
+            "control": "reload", "target": "leak_patterns", "request_id": "r1",
+        });
+        client.write_all(reload.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let ack = read_response_line(&mut client).await;
+        assert_eq!(ack["success"], true);
+        assert_eq!(ack["data"]["reloaded"], false);
+    }
+
+    fn health_entry(request_id: &str) -> serde_json::Value {
+        serde_json::json!({ "request_id": request_id, "tool_name": "_health", "args": {} })
+    }
+
+    fn unknown_tool_entry(request_id: &str) -> serde_json::Value {
+        serde_json::json!({ "request_id": request_id, "tool_name": "does_not_exist", "args": {} })
+    }
+
+    #[tokio::test]
+    async fn batch_sequential_stops_at_first_failure_by_default() {
+        let mut client = spawn_connection(ceiling(&[])).await;
+
+        let batch = serde_json::json!({
+            "batch": [health_entry("r1"), unknown_tool_entry("r2"), health_entry("r3")],
+            "mode": "sequential",
+            "request_id": "batch-1",
+        });
+        client.write_all(batch.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let envelope = read_response_line(&mut client).await;
+        assert_eq!(envelope["request_id"], "batch-1");
+        assert_eq!(envelope["success"], false);
+        assert_eq!(envelope["error_code"], "batch_partial_failure");
+        let entries = envelope["data"].as_array().unwrap();
+        assert_eq!(entries.len(), 2, "should stop after the second entry fails: {entries:?}");
+        assert_eq!(entries[0]["request_id"], "r1");
+        assert_eq!(entries[0]["success"], true);
+        assert_eq!(entries[1]["request_id"], "r2");
+        assert_eq!(entries[1]["success"], false);
+        assert_eq!(entries[1]["error_code"], "unknown_tool");
+    }
+
+    #[tokio::test]
+    async fn batch_sequential_continues_past_failure_when_requested() {
+        let mut client = spawn_connection(ceiling(&[])).await;
+
+        let batch = serde_json::json!({
+            "batch": [health_entry("r1"), unknown_tool_entry("r2"), health_entry("r3")],
+            "mode": "sequential",
+            "request_id": "batch-1",
+            "continue_on_error": true,
+        });
+        client.write_all(batch.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let envelope = read_response_line(&mut client).await;
+        assert_eq!(envelope["success"], false);
+        let entries = envelope["data"].as_array().unwrap();
+        assert_eq!(entries.len(), 3, "continue_on_error should run every entry: {entries:?}");
+        assert_eq!(entries[2]["request_id"], "r3");
+        assert_eq!(entries[2]["success"], true);
+    }
+
+    #[tokio::test]
+    async fn batch_parallel_runs_every_entry_and_preserves_order() {
+        let mut client = spawn_connection(ceiling(&[])).await;
+
+        let batch = serde_json::json!({
+            "batch": [health_entry("r1"), unknown_tool_entry("r2"), health_entry("r3")],
+            "mode": "parallel",
+            "request_id": "batch-1",
+        });
+        client.write_all(batch.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let envelope = read_response_line(&mut client).await;
+        assert_eq!(envelope["success"], false);
+        let entries = envelope["data"].as_array().unwrap();
+        assert_eq!(entries.len(), 3, "parallel mode runs every entry regardless of failures");
+        assert_eq!(entries[0]["request_id"], "r1");
+        assert_eq!(entries[1]["request_id"], "r2");
+        assert_eq!(entries[2]["request_id"], "r3");
+        assert_eq!(entries[0]["success"], true);
+        assert_eq!(entries[1]["success"], false);
+        assert_eq!(entries[2]["success"], true);
+    }
+
+    #[tokio::test]
+    async fn batch_of_all_successes_reports_overall_success() {
+        let mut client = spawn_connection(ceiling(&[])).await;
+
+        let batch = serde_json::json!({
+            "batch": [health_entry("r1"), health_entry("r2")],
+            "mode": "parallel",
+            "request_id": "batch-1",
+        });
+        client.write_all(batch.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let envelope = read_response_line(&mut client).await;
+        assert_eq!(envelope["success"], true);
+        assert_eq!(envelope["data"].as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn batch_with_unknown_mode_is_rejected_without_running_anything() {
+        let mut client = spawn_connection(ceiling(&[])).await;
+
+        let batch = serde_json::json!({
+            "batch": [health_entry("r1")],
+            "mode": "bogus",
+            "request_id": "batch-1",
+        });
+        client.write_all(batch.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let envelope = read_response_line(&mut client).await;
+        assert_eq!(envelope["request_id"], "batch-1");
+        assert_eq!(envelope["success"], false);
+        assert_eq!(envelope["error_code"], "invalid_request");
+    }
+
+    #[tokio::test]
+    async fn batch_over_max_batch_size_is_rejected_without_running_anything() {
+        let config = SidecarConfig { max_batch_size: 2, ..SidecarConfig::default() };
+        let mut client = spawn_connection_with_config(ceiling(&[]), config).await;
+
+        let batch = serde_json::json!({
+            "batch": [health_entry("r1"), health_entry("r2"), health_entry("r3")],
+            "mode": "sequential",
+            "request_id": "batch-1",
+        });
+        client.write_all(batch.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let envelope = read_response_line(&mut client).await;
+        assert_eq!(envelope["request_id"], "batch-1");
+        assert_eq!(envelope["success"], false);
+        assert_eq!(envelope["error_code"], "batch_too_large");
+    }
+
+    /// Bind the TCP listener (synth-1169) on an ephemeral port with `token`
+    /// required, run its accept loop into a fresh channel, and drive
+    /// `handle_connection` for each accepted connection exactly like `main`
+    /// does. Returns a connected client.
+    async fn spawn_tcp_connection(token: &str) -> tokio::net::TcpStream {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("bind ephemeral TCP port");
+        let addr = listener.local_addr().expect("local addr");
+        let ceiling = Arc::new(ceiling(&["read_file"]));
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<AcceptedConnection>(4);
+        let shutdown = Arc::new(tokio::sync::Notify::new());
+        spawn_tcp_accept_loop(listener, ceiling, Arc::from(token), None, tx, shutdown);
+
+        let client = tokio::net::TcpStream::connect(addr).await.expect("connect to TCP listener");
+
+        let accepted = rx.recv().await.expect("TCP listener forwarded the accepted connection");
+        let config = SidecarConfig::default();
+        let active_children = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        let engine = Arc::new(SandboxEngine::new(&config, active_children).expect("sandbox engine"));
+        let registry = Arc::new(RwLock::new(Arc::new(ToolRegistry::new())));
+        let leak_detector = Arc::new(LeakDetector::new());
+        let tool_dir = std::env::temp_dir().join("sentinel_test_main_tcp_tool_dir");
+        let execution_semaphore = Arc::new(PriorityScheduler::new(16));
+        tokio::spawn(async move {
+            let _permit = accepted.permit;
+            let _ = handle_connection(
+                accepted.stream,
+                &engine,
+                &registry,
+                &leak_detector,
+                &accepted.ceiling,
+                &tool_dir,
+                false,
+                &[],
+                false,
+                false,
+                None,
+                &execution_semaphore,
+                accepted.required_token.as_deref(),
+                PayloadEncoding::Json,
+                4 * 1024 * 1024,
+                300,
+                &Arc::new(tokio::sync::Notify::new()),
+            )
+            .await;
+        });
+
+        client
+    }
+
+    #[tokio::test]
+    async fn tcp_listener_rejects_wrong_auth_token() {
+        let mut client = spawn_tcp_connection("correct-token").await;
+        client.write_all(br#"{"auth": {"token": "wrong-token"}}"#).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let response = read_response_line(&mut client).await;
+        assert_eq!(response["success"], false);
+        assert_eq!(response["error_code"], "unauthorized");
+
+        // The connection is closed after a bad token — nothing further is
+        // ever written back, even for a line that would otherwise succeed.
+        // The write itself may fail outright (broken pipe) or succeed into
+        // a closed socket's buffer; either way no more data ever arrives.
+        let _ = client.write_all(br#"{"request_id": "r1", "tool_name": "_health", "args": {}, "capabilities": []}"#).await;
+        let _ = client.write_all(b"\n").await;
+        let mut byte = [0u8; 1];
+        assert_eq!(client.read(&mut byte).await.unwrap_or(0), 0, "connection should be closed after unauthorized");
+    }
+
+    #[tokio::test]
+    async fn tcp_listener_rejects_missing_auth_line() {
+        let mut client = spawn_tcp_connection("correct-token").await;
+        client
+            .write_all(br#"{"request_id": "r1", "tool_name": "_health", "args": {}, "capabilities": []}"#)
+            .await
+            .unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let response = read_response_line(&mut client).await;
+        assert_eq!(response["success"], false);
+        assert_eq!(response["error_code"], "unauthorized");
+    }
+
+    #[tokio::test]
+    async fn tcp_listener_accepts_correct_token_and_serves_health() {
+        let mut client = spawn_tcp_connection("correct-token").await;
+        client.write_all(br#"{"auth": {"token": "correct-token"}}"#).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let auth_ack = read_response_line(&mut client).await;
+        assert_eq!(auth_ack["success"], true);
+
+        let health = serde_json::json!({
+            "request_id": "health-1",
+            "tool_name": "_health",
+            "args": {},
+            "capabilities": [],
+        });
+        client.write_all(health.to_string().as_bytes()).await.unwrap();
+        client.write_all(b"\n").await.unwrap();
+
+        let response = read_response_line(&mut client).await;
+        assert_eq!(response["success"], true);
+    }
+}
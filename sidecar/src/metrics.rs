@@ -0,0 +1,213 @@
+//! Prometheus text-exposition metrics (synth-1168).
+//!
+//! No `prometheus`/`metrics` crate is vendored for this build, so this
+//! renders the same exposition format by hand — one `# HELP`/`# TYPE`
+//! pair per metric family, then one line per label combination actually
+//! seen. Counters are kept in a `Mutex<HashMap<..>>` keyed by label
+//! values, the same pattern `SandboxEngine::in_flight` and
+//! `registry::ToolMetrics` already use for keyed/atomic state. Disabled
+//! by default via `SidecarConfig::metrics_enabled` — collection itself is
+//! always-on and cheap (a HashMap entry increment), only exposition via
+//! the `_metrics` meta-request is gated.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Sum and count of a per-tool quantity (execution duration, fuel
+/// consumed), exposed as the usual Prometheus `_sum`/`_count` counter pair
+/// rather than a true histogram — no bucket boundaries are declared here.
+#[derive(Default)]
+struct DurationAgg {
+    sum: u64,
+    count: u64,
+}
+
+/// Process-wide metrics registry, one instance per [`crate::sandbox::SandboxEngine`].
+#[derive(Default)]
+pub struct Metrics {
+    requests_total: Mutex<HashMap<(String, String), u64>>,
+    execution_duration_ms: Mutex<HashMap<String, DurationAgg>>,
+    fuel_consumed: Mutex<HashMap<String, DurationAgg>>,
+    leak_detections_total: AtomicU64,
+    host_calls_total: Mutex<HashMap<String, u64>>,
+    http_fetch_total: Mutex<HashMap<String, u64>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one completed tool execution — `error_code` empty on success,
+    /// mirroring `Response::error_code`'s own "only set on failure" contract.
+    pub fn record_request(&self, tool: &str, error_code: &str) {
+        let mut map = self.requests_total.lock().unwrap_or_else(|e| e.into_inner());
+        *map.entry((tool.to_string(), error_code.to_string())).or_insert(0) += 1;
+    }
+
+    pub fn record_duration_ms(&self, tool: &str, duration_ms: u64) {
+        let mut map = self.execution_duration_ms.lock().unwrap_or_else(|e| e.into_inner());
+        let agg = map.entry(tool.to_string()).or_default();
+        agg.sum += duration_ms;
+        agg.count += 1;
+    }
+
+    pub fn record_fuel(&self, tool: &str, fuel: u64) {
+        let mut map = self.fuel_consumed.lock().unwrap_or_else(|e| e.into_inner());
+        let agg = map.entry(tool.to_string()).or_default();
+        agg.sum += fuel;
+        agg.count += 1;
+    }
+
+    pub fn record_leak_detection(&self) {
+        self.leak_detections_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record one `host_call` dispatch, keyed by op name (`"read_file"`,
+    /// `"http_fetch"`, ...).
+    pub fn record_host_call(&self, op: &str) {
+        let mut map = self.host_calls_total.lock().unwrap_or_else(|e| e.into_inner());
+        *map.entry(op.to_string()).or_insert(0) += 1;
+    }
+
+    /// Record one `http_fetch` outcome — a status class (`"2xx"`, `"4xx"`,
+    /// ...) on a completed request, or `"error"` when the fetch itself
+    /// failed (denied, DNS, timeout, ...) before a status was ever received.
+    pub fn record_http_fetch(&self, outcome: &str) {
+        let mut map = self.http_fetch_total.lock().unwrap_or_else(|e| e.into_inner());
+        *map.entry(outcome.to_string()).or_insert(0) += 1;
+    }
+
+    /// Render every counter in Prometheus text exposition format
+    /// (https://prometheus.io/docs/instrumenting/exposition_formats/).
+    /// `in_flight` and `open_connections` (synth-1173) are supplied by the
+    /// caller since they're tracked on `SandboxEngine`, not here.
+    pub fn render(&self, in_flight: u64, open_connections: u64) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP sentinel_sidecar_requests_total Tool executions by tool and error_code (error_code empty on success).\n");
+        out.push_str("# TYPE sentinel_sidecar_requests_total counter\n");
+        for ((tool, error_code), count) in self.requests_total.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+            out.push_str(&format!(
+                "sentinel_sidecar_requests_total{{tool=\"{}\",error_code=\"{}\"}} {count}\n",
+                escape(tool),
+                escape(error_code),
+            ));
+        }
+
+        out.push_str("# HELP sentinel_sidecar_execution_duration_ms_sum Total execution wall-clock time by tool, in milliseconds.\n");
+        out.push_str("# TYPE sentinel_sidecar_execution_duration_ms_sum counter\n");
+        out.push_str("# HELP sentinel_sidecar_execution_duration_ms_count Number of executions by tool.\n");
+        out.push_str("# TYPE sentinel_sidecar_execution_duration_ms_count counter\n");
+        for (tool, agg) in self.execution_duration_ms.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+            out.push_str(&format!("sentinel_sidecar_execution_duration_ms_sum{{tool=\"{}\"}} {}\n", escape(tool), agg.sum));
+            out.push_str(&format!("sentinel_sidecar_execution_duration_ms_count{{tool=\"{}\"}} {}\n", escape(tool), agg.count));
+        }
+
+        out.push_str("# HELP sentinel_sidecar_fuel_consumed_sum Total WASM fuel consumed by tool.\n");
+        out.push_str("# TYPE sentinel_sidecar_fuel_consumed_sum counter\n");
+        out.push_str("# HELP sentinel_sidecar_fuel_consumed_count Number of fuel-metered executions by tool.\n");
+        out.push_str("# TYPE sentinel_sidecar_fuel_consumed_count counter\n");
+        for (tool, agg) in self.fuel_consumed.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+            out.push_str(&format!("sentinel_sidecar_fuel_consumed_sum{{tool=\"{}\"}} {}\n", escape(tool), agg.sum));
+            out.push_str(&format!("sentinel_sidecar_fuel_consumed_count{{tool=\"{}\"}} {}\n", escape(tool), agg.count));
+        }
+
+        out.push_str("# HELP sentinel_sidecar_leak_detections_total Leak detector matches across every execution.\n");
+        out.push_str("# TYPE sentinel_sidecar_leak_detections_total counter\n");
+        out.push_str(&format!(
+            "sentinel_sidecar_leak_detections_total {}\n",
+            self.leak_detections_total.load(Ordering::Relaxed)
+        ));
+
+        out.push_str("# HELP sentinel_sidecar_host_calls_total Host function invocations by op.\n");
+        out.push_str("# TYPE sentinel_sidecar_host_calls_total counter\n");
+        for (op, count) in self.host_calls_total.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+            out.push_str(&format!("sentinel_sidecar_host_calls_total{{op=\"{}\"}} {count}\n", escape(op)));
+        }
+
+        out.push_str("# HELP sentinel_sidecar_http_fetch_total http_fetch outcomes by status class.\n");
+        out.push_str("# TYPE sentinel_sidecar_http_fetch_total counter\n");
+        for (outcome, count) in self.http_fetch_total.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+            out.push_str(&format!("sentinel_sidecar_http_fetch_total{{outcome=\"{}\"}} {count}\n", escape(outcome)));
+        }
+
+        out.push_str("# HELP sentinel_sidecar_in_flight_executions Executions currently running.\n");
+        out.push_str("# TYPE sentinel_sidecar_in_flight_executions gauge\n");
+        out.push_str(&format!("sentinel_sidecar_in_flight_executions {in_flight}\n"));
+
+        out.push_str("# HELP sentinel_sidecar_open_connections Connections currently accepted and not yet closed.\n");
+        out.push_str("# TYPE sentinel_sidecar_open_connections gauge\n");
+        out.push_str(&format!("sentinel_sidecar_open_connections {open_connections}\n"));
+
+        out
+    }
+}
+
+/// Escapes a label value per the exposition format spec: backslash, double
+/// quote, and newline are the only characters that need it.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_includes_help_and_type_for_every_family_even_when_empty() {
+        let metrics = Metrics::new();
+        let text = metrics.render(0, 0);
+        for family in [
+            "sentinel_sidecar_requests_total",
+            "sentinel_sidecar_execution_duration_ms_sum",
+            "sentinel_sidecar_execution_duration_ms_count",
+            "sentinel_sidecar_fuel_consumed_sum",
+            "sentinel_sidecar_fuel_consumed_count",
+            "sentinel_sidecar_leak_detections_total",
+            "sentinel_sidecar_host_calls_total",
+            "sentinel_sidecar_http_fetch_total",
+            "sentinel_sidecar_in_flight_executions",
+            "sentinel_sidecar_open_connections",
+        ] {
+            assert!(text.contains(&format!("# HELP {family} ")), "missing HELP for {family}");
+            assert!(text.contains(&format!("# TYPE {family} ")), "missing TYPE for {family}");
+        }
+        assert!(text.contains("sentinel_sidecar_leak_detections_total 0"));
+        assert!(text.contains("sentinel_sidecar_in_flight_executions 0"));
+        assert!(text.contains("sentinel_sidecar_open_connections 0"));
+    }
+
+    #[test]
+    fn render_reports_recorded_counters_with_labels() {
+        let metrics = Metrics::new();
+        metrics.record_request("echo", "");
+        metrics.record_request("echo", "timeout");
+        metrics.record_duration_ms("echo", 42);
+        metrics.record_fuel("echo", 1000);
+        metrics.record_leak_detection();
+        metrics.record_host_call("read_file");
+        metrics.record_host_call("read_file");
+        metrics.record_http_fetch("2xx");
+
+        let text = metrics.render(3, 7);
+        assert!(text.contains("sentinel_sidecar_requests_total{tool=\"echo\",error_code=\"\"} 1"));
+        assert!(text.contains("sentinel_sidecar_requests_total{tool=\"echo\",error_code=\"timeout\"} 1"));
+        assert!(text.contains("sentinel_sidecar_execution_duration_ms_sum{tool=\"echo\"} 42"));
+        assert!(text.contains("sentinel_sidecar_fuel_consumed_sum{tool=\"echo\"} 1000"));
+        assert!(text.contains("sentinel_sidecar_leak_detections_total 1"));
+        assert!(text.contains("sentinel_sidecar_host_calls_total{op=\"read_file\"} 2"));
+        assert!(text.contains("sentinel_sidecar_http_fetch_total{outcome=\"2xx\"} 1"));
+        assert!(text.contains("sentinel_sidecar_in_flight_executions 3"));
+        assert!(text.contains("sentinel_sidecar_open_connections 7"));
+    }
+
+    #[test]
+    fn label_values_containing_quotes_are_escaped() {
+        let metrics = Metrics::new();
+        metrics.record_request("weird\"tool", "");
+        let text = metrics.render(0, 0);
+        assert!(text.contains("tool=\"weird\\\"tool\""));
+    }
+}
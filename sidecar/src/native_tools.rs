@@ -0,0 +1,54 @@
+//! Built-in native tools (synth-1147) — trusted Rust code registered
+//! directly with the [`crate::registry::ToolRegistry`] and dispatched by
+//! `SandboxEngine::execute` without a WASM sandbox. Reserved for operations
+//! simple and well-reviewed enough that sandboxing would be pure overhead;
+//! anything that touches the filesystem, network, or a subprocess belongs
+//! in a real WASM tool instead.
+
+use crate::capabilities::CapabilitySet;
+use crate::protocol::Request;
+use crate::registry::NativeTool;
+
+/// Echoes `request.args` back as its output. Exists as the reference
+/// implementation of [`NativeTool`] — a stand-in for the kind of trivial,
+/// no-I/O operation this mechanism is for.
+pub struct EchoTool;
+
+impl NativeTool for EchoTool {
+    fn run(&self, request: &Request, _granted: &CapabilitySet) -> anyhow::Result<String> {
+        Ok(request.args.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn request_with_args(args: serde_json::Value) -> Request {
+        Request {
+            request_id: "req-1".to_string(),
+            tool_name: "echo".to_string(),
+            protocol_version: None,
+            tool_version: None,
+            args,
+            capabilities: Vec::new(),
+            denied_capabilities: Vec::new(),
+            timeout_ms: None,
+            fuel: None,
+            http_timeout_ms: None,
+            credentials: HashMap::new(),
+            http_allowlist: None,
+            priority: None,
+            deadline_epoch_ms: None,
+        }
+    }
+
+    #[test]
+    fn echo_tool_returns_args_as_output() {
+        let request = request_with_args(serde_json::json!({ "message": "hi" }));
+        let output = EchoTool.run(&request, &CapabilitySet::new()).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(parsed, serde_json::json!({ "message": "hi" }));
+    }
+}
@@ -3,31 +3,366 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-/// A tool execution request from the Python controller.
+/// Wire encoding for the controller-facing protocol (synth-1175) — JSON (the
+/// historical default) or MessagePack, which skips the CPU cost of
+/// encoding/decoding big base64'd file contents as JSON text. Only the
+/// controller-facing protocol is affected; the guest-host IO buffer between
+/// the sidecar and a WASM tool stays JSON regardless. Negotiated per
+/// connection by [`HelloBody::encoding`], or defaulted per Unix listener via
+/// `crate::config::ListenerConfig::encoding` — see `main::handle_connection`
+/// and `main::read_frame`/`main::encode_frame` for the actual framing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadEncoding {
+    #[default]
+    Json,
+    MsgPack,
+}
+
+impl PayloadEncoding {
+    /// Parse a config/hello-supplied encoding name. Unrecognized names
+    /// (including a typo like `"msg_pack"`) return `None` rather than an
+    /// error — the caller falls back to whatever encoding was already in
+    /// effect, same as `HelloBody::max_capabilities`'s "ignore, don't reject"
+    /// treatment of an unknown capability name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(Self::Json),
+            "msgpack" => Some(Self::MsgPack),
+            _ => None,
+        }
+    }
+}
+
+/// Oldest wire protocol version this build still understands (synth-1176).
+pub const MIN_SUPPORTED_PROTOCOL_VERSION: u32 = 1;
+/// Newest wire protocol version this build understands (synth-1176).
+/// Bumped only for a breaking change to the wire shape itself — a new
+/// optional field on `Request`/`Response`/a control message never needs a
+/// bump, since deserialization already tolerates unknown fields and every
+/// new field defaults when absent.
+pub const MAX_SUPPORTED_PROTOCOL_VERSION: u32 = 1;
+
+/// Whether `version` falls within the supported
+/// `[MIN_SUPPORTED_PROTOCOL_VERSION, MAX_SUPPORTED_PROTOCOL_VERSION]` range
+/// (synth-1176). `None` — a caller from before this field existed — is
+/// always treated as compatible, the same "absent means don't restrict
+/// anything" convention as [`HelloBody::max_capabilities`] being empty.
+pub fn protocol_version_supported(version: Option<u32>) -> bool {
+    match version {
+        None => true,
+        Some(v) => (MIN_SUPPORTED_PROTOCOL_VERSION..=MAX_SUPPORTED_PROTOCOL_VERSION).contains(&v),
+    }
+}
+
+/// Optional first line on a connection (synth-1124), declaring the maximum
+/// capability set that connection may ever grant regardless of what
+/// individual requests ask for, e.g.
+/// `{"hello": {"max_capabilities": ["read_file"], "client": "low-trust-worker"}}`.
+/// Handled in `main::handle_connection` before any `Request` is processed.
+#[derive(Debug, Deserialize)]
+pub struct HelloMessage {
+    pub hello: HelloBody,
+}
+
+/// Body of a [`HelloMessage`].
+#[derive(Debug, Deserialize)]
+pub struct HelloBody {
+    /// Capability names this connection may ever grant. Unknown names are
+    /// ignored rather than rejected — a ceiling only ever restricts, so a
+    /// typo here just means that name can never be granted, not a fatal
+    /// connection error.
+    #[serde(default)]
+    pub max_capabilities: Vec<String>,
+    /// Human-readable client identifier, logged for diagnostics only.
+    #[serde(default)]
+    pub client: Option<String>,
+    /// Switches this connection to a different wire encoding (synth-1175),
+    /// e.g. `"encoding": "msgpack"`. The `hello` line itself is always read
+    /// in whatever encoding the connection already started in (its
+    /// listener's configured default, or JSON on TCP) — this only takes
+    /// effect for the hello ack and every message after it. An unrecognized
+    /// name is ignored, leaving the connection's starting encoding in place.
+    #[serde(default)]
+    pub encoding: Option<String>,
+    /// Protocol version this caller speaks (synth-1176), checked against
+    /// [`MIN_SUPPORTED_PROTOCOL_VERSION`]/[`MAX_SUPPORTED_PROTOCOL_VERSION`]
+    /// before the ceiling is applied. Absent — an old caller predating this
+    /// field — is treated as compatible; see
+    /// [`protocol_version_supported`]. `main::handle_connection` rejects an
+    /// out-of-range version with `error_code: "unsupported_protocol"`
+    /// instead of acknowledging the hello.
+    #[serde(default)]
+    pub protocol_version: Option<u32>,
+}
+
+/// Control message (synth-1130): pre-authorizes a capability bundle for
+/// this connection for a bounded time, so a long-lived pipelined connection
+/// can omit repetitive grants on every request, e.g.
+/// `{"grant": {"capabilities": ["write_file"], "ttl_ms": 60000}}`. Handled
+/// in `main::handle_connection`, which unions the bundle into every
+/// subsequent `Request`'s capabilities until it expires, and never lets it
+/// exceed the connection's active ceiling.
+#[derive(Debug, Deserialize)]
+pub struct GrantMessage {
+    pub grant: GrantBody,
+}
+
+/// Body of a [`GrantMessage`].
+#[derive(Debug, Deserialize)]
+pub struct GrantBody {
+    /// Capability names to pre-authorize, same string form as
+    /// `Request.capabilities`.
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    /// How long the grant remains active, in milliseconds.
+    pub ttl_ms: u64,
+}
+
+/// Control message (synth-1132): hot-reloads the tool registry from disk
+/// without dropping connections, e.g. `{"reload": "registry"}`. Handled in
+/// `main::handle_connection`, which diffs the reloaded registry against the
+/// previous one and replies with a [`Response`] describing what changed.
+/// `"config"` (synth-1152) and `"credentials"` (synth-1155) reload the
+/// sidecar config and the credential provider the same way; any other
+/// value is rejected as an unknown reload target.
+#[derive(Debug, Deserialize)]
+pub struct ReloadMessage {
+    pub reload: String,
+}
+
+/// Control message, distinguished from a [`Request`] by the presence of
+/// `control` (a `Request` has no such field, and this struct has no
+/// `tool_name`) rather than by trying `control` values first. Handled in
+/// `main::handle_connection`, which branches on `control`:
+///
+/// - `{"control": "cancel", "request_id": "..."}` (synth-1166) interrupts an
+///   in-flight execution, looking `request_id` up in
+///   [`crate::sandbox::SandboxEngine`]'s in-flight tracker and replying with
+///   a `cancel_ack` event. Cancelling a request submitted on a *different*
+///   connection is only honored when this connection's hello granted the
+///   `admin` capability; otherwise cancellation is scoped to requests
+///   submitted on the same connection.
+/// - `{"control": "reload", "target": "registry" | "config" | "leak_patterns"
+///   | "all", "request_id": "..."}` (synth-1182) does over the socket what
+///   the `{"reload": "..."}` [`ReloadMessage`] already does from a SIGHUP or
+///   trusted local caller, but gated to `admin`-capable connections since a
+///   reload is a connection-wide, not per-request, effect — same admin gate
+///   as cross-connection `cancel`. `target` is unused by `"cancel"` and
+///   ignored by deserialization when absent.
+#[derive(Debug, Deserialize)]
+pub struct ControlMessage {
+    pub control: String,
+    pub request_id: String,
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+/// A batch of requests submitted as one envelope (synth-1183):
+/// `{"batch": [Request, ...], "mode": "sequential" | "parallel",
+/// "request_id": "..."}`, executed by `main::handle_connection` and
+/// answered with a single [`Response`] whose `data` is the ordered array
+/// of each entry's own `Response`. `"parallel"` runs every entry
+/// concurrently, admitted through the same execution semaphore as any
+/// other request; `"sequential"` runs them in order and stops at the first
+/// entry that doesn't succeed unless `continue_on_error` is set, in which
+/// case every entry still runs. Distinguished from a plain [`Request`] by
+/// the presence of `batch`, the same way [`ControlMessage`] is
+/// distinguished by `control`. Capabilities and credentials are per-entry
+/// — each item in `batch` is a full `Request` with its own — not shared
+/// across the envelope.
+#[derive(Debug, Deserialize)]
+pub struct BatchMessage {
+    pub batch: Vec<Request>,
+    pub mode: String,
+    pub request_id: String,
+    /// Keep running the remaining entries of a `"sequential"` batch after
+    /// one fails, instead of stopping at the first failure. Ignored by
+    /// `"parallel"`, which always runs every entry regardless.
+    #[serde(default)]
+    pub continue_on_error: bool,
+}
+
+/// Mandatory first line on a TCP connection (synth-1169), presenting the
+/// bearer token configured as `SidecarConfig::tcp_auth_token`, e.g.
+/// `{"auth": {"token": "..."}}`. The Unix socket has no equivalent — its
+/// authentication is the filesystem permissions on the socket file itself —
+/// so this is only ever checked by `main::handle_connection` when the
+/// connection came in on the TCP listener.
 #[derive(Debug, Deserialize)]
+pub struct AuthMessage {
+    pub auth: AuthBody,
+}
+
+/// Body of an [`AuthMessage`].
+#[derive(Debug, Deserialize)]
+pub struct AuthBody {
+    pub token: String,
+}
+
+/// A tool execution request from the Python controller.
+///
+/// Also derives `Serialize` (synth-1175), which production code never uses —
+/// the sidecar only ever decodes a `Request`, never encodes one — purely so
+/// a msgpack-encoding round-trip test can build wire bytes for one.
+///
+/// Deriving plain `Deserialize` with no `#[serde(deny_unknown_fields)]`
+/// already tolerates fields this build doesn't know about (synth-1176) — an
+/// older controller field being dropped or a newer one added never breaks
+/// decoding here, which is why an additive `Request`/`Response`/control
+/// message field never needs a [`MAX_SUPPORTED_PROTOCOL_VERSION`] bump.
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Request {
     /// Unique request ID for correlation.
     pub request_id: String,
     /// Name of the tool to execute (e.g. "file_read", "shell_exec").
     pub tool_name: String,
+    /// Protocol version this request was built against (synth-1176),
+    /// checked against
+    /// [`MIN_SUPPORTED_PROTOCOL_VERSION`]/[`MAX_SUPPORTED_PROTOCOL_VERSION`]
+    /// before execution. Absent — an old controller predating this field —
+    /// is treated as compatible; see [`protocol_version_supported`].
+    /// `main::handle_connection` rejects an out-of-range version with
+    /// `error_code: "unsupported_protocol"` instead of running the tool.
+    #[serde(default)]
+    pub protocol_version: Option<u32>,
+    /// Pin execution to a specific tool version (synth-1136), e.g. `"1.2.0"`,
+    /// for gradual rollouts where some callers need the old behavior while
+    /// others opt into the new one. `None` resolves to the tool's default
+    /// version via [`crate::registry::ToolRegistry::lookup`].
+    #[serde(default)]
+    pub tool_version: Option<String>,
     /// Tool arguments as a JSON object.
     pub args: serde_json::Value,
-    /// Capabilities granted for this execution.
+    /// Capabilities granted for this execution, as raw strings. Kept as
+    /// `Vec<String>` on the wire (rather than deserializing straight into a
+    /// [`crate::capabilities::CapabilitySet`]) because `@profile` references
+    /// (synth-1121) can only be expanded against the server's configured
+    /// profile map, which isn't available to a plain `Deserialize` impl.
+    /// [`crate::sandbox::SandboxEngine::execute`] resolves and strictly
+    /// validates this list via
+    /// [`crate::capabilities::CapabilitySet::from_strings_strict_with_profiles`].
     #[serde(default)]
     pub capabilities: Vec<String>,
-    /// Per-request timeout override in milliseconds.
+    /// Capabilities explicitly denied for this execution, as raw capability
+    /// names (synth-1127) — always wins over a grant of the same capability,
+    /// letting a caller run a normally-privileged tool in a degraded mode
+    /// ("grant everything it asks for except shell_exec") without editing
+    /// its `capabilities` list. Applied in
+    /// [`crate::sandbox::SandboxEngine::execute`] after profile expansion.
+    #[serde(default)]
+    pub denied_capabilities: Vec<String>,
+    /// Per-request timeout override in milliseconds. Clamped/rejected
+    /// against `max_timeout_ms` (synth-1157) per `request_override_policy`.
     #[serde(default)]
     pub timeout_ms: Option<u64>,
+    /// Per-request fuel override (synth-1157), same shape as `timeout_ms`
+    /// but for the WASM instruction budget — clamped/rejected against
+    /// `max_request_fuel`.
+    #[serde(default)]
+    pub fuel: Option<u64>,
+    /// Per-request `http_fetch` timeout override in milliseconds
+    /// (synth-1157), same shape as `timeout_ms` — clamped/rejected against
+    /// `max_request_http_timeout_ms`.
+    #[serde(default)]
+    pub http_timeout_ms: Option<u64>,
     /// Per-execution credential map (name → value).
     #[serde(default)]
     pub credentials: HashMap<String, String>,
     /// Per-execution URL allowlist for HTTP fetch operations.
     #[serde(default)]
     pub http_allowlist: Option<Vec<String>>,
+    /// Scheduling priority for execution admission (synth-1178), 0 highest
+    /// through 9 lowest — an interactive request should outrank a
+    /// background batch job queued on the same sidecar.
+    /// `None`/[`crate::scheduler::DEFAULT_PRIORITY`] is a caller that
+    /// doesn't care, and is neither favored nor penalized relative to
+    /// others at the default. Enforced by
+    /// [`crate::scheduler::PriorityScheduler`], which ages a long-queued
+    /// low-priority request so it isn't starved forever behind a steady
+    /// stream of higher-priority ones.
+    #[serde(default)]
+    pub priority: Option<u8>,
+    /// Absolute deadline (synth-1180), milliseconds since the Unix epoch,
+    /// past which the result is no longer useful to the caller — e.g. a
+    /// request that sat behind a batch flood in the execution admission
+    /// queue longer than the caller cares to wait. Checked once in
+    /// `main::handle_connection`, before the capability ceiling or the
+    /// admission queue, so an already-stale request is rejected with
+    /// `error_code: "deadline_exceeded"` without spending a queue slot or a
+    /// sandboxed execution on it. `None` (the default, and every caller
+    /// predating this field) never expires.
+    #[serde(default)]
+    pub deadline_epoch_ms: Option<u64>,
+}
+
+/// Mirror of [`Request`] with `#[serde(deny_unknown_fields)]` (synth-1177),
+/// decoded instead of `Request` when `SidecarConfig::strict_requests` is on
+/// so a typo'd field name (`"capabilties"`) is rejected as `invalid_request`
+/// naming the unknown key, rather than silently ignored and the request run
+/// with an empty capability list. Kept as a parallel type rather than a
+/// flag on `Request` itself — `serde`'s `deny_unknown_fields` is a
+/// compile-time attribute, not a runtime toggle, and `Request` is decoded
+/// leniently everywhere else (the default mode, the msgpack round-trip
+/// test). `main::handle_connection` converts a successful decode into a
+/// `Request` via [`From`] and proceeds identically either way.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct StrictRequest {
+    pub request_id: String,
+    pub tool_name: String,
+    #[serde(default)]
+    pub protocol_version: Option<u32>,
+    #[serde(default)]
+    pub tool_version: Option<String>,
+    pub args: serde_json::Value,
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    #[serde(default)]
+    pub denied_capabilities: Vec<String>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub fuel: Option<u64>,
+    #[serde(default)]
+    pub http_timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub credentials: HashMap<String, String>,
+    #[serde(default)]
+    pub http_allowlist: Option<Vec<String>>,
+    #[serde(default)]
+    pub priority: Option<u8>,
+    #[serde(default)]
+    pub deadline_epoch_ms: Option<u64>,
+}
+
+impl From<StrictRequest> for Request {
+    fn from(r: StrictRequest) -> Self {
+        Request {
+            request_id: r.request_id,
+            tool_name: r.tool_name,
+            protocol_version: r.protocol_version,
+            tool_version: r.tool_version,
+            args: r.args,
+            capabilities: r.capabilities,
+            denied_capabilities: r.denied_capabilities,
+            timeout_ms: r.timeout_ms,
+            fuel: r.fuel,
+            http_timeout_ms: r.http_timeout_ms,
+            credentials: r.credentials,
+            http_allowlist: r.http_allowlist,
+            priority: r.priority,
+            deadline_epoch_ms: r.deadline_epoch_ms,
+        }
+    }
 }
 
 /// A tool execution response back to the Python controller.
-#[derive(Debug, Serialize)]
+///
+/// Also derives `Deserialize` (synth-1175), which production code never
+/// uses — the sidecar only ever encodes a `Response`, never decodes one —
+/// purely so a msgpack-encoding round-trip test can decode one back.
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Response {
     /// Whether the execution succeeded.
     pub success: bool,
@@ -42,6 +377,138 @@ pub struct Response {
     /// Fuel consumed by the WASM execution (if available).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fuel_consumed: Option<u64>,
+    /// Machine-readable error code (synth-1163), so a controller can branch
+    /// on failure kind without regexing `result`'s human text, which changes
+    /// wording freely. Only set on failure. The documented set: `unknown_tool`,
+    /// `capability_denied`, `invalid_args`, `timeout`, `fuel_exhausted`,
+    /// `memory_limit`, `tool_trap`, `tool_exit`, `leak_blocked`, `overloaded`,
+    /// `invalid_request`, `internal`, `cancelled` (synth-1166, a `cancel`
+    /// control message interrupted the execution), `not_ready` (synth-1167,
+    /// `_ready` reporting an empty tool registry), `unauthorized` (synth-1169,
+    /// a TCP connection's first line wasn't a valid bearer-token `auth`
+    /// message; reused by synth-1170 for a Unix connection whose `SO_PEERCRED`
+    /// uid isn't in `allowed_uids`), `deadline_exceeded` (synth-1180,
+    /// `Request.deadline_epoch_ms` had already passed before execution) —
+    /// plus a handful of older, more specific codes (e.g.
+    /// `unknown_tool_version`, `credentials_rejected`,
+    /// `request_limit_exceeded`) predating this set that remain in place
+    /// alongside it. `overloaded` is reserved: nothing in this codebase yet
+    /// tracks in-flight request load to produce it — it becomes live once a
+    /// concurrency limiter lands (see the execution-concurrency work tracked
+    /// separately).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    /// Names (never values) of credentials too short to safely scan for —
+    /// leak protection is weakened for these entries. Empty when every
+    /// credential met the minimum length.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub short_credential_names: Vec<String>,
+    /// Per-grant usage counts recorded during execution (synth-1123), keyed
+    /// by the granted capability string (e.g. `"use_credential:api_key"`).
+    /// Lets callers track consumption of one-shot/usage-limited grants
+    /// without polling the sidecar separately. Empty when nothing
+    /// usage-limited was exercised.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub capability_use_counts: HashMap<String, u32>,
+    /// Granted capabilities whose base check passed at least once during
+    /// execution (synth-1126) — lets operators tighten a tool.toml's
+    /// required capabilities down to what it actually exercises. Empty when
+    /// nothing was checked (e.g. an error before any host call).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capabilities_used: Vec<String>,
+    /// All capabilities granted for this execution, for comparison against
+    /// `capabilities_used`. Empty when the request granted none.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capabilities_granted: Vec<String>,
+    /// The tool's `deprecated` manifest notice (synth-1138), e.g. "use
+    /// file_read_v2 instead". Only set on a successful execution of a
+    /// deprecated tool — the tool still ran, this is just a migration nudge.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecation: Option<String>,
+    /// The tool's canonical name (synth-1142), set only when `request.tool_name`
+    /// was actually an alias (e.g. `shell_exec` for `run_command`) — a nudge
+    /// for callers to migrate off the alias before it's retired. Unset when
+    /// the request already used the canonical name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_tool_name: Option<String>,
+    /// Violations found while validating a successful result against the
+    /// tool's declared `output_schema` (synth-1146), when
+    /// `output_schema_warn_only` is enabled — the execution still succeeds,
+    /// this is just a heads-up that the tool's output contract drifted.
+    /// Empty unless the tool has an `output_schema` and warn-only mode is on.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub output_validation_warnings: Vec<String>,
+    /// Effective per-request limits actually applied to this execution
+    /// (synth-1157) — set only when the request supplied at least one of
+    /// `timeout_ms`/`fuel`/`http_timeout_ms`, so a caller whose override
+    /// got clamped (or accepted as-is) can see what actually ran without
+    /// re-deriving it from the config. Unset when the request took every
+    /// default untouched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub applied_limits: Option<AppliedRequestLimits>,
+    /// Milliseconds this request spent queued behind
+    /// `SidecarConfig::max_concurrent_executions` before its execution
+    /// admitted it (synth-1178) — set once, in `main::handle_connection`,
+    /// right after `PriorityScheduler::acquire_owned` returns. Makes the
+    /// effect of `Request.priority` observable: an interactive request
+    /// jumping a batch queue should show a small value here even while the
+    /// batch requests behind it climb.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue_wait_ms: Option<u64>,
+    /// Non-fatal diagnostics generated while satisfying this request
+    /// (synth-1181) — a request-limit override clamped against its ceiling,
+    /// a shell tool's stdout/stderr truncated to `max_output_bytes`, and
+    /// similar signals that don't fail the execution but are worth a
+    /// caller's attention. Distinct from the dedicated fields above
+    /// (`deprecation`, `short_credential_names`, `output_validation_warnings`,
+    /// `applied_limits`), which already carry their own structured signal;
+    /// this is the catch-all for everything else the sidecar used to only
+    /// log. Empty (and omitted) when execution raised nothing worth flagging.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// `Op::Progress` events a tool emitted during execution (synth-1195),
+    /// oldest first, capped at `host_functions::MAX_PROGRESS_EVENTS` — a
+    /// chatty tool can't grow the response without bound. Empty unless the
+    /// tool called `tool_common::report_progress` at least once. Streaming
+    /// delivery doesn't exist yet, so every event lands here, in the final
+    /// Response, rather than trickling out mid-execution.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub progress: Vec<ProgressEvent>,
+    /// Echoes `Request.request_id` (synth-1164), so a controller can match a
+    /// response back to its request once responses may arrive out of order —
+    /// `main::handle_connection` now spawns each request into its own task,
+    /// so a slow tool no longer blocks the ones pipelined behind it on the
+    /// same connection. Set once in `main::handle_connection` right before a
+    /// response is written; empty for connection-level acks (hello/reload/
+    /// grant) that aren't tied to a single request.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub request_id: String,
+}
+
+/// One `Op::Progress` event a tool reported during execution, in the order
+/// the host received them. See [`Response::progress`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProgressEvent {
+    /// Host-assigned order within this execution, starting at 0 — guests
+    /// don't supply this, so ordering stays correct regardless of how the
+    /// guest tracks its own progress.
+    pub seq: u64,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+/// See [`Response::applied_limits`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedRequestLimits {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fuel: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_timeout_ms: Option<u64>,
 }
 
 impl Response {
@@ -52,6 +519,19 @@ impl Response {
             data: None,
             leaked: false,
             fuel_consumed: None,
+            error_code: None,
+            short_credential_names: Vec::new(),
+            capability_use_counts: HashMap::new(),
+            capabilities_used: Vec::new(),
+            capabilities_granted: Vec::new(),
+            deprecation: None,
+            resolved_tool_name: None,
+            output_validation_warnings: Vec::new(),
+            applied_limits: None,
+            queue_wait_ms: None,
+            warnings: Vec::new(),
+            progress: Vec::new(),
+            request_id: String::new(),
         }
     }
 
@@ -62,16 +542,66 @@ impl Response {
             data: Some(data),
             leaked: false,
             fuel_consumed: None,
+            error_code: None,
+            short_credential_names: Vec::new(),
+            capability_use_counts: HashMap::new(),
+            capabilities_used: Vec::new(),
+            capabilities_granted: Vec::new(),
+            deprecation: None,
+            resolved_tool_name: None,
+            output_validation_warnings: Vec::new(),
+            applied_limits: None,
+            queue_wait_ms: None,
+            warnings: Vec::new(),
+            progress: Vec::new(),
+            request_id: String::new(),
         }
     }
 
-    pub fn error(message: String) -> Self {
+    /// An error response tagged with a machine-readable error code.
+    pub fn error_with_code(message: String, error_code: &str) -> Self {
         Self {
             success: false,
             result: message,
             data: None,
             leaked: false,
             fuel_consumed: None,
+            error_code: Some(error_code.to_string()),
+            short_credential_names: Vec::new(),
+            capability_use_counts: HashMap::new(),
+            capabilities_used: Vec::new(),
+            capabilities_granted: Vec::new(),
+            deprecation: None,
+            resolved_tool_name: None,
+            output_validation_warnings: Vec::new(),
+            applied_limits: None,
+            queue_wait_ms: None,
+            warnings: Vec::new(),
+            progress: Vec::new(),
+            request_id: String::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warnings_are_omitted_when_empty() {
+        let response = Response::success("ok".to_string());
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(!json.contains("warnings"));
+    }
+
+    #[test]
+    fn warnings_round_trip_through_json() {
+        let mut response = Response::success("ok".to_string());
+        response.warnings = vec!["timeout_ms override clamped to 300000ms (ceiling 300000)".to_string()];
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains("\"warnings\":[\"timeout_ms override clamped"));
+
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["warnings"].as_array().unwrap().len(), 1);
+    }
+}
@@ -5,22 +5,47 @@
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use base64::Engine as _;
 use wasmtime::{Engine, Module};
 
-use crate::capabilities::Capability;
+use crate::capabilities::{Capability, CapabilitySet};
+use crate::protocol::Request;
+use crate::schema::Schema;
 
 /// Metadata about a registered tool, loaded from tool.toml.
 pub struct ToolMeta {
     /// Tool name (e.g. "file_read").
     pub name: String,
+    /// Tool version (synth-1136), e.g. `"1.2.0"`. Defaults to `"0.0.0"` for
+    /// manifests that don't declare one, so unversioned tools keep working
+    /// as a single implicit version rather than being rejected.
+    pub version: String,
+    /// Whether this is the version [`ToolRegistry::lookup`] resolves to when
+    /// a request omits `tool_version` (synth-1136) — either declared
+    /// explicitly via the manifest's `default = true`, or, absent that,
+    /// whichever registered version for this name parses as the highest
+    /// semver.
+    pub is_default: bool,
     /// Human-readable description.
     pub description: String,
-    /// Path to the compiled .wasm file.
+    /// Path to the compiled .wasm file, or, for a cwasm-only manifest, the
+    /// precompiled artifact itself — always the file `sha256`/signature
+    /// checks are computed against.
     pub wasm_path: PathBuf,
+    /// Path to the manifest's `cwasm` artifact (synth-1143), if declared,
+    /// regardless of whether it was actually used to load `module` — purely
+    /// informational for `_list_tools`.
+    pub cwasm_path: Option<PathBuf>,
+    /// Whether `module` was loaded from `cwasm_path` via
+    /// `Module::deserialize_file` (synth-1143) rather than compiled from
+    /// `.wasm` source — surfaced so operators can confirm the fast path is
+    /// actually in effect rather than silently falling back every load.
+    pub precompiled: bool,
     /// Pre-compiled Wasmtime module (compiled once at startup, reused per-call).
     /// Wasmtime supports Module::serialize()/deserialize() for persistent caching
-    /// across restarts — deferred since restarts are infrequent (~monthly).
+    /// across restarts (synth-1143) — see `cwasm`/`SENTINEL_SIDECAR_ALLOW_PRECOMPILED`.
     pub module: Module,
     /// Capabilities required by this tool.
     pub required_capabilities: Vec<Capability>,
@@ -28,122 +53,2385 @@ pub struct ToolMeta {
     pub timeout_ms: Option<u64>,
     /// HTTP URL allowlist (for http_fetch tools).
     pub http_allowlist: Option<Vec<String>>,
+    /// Hash of the WASM bytes plus the manifest fields that affect
+    /// execution (synth-1132) — used only to detect whether a tool's
+    /// content actually changed across a hot [`ToolRegistry::reload`], not
+    /// for integrity verification.
+    content_hash: u64,
+    /// Expected sha256 digest of the WASM file, hex-encoded, from the
+    /// manifest's optional `sha256` field (synth-1134). When set,
+    /// `SandboxEngine::execute` re-hashes the file on disk before every
+    /// execution and refuses to run it if the bytes no longer match —
+    /// catching tampering that happens after the tool was loaded.
+    pub sha256: Option<String>,
+    /// Compiled `args_schema` from the manifest (synth-1135), if declared.
+    /// `SandboxEngine::execute` validates `request.args` against it before
+    /// reading the WASM file, so malformed args fail fast with a structured
+    /// error instead of an opaque exit code after a full sandbox spin-up.
+    pub args_schema: Option<Schema>,
+    /// Compiled `output_schema` from the manifest (synth-1146), if declared.
+    /// `SandboxEngine::execute` validates a successful result's structured
+    /// `data` against it after the tool runs, catching output-contract drift
+    /// (e.g. a renamed field) instead of letting callers discover it at
+    /// parse time. A violation is a hard `invalid_tool_output` error unless
+    /// `SidecarConfig::output_schema_warn_only` downgrades it to a warning.
+    pub output_schema: Option<Schema>,
+    /// Whether this tool version may be executed (synth-1138). Defaults to
+    /// `true`; set `enabled = false` in the manifest to pull a tool from
+    /// service (e.g. incident response) without deleting its files —
+    /// [`ToolRegistry::lookup`] still resolves it (so `_list_tools` can show
+    /// it), but refuses to hand it to `SandboxEngine::execute`.
+    pub enabled: bool,
+    /// Deprecation notice from the manifest's `deprecated` field
+    /// (synth-1138), e.g. `"use file_read_v2 instead"`. A deprecated tool
+    /// still executes normally; `SandboxEngine::execute` just copies this
+    /// into the [`crate::protocol::Response::deprecation`] warning.
+    pub deprecated: Option<String>,
+    /// Whether the manifest's `signature` field (synth-1141) verified
+    /// against at least one configured signing key. `false` for both an
+    /// absent signature and a present-but-invalid one — `_list_tools` and
+    /// `_health` only need to know "trustworthy or not", not why.
+    pub signed: bool,
+    /// Alternate names this tool may also be invoked as (synth-1142), e.g.
+    /// `["shell_exec"]` while migrating callers from `shell_exec` to
+    /// `run_command`. [`ToolRegistry::lookup`] resolves either name to this
+    /// same `ToolMeta`; `_list_tools` echoes this list so callers can spot
+    /// which of the names they're using is due to retire.
+    pub aliases: Vec<String>,
+    /// Other tools this tool declares it may call via the (not yet
+    /// implemented) `InvokeTool` host op (synth-1144), by name or alias,
+    /// e.g. `["file_read", "http_fetch"]`. Validated at load — missing
+    /// targets and dependency cycles are recorded as
+    /// [`LoadIssueKind::InvalidDependency`] — so the transitive capability
+    /// closure and cycle safety are known up front rather than discovered
+    /// mid-run. Enforcing that an invocation only reaches a declared target
+    /// is `handle_invoke_tool`'s job once that host op exists.
+    pub invokes: Vec<String>,
+    /// Runtime usage counters (synth-1145), updated by
+    /// `SandboxEngine::execute` after every run and surfaced through
+    /// `_list_tools`/`_health` so operators can spot tools worth retiring.
+    pub metrics: ToolMetrics,
+    /// Whether this tool runs as a compiled WASM module in the sandbox or
+    /// as native Rust code in the sidecar process (synth-1147). `wasm_path`
+    /// and `module` are meaningless placeholders for a [`ToolKind::Native`]
+    /// tool — `SandboxEngine::execute` dispatches on this before it ever
+    /// touches them.
+    pub kind: ToolKind,
+}
+
+/// How a tool is actually executed (synth-1147).
+pub enum ToolKind {
+    /// The default: a compiled WASM module, sandboxed per-invocation by
+    /// `SandboxEngine::execute`.
+    Wasm,
+    /// Runs directly in the sidecar process via [`NativeTool::run`], with no
+    /// WASM sandbox at all — for trivial host-side operations (e.g.
+    /// `_health`-style introspection, a pure "echo") that don't need one.
+    /// `SandboxEngine::execute` still enforces `required_capabilities` and
+    /// leak-scans the result before returning it, exactly as it does for a
+    /// WASM tool's stdout.
+    Native(Arc<dyn NativeTool>),
+}
+
+impl Default for ToolKind {
+    fn default() -> Self {
+        ToolKind::Wasm
+    }
+}
+
+/// A tool implemented natively rather than as a WASM module (synth-1147).
+/// Implementors run in the sidecar process itself, so they're trusted code —
+/// unlike a WASM tool, there's no sandbox isolating a bug or a malicious
+/// implementation from the rest of the process. Only ship one for
+/// operations simple and well-reviewed enough that the sandbox would be
+/// pure overhead.
+pub trait NativeTool: Send + Sync {
+    /// Run the tool and return its raw output, exactly as a WASM tool's
+    /// captured stdout would be — `SandboxEngine::execute` feeds it through
+    /// the same `finalize_success` leak-scan/redact/JSON-parse pipeline, so
+    /// a native tool gets identical leak protection and response shape to a
+    /// sandboxed one. `granted` is the same capability set
+    /// `SandboxEngine::execute` already checked against
+    /// `ToolMeta::required_capabilities` before calling this — implementors
+    /// that gate optional behavior on a capability beyond the tool's base
+    /// requirement can check `granted.has(..)` directly. An `Err` becomes a
+    /// plain execution-failed response, the same as a WASM trap.
+    fn run(&self, request: &Request, granted: &CapabilitySet) -> anyhow::Result<String>;
+}
+
+/// Usage counters for one [`ToolMeta`] (synth-1145). Plain atomics rather
+/// than a lock, since updates are independent per-field increments with no
+/// invariant across them that a reader needs to observe atomically.
+#[derive(Debug, Default)]
+pub struct ToolMetrics {
+    /// Total number of times this tool has been executed.
+    pub invocations: std::sync::atomic::AtomicU64,
+    /// Of those, how many did not succeed.
+    pub failures: std::sync::atomic::AtomicU64,
+    /// Sum of wall-clock execution time across every invocation, in
+    /// milliseconds — divide by `invocations` for the mean.
+    pub total_duration_ms: std::sync::atomic::AtomicU64,
+    /// Milliseconds since the Unix epoch of the most recent invocation, or
+    /// 0 if this tool has never run.
+    pub last_used_ms: std::sync::atomic::AtomicU64,
+}
+
+impl ToolMetrics {
+    /// Record the outcome of one execution.
+    pub fn record(&self, success: bool, duration_ms: u64, now_ms: u64) {
+        use std::sync::atomic::Ordering;
+        self.invocations.fetch_add(1, Ordering::Relaxed);
+        if !success {
+            self.failures.fetch_add(1, Ordering::Relaxed);
+        }
+        self.total_duration_ms.fetch_add(duration_ms, Ordering::Relaxed);
+        self.last_used_ms.store(now_ms, Ordering::Relaxed);
+    }
+
+    /// Copy another tool's counters into this one (synth-1145) — used by
+    /// [`ToolRegistry::reload`] so a tool that's unchanged across a hot
+    /// reload keeps its history instead of resetting to zero.
+    pub fn carry_forward(&self, previous: &ToolMetrics) {
+        use std::sync::atomic::Ordering;
+        self.invocations.store(previous.invocations.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.failures.store(previous.failures.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.total_duration_ms
+            .store(previous.total_duration_ms.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.last_used_ms.store(previous.last_used_ms.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
 }
 
 /// TOML structure for tool.toml files.
 #[derive(serde::Deserialize)]
 struct ToolToml {
     name: String,
+    /// Tool version (synth-1136), e.g. `"1.2.0"`. Manifests that omit this
+    /// are treated as the single implicit `"0.0.0"` version of their name.
+    #[serde(default)]
+    version: Option<String>,
+    /// Marks this version as the one `tool_version: None` resolves to
+    /// (synth-1136), overriding the highest-semver default. Useful for
+    /// pinning the default to a known-good version while a newer one is
+    /// still rolling out.
+    #[serde(default)]
+    default: bool,
     description: String,
-    wasm: String,
+    /// Path to the .wasm source, relative to the tool directory. Optional
+    /// only when `cwasm` is given instead (synth-1143) — at least one of the
+    /// two must be present.
+    #[serde(default)]
+    wasm: Option<String>,
+    /// Path to a precompiled `Module::serialize()` artifact (synth-1143),
+    /// relative to the tool directory, loaded via `Module::deserialize_file`
+    /// when `SENTINEL_SIDECAR_ALLOW_PRECOMPILED=true` to skip the Cranelift
+    /// compile at startup. Falls back to compiling `wasm` when deserializing
+    /// fails, e.g. because the artifact was built against a different
+    /// Wasmtime engine configuration.
+    #[serde(default)]
+    cwasm: Option<String>,
     capabilities: Vec<String>,
     #[serde(default)]
     timeout_ms: Option<u64>,
     #[serde(default)]
     http_allowlist: Option<Vec<String>>,
+    /// Expected sha256 digest of `wasm`, hex-encoded (synth-1134).
+    #[serde(default)]
+    sha256: Option<String>,
+    /// JSON Schema for `request.args` (synth-1135): either an inline TOML
+    /// table (interpreted directly as JSON Schema) or a string path to a
+    /// `.schema.json` file resolved relative to the tool directory.
+    #[serde(default)]
+    args_schema: Option<ArgsSchemaSource>,
+    /// JSON Schema for a successful result's structured `data` (synth-1146),
+    /// same inline-table-or-path shape as `args_schema`.
+    #[serde(default)]
+    output_schema: Option<ArgsSchemaSource>,
+    /// Pull this tool version from service without deleting its files
+    /// (synth-1138). Defaults to `true`.
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+    /// Deprecation notice, e.g. `"use file_read_v2 instead"` (synth-1138).
+    #[serde(default)]
+    deprecated: Option<String>,
+    /// Base64-encoded Ed25519 signature (synth-1141) over
+    /// [`signing_payload`] for this tool's `(name, version, wasm sha256)`,
+    /// checked at load against the configured signing keys. Hash pinning
+    /// (`sha256` above) only protects against accidental corruption; a
+    /// signature also protects against someone who can edit both the wasm
+    /// and the toml, as long as they don't hold a trusted private key.
+    #[serde(default)]
+    signature: Option<String>,
+    /// Alternate names this tool may also be invoked as (synth-1142), for a
+    /// rename migration window where both the old and new name resolve to
+    /// the same tool. Rejected at load if an alias collides with another
+    /// tool's real name or another tool's alias — see
+    /// [`ToolRegistry::resolve_aliases`].
+    #[serde(default)]
+    aliases: Vec<String>,
+    /// Tools this tool declares it may call via `InvokeTool` (synth-1144),
+    /// by name or alias. See [`ToolMeta::invokes`].
+    #[serde(default)]
+    invokes: Vec<String>,
 }
 
-/// Registry of available tools and their metadata.
+fn default_enabled() -> bool {
+    true
+}
+
+/// Where a tool.toml's `args_schema` value came from (synth-1135).
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum ArgsSchemaSource {
+    /// A path to a `.schema.json` file, resolved relative to the tool dir.
+    Path(String),
+    /// An inline TOML table, used directly as a JSON Schema document.
+    Inline(serde_json::Value),
+}
+
+/// Registry of available tools and their metadata, keyed by name and then
+/// by version (synth-1136) — a tool can have several versions registered
+/// side-by-side, e.g. while a new one is rolling out gradually.
 pub struct ToolRegistry {
-    tools: HashMap<String, ToolMeta>,
+    tools: HashMap<String, HashMap<String, ToolMeta>>,
+    /// Alias name -> canonical tool name (synth-1142), built once per load
+    /// by [`Self::resolve_aliases`] after every tool.toml in the directory
+    /// has registered, so an alias can be checked against every real tool
+    /// name regardless of load order.
+    aliases: HashMap<String, String>,
+    /// Problems hit while loading tools from disk (synth-1139), kept on the
+    /// registry itself rather than only ever logged, so operators can see
+    /// broken tools through `_health`/`_list_tools` without grepping stderr.
+    issues: Vec<LoadIssue>,
+}
+
+/// One problem hit while loading a tool.toml from disk (synth-1139).
+#[derive(Debug, Clone)]
+pub struct LoadIssue {
+    /// Path of the offending tool.toml.
+    pub path: String,
+    /// Coarse category, for grouping/counting without parsing `message`.
+    pub kind: LoadIssueKind,
+    /// Human-readable detail.
+    pub message: String,
+}
+
+/// Coarse category of a [`LoadIssue`] (synth-1139).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadIssueKind {
+    /// Same (name, version) pair already registered from another file.
+    Duplicate,
+    /// The TOML itself failed to parse, or couldn't be read.
+    ParseError,
+    /// The manifest's `wasm` path doesn't exist or couldn't be read.
+    MissingWasm,
+    /// The manifest's `capabilities` list contains an unknown name.
+    InvalidCapabilities,
+    /// The manifest is unsigned, or its `signature` doesn't verify against
+    /// any configured key, while SENTINEL_SIDECAR_REQUIRE_SIGNED_TOOLS is
+    /// set (synth-1141).
+    Unsigned,
+    /// A declared `invokes` target doesn't resolve to any registered tool
+    /// or alias, or the tool participates in an `invokes` dependency cycle
+    /// (synth-1144).
+    InvalidDependency,
+    /// Anything else (bad args_schema, missing required sha256, etc.).
+    Other,
+}
+
+impl LoadIssueKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoadIssueKind::Duplicate => "duplicate",
+            LoadIssueKind::ParseError => "parse_error",
+            LoadIssueKind::MissingWasm => "missing_wasm",
+            LoadIssueKind::InvalidCapabilities => "invalid_capabilities",
+            LoadIssueKind::Unsigned => "unsigned",
+            LoadIssueKind::InvalidDependency => "invalid_dependency",
+            LoadIssueKind::Other => "other",
+        }
+    }
+}
+
+impl std::fmt::Display for LoadIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Classify a `load_tool_toml` failure message by which stage produced it
+/// (synth-1139). `load_tool_toml` stays a plain `anyhow::Result` — every
+/// bail site already writes a distinctive, stable message prefix, so
+/// matching on those is simpler than threading a parallel typed error
+/// through every `?` in that function for a distinction only the
+/// diagnostics surface needs.
+fn classify_load_error(message: &str) -> LoadIssueKind {
+    if message.contains("failed to parse") {
+        LoadIssueKind::ParseError
+    } else if message.contains("failed to read WASM") {
+        LoadIssueKind::MissingWasm
+    } else if message.contains("unknown capabilities") {
+        LoadIssueKind::InvalidCapabilities
+    } else if message.contains("unsigned or has an invalid signature") {
+        LoadIssueKind::Unsigned
+    } else if message.contains("failed to read") {
+        LoadIssueKind::ParseError
+    } else {
+        LoadIssueKind::Other
+    }
+}
+
+/// Canonical bytes a tool.toml's `signature` field is expected to cover
+/// (synth-1141): the tool name, version, and wasm sha256 digest, joined by
+/// newlines so the fields can't be confused for one another by
+/// concatenation (e.g. name="a", version="bc" vs. name="ab", version="c").
+/// `pub(crate)` so `main`'s `sign-tool` helper can build the exact same
+/// payload a manifest author needs to sign.
+pub(crate) fn signing_payload(name: &str, version: &str, sha256_hex: &str) -> Vec<u8> {
+    format!("{name}\n{version}\n{sha256_hex}").into_bytes()
+}
+
+/// Check a manifest's base64 `signature` against [`signing_payload`] for
+/// every configured signing key (synth-1141), succeeding if any one
+/// verifies. A malformed base64 signature is treated as "not signed" rather
+/// than a load error — the caller decides whether that's fatal via
+/// `require_signed`.
+fn verify_tool_signature(
+    name: &str,
+    version: &str,
+    sha256_hex: &str,
+    signature_b64: &str,
+    signing_keys: &[Vec<u8>],
+) -> bool {
+    let Ok(signature) = base64::engine::general_purpose::STANDARD.decode(signature_b64) else {
+        return false;
+    };
+    let payload = signing_payload(name, version, sha256_hex);
+    signing_keys.iter().any(|key_bytes| {
+        ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, key_bytes)
+            .verify(&payload, &signature)
+            .is_ok()
+    })
+}
+
+/// Why [`ToolRegistry::lookup`] failed to resolve a tool (synth-1136).
+#[derive(Debug)]
+pub enum LookupError {
+    /// No tool is registered under this name, at any version.
+    UnknownTool,
+    /// The name is registered, but not at the requested version.
+    UnknownVersion {
+        /// Versions that *are* registered for this tool name, sorted.
+        available: Vec<String>,
+    },
 }
 
 impl ToolRegistry {
     pub fn new() -> Self {
         Self {
             tools: HashMap::new(),
+            aliases: HashMap::new(),
+            issues: Vec::new(),
         }
     }
 
-    /// Load all tool.toml files from a directory, pre-compiling WASM modules.
-    /// Each .toml file defines one tool. The WASM file path is resolved
-    /// relative to the tool directory.
-    pub fn load(tool_dir: &Path, engine: &Engine) -> anyhow::Result<Self> {
-        let mut registry = Self::new();
-
-        if !tool_dir.exists() {
-            eprintln!("sidecar: tool directory {} does not exist, starting with empty registry", tool_dir.display());
-            return Ok(registry);
+    /// Register a native tool (synth-1147) — one implemented in Rust rather
+    /// than as a WASM module — under `name`. `engine` is only used to
+    /// compile the placeholder empty module `ToolMeta::module` needs to
+    /// carry (native tools never actually run it); `SandboxEngine::execute`
+    /// dispatches on `ToolMeta::kind` before it would ever be touched.
+    /// Errors if `name` already has a registered tool of any kind — native
+    /// tools don't participate in the version/alias machinery WASM tools
+    /// use, so there's nothing sensible to do with a collision.
+    pub fn register_native(
+        &mut self,
+        engine: &Engine,
+        name: &str,
+        description: &str,
+        required_capabilities: Vec<Capability>,
+        native: Arc<dyn NativeTool>,
+    ) -> anyhow::Result<()> {
+        if self.tools.contains_key(name) {
+            anyhow::bail!("a tool named '{name}' is already registered");
         }
+        let module = Module::new(engine, "(module)")
+            .map_err(|e| anyhow::anyhow!("failed to compile placeholder module for native tool: {e}"))?;
+        let meta = ToolMeta {
+            name: name.to_string(),
+            version: "0.0.0".to_string(),
+            is_default: true,
+            description: description.to_string(),
+            wasm_path: PathBuf::new(),
+            cwasm_path: None,
+            precompiled: false,
+            module,
+            required_capabilities,
+            timeout_ms: None,
+            http_allowlist: None,
+            content_hash: 0,
+            sha256: None,
+            args_schema: None,
+            output_schema: None,
+            enabled: true,
+            deprecated: None,
+            signed: false,
+            aliases: Vec::new(),
+            invokes: Vec::new(),
+            metrics: ToolMetrics::default(),
+            kind: ToolKind::Native(native),
+        };
+        self.tools.entry(name.to_string()).or_default().insert(meta.version.clone(), meta);
+        Ok(())
+    }
 
-        let entries = std::fs::read_dir(tool_dir)
-            .map_err(|e| anyhow::anyhow!("failed to read tool dir {}: {e}", tool_dir.display()))?;
-
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension().and_then(|e| e.to_str()) == Some("toml") {
-                match Self::load_tool_toml(&path, tool_dir, engine) {
-                    Ok(meta) => {
-                        eprintln!("sidecar: loaded tool '{}' from {}", meta.name, path.display());
-                        registry.tools.insert(meta.name.clone(), meta);
-                    }
-                    Err(e) => {
-                        eprintln!("sidecar: failed to load {}: {e}", path.display());
-                    }
-                }
-            }
-        }
+    /// Load issues collected on the most recent [`Self::load`]/[`Self::reload`]
+    /// (synth-1139) — duplicates, parse errors, missing wasm files, invalid
+    /// capabilities. Empty when every tool.toml in the directory loaded clean.
+    pub fn issues(&self) -> &[LoadIssue] {
+        &self.issues
+    }
 
+    /// Load all tool.toml files from a directory, pre-compiling WASM modules.
+    /// Each .toml file defines one tool. The WASM file path is resolved
+    /// relative to the tool directory. `signing_keys`/`require_signed`
+    /// (synth-1141) control manifest signature verification the same way
+    /// `require_hash` controls sha256 enforcement.
+    pub fn load(
+        tool_dir: &Path,
+        engine: &Engine,
+        require_hash: bool,
+        signing_keys: &[Vec<u8>],
+        require_signed: bool,
+        allow_precompiled: bool,
+    ) -> anyhow::Result<Self> {
+        let (registry, _errors) = Self::load_with_errors(
+            tool_dir, engine, require_hash, signing_keys, require_signed, allow_precompiled,
+        )?;
         Ok(registry)
     }
 
     /// Parse a single tool.toml file into ToolMeta, pre-compiling the WASM module.
-    fn load_tool_toml(toml_path: &Path, tool_dir: &Path, engine: &Engine) -> anyhow::Result<ToolMeta> {
+    /// `require_hash` (synth-1134) rejects any manifest missing a `sha256`
+    /// field, for deployments where hashing is mandatory. `require_signed`
+    /// (synth-1141) does the same for a verified `signature`. `allow_precompiled`
+    /// (synth-1143) permits loading a `cwasm` artifact via
+    /// `Module::deserialize_file` instead of compiling `wasm` from source.
+    fn load_tool_toml(
+        toml_path: &Path,
+        tool_dir: &Path,
+        engine: &Engine,
+        require_hash: bool,
+        signing_keys: &[Vec<u8>],
+        require_signed: bool,
+        allow_precompiled: bool,
+    ) -> anyhow::Result<ToolMeta> {
         let content = std::fs::read_to_string(toml_path)
             .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", toml_path.display()))?;
 
         let parsed: ToolToml = toml::from_str(&content)
             .map_err(|e| anyhow::anyhow!("failed to parse {}: {e}", toml_path.display()))?;
 
-        let wasm_path = tool_dir.join(&parsed.wasm);
+        // synth-1119: a typo in tool.toml's capabilities list must fail the
+        // tool load, not silently register the tool with fewer requirements
+        // than the manifest declares. Checked before the (much more
+        // expensive) WASM compile so a bad manifest fails fast.
+        let (capability_set, _) = CapabilitySet::from_strings_strict(&parsed.capabilities)
+            .map_err(|e| {
+                anyhow::anyhow!("tool '{}' has unknown capabilities: {e}", parsed.name)
+            })?;
+        let required_capabilities = capability_set.capabilities();
 
-        // Pre-compile WASM module at startup (avoids per-call Cranelift JIT cost)
-        let wasm_bytes = std::fs::read(&wasm_path)
-            .map_err(|e| anyhow::anyhow!("failed to read WASM {}: {e}", wasm_path.display()))?;
-        let module = Module::new(engine, &wasm_bytes)
-            .map_err(|e| anyhow::anyhow!("failed to compile WASM {}: {e}", wasm_path.display()))?;
+        // synth-1134: in strict mode, a manifest without a sha256 can never
+        // be verified at execution time, so it's rejected the same way an
+        // unknown capability is — at load, before the expensive compile.
+        if require_hash && parsed.sha256.is_none() {
+            anyhow::bail!(
+                "tool '{}' has no sha256 and SENTINEL_SIDECAR_REQUIRE_TOOL_HASH is set",
+                parsed.name
+            );
+        }
 
-        let required_capabilities: Vec<Capability> = parsed
-            .capabilities
-            .iter()
-            .filter_map(|s| {
-                match Capability::from_str(s) {
-                    Some(cap) => Some(cap),
-                    None => {
-                        eprintln!(
-                            "sidecar: warning: tool '{}' has unknown capability '{}', skipping",
-                            parsed.name, s
+        // synth-1135: a schema compilation error is a manifest bug, so it
+        // fails the tool load the same way an unknown capability does,
+        // rather than surfacing at every request as a validation failure.
+        let args_schema = match &parsed.args_schema {
+            None => None,
+            Some(ArgsSchemaSource::Inline(value)) => {
+                Some(Schema::compile(value.clone()).map_err(|e| {
+                    anyhow::anyhow!("tool '{}' has an invalid inline args_schema: {e}", parsed.name)
+                })?)
+            }
+            Some(ArgsSchemaSource::Path(rel_path)) => {
+                let schema_path = tool_dir.join(rel_path);
+                let content = std::fs::read_to_string(&schema_path).map_err(|e| {
+                    anyhow::anyhow!("failed to read args_schema {}: {e}", schema_path.display())
+                })?;
+                let value: serde_json::Value = serde_json::from_str(&content).map_err(|e| {
+                    anyhow::anyhow!("failed to parse args_schema {}: {e}", schema_path.display())
+                })?;
+                Some(Schema::compile(value).map_err(|e| {
+                    anyhow::anyhow!("tool '{}' has an invalid args_schema: {e}", parsed.name)
+                })?)
+            }
+        };
+
+        // synth-1146: same compile-at-load treatment as args_schema above.
+        let output_schema = match &parsed.output_schema {
+            None => None,
+            Some(ArgsSchemaSource::Inline(value)) => {
+                Some(Schema::compile(value.clone()).map_err(|e| {
+                    anyhow::anyhow!("tool '{}' has an invalid inline output_schema: {e}", parsed.name)
+                })?)
+            }
+            Some(ArgsSchemaSource::Path(rel_path)) => {
+                let schema_path = tool_dir.join(rel_path);
+                let content = std::fs::read_to_string(&schema_path).map_err(|e| {
+                    anyhow::anyhow!("failed to read output_schema {}: {e}", schema_path.display())
+                })?;
+                let value: serde_json::Value = serde_json::from_str(&content).map_err(|e| {
+                    anyhow::anyhow!("failed to parse output_schema {}: {e}", schema_path.display())
+                })?;
+                Some(Schema::compile(value).map_err(|e| {
+                    anyhow::anyhow!("tool '{}' has an invalid output_schema: {e}", parsed.name)
+                })?)
+            }
+        };
+
+        if parsed.wasm.is_none() && parsed.cwasm.is_none() {
+            anyhow::bail!("tool '{}' declares neither wasm nor cwasm", parsed.name);
+        }
+        let source_wasm_path = parsed.wasm.as_deref().map(|w| tool_dir.join(w));
+        let cwasm_path = parsed.cwasm.as_deref().map(|c| tool_dir.join(c));
+
+        // synth-1143: try the precompiled artifact first when allowed, so
+        // cold-start avoids the Cranelift compile entirely. A deserialize
+        // failure (most commonly an engine-configuration mismatch, since
+        // `Module::deserialize_file` embeds a compatibility fingerprint)
+        // falls back to compiling from `wasm`, exactly like there was no
+        // `cwasm` at all.
+        let (module, wasm_path, wasm_bytes, precompiled) =
+            if let (true, Some(cwasm_path)) = (allow_precompiled, &cwasm_path) {
+                // Safety: `deserialize_file` trusts that the artifact was
+                // produced by `Module::serialize` for a compatible engine —
+                // the embedded fingerprint check makes a mismatched engine
+                // fail cleanly rather than executing corrupted code, but a
+                // maliciously crafted file for a *matching* fingerprint is
+                // still out of scope here, same as any other file this
+                // process is configured to load and trust.
+                match unsafe { Module::deserialize_file(engine, cwasm_path) } {
+                    Ok(module) => {
+                        let wasm_path = source_wasm_path.clone().unwrap_or_else(|| cwasm_path.clone());
+                        let wasm_bytes = std::fs::read(&wasm_path).map_err(|e| {
+                            anyhow::anyhow!("failed to read WASM {}: {e}", wasm_path.display())
+                        })?;
+                        (module, wasm_path, wasm_bytes, true)
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            tool = %parsed.name,
+                            path = %cwasm_path.display(),
+                            error = %e,
+                            "precompiled module failed to deserialize, falling back to compiling from source"
                         );
-                        None
+                        Self::compile_from_wasm(&parsed.name, source_wasm_path.as_deref(), engine)?
                     }
                 }
-            })
-            .collect();
+            } else {
+                Self::compile_from_wasm(&parsed.name, source_wasm_path.as_deref(), engine)?
+            };
+        let content_hash = Self::content_hash(&wasm_bytes, &parsed);
+
+        let version = parsed.version.clone().unwrap_or_else(|| "0.0.0".to_string());
+        let default = parsed.default;
+
+        // synth-1141: signed against the *actual* wasm bytes just read, not
+        // the (possibly absent or stale) declared `sha256` field, so a
+        // signature can't be reused after the wasm changes underneath it.
+        let actual_sha256 = Self::sha256_hex(&wasm_bytes);
+        let signed = parsed.signature.as_deref().is_some_and(|signature_b64| {
+            verify_tool_signature(&parsed.name, &version, &actual_sha256, signature_b64, signing_keys)
+        });
+        if require_signed && !signed {
+            anyhow::bail!(
+                "tool '{}' is unsigned or has an invalid signature and SENTINEL_SIDECAR_REQUIRE_SIGNED_TOOLS is set",
+                parsed.name
+            );
+        }
 
         Ok(ToolMeta {
             name: parsed.name,
+            version,
+            // Resolved once every tool.toml in the directory has been
+            // loaded, in `load_with_errors` — a single file can't know
+            // whether it's the highest semver among its siblings.
+            is_default: default,
             description: parsed.description,
             wasm_path,
+            cwasm_path,
+            precompiled,
             module,
             required_capabilities,
             timeout_ms: parsed.timeout_ms,
             http_allowlist: parsed.http_allowlist,
+            content_hash,
+            sha256: parsed.sha256,
+            args_schema,
+            output_schema,
+            enabled: parsed.enabled,
+            deprecated: parsed.deprecated,
+            signed,
+            aliases: parsed.aliases,
+            invokes: parsed.invokes,
+            metrics: ToolMetrics::default(),
+            kind: ToolKind::Wasm,
         })
     }
 
-    /// Look up a tool by name. Returns None if not registered.
-    pub fn lookup(&self, name: &str) -> Option<&ToolMeta> {
-        self.tools.get(name)
+    /// Compile a Module from `wasm_path`'s source bytes (the pre-synth-1143
+    /// path, and the fallback whenever a `cwasm` artifact can't be used).
+    /// Fails if there's no wasm source at all — a cwasm-only manifest with
+    /// precompiled loading unavailable or disabled has nothing left to
+    /// compile from.
+    fn compile_from_wasm(
+        name: &str,
+        wasm_path: Option<&Path>,
+        engine: &Engine,
+    ) -> anyhow::Result<(Module, PathBuf, Vec<u8>, bool)> {
+        let wasm_path = wasm_path.ok_or_else(|| {
+            anyhow::anyhow!(
+                "tool '{name}' has no wasm source to compile from (its cwasm-only manifest needs \
+                 SENTINEL_SIDECAR_ALLOW_PRECOMPILED=true and a precompiled artifact that matches this engine)"
+            )
+        })?;
+        let wasm_bytes = std::fs::read(wasm_path)
+            .map_err(|e| anyhow::anyhow!("failed to read WASM {}: {e}", wasm_path.display()))?;
+        let module = Module::new(engine, &wasm_bytes)
+            .map_err(|e| anyhow::anyhow!("failed to compile WASM {}: {e}", wasm_path.display()))?;
+        Ok((module, wasm_path.to_path_buf(), wasm_bytes, false))
+    }
+
+    /// Compute the sha256 digest of a WASM file on disk, hex-encoded
+    /// (synth-1134). Used both by `Self::execute`-time integrity checks and
+    /// by tooling that wants to populate a manifest's `sha256` field.
+    pub fn sha256_hex(bytes: &[u8]) -> String {
+        use sha2::Digest;
+        let digest = sha2::Sha256::digest(bytes);
+        digest.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    /// Hash of everything that affects how a tool executes (synth-1132):
+    /// the WASM bytes plus the manifest fields carried into `ToolMeta`.
+    /// Not a security integrity check — just cheap enough to compute on
+    /// every load so [`Self::reload`] can tell "unchanged" from "replaced"
+    /// without diffing byte-for-byte.
+    fn content_hash(wasm_bytes: &[u8], parsed: &ToolToml) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        wasm_bytes.hash(&mut hasher);
+        parsed.capabilities.hash(&mut hasher);
+        parsed.timeout_ms.hash(&mut hasher);
+        parsed.http_allowlist.hash(&mut hasher);
+        parsed.enabled.hash(&mut hasher);
+        parsed.deprecated.hash(&mut hasher);
+        parsed.signature.hash(&mut hasher);
+        parsed.aliases.hash(&mut hasher);
+        parsed.cwasm.hash(&mut hasher);
+        parsed.invokes.hash(&mut hasher);
+        hasher.finish()
     }
 
-    /// Get the number of registered tools.
+    /// Look up a tool by name and, optionally, a specific version
+    /// (synth-1136). `version: None` resolves to the name's default
+    /// version — whichever entry has `is_default` set, which
+    /// [`Self::resolve_defaults`] guarantees exists whenever the name has
+    /// at least one registered version. An unknown name fails with
+    /// [`LookupError::UnknownTool`]; a known name at an unregistered
+    /// version fails with [`LookupError::UnknownVersion`] listing what *is*
+    /// available, so callers can report a useful error instead of a bare
+    /// "not found".
+    ///
+    /// `name` may be either a tool's canonical name or a registered alias
+    /// (synth-1142) — the returned `ToolMeta` always reports its own
+    /// canonical `name`, so callers can tell when a request came in under
+    /// an alias.
+    pub fn lookup(&self, name: &str, version: Option<&str>) -> Result<&ToolMeta, LookupError> {
+        let name = self.aliases.get(name).map(String::as_str).unwrap_or(name);
+        let versions = self.tools.get(name).ok_or(LookupError::UnknownTool)?;
+        match version {
+            None => versions.values().find(|meta| meta.is_default).ok_or(LookupError::UnknownTool),
+            Some(version) => versions.get(version).ok_or_else(|| {
+                let mut available: Vec<String> = versions.keys().cloned().collect();
+                available.sort();
+                LookupError::UnknownVersion { available }
+            }),
+        }
+    }
+
+    /// Get the number of registered (name, version) tool entries.
     pub fn len(&self) -> usize {
-        self.tools.len()
+        self.tools.values().map(|versions| versions.len()).sum()
     }
 
     /// Check if the registry is empty.
     pub fn is_empty(&self) -> bool {
         self.tools.is_empty()
     }
+
+    /// Iterate over every registered tool version's metadata (synth-1133),
+    /// for callers building a summary of the whole registry (e.g. the
+    /// `_list_tools` meta-request) rather than looking up one tool by name.
+    /// A tool with several registered versions (synth-1136) yields one
+    /// entry per version.
+    pub fn tools(&self) -> impl Iterator<Item = &ToolMeta> {
+        self.tools.values().flat_map(|versions| versions.values())
+    }
+
+    /// Pick the default version for every registered tool name
+    /// (synth-1136): an explicit `default = true` wins; otherwise the
+    /// highest-semver version does. Called once per [`Self::load_with_errors`]
+    /// pass, after every tool.toml in the directory has been parsed, since a
+    /// single file can't know whether it's the highest semver among its
+    /// siblings.
+    fn resolve_defaults(tools: &mut HashMap<String, HashMap<String, ToolMeta>>) {
+        for versions in tools.values_mut() {
+            let explicit: Vec<String> = versions
+                .iter()
+                .filter(|(_, meta)| meta.is_default)
+                .map(|(version, _)| version.clone())
+                .collect();
+            let candidates: Vec<String> = if explicit.is_empty() {
+                versions.keys().cloned().collect()
+            } else {
+                explicit
+            };
+            let winner = Self::highest_semver(&candidates);
+            for (version, meta) in versions.iter_mut() {
+                meta.is_default = *version == winner;
+            }
+        }
+    }
+
+    /// Build the alias -> canonical name index (synth-1142), once every
+    /// tool.toml in the directory has registered — a single file can't know
+    /// whether its declared alias collides with a tool name or another
+    /// tool's alias registered from a different file. A collision with a
+    /// real tool name, or with an alias already claimed by a different
+    /// tool, is recorded as a [`LoadIssueKind::Duplicate`] and the alias is
+    /// dropped rather than resolved ambiguously.
+    fn resolve_aliases(registry: &mut Self) {
+        let mut claims: HashMap<String, Vec<String>> = HashMap::new();
+        for versions in registry.tools.values() {
+            for meta in versions.values() {
+                for alias in &meta.aliases {
+                    claims.entry(alias.clone()).or_default().push(meta.name.clone());
+                }
+            }
+        }
+
+        let mut aliases = HashMap::new();
+        for (alias, mut names) in claims {
+            names.sort();
+            names.dedup();
+            if registry.tools.contains_key(&alias) {
+                registry.issues.push(LoadIssue {
+                    path: alias.clone(),
+                    kind: LoadIssueKind::Duplicate,
+                    message: format!("alias '{alias}' collides with an existing tool name"),
+                });
+                continue;
+            }
+            if names.len() > 1 {
+                registry.issues.push(LoadIssue {
+                    path: alias.clone(),
+                    kind: LoadIssueKind::Duplicate,
+                    message: format!(
+                        "alias '{alias}' is claimed by multiple tools: {}",
+                        names.join(", ")
+                    ),
+                });
+                continue;
+            }
+            aliases.insert(alias, names.into_iter().next().unwrap());
+        }
+        registry.aliases = aliases;
+    }
+
+    /// Validate the `invokes` dependency graph (synth-1144), once every
+    /// tool.toml in the directory has registered and aliases have resolved
+    /// — a declared target may itself be an alias. A target that resolves
+    /// to nothing, or a tool that (transitively) invokes itself, is
+    /// recorded as a [`LoadIssueKind::InvalidDependency`] rather than
+    /// failing the whole load, consistent with how other cross-file
+    /// problems (duplicate names, ambiguous aliases) are surfaced.
+    fn resolve_invokes(registry: &mut Self) {
+        let mut graph: HashMap<String, Vec<String>> = HashMap::new();
+        let mut missing: Vec<(String, String)> = Vec::new();
+
+        for meta in registry.tools.values().flat_map(|versions| versions.values()) {
+            if meta.invokes.is_empty() {
+                continue;
+            }
+            let mut resolved = Vec::new();
+            for target in &meta.invokes {
+                if registry.tools.contains_key(target) {
+                    resolved.push(target.clone());
+                } else if let Some(canonical) = registry.aliases.get(target) {
+                    resolved.push(canonical.clone());
+                } else {
+                    missing.push((meta.name.clone(), target.clone()));
+                }
+            }
+            graph.entry(meta.name.clone()).or_default().extend(resolved);
+        }
+
+        for (tool, target) in missing {
+            registry.issues.push(LoadIssue {
+                path: tool.clone(),
+                kind: LoadIssueKind::InvalidDependency,
+                message: format!(
+                    "tool '{tool}' declares invokes = [\"{target}\"], but no such tool is registered"
+                ),
+            });
+        }
+
+        for name in Self::find_invoke_cycles(&graph) {
+            registry.issues.push(LoadIssue {
+                path: name.clone(),
+                kind: LoadIssueKind::InvalidDependency,
+                message: format!("tool '{name}' is part of an invokes dependency cycle"),
+            });
+        }
+    }
+
+    /// DFS-based cycle detection over the `invokes` dependency graph
+    /// (synth-1144), returning every tool name that participates in at
+    /// least one cycle, sorted. A tool that (transitively) invokes itself
+    /// can never safely resolve a finite capability closure, so this must
+    /// be caught at load time rather than surfacing as a runtime recursion
+    /// limit once `InvokeTool` exists.
+    fn find_invoke_cycles(graph: &HashMap<String, Vec<String>>) -> Vec<String> {
+        enum Mark {
+            Visiting,
+            Done,
+        }
+
+        fn visit<'a>(
+            node: &'a str,
+            graph: &'a HashMap<String, Vec<String>>,
+            marks: &mut HashMap<&'a str, Mark>,
+            stack: &mut Vec<&'a str>,
+            cyclic: &mut std::collections::HashSet<String>,
+        ) {
+            match marks.get(node) {
+                Some(Mark::Done) => return,
+                Some(Mark::Visiting) => {
+                    if let Some(pos) = stack.iter().position(|n| *n == node) {
+                        for n in &stack[pos..] {
+                            cyclic.insert((*n).to_string());
+                        }
+                    }
+                    return;
+                }
+                None => {}
+            }
+            marks.insert(node, Mark::Visiting);
+            stack.push(node);
+            if let Some(targets) = graph.get(node) {
+                for target in targets {
+                    visit(target, graph, marks, stack, cyclic);
+                }
+            }
+            stack.pop();
+            marks.insert(node, Mark::Done);
+        }
+
+        let mut marks = HashMap::new();
+        let mut cyclic = std::collections::HashSet::new();
+        for name in graph.keys() {
+            let mut stack = Vec::new();
+            visit(name, graph, &mut marks, &mut stack, &mut cyclic);
+        }
+        let mut cyclic: Vec<String> = cyclic.into_iter().collect();
+        cyclic.sort();
+        cyclic
+    }
+
+    /// The highest-semver string among `versions`, falling back to the
+    /// first entry if none parse — an unparsable version shouldn't panic or
+    /// silently drop the tool, just lose the tiebreak against anything that
+    /// does parse.
+    fn highest_semver(versions: &[String]) -> String {
+        versions
+            .iter()
+            .max_by_key(|v| semver::Version::parse(v).ok())
+            .cloned()
+            .unwrap_or_else(|| versions[0].clone())
+    }
+
+    /// Load-with-diagnostics shared by [`Self::load`] and [`Self::reload`]:
+    /// same directory scan as `load`, but also collects every per-file
+    /// error instead of only logging it, so a reload can report them back
+    /// over the wire.
+    fn load_with_errors(
+        tool_dir: &Path,
+        engine: &Engine,
+        require_hash: bool,
+        signing_keys: &[Vec<u8>],
+        require_signed: bool,
+        allow_precompiled: bool,
+    ) -> anyhow::Result<(Self, Vec<String>)> {
+        let mut registry = Self::new();
+
+        if !tool_dir.exists() {
+            tracing::warn!(path = %tool_dir.display(), "tool directory does not exist, starting with empty registry");
+            return Ok((registry, Vec::new()));
+        }
+
+        let entries = std::fs::read_dir(tool_dir)
+            .map_err(|e| anyhow::anyhow!("failed to read tool dir {}: {e}", tool_dir.display()))?;
+
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+
+            // synth-1137: a subdirectory with its own tool.toml is a
+            // directory-per-tool layout entry — wasm (and args_schema file
+            // paths) resolve relative to that subdirectory, not tool_dir.
+            if path.is_dir() {
+                let sub_toml = path.join("tool.toml");
+                if sub_toml.is_file() {
+                    Self::load_and_insert(
+                        &mut registry, &sub_toml, &path, engine, require_hash, signing_keys,
+                        require_signed, allow_precompiled, "directory",
+                    );
+                }
+                continue;
+            }
+
+            if path.extension().and_then(|e| e.to_str()) == Some("toml") {
+                Self::load_and_insert(
+                    &mut registry, &path, tool_dir, engine, require_hash, signing_keys,
+                    require_signed, allow_precompiled, "flat",
+                );
+            }
+        }
+
+        Self::resolve_defaults(&mut registry.tools);
+        Self::resolve_aliases(&mut registry);
+        Self::resolve_invokes(&mut registry);
+
+        let errors = registry.issues.iter().map(|issue| issue.to_string()).collect();
+        Ok((registry, errors))
+    }
+
+    /// Parse one tool.toml and insert it into `registry`, recording a load
+    /// or duplicate-version issue (synth-1139) instead of returning early —
+    /// so one bad manifest doesn't stop the rest of the directory scan
+    /// (shared by both the flat and directory-per-tool layouts, synth-1137).
+    /// `layout` is purely for the log line, so operators can tell at a
+    /// glance which layout a given tool came from.
+    #[allow(clippy::too_many_arguments)]
+    fn load_and_insert(
+        registry: &mut Self,
+        toml_path: &Path,
+        tool_dir: &Path,
+        engine: &Engine,
+        require_hash: bool,
+        signing_keys: &[Vec<u8>],
+        require_signed: bool,
+        allow_precompiled: bool,
+        layout: &str,
+    ) {
+        match Self::load_tool_toml(
+            toml_path, tool_dir, engine, require_hash, signing_keys, require_signed, allow_precompiled,
+        ) {
+            Ok(meta) => {
+                let versions = registry.tools.entry(meta.name.clone()).or_default();
+                if versions.contains_key(&meta.version) {
+                    let message = format!(
+                        "tool '{}' version '{}' is already registered from another file",
+                        meta.name, meta.version
+                    );
+                    tracing::warn!(path = %toml_path.display(), error = %message, "failed to load tool");
+                    registry.issues.push(LoadIssue {
+                        path: toml_path.display().to_string(),
+                        kind: LoadIssueKind::Duplicate,
+                        message,
+                    });
+                } else {
+                    tracing::info!(
+                        tool = %meta.name,
+                        version = %meta.version,
+                        path = %toml_path.display(),
+                        layout,
+                        "loaded tool"
+                    );
+                    versions.insert(meta.version.clone(), meta);
+                }
+            }
+            Err(e) => {
+                let message = e.to_string();
+                tracing::warn!(path = %toml_path.display(), error = %message, "failed to load tool");
+                registry.issues.push(LoadIssue {
+                    path: toml_path.display().to_string(),
+                    kind: classify_load_error(&message),
+                    message,
+                });
+            }
+        }
+    }
+
+    /// Rebuild the registry from `tool_dir` and diff it against `previous`
+    /// (synth-1132), for hot-reloading without restarting the sidecar or
+    /// dropping active connections. Any execution already holding an
+    /// `Arc<ToolRegistry>` snapshot of `previous` keeps using it
+    /// undisturbed — only the caller's swap of the shared handle affects
+    /// what *new* requests see.
+    pub fn reload(
+        tool_dir: &Path,
+        engine: &Engine,
+        previous: &ToolRegistry,
+        require_hash: bool,
+        signing_keys: &[Vec<u8>],
+        require_signed: bool,
+        allow_precompiled: bool,
+    ) -> anyhow::Result<(Self, ReloadReport)> {
+        let (mut registry, errors) = Self::load_with_errors(
+            tool_dir, engine, require_hash, signing_keys, require_signed, allow_precompiled,
+        )?;
+
+        // synth-1145: a tool that still exists after the reload keeps its
+        // usage history rather than resetting to zero — only genuinely new
+        // (name, version) pairs start from a blank counter.
+        for (name, versions) in registry.tools.iter_mut() {
+            if let Some(prev_versions) = previous.tools.get(name) {
+                for (version, meta) in versions.iter_mut() {
+                    if let Some(prev_meta) = prev_versions.get(version) {
+                        meta.metrics.carry_forward(&prev_meta.metrics);
+                    }
+                }
+            }
+        }
+
+        // Tools with a single version on both sides of the reload are
+        // reported by bare name, same as before synth-1136 introduced
+        // versioning — only a genuinely multi-version tool needs the
+        // "name@version" form to say *which* version changed.
+        let mut added: Vec<String> = Vec::new();
+        let mut removed: Vec<String> = Vec::new();
+        let mut changed: Vec<String> = Vec::new();
+
+        for (name, versions) in &registry.tools {
+            let prev_versions = previous.tools.get(name);
+            let single = versions.len() == 1 && prev_versions.is_none_or(|p| p.len() <= 1);
+            for (version, meta) in versions {
+                let label = if single { name.clone() } else { format!("{name}@{version}") };
+                match prev_versions.and_then(|p| p.get(version)) {
+                    None => added.push(label),
+                    Some(prev_meta) => {
+                        if prev_meta.content_hash != meta.content_hash {
+                            changed.push(label);
+                        }
+                    }
+                }
+            }
+        }
+
+        for (name, prev_versions) in &previous.tools {
+            let new_versions = registry.tools.get(name);
+            let single = prev_versions.len() == 1 && new_versions.is_none_or(|n| n.len() <= 1);
+            for version in prev_versions.keys() {
+                if new_versions.is_none_or(|n| !n.contains_key(version)) {
+                    let label = if single { name.clone() } else { format!("{name}@{version}") };
+                    removed.push(label);
+                }
+            }
+        }
+
+        added.sort();
+        removed.sort();
+        changed.sort();
+
+        Ok((registry, ReloadReport { added, removed, changed, errors }))
+    }
+}
+
+/// Outcome of [`ToolRegistry::reload`] (synth-1132): which tool names were
+/// added, removed, or replaced with different content, plus any per-file
+/// parse/compile errors hit along the way. Reported back on the
+/// `{"reload": "registry"}` control message's Response and logged.
+#[derive(Debug, Default)]
+pub struct ReloadReport {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_tool_toml_rejects_unknown_capability() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_bad_capability");
+        std::fs::create_dir_all(&tmp).unwrap();
+        let toml_path = tmp.join("tool.toml");
+        std::fs::write(
+            &toml_path,
+            r#"
+name = "bad_tool"
+description = "typo in capabilities"
+wasm = "bad_tool.wasm"
+capabilities = ["shell_execute"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        match ToolRegistry::load_tool_toml(&toml_path, &tmp, &engine, false, &[], false, false) {
+            Err(e) => assert!(e.to_string().contains("shell_execute")),
+            Ok(_) => panic!("expected an unknown-capability error"),
+        }
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    const MINIMAL_WASM: &[u8] = &[0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+
+    #[test]
+    fn test_sha256_hex_matches_known_digest() {
+        // sha256("") — a well-known test vector.
+        assert_eq!(
+            ToolRegistry::sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_load_tool_toml_records_sha256_without_verifying_it() {
+        // synth-1134: load_tool_toml just records whatever sha256 the
+        // manifest declares — mismatch detection happens later, at
+        // execution time in SandboxEngine::execute, not at load time.
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_sha256_recorded");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("tool.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+capabilities = ["read_file"]
+sha256 = "deadbeef"
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let meta = ToolRegistry::load_tool_toml(&tmp.join("tool.toml"), &tmp, &engine, false, &[], false, false).unwrap();
+        assert_eq!(meta.sha256.as_deref(), Some("deadbeef"));
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_require_hash_rejects_manifest_without_sha256() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_require_hash");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("tool.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        match ToolRegistry::load_tool_toml(&tmp.join("tool.toml"), &tmp, &engine, true, &[], false, false) {
+            Err(e) => assert!(e.to_string().contains("sha256")),
+            Ok(_) => panic!("expected strict mode to reject a hashless manifest"),
+        }
+        // Non-strict mode still accepts it.
+        assert!(ToolRegistry::load_tool_toml(&tmp.join("tool.toml"), &tmp, &engine, false, &[], false, false).is_ok());
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_load_tool_toml_compiles_inline_args_schema() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_inline_schema");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("tool.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+capabilities = ["read_file"]
+
+[args_schema]
+type = "object"
+required = ["path"]
+
+[args_schema.properties.path]
+type = "string"
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let meta = ToolRegistry::load_tool_toml(&tmp.join("tool.toml"), &tmp, &engine, false, &[], false, false).unwrap();
+        let schema = meta.args_schema.expect("args_schema should have compiled");
+        assert!(!schema.validate(&serde_json::json!({})).is_empty());
+        assert!(schema.validate(&serde_json::json!({ "path": "/tmp/x" })).is_empty());
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_load_tool_toml_reads_args_schema_from_file() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_schema_file");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("t.schema.json"),
+            r#"{"type": "object", "required": ["path"]}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            tmp.join("tool.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+capabilities = ["read_file"]
+args_schema = "t.schema.json"
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let meta = ToolRegistry::load_tool_toml(&tmp.join("tool.toml"), &tmp, &engine, false, &[], false, false).unwrap();
+        let schema = meta.args_schema.expect("args_schema should have loaded from file");
+        assert!(!schema.validate(&serde_json::json!({})).is_empty());
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_load_tool_toml_rejects_invalid_args_schema() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_bad_schema");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("tool.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+capabilities = ["read_file"]
+args_schema = 5
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        match ToolRegistry::load_tool_toml(&tmp.join("tool.toml"), &tmp, &engine, false, &[], false, false) {
+            Err(e) => assert!(e.to_string().contains("args_schema")),
+            Ok(_) => panic!("expected a non-object inline args_schema to fail the load"),
+        }
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_load_tool_toml_compiles_inline_output_schema() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_inline_output_schema");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("tool.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+capabilities = ["read_file"]
+
+[output_schema]
+type = "object"
+required = ["ok"]
+
+[output_schema.properties.ok]
+type = "boolean"
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let meta = ToolRegistry::load_tool_toml(&tmp.join("tool.toml"), &tmp, &engine, false, &[], false, false).unwrap();
+        let schema = meta.output_schema.expect("output_schema should have compiled");
+        assert!(!schema.validate(&serde_json::json!({})).is_empty());
+        assert!(schema.validate(&serde_json::json!({ "ok": true })).is_empty());
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_load_tool_toml_reads_output_schema_from_file() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_output_schema_file");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("t.output.schema.json"),
+            r#"{"type": "object", "required": ["ok"]}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            tmp.join("tool.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+capabilities = ["read_file"]
+output_schema = "t.output.schema.json"
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let meta = ToolRegistry::load_tool_toml(&tmp.join("tool.toml"), &tmp, &engine, false, &[], false, false).unwrap();
+        let schema = meta.output_schema.expect("output_schema should have loaded from file");
+        assert!(!schema.validate(&serde_json::json!({})).is_empty());
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_load_tool_toml_rejects_invalid_output_schema() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_bad_output_schema");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("tool.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+capabilities = ["read_file"]
+output_schema = 5
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        match ToolRegistry::load_tool_toml(&tmp.join("tool.toml"), &tmp, &engine, false, &[], false, false) {
+            Err(e) => assert!(e.to_string().contains("output_schema")),
+            Ok(_) => panic!("expected a non-object inline output_schema to fail the load"),
+        }
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_reload_reports_added_removed_and_changed_tools() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_reload");
+        std::fs::create_dir_all(&tmp).unwrap();
+        let engine = Engine::default();
+
+        let write_wasm_module = |name: &str| {
+            let wasm_path = tmp.join(format!("{name}.wasm"));
+            // Smallest valid WASM module: `(module)`.
+            std::fs::write(&wasm_path, &[0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00]).unwrap();
+        };
+        let write_tool_toml = |name: &str, capabilities: &str| {
+            std::fs::write(
+                tmp.join(format!("{name}.toml")),
+                format!(
+                    r#"
+name = "{name}"
+description = "test tool"
+wasm = "{name}.wasm"
+capabilities = [{capabilities}]
+"#
+                ),
+            )
+            .unwrap();
+        };
+
+        write_wasm_module("kept");
+        write_wasm_module("removed_later");
+        write_tool_toml("kept", r#""read_file""#);
+        write_tool_toml("removed_later", r#""read_file""#);
+        let before = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+        assert_eq!(before.len(), 2);
+
+        std::fs::remove_file(tmp.join("removed_later.toml")).unwrap();
+        std::fs::remove_file(tmp.join("removed_later.wasm")).unwrap();
+        write_tool_toml("kept", r#""read_file", "write_file""#); // content change
+        write_wasm_module("added_later");
+        write_tool_toml("added_later", r#""shell_exec""#);
+
+        let (after, report) = ToolRegistry::reload(&tmp, &engine, &before, false, &[], false, false).unwrap();
+        assert_eq!(after.len(), 2);
+        assert_eq!(report.added, vec!["added_later".to_string()]);
+        assert_eq!(report.removed, vec!["removed_later".to_string()]);
+        assert_eq!(report.changed, vec!["kept".to_string()]);
+        assert!(report.errors.is_empty());
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    fn write_versioned_tool(tmp: &Path, name: &str, version: &str, extra_toml: &str) {
+        let wasm_path = tmp.join(format!("{name}-{version}.wasm"));
+        std::fs::write(&wasm_path, MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join(format!("{name}-{version}.toml")),
+            format!(
+                r#"
+name = "{name}"
+version = "{version}"
+description = "test tool"
+wasm = "{name}-{version}.wasm"
+capabilities = ["read_file"]
+{extra_toml}
+"#
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_lookup_defaults_to_highest_semver_version() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_semver_default");
+        std::fs::create_dir_all(&tmp).unwrap();
+        write_versioned_tool(&tmp, "http_fetch", "1.0.0", "");
+        write_versioned_tool(&tmp, "http_fetch", "1.2.0", "");
+        write_versioned_tool(&tmp, "http_fetch", "1.10.0", "");
+
+        let engine = Engine::default();
+        let registry = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+        assert_eq!(registry.len(), 3);
+
+        // 1.10.0 is the highest semver, not the lexicographically-last "1.2.0".
+        let default = registry.lookup("http_fetch", None).unwrap();
+        assert_eq!(default.version, "1.10.0");
+        assert!(default.is_default);
+
+        let pinned = registry.lookup("http_fetch", Some("1.0.0")).unwrap();
+        assert_eq!(pinned.version, "1.0.0");
+        assert!(!pinned.is_default);
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_lookup_explicit_default_overrides_semver_ordering() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_explicit_default");
+        std::fs::create_dir_all(&tmp).unwrap();
+        write_versioned_tool(&tmp, "http_fetch", "1.0.0", "default = true");
+        write_versioned_tool(&tmp, "http_fetch", "2.0.0", "");
+
+        let engine = Engine::default();
+        let registry = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+
+        let default = registry.lookup("http_fetch", None).unwrap();
+        assert_eq!(default.version, "1.0.0");
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_lookup_unknown_version_lists_available_versions() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_unknown_version");
+        std::fs::create_dir_all(&tmp).unwrap();
+        write_versioned_tool(&tmp, "http_fetch", "1.0.0", "");
+        write_versioned_tool(&tmp, "http_fetch", "2.0.0", "");
+
+        let engine = Engine::default();
+        let registry = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+
+        match registry.lookup("http_fetch", Some("9.9.9")) {
+            Err(LookupError::UnknownVersion { available }) => {
+                assert_eq!(available, vec!["1.0.0".to_string(), "2.0.0".to_string()]);
+            }
+            Ok(_) => panic!("expected UnknownVersion"),
+            Err(LookupError::UnknownTool) => panic!("expected UnknownVersion, got UnknownTool"),
+        }
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_lookup_unknown_tool_name() {
+        let registry = ToolRegistry::new();
+        match registry.lookup("nope", None) {
+            Err(LookupError::UnknownTool) => {}
+            Ok(_) => panic!("expected UnknownTool"),
+            Err(LookupError::UnknownVersion { .. }) => panic!("expected UnknownTool, got UnknownVersion"),
+        }
+    }
+
+    #[test]
+    fn test_unversioned_tool_defaults_to_itself() {
+        // A manifest with no `version` field is registered as a single
+        // implicit "0.0.0" version and resolves as its own default.
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_unversioned_default");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("tool.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let registry = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+        let meta = registry.lookup("t", None).unwrap();
+        assert_eq!(meta.version, "0.0.0");
+        assert!(meta.is_default);
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_duplicate_version_for_same_tool_is_reported_as_a_load_error() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_duplicate_version");
+        std::fs::create_dir_all(&tmp).unwrap();
+        write_versioned_tool(&tmp, "http_fetch", "1.0.0", "");
+        // Same name and version, second file — must not silently overwrite.
+        let wasm_path = tmp.join("http_fetch-1.0.0-dup.wasm");
+        std::fs::write(&wasm_path, MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("http_fetch-1.0.0-dup.toml"),
+            r#"
+name = "http_fetch"
+version = "1.0.0"
+description = "duplicate"
+wasm = "http_fetch-1.0.0-dup.wasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let (registry, errors) = ToolRegistry::load_with_errors(&tmp, &engine, false, &[], false, false).unwrap();
+        assert_eq!(registry.len(), 1);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("already registered"));
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_reload_reports_versioned_changes_with_name_at_version_label() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_reload_versioned");
+        std::fs::create_dir_all(&tmp).unwrap();
+        let engine = Engine::default();
+
+        write_versioned_tool(&tmp, "http_fetch", "1.0.0", "");
+        let before = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+
+        write_versioned_tool(&tmp, "http_fetch", "2.0.0", "");
+        let (after, report) = ToolRegistry::reload(&tmp, &engine, &before, false, &[], false, false).unwrap();
+
+        assert_eq!(after.len(), 2);
+        assert_eq!(report.added, vec!["http_fetch@2.0.0".to_string()]);
+        assert!(report.removed.is_empty());
+        assert!(report.changed.is_empty());
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_load_mixes_flat_and_directory_per_tool_layouts() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_mixed_layout");
+        std::fs::create_dir_all(&tmp).unwrap();
+
+        // Flat layout: tool.toml and wasm side by side in tool_dir itself.
+        std::fs::write(tmp.join("flat_tool.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("flat_tool.toml"),
+            r#"
+name = "flat_tool"
+description = "flat layout"
+wasm = "flat_tool.wasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        // Directory-per-tool layout: subdirectory holding its own tool.toml
+        // and wasm, both resolved relative to that subdirectory.
+        let subdir = tmp.join("dir_tool");
+        std::fs::create_dir_all(&subdir).unwrap();
+        std::fs::write(subdir.join("dir_tool.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            subdir.join("tool.toml"),
+            r#"
+name = "dir_tool"
+description = "directory layout"
+wasm = "dir_tool.wasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let registry = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+        assert_eq!(registry.len(), 2);
+        assert!(registry.lookup("flat_tool", None).is_ok());
+        assert!(registry.lookup("dir_tool", None).is_ok());
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_load_reports_duplicate_name_across_flat_and_directory_layouts() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_duplicate_layout");
+        std::fs::create_dir_all(&tmp).unwrap();
+
+        std::fs::write(tmp.join("dup.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("dup.toml"),
+            r#"
+name = "dup"
+description = "flat copy"
+wasm = "dup.wasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        let subdir = tmp.join("dup");
+        std::fs::create_dir_all(&subdir).unwrap();
+        std::fs::write(subdir.join("dup.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            subdir.join("tool.toml"),
+            r#"
+name = "dup"
+description = "directory copy"
+wasm = "dup.wasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let (registry, errors) = ToolRegistry::load_with_errors(&tmp, &engine, false, &[], false, false).unwrap();
+        assert_eq!(registry.len(), 1);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("already registered"));
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_tool_defaults_to_enabled_with_no_deprecation() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_default_enabled");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("tool.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let meta = ToolRegistry::load_tool_toml(&tmp.join("tool.toml"), &tmp, &engine, false, &[], false, false).unwrap();
+        assert!(meta.enabled);
+        assert!(meta.deprecated.is_none());
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_tool_toml_parses_enabled_and_deprecated_fields() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_enabled_deprecated");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("tool.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+capabilities = ["read_file"]
+enabled = false
+deprecated = "use t_v2 instead"
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let meta = ToolRegistry::load_tool_toml(&tmp.join("tool.toml"), &tmp, &engine, false, &[], false, false).unwrap();
+        assert!(!meta.enabled);
+        assert_eq!(meta.deprecated.as_deref(), Some("use t_v2 instead"));
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_issues_reports_duplicate_name_and_version() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_issues_duplicate");
+        std::fs::create_dir_all(&tmp).unwrap();
+        write_versioned_tool(&tmp, "http_fetch", "1.0.0", "");
+        std::fs::write(tmp.join("dup.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("dup.toml"),
+            r#"
+name = "http_fetch"
+version = "1.0.0"
+description = "dup"
+wasm = "dup.wasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let registry = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+        assert_eq!(registry.issues().len(), 1);
+        assert_eq!(registry.issues()[0].kind, LoadIssueKind::Duplicate);
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_issues_reports_parse_error() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_issues_parse");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("bad.toml"), "not valid = = toml").unwrap();
+
+        let engine = Engine::default();
+        let registry = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+        assert_eq!(registry.issues().len(), 1);
+        assert_eq!(registry.issues()[0].kind, LoadIssueKind::ParseError);
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_issues_reports_missing_wasm() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_issues_missing_wasm");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(
+            tmp.join("t.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "does_not_exist.wasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let registry = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+        assert_eq!(registry.issues().len(), 1);
+        assert_eq!(registry.issues()[0].kind, LoadIssueKind::MissingWasm);
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_issues_reports_invalid_capabilities() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_issues_bad_caps");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("t.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+capabilities = ["shell_execute"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let registry = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+        assert_eq!(registry.issues().len(), 1);
+        assert_eq!(registry.issues()[0].kind, LoadIssueKind::InvalidCapabilities);
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_clean_directory_has_no_issues() {
+        let (tmp, registry) = temp_registry_with_hashed_tool_helper();
+        assert!(registry.issues().is_empty());
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    fn temp_registry_with_hashed_tool_helper() -> (std::path::PathBuf, ToolRegistry) {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_issues_clean");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("t.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+        let engine = Engine::default();
+        let registry = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+        (tmp, registry)
+    }
+
+    /// Generate an Ed25519 keypair for signature tests, returning the raw
+    /// public key bytes (as `SENTINEL_SIDECAR_TOOL_SIGNING_KEYS` would carry
+    /// them, decoded) alongside the parsed keypair for signing.
+    fn test_keypair() -> (Vec<u8>, ring::signature::Ed25519KeyPair) {
+        use ring::signature::KeyPair;
+        let rng = ring::rand::SystemRandom::new();
+        let pkcs8 = ring::signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let keypair = ring::signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let public_key = keypair.public_key().as_ref().to_vec();
+        (public_key, keypair)
+    }
+
+    fn write_signed_tool(
+        tmp: &Path,
+        name: &str,
+        keypair: &ring::signature::Ed25519KeyPair,
+        tamper: bool,
+    ) {
+        let wasm_path = tmp.join(format!("{name}.wasm"));
+        std::fs::write(&wasm_path, MINIMAL_WASM).unwrap();
+        let sha256 = ToolRegistry::sha256_hex(MINIMAL_WASM);
+        let payload = signing_payload(name, "0.0.0", &sha256);
+        let signature = keypair.sign(&payload);
+        let mut signature_b64 = base64::engine::general_purpose::STANDARD.encode(signature.as_ref());
+        if tamper {
+            // Flip the first base64 character so the decoded bytes (and thus
+            // the signature) no longer match what was actually signed.
+            let mut chars: Vec<char> = signature_b64.chars().collect();
+            chars[0] = if chars[0] == 'A' { 'B' } else { 'A' };
+            signature_b64 = chars.into_iter().collect();
+        }
+        std::fs::write(
+            tmp.join(format!("{name}.toml")),
+            format!(
+                r#"
+name = "{name}"
+description = "test tool"
+wasm = "{name}.wasm"
+capabilities = ["read_file"]
+signature = "{signature_b64}"
+"#
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_valid_signature_verifies_against_configured_key() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_signature_valid");
+        std::fs::create_dir_all(&tmp).unwrap();
+        let (public_key, keypair) = test_keypair();
+        write_signed_tool(&tmp, "signed_tool", &keypair, false);
+
+        let engine = Engine::default();
+        let meta = ToolRegistry::load_tool_toml(
+            &tmp.join("signed_tool.toml"),
+            &tmp,
+            &engine,
+            false,
+            &[public_key],
+            false,
+            false,
+        )
+        .unwrap();
+        assert!(meta.signed);
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_tampered_signature_does_not_verify() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_signature_tampered");
+        std::fs::create_dir_all(&tmp).unwrap();
+        let (public_key, keypair) = test_keypair();
+        write_signed_tool(&tmp, "signed_tool", &keypair, true);
+
+        let engine = Engine::default();
+        let meta = ToolRegistry::load_tool_toml(
+            &tmp.join("signed_tool.toml"),
+            &tmp,
+            &engine,
+            false,
+            &[public_key],
+            false,
+            false,
+        )
+        .unwrap();
+        assert!(!meta.signed);
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_signature_from_unknown_key_does_not_verify() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_signature_unknown_key");
+        std::fs::create_dir_all(&tmp).unwrap();
+        let (_signing_public_key, keypair) = test_keypair();
+        let (other_public_key, _other_keypair) = test_keypair();
+        write_signed_tool(&tmp, "signed_tool", &keypair, false);
+
+        let engine = Engine::default();
+        let meta = ToolRegistry::load_tool_toml(
+            &tmp.join("signed_tool.toml"),
+            &tmp,
+            &engine,
+            false,
+            &[other_public_key],
+            false,
+            false,
+        )
+        .unwrap();
+        assert!(!meta.signed);
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_unsigned_manifest_is_not_signed() {
+        let (tmp, registry) = temp_registry_with_hashed_tool_helper();
+        let meta = registry.lookup("t", None).unwrap();
+        assert!(!meta.signed);
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_require_signed_rejects_unsigned_manifest() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_require_signed_unsigned");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("tool.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        match ToolRegistry::load_tool_toml(&tmp.join("tool.toml"), &tmp, &engine, false, &[], true, false) {
+            Err(e) => assert!(e.to_string().contains("unsigned or has an invalid signature")),
+            Ok(_) => panic!("expected strict mode to reject an unsigned manifest"),
+        }
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_require_signed_accepts_validly_signed_manifest() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_require_signed_valid");
+        std::fs::create_dir_all(&tmp).unwrap();
+        let (public_key, keypair) = test_keypair();
+        write_signed_tool(&tmp, "signed_tool", &keypair, false);
+
+        let engine = Engine::default();
+        let meta = ToolRegistry::load_tool_toml(
+            &tmp.join("signed_tool.toml"),
+            &tmp,
+            &engine,
+            false,
+            &[public_key],
+            true,
+            false,
+        )
+        .unwrap();
+        assert!(meta.signed);
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_lookup_resolves_alias_to_canonical_tool() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_alias_lookup");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("tool.toml"),
+            r#"
+name = "run_command"
+description = "test"
+wasm = "t.wasm"
+capabilities = ["read_file"]
+aliases = ["shell_exec"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let registry = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+
+        let via_alias = registry.lookup("shell_exec", None).unwrap();
+        assert_eq!(via_alias.name, "run_command");
+        let via_canonical = registry.lookup("run_command", None).unwrap();
+        assert_eq!(via_canonical.name, "run_command");
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_alias_colliding_with_real_tool_name_is_reported_and_dropped() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_alias_collides_with_tool");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("a.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("a.toml"),
+            r#"
+name = "run_command"
+description = "test"
+wasm = "a.wasm"
+capabilities = ["read_file"]
+aliases = ["shell_exec"]
+"#,
+        )
+        .unwrap();
+        std::fs::write(tmp.join("b.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("b.toml"),
+            r#"
+name = "shell_exec"
+description = "still a real tool"
+wasm = "b.wasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let registry = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+
+        // The real "shell_exec" tool wins; the alias is dropped rather than
+        // silently shadowing it.
+        assert_eq!(registry.lookup("shell_exec", None).unwrap().name, "shell_exec");
+        assert_eq!(registry.issues().len(), 1);
+        assert_eq!(registry.issues()[0].kind, LoadIssueKind::Duplicate);
+        assert!(registry.issues()[0].message.contains("collides with an existing tool name"));
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_alias_claimed_by_two_tools_is_reported_and_dropped() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_alias_claimed_twice");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("a.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("a.toml"),
+            r#"
+name = "run_command"
+description = "test"
+wasm = "a.wasm"
+capabilities = ["read_file"]
+aliases = ["legacy_exec"]
+"#,
+        )
+        .unwrap();
+        std::fs::write(tmp.join("b.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("b.toml"),
+            r#"
+name = "shell_exec"
+description = "also claims the alias"
+wasm = "b.wasm"
+capabilities = ["read_file"]
+aliases = ["legacy_exec"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let registry = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+
+        match registry.lookup("legacy_exec", None) {
+            Err(LookupError::UnknownTool) => {}
+            Err(LookupError::UnknownVersion { .. }) => panic!("expected UnknownTool"),
+            Ok(_) => panic!("expected an ambiguous alias to resolve to nothing"),
+        }
+        assert_eq!(registry.issues().len(), 1);
+        assert_eq!(registry.issues()[0].kind, LoadIssueKind::Duplicate);
+        assert!(registry.issues()[0].message.contains("claimed by multiple tools"));
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_issues_reports_unsigned_tool_as_unsigned_kind() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_issues_unsigned");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("t.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let registry = ToolRegistry::load(&tmp, &engine, false, &[], true, false).unwrap();
+        assert_eq!(registry.issues().len(), 1);
+        assert_eq!(registry.issues()[0].kind, LoadIssueKind::Unsigned);
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_precompiled_cwasm_round_trips_within_one_engine() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_cwasm_round_trip");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+
+        let engine = Engine::new(&crate::sandbox::wasm_engine_config()).unwrap();
+        let module = Module::new(&engine, MINIMAL_WASM).unwrap();
+        std::fs::write(tmp.join("t.cwasm"), module.serialize().unwrap()).unwrap();
+        std::fs::write(
+            tmp.join("tool.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+cwasm = "t.cwasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        let meta = ToolRegistry::load_tool_toml(&tmp.join("tool.toml"), &tmp, &engine, false, &[], false, true)
+            .unwrap();
+        assert!(meta.precompiled);
+        assert_eq!(meta.cwasm_path, Some(tmp.join("t.cwasm")));
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_precompiled_cwasm_disabled_falls_back_to_compiling_from_wasm() {
+        // Same manifest as above, but `allow_precompiled: false` — the
+        // cwasm artifact must be ignored entirely, not merely attempted.
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_cwasm_disabled");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+
+        let engine = Engine::new(&crate::sandbox::wasm_engine_config()).unwrap();
+        let module = Module::new(&engine, MINIMAL_WASM).unwrap();
+        std::fs::write(tmp.join("t.cwasm"), module.serialize().unwrap()).unwrap();
+        std::fs::write(
+            tmp.join("tool.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+cwasm = "t.cwasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        let meta = ToolRegistry::load_tool_toml(&tmp.join("tool.toml"), &tmp, &engine, false, &[], false, false)
+            .unwrap();
+        assert!(!meta.precompiled);
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_corrupt_cwasm_falls_back_to_compiling_from_wasm() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_cwasm_corrupt");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("t.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(tmp.join("t.cwasm"), b"not a real cwasm artifact").unwrap();
+        std::fs::write(
+            tmp.join("tool.toml"),
+            r#"
+name = "t"
+description = "test"
+wasm = "t.wasm"
+cwasm = "t.cwasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::new(&crate::sandbox::wasm_engine_config()).unwrap();
+        let meta = ToolRegistry::load_tool_toml(&tmp.join("tool.toml"), &tmp, &engine, false, &[], false, true)
+            .unwrap();
+        assert!(!meta.precompiled);
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_cwasm_only_manifest_requires_allow_precompiled() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_cwasm_only");
+        std::fs::create_dir_all(&tmp).unwrap();
+
+        let engine = Engine::new(&crate::sandbox::wasm_engine_config()).unwrap();
+        let module = Module::new(&engine, MINIMAL_WASM).unwrap();
+        std::fs::write(tmp.join("t.cwasm"), module.serialize().unwrap()).unwrap();
+        std::fs::write(
+            tmp.join("tool.toml"),
+            r#"
+name = "t"
+description = "test"
+cwasm = "t.cwasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        match ToolRegistry::load_tool_toml(&tmp.join("tool.toml"), &tmp, &engine, false, &[], false, false) {
+            Err(e) => assert!(e.to_string().contains("no wasm source to compile from")),
+            Ok(_) => panic!("expected a cwasm-only manifest to fail without allow_precompiled"),
+        }
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_valid_invokes_chain_reports_no_issues() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_invokes_valid_chain");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("a.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("a.toml"),
+            r#"
+name = "orchestrator"
+description = "calls b, which calls c"
+wasm = "a.wasm"
+capabilities = ["invoke_tool"]
+invokes = ["worker"]
+"#,
+        )
+        .unwrap();
+        std::fs::write(tmp.join("b.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("b.toml"),
+            r#"
+name = "worker"
+description = "calls c"
+wasm = "b.wasm"
+capabilities = ["invoke_tool"]
+invokes = ["leaf"]
+"#,
+        )
+        .unwrap();
+        std::fs::write(tmp.join("c.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("c.toml"),
+            r#"
+name = "leaf"
+description = "calls nothing"
+wasm = "c.wasm"
+capabilities = ["read_file"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let registry = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+        assert!(registry.issues().is_empty());
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_invokes_missing_target_is_reported() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_invokes_missing");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("a.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("a.toml"),
+            r#"
+name = "orchestrator"
+description = "calls a tool that doesn't exist"
+wasm = "a.wasm"
+capabilities = ["invoke_tool"]
+invokes = ["nonexistent_tool"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let registry = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+        assert_eq!(registry.issues().len(), 1);
+        assert_eq!(registry.issues()[0].kind, LoadIssueKind::InvalidDependency);
+        assert!(registry.issues()[0].message.contains("nonexistent_tool"));
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
+
+    #[test]
+    fn test_invokes_two_tool_cycle_is_reported() {
+        let tmp = std::env::temp_dir().join("sentinel_test_registry_invokes_cycle");
+        std::fs::create_dir_all(&tmp).unwrap();
+        std::fs::write(tmp.join("a.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("a.toml"),
+            r#"
+name = "ping"
+description = "calls pong"
+wasm = "a.wasm"
+capabilities = ["invoke_tool"]
+invokes = ["pong"]
+"#,
+        )
+        .unwrap();
+        std::fs::write(tmp.join("b.wasm"), MINIMAL_WASM).unwrap();
+        std::fs::write(
+            tmp.join("b.toml"),
+            r#"
+name = "pong"
+description = "calls ping"
+wasm = "b.wasm"
+capabilities = ["invoke_tool"]
+invokes = ["ping"]
+"#,
+        )
+        .unwrap();
+
+        let engine = Engine::default();
+        let registry = ToolRegistry::load(&tmp, &engine, false, &[], false, false).unwrap();
+        let cycle_issues: Vec<_> = registry
+            .issues()
+            .iter()
+            .filter(|i| i.kind == LoadIssueKind::InvalidDependency)
+            .collect();
+        assert_eq!(cycle_issues.len(), 2);
+        assert!(cycle_issues.iter().any(|i| i.path == "ping"));
+        assert!(cycle_issues.iter().any(|i| i.path == "pong"));
+
+        std::fs::remove_dir_all(&tmp).ok();
+    }
 }
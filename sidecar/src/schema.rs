@@ -0,0 +1,191 @@
+//! Minimal JSON Schema subset for validating tool arguments (synth-1135).
+//!
+//! Full JSON Schema is far more than tool.toml authors need — this covers
+//! `type`, `required`, `properties`, `items`, and `enum`, which is enough to
+//! catch the failure mode this exists for: a caller passing the wrong shape
+//! of `args` and getting an opaque WASM exit code back instead of a clear
+//! error before the sandbox ever spins up.
+
+use serde_json::Value;
+
+/// A compiled schema, ready to validate `args` objects against.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    root: Value,
+}
+
+impl Schema {
+    /// Compile a schema from its JSON representation. Fails if `schema`
+    /// isn't a JSON object, since every schema this validator understands
+    /// is keyed by `type`/`properties`/`required`/etc.
+    pub fn compile(schema: Value) -> anyhow::Result<Self> {
+        if !schema.is_object() {
+            anyhow::bail!("args_schema must be a JSON object");
+        }
+        Ok(Self { root: schema })
+    }
+
+    /// Validate `value` against this schema, returning one message per
+    /// violation (empty when valid). Each message is prefixed with the
+    /// JSON-pointer-style path to the offending field, e.g. `/name: ...`.
+    pub fn validate(&self, value: &Value) -> Vec<String> {
+        let mut violations = Vec::new();
+        validate_node(&self.root, value, "", &mut violations);
+        violations
+    }
+}
+
+fn validate_node(schema: &Value, value: &Value, path: &str, violations: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+
+    if let Some(expected) = schema.get("type").and_then(|t| t.as_str()) {
+        if !matches_type(expected, value) {
+            violations.push(format!(
+                "{}: expected type {expected}, got {}",
+                display_path(path),
+                type_name(value)
+            ));
+            // Further structural checks (properties/items) don't make sense
+            // once the top-level type itself is wrong.
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(|e| e.as_array()) {
+        if !allowed.contains(value) {
+            violations.push(format!("{}: value is not one of the allowed enum values", display_path(path)));
+        }
+    }
+
+    if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+        if let Some(obj) = value.as_object() {
+            for name in required {
+                if let Some(name) = name.as_str() {
+                    if !obj.contains_key(name) {
+                        violations.push(format!("{}/{name}: missing required field", display_path(path)));
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+        if let Some(obj) = value.as_object() {
+            for (name, sub_schema) in properties {
+                if let Some(sub_value) = obj.get(name) {
+                    validate_node(sub_schema, sub_value, &format!("{path}/{name}"), violations);
+                }
+            }
+        }
+    }
+
+    if let Some(items_schema) = schema.get("items") {
+        if let Some(array) = value.as_array() {
+            for (i, item) in array.iter().enumerate() {
+                validate_node(items_schema, item, &format!("{path}/{i}"), violations);
+            }
+        }
+    }
+}
+
+fn matches_type(expected: &str, value: &Value) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "null" => value.is_null(),
+        _ => true, // unknown type keywords are ignored rather than rejected
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn display_path(path: &str) -> &str {
+    if path.is_empty() {
+        "/"
+    } else {
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema(json: serde_json::Value) -> Schema {
+        Schema::compile(json).unwrap()
+    }
+
+    #[test]
+    fn compile_rejects_non_object_schema() {
+        assert!(Schema::compile(serde_json::json!("not an object")).is_err());
+    }
+
+    #[test]
+    fn validate_reports_missing_required_field() {
+        let s = schema(serde_json::json!({
+            "type": "object",
+            "required": ["path"],
+            "properties": { "path": { "type": "string" } },
+        }));
+        let violations = s.validate(&serde_json::json!({}));
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].contains("/path"));
+        assert!(violations[0].contains("missing required field"));
+    }
+
+    #[test]
+    fn validate_reports_wrong_type() {
+        let s = schema(serde_json::json!({
+            "type": "object",
+            "properties": { "count": { "type": "integer" } },
+        }));
+        let violations = s.validate(&serde_json::json!({ "count": "five" }));
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].contains("/count"));
+        assert!(violations[0].contains("expected type integer"));
+    }
+
+    #[test]
+    fn validate_passes_well_formed_args() {
+        let s = schema(serde_json::json!({
+            "type": "object",
+            "required": ["path"],
+            "properties": { "path": { "type": "string" }, "recursive": { "type": "boolean" } },
+        }));
+        let violations = s.validate(&serde_json::json!({ "path": "/tmp/x", "recursive": true }));
+        assert!(violations.is_empty());
+    }
+
+    #[test]
+    fn validate_checks_array_items() {
+        let s = schema(serde_json::json!({
+            "type": "array",
+            "items": { "type": "string" },
+        }));
+        let violations = s.validate(&serde_json::json!(["a", 2, "c"]));
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].contains("/1"));
+    }
+
+    #[test]
+    fn validate_checks_enum() {
+        let s = schema(serde_json::json!({ "enum": ["a", "b"] }));
+        assert!(s.validate(&serde_json::json!("a")).is_empty());
+        assert_eq!(s.validate(&serde_json::json!("c")).len(), 1);
+    }
+}
@@ -1,80 +1,491 @@
 //! Shared guest-side helpers for Sentinel WASM tools.
 //!
 //! Provides the IO_BUFFER for host function communication, the host_call
-//! import, and a safe `call_host()` wrapper that handles JSON serialization.
+//! import, and safe `call_host()`/`call_host_typed()` wrappers that handle
+//! JSON serialization. `Op` and `messages` (the request/response structs for
+//! each op) are re-exported from `sentinel-ops` (synth-1187) so this crate
+//! and `host_functions.rs` share exactly one definition of each.
+//!
+//! The wasm-only pieces — the `get_io_buffer`/`alloc_io_buffer` C ABI
+//! exports and the real `host_call` import — are behind the `wasm-guest`
+//! feature (on by default, synth-1201). Everything else (the error types,
+//! `parse_args`/`write_result`, and `MockHost`) builds on any target, so
+//! the host side can depend on this crate with `default-features = false`
+//! for the shared protocol types without pulling in exports meant for an
+//! actual wasm guest module.
 
 use std::cell::UnsafeCell;
+use base64::Engine;
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
 
-/// 1 MiB shared buffer for host function I/O.
+/// Starting size of the shared buffer for host function I/O.
 /// Guest writes request JSON here, calls host_call, then reads response JSON back.
-const IO_BUFFER_SIZE: usize = 1024 * 1024;
+/// Grows on demand (synth-1186) via `alloc_io_buffer` — see [`ensure_io_buffer`].
+const DEFAULT_IO_BUFFER_SIZE: usize = 1024 * 1024;
 
 /// Wrapper to avoid `static mut` deprecation warnings.
 /// Safety: WASM is single-threaded, so UnsafeCell is fine here.
-#[repr(transparent)]
-struct IoBuffer(UnsafeCell<[u8; IO_BUFFER_SIZE]>);
+struct IoBuffer(UnsafeCell<Vec<u8>>);
 unsafe impl Sync for IoBuffer {}
 
-static IO_BUFFER: IoBuffer = IoBuffer(UnsafeCell::new([0u8; IO_BUFFER_SIZE]));
+static IO_BUFFER: IoBuffer = IoBuffer(UnsafeCell::new(Vec::new()));
+
+/// Grows IO_BUFFER to at least `size` bytes (never shrinks, and never below
+/// `DEFAULT_IO_BUFFER_SIZE`) and returns a pointer to it. Shared by
+/// `get_io_buffer`/`alloc_io_buffer` (the host-facing exports) and
+/// `call_host_raw`'s own writes, so both agree on where the buffer lives
+/// after a grow (synth-1186).
+fn ensure_io_buffer(size: usize) -> *mut u8 {
+    let buf = unsafe { &mut *IO_BUFFER.0.get() };
+    let target = size.max(DEFAULT_IO_BUFFER_SIZE);
+    if buf.len() < target {
+        buf.resize(target, 0);
+    }
+    buf.as_mut_ptr()
+}
+
+/// Current capacity of IO_BUFFER, for `call_host_raw`'s own size checks.
+fn io_buffer_capacity() -> usize {
+    unsafe { (*IO_BUFFER.0.get()).len() }.max(DEFAULT_IO_BUFFER_SIZE)
+}
 
 /// Exported function for the host to locate the IO buffer in guest memory.
+/// Behind `wasm-guest` (synth-1201, on by default) since this is a C ABI
+/// symbol meant for the real wasm host to find via module introspection —
+/// a native build embedding this crate for its shared types isn't a guest
+/// and shouldn't export it.
+#[cfg(feature = "wasm-guest")]
 #[no_mangle]
 pub extern "C" fn get_io_buffer() -> *mut u8 {
-    IO_BUFFER.0.get() as *mut u8
+    ensure_io_buffer(0)
+}
+
+/// Exported function (synth-1186) letting the host grow the IO buffer from
+/// the guest's own heap when a response doesn't fit in the previously
+/// negotiated size — see `host_call_dispatch`'s buffer-growth negotiation.
+/// Returns the (possibly relocated) pointer. Behind `wasm-guest` for the
+/// same reason as `get_io_buffer` (synth-1201).
+#[cfg(feature = "wasm-guest")]
+#[no_mangle]
+pub extern "C" fn alloc_io_buffer(size: i32) -> i32 {
+    ensure_io_buffer(size.max(0) as usize) as i32
+}
+
+/// Where `call_host_once` gets its host from (synth-1190). `op` is the
+/// operation code, `len` is the request JSON length already sitting in
+/// IO_BUFFER; the return value is a response length (positive) or error
+/// code (negative), same contract as the real `host_call` import.
+///
+/// Exactly one implementation is compiled in per target: `WasmHost` on
+/// `wasm32` (the real host), `MockHost` everywhere else, since a native
+/// build — i.e. every `cargo test` — has no real wasm host to link
+/// against. Swappable via `set_host_transport`/`with_mock_host` so a tool
+/// crate can drive its own logic against canned responses natively instead
+/// of only being testable by compiling to wasm and running under the real
+/// sidecar.
+pub trait HostTransport {
+    fn call(&mut self, op: i32, len: i32) -> i32;
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm-guest"))]
+struct WasmHost;
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm-guest"))]
+impl HostTransport for WasmHost {
+    fn call(&mut self, op: i32, len: i32) -> i32 {
+        unsafe { _host_call(op, len) }
+    }
 }
 
 // Import from the "sentinel" host namespace.
 // `op` is the operation code, `len` is the request JSON length in IO_BUFFER.
 // Returns response length (positive) or error code (negative).
+#[cfg(all(target_arch = "wasm32", feature = "wasm-guest"))]
 #[link(wasm_import_module = "sentinel")]
 extern "C" {
     #[link_name = "host_call"]
     fn _host_call(op: i32, len: i32) -> i32;
 }
 
-/// Operation codes for host function dispatch.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(i32)]
-pub enum Op {
-    ReadFile = 1,
-    WriteFile = 2,
-    ShellExec = 3,
-    HttpFetch = 4,
-    GetCredential = 5,
+/// Records expected `(op, request)` calls in order and answers each with a
+/// canned response or a structured error payload (synth-1188 shape),
+/// panicking on an unexpected op or a request that doesn't match. The
+/// default transport on every non-`wasm32` target, so tool binaries build
+/// and their tests run natively without a real host — install one
+/// explicitly (`set_host_transport`/`with_mock_host`) to script specific
+/// responses instead of relying on the empty default, which panics on the
+/// first call.
+#[derive(Default)]
+pub struct MockHost {
+    expectations: std::collections::VecDeque<MockExpectation>,
+}
+
+struct MockExpectation {
+    op: Op,
+    request: MockRequest,
+    outcome: MockOutcome,
+}
+
+/// The shape a queued expectation matches an incoming request against —
+/// either a plain JSON body, or a binary-envelope header/payload pair
+/// (synth-1203). `MockHost::call` decides which one a given call actually
+/// used and compares like for like.
+enum MockRequest {
+    Json(serde_json::Value),
+    Binary { header: serde_json::Value, payload: Vec<u8> },
+}
+
+enum MockOutcome {
+    Response(serde_json::Value),
+    Error { code: String, message: String },
 }
 
+impl MockHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues an expected call: the next `host_call` must be for `op` with
+    /// exactly `request` as its JSON body, and is answered with `response`.
+    pub fn expect(&mut self, op: Op, request: serde_json::Value, response: serde_json::Value) -> &mut Self {
+        self.expectations.push_back(MockExpectation {
+            op,
+            request: MockRequest::Json(request),
+            outcome: MockOutcome::Response(response),
+        });
+        self
+    }
+
+    /// Queues an expected call that fails the way a handler `Err` does
+    /// (synth-1188) — the next `host_call` must be for `op` with exactly
+    /// `request` as its JSON body, and is answered with a structured
+    /// `{code, message}` error payload instead of a response.
+    pub fn expect_error(&mut self, op: Op, request: serde_json::Value, code: &str, message: &str) -> &mut Self {
+        self.expectations.push_back(MockExpectation {
+            op,
+            request: MockRequest::Json(request),
+            outcome: MockOutcome::Error { code: code.to_string(), message: message.to_string() },
+        });
+        self
+    }
+
+    /// Queues an expected call made via `call_host_binary` (synth-1203): the
+    /// next `host_call` must be a binary envelope for `op` with exactly
+    /// `header`/`payload`, and is answered with `response` as a plain JSON
+    /// body (no host handler round-trips a payload back through the
+    /// envelope today).
+    pub fn expect_binary(
+        &mut self,
+        op: Op,
+        header: serde_json::Value,
+        payload: &[u8],
+        response: serde_json::Value,
+    ) -> &mut Self {
+        self.expectations.push_back(MockExpectation {
+            op,
+            request: MockRequest::Binary { header, payload: payload.to_vec() },
+            outcome: MockOutcome::Response(response),
+        });
+        self
+    }
+
+    /// Binary-envelope sibling of `expect_error`.
+    pub fn expect_binary_error(
+        &mut self,
+        op: Op,
+        header: serde_json::Value,
+        payload: &[u8],
+        code: &str,
+        message: &str,
+    ) -> &mut Self {
+        self.expectations.push_back(MockExpectation {
+            op,
+            request: MockRequest::Binary { header, payload: payload.to_vec() },
+            outcome: MockOutcome::Error { code: code.to_string(), message: message.to_string() },
+        });
+        self
+    }
+}
+
+impl HostTransport for MockHost {
+    fn call(&mut self, op: i32, len: i32) -> i32 {
+        let raw_bytes = read_io_buffer(len as usize);
+        let expectation = self
+            .expectations
+            .pop_front()
+            .unwrap_or_else(|| panic!("MockHost: unexpected call for op {op}, no expectation queued"));
+        assert_eq!(op, expectation.op as i32, "MockHost: expected op {:?} but got {op}", expectation.op);
+
+        match &expectation.request {
+            MockRequest::Json(expected) => {
+                let request: serde_json::Value =
+                    serde_json::from_slice(&raw_bytes).unwrap_or(serde_json::Value::Null);
+                assert_eq!(&request, expected, "MockHost: request mismatch for {:?}", expectation.op);
+            }
+            MockRequest::Binary { header, payload } => match sentinel_ops::binary_envelope::decode(&raw_bytes) {
+                Some((actual_header, actual_payload)) => {
+                    assert_eq!(&actual_header, header, "MockHost: envelope header mismatch for {:?}", expectation.op);
+                    assert_eq!(actual_payload, payload.as_slice(), "MockHost: envelope payload mismatch for {:?}", expectation.op);
+                }
+                None => panic!("MockHost: expected a binary envelope for {:?} but got plain JSON", expectation.op),
+            },
+        }
+
+        match expectation.outcome {
+            MockOutcome::Response(value) => {
+                let bytes = serde_json::to_vec(&value).unwrap();
+                let ptr = ensure_io_buffer(bytes.len());
+                unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len()) };
+                bytes.len() as i32
+            }
+            MockOutcome::Error { code, message } => {
+                let bytes = serde_json::to_vec(&sentinel_ops::ErrorPayload { code, message }).unwrap();
+                let ptr = ensure_io_buffer(bytes.len());
+                unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len()) };
+                -(1000 + bytes.len() as i32)
+            }
+        }
+    }
+}
+
+pub use sentinel_ops::Op;
+
+/// `host_call` return codes (synth-1187) — see `sentinel_ops::error_codes`
+/// for what each one means; `interpret_host_response` is the only thing in
+/// this crate that switches on them directly.
+pub use sentinel_ops::error_codes;
+
+/// Codes below this are `Op` variants; codes at or above it are
+/// deployment-specific custom ops (synth-1197) dispatched through a handler
+/// registered with `HostState::register_custom_op` on the host side. A
+/// guest tool built against a specific deployment picks a code in this
+/// range and passes it directly to [`call_host`]/[`call_host_typed`] —
+/// there's no `Op` variant to name it by.
+pub use sentinel_ops::CUSTOM_OP_RANGE_START;
+
 /// Error returned by host function calls.
 #[derive(Debug)]
 pub enum HostError {
     /// Request too large for IO_BUFFER.
     RequestTooLarge(usize),
-    /// Host returned a negative error code (no message available).
+    /// Host returned a negative error code with no payload in IO_BUFFER — a
+    /// transport-level failure (unknown op, buffer I/O error, or a fallback
+    /// when the host itself couldn't write an error payload), not something
+    /// a handler chose to fail with.
     HostError(i32),
-    /// Host operation failed with a descriptive error message.
-    OperationError(String),
+    /// Handler failure surfaced by the host as a structured payload
+    /// (synth-1188) instead of a bare message — `code` is a stable,
+    /// machine-matchable string (currently always `"operation_error"`, since
+    /// handlers don't yet classify their own errors) and `message` is the
+    /// human-readable text, e.g. "path '/etc/x' is not under an allowed
+    /// directory".
+    Operation { code: String, message: String },
     /// Failed to serialize request JSON.
     SerializeError(String),
     /// Failed to deserialize response JSON.
     DeserializeError(String),
+    /// The host reported a response length that doesn't fit IO_BUFFER — a
+    /// buggy or hostile transport that would otherwise send us reading past
+    /// the end of the static buffer (synth-1191).
+    InvalidResponseLength(i32),
+    /// A `call_host`/`call_host_typed` round trip was already in flight on
+    /// this thread when another one started (synth-1200) — e.g. a callback
+    /// invoked mid-call, or a helper holding a borrowed view into IO_BUFFER
+    /// that itself triggers another call. Without this check the second
+    /// call would silently overwrite the first's bytes still sitting in
+    /// IO_BUFFER instead of failing loudly.
+    BufferBusy,
 }
 
 impl std::fmt::Display for HostError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::RequestTooLarge(size) => {
-                write!(f, "request too large: {size} bytes (max {IO_BUFFER_SIZE})")
+                write!(f, "request too large: {size} bytes (max {})", io_buffer_capacity())
             }
             Self::HostError(code) => write!(f, "host error code: {code}"),
-            Self::OperationError(msg) => write!(f, "host operation error: {msg}"),
+            Self::Operation { code, message } => write!(f, "{code}: {message}"),
             Self::SerializeError(e) => write!(f, "serialize error: {e}"),
             Self::DeserializeError(e) => write!(f, "deserialize error: {e}"),
+            Self::InvalidResponseLength(len) => {
+                write!(f, "host reported invalid response length: {len} (max {})", io_buffer_capacity())
+            }
+            Self::BufferBusy => write!(f, "IO buffer is already in use by another host call"),
         }
     }
 }
 
 impl std::error::Error for HostError {}
 
+thread_local! {
+    /// The host transport (synth-1190) `call_host_once` dispatches to for
+    /// the current thread. Defaults to `WasmHost` on `wasm32`, `MockHost`
+    /// everywhere else — see `HostTransport`.
+    static HOST_TRANSPORT: std::cell::RefCell<Box<dyn HostTransport>> =
+        std::cell::RefCell::new(default_transport());
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm-guest"))]
+fn default_transport() -> Box<dyn HostTransport> {
+    Box::new(WasmHost)
+}
+
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm-guest")))]
+fn default_transport() -> Box<dyn HostTransport> {
+    Box::new(MockHost::new())
+}
+
+/// Installs `transport` as the active `HostTransport` for the current
+/// thread (synth-1190). A tool's own tests call this (or the `with_mock_host`
+/// shorthand) with a `MockHost` to drive its logic without a real wasm host.
+pub fn set_host_transport(transport: impl HostTransport + 'static) {
+    HOST_TRANSPORT.with(|cell| *cell.borrow_mut() = Box::new(transport));
+}
+
+/// Serializes tests that touch IO_BUFFER or HOST_TRANSPORT — both are
+/// process-wide statics standing in for real WASM linear memory and a real
+/// host, not per-test state, so two tests running in parallel could
+/// otherwise interleave their traffic through them.
+static TEST_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// Installs `mock` as the active host transport and runs `body` against it
+/// (synth-1190), holding `TEST_LOCK` for the duration. Shorthand for
+/// `set_host_transport` + a lock a tool's own `#[cfg(test)]` module would
+/// otherwise have to take out itself.
+pub fn with_mock_host<R>(mock: MockHost, body: impl FnOnce() -> R) -> R {
+    let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    set_host_transport(mock);
+    body()
+}
+
+thread_local! {
+    /// Guards IO_BUFFER against re-entrant use (synth-1200). Held for the
+    /// duration of one `call_host_raw` round trip — request written,
+    /// transport invoked, response read back out — so a nested
+    /// `call_host`/`call_host_typed` triggered while that's in flight (a
+    /// callback-style host API calling back into the guest, say, or a
+    /// helper that lazily deserializes a borrowed slice and only then
+    /// makes another call) fails with `HostError::BufferBusy` instead of
+    /// silently clobbering the first call's bytes still in IO_BUFFER.
+    static IO_BUFFER_IN_USE: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// RAII handle on `IO_BUFFER_IN_USE`, released on drop so an early return or
+/// a panic mid-call doesn't leave the flag stuck set.
+struct IoBufferGuard;
+
+impl IoBufferGuard {
+    fn acquire() -> Result<Self, HostError> {
+        let already_busy = IO_BUFFER_IN_USE.with(|cell| cell.replace(true));
+        if already_busy {
+            return Err(HostError::BufferBusy);
+        }
+        Ok(IoBufferGuard)
+    }
+}
+
+impl Drop for IoBufferGuard {
+    fn drop(&mut self) {
+        IO_BUFFER_IN_USE.with(|cell| cell.set(false));
+    }
+}
+
+/// Writes `req_bytes` into IO_BUFFER and invokes the active `HostTransport`
+/// with the given operation code, returning its raw return value.
+fn call_host_once(op: Op, req_bytes: &[u8]) -> i32 {
+    let buf_ptr = ensure_io_buffer(req_bytes.len());
+    unsafe {
+        std::ptr::copy_nonoverlapping(req_bytes.as_ptr(), buf_ptr, req_bytes.len());
+    }
+    HOST_TRANSPORT.with(|cell| cell.borrow_mut().call(op.into(), req_bytes.len() as i32))
+}
+
+/// Copies `len` bytes out of IO_BUFFER.
+fn read_io_buffer(len: usize) -> Vec<u8> {
+    let buf = unsafe { &*IO_BUFFER.0.get() };
+    buf[..len].to_vec()
+}
+
+/// Writes `req_bytes` into IO_BUFFER, invokes the host with the given
+/// operation code, and returns the raw response bytes from IO_BUFFER.
+///
+/// Shared by `call_host` and `call_host_typed` so the unsafe buffer
+/// plumbing lives in exactly one place.
+fn call_host_raw(op: Op, req_bytes: &[u8]) -> Result<Vec<u8>, HostError> {
+    if req_bytes.len() > io_buffer_capacity() {
+        return Err(HostError::RequestTooLarge(req_bytes.len()));
+    }
+
+    let _guard = IoBufferGuard::acquire()?;
+
+    let mut resp_len = call_host_once(op, req_bytes);
+
+    if resp_len == sentinel_ops::error_codes::RESPONSE_TOO_LARGE {
+        // synth-1186: the response didn't fit in our current buffer — the
+        // host wrote the needed size as an 8-byte little-endian u64 in
+        // place of a response. Grow to fit and retry once. Since we export
+        // `alloc_io_buffer`, the host should already have grown the buffer
+        // itself and written the response there instead of returning -5 —
+        // this is a defense-in-depth fallback, not the common path.
+        let needed = u64::from_le_bytes(read_io_buffer(8).try_into().unwrap()) as usize;
+        ensure_io_buffer(needed);
+        resp_len = call_host_once(op, req_bytes);
+    }
+
+    interpret_host_response(resp_len)
+}
+
+/// Interprets a raw `host_call` return value together with whatever the host
+/// left in IO_BUFFER, split out of `call_host_raw` (synth-1188) so the
+/// error-payload parsing can be unit-tested directly — the real `_host_call`
+/// import only resolves once linked into an actual WASM module, so
+/// `call_host_raw` itself can't run under `cargo test`.
+fn interpret_host_response(resp_len: i32) -> Result<Vec<u8>, HostError> {
+    if resp_len < 0 {
+        if resp_len <= -1000 {
+            // Host wrote an error payload to IO buffer; length = -(resp_len + 1000)
+            let payload_len = (-(resp_len + 1000)) as usize;
+            if payload_len > 0 && payload_len <= io_buffer_capacity() {
+                return Err(parse_error_payload(&read_io_buffer(payload_len)));
+            }
+            // Recognized shape (<= -1000) but the payload length it implies
+            // doesn't fit IO_BUFFER — a buggy or hostile-in-tests host, not
+            // something we can recover a payload from. Fall through to the
+            // bare error-code path below rather than reading out of bounds.
+            return Err(HostError::InvalidResponseLength(resp_len));
+        }
+        if !sentinel_ops::error_codes::is_recognized(resp_len) {
+            return Err(HostError::InvalidResponseLength(resp_len));
+        }
+        return Err(HostError::HostError(resp_len));
+    }
+
+    // synth-1191: a positive length greater than IO_BUFFER's capacity would
+    // otherwise slice past the end of the static buffer below — undefined
+    // behavior, since read_io_buffer trusts its caller.
+    if resp_len as usize > io_buffer_capacity() {
+        return Err(HostError::InvalidResponseLength(resp_len));
+    }
+
+    Ok(read_io_buffer(resp_len as usize))
+}
+
+/// Parses the `{code, message}` JSON a handler failure writes to IO_BUFFER
+/// (synth-1188) into a `HostError::Operation`. Falls back to treating the
+/// bytes as a raw message under a generic code if they aren't valid
+/// `ErrorPayload` JSON, so a host running an older binary that still writes
+/// a bare message string doesn't regress to an opaque numeric code.
+fn parse_error_payload(bytes: &[u8]) -> HostError {
+    match serde_json::from_slice::<sentinel_ops::ErrorPayload>(bytes) {
+        Ok(payload) => HostError::Operation { code: payload.code, message: payload.message },
+        Err(_) => HostError::Operation {
+            code: "operation_error".to_string(),
+            message: String::from_utf8_lossy(bytes).to_string(),
+        },
+    }
+}
+
 /// Safe wrapper around the host_call import.
 ///
 /// Serializes `request` as JSON into IO_BUFFER, calls the host with the
@@ -82,38 +493,565 @@ impl std::error::Error for HostError {}
 pub fn call_host(op: Op, request: &serde_json::Value) -> Result<serde_json::Value, HostError> {
     let req_bytes =
         serde_json::to_vec(request).map_err(|e| HostError::SerializeError(e.to_string()))?;
+    let resp_bytes = call_host_raw(op, &req_bytes)?;
+    serde_json::from_slice(&resp_bytes).map_err(|e| HostError::DeserializeError(e.to_string()))
+}
 
-    if req_bytes.len() > IO_BUFFER_SIZE {
-        return Err(HostError::RequestTooLarge(req_bytes.len()));
+/// Typed sibling of `call_host`.
+///
+/// Serializes `req` as JSON into IO_BUFFER, calls the host with the given
+/// operation code, then deserializes the JSON response straight into `Resp`
+/// instead of leaving callers to string-pick fields out of a `Value` (which
+/// silently turns a missing field into an empty string or a zero).
+pub fn call_host_typed<Req, Resp>(op: Op, req: &Req) -> Result<Resp, HostError>
+where
+    Req: Serialize,
+    Resp: for<'de> Deserialize<'de>,
+{
+    let req_bytes = serde_json::to_vec(req).map_err(|e| HostError::SerializeError(e.to_string()))?;
+    let resp_bytes = call_host_raw(op, &req_bytes)?;
+    serde_json::from_slice(&resp_bytes).map_err(|e| HostError::DeserializeError(e.to_string()))
+}
+
+/// Sibling of `call_host`/`call_host_typed` for a payload that shouldn't
+/// travel as a JSON string field (synth-1203) — a `WriteFile` write or an
+/// `HttpFetch` body, say, where wrapping the bytes in JSON would mean
+/// escaping and re-parsing the whole thing just to move it. `header` covers
+/// everything about the call except `payload`; the host reassembles the two
+/// with `sentinel_ops::binary_envelope::decode` instead of deserializing a
+/// single JSON body. Only a handful of ops accept an envelope on the host
+/// side (see `host_call_dispatch`) — anything else answers with an
+/// `operation_error`. A response with no envelope of its own is treated as
+/// header-only with an empty payload, since not every op needs to send raw
+/// bytes back (`write_file_bytes`'s `WriteFileResponse`, for one).
+pub fn call_host_binary(
+    op: Op,
+    header: &serde_json::Value,
+    payload: &[u8],
+) -> Result<(serde_json::Value, Vec<u8>), HostError> {
+    let req_bytes = sentinel_ops::binary_envelope::encode(header, payload);
+    let resp_bytes = call_host_raw(op, &req_bytes)?;
+    match sentinel_ops::binary_envelope::decode(&resp_bytes) {
+        Some((resp_header, resp_payload)) => Ok((resp_header, resp_payload.to_vec())),
+        None => {
+            let resp_header = serde_json::from_slice(&resp_bytes)
+                .map_err(|e| HostError::DeserializeError(e.to_string()))?;
+            Ok((resp_header, Vec::new()))
+        }
     }
+}
 
-    // Write request to the shared buffer and call host
-    let buf_ptr = IO_BUFFER.0.get() as *mut u8;
-    let resp_len = unsafe {
-        std::ptr::copy_nonoverlapping(req_bytes.as_ptr(), buf_ptr, req_bytes.len());
-        _host_call(op as i32, req_bytes.len() as i32)
-    };
+/// Chunk size `call_host_chunked` uses for `Op::ChunkAppend` uploads
+/// (synth-1185). Comfortably under DEFAULT_IO_BUFFER_SIZE once
+/// base64-encoded (≈1.34x) plus the surrounding JSON envelope.
+const CHUNK_APPEND_BYTES: usize = 512 * 1024;
 
-    if resp_len < 0 {
-        if resp_len <= -1000 {
-            // Host wrote error message to IO buffer; length = -(resp_len + 1000)
-            let msg_len = (-(resp_len + 1000)) as usize;
-            if msg_len > 0 && msg_len <= IO_BUFFER_SIZE {
-                let msg_bytes = unsafe {
-                    std::slice::from_raw_parts(buf_ptr as *const u8, msg_len)
-                };
-                let msg = String::from_utf8_lossy(msg_bytes).to_string();
-                return Err(HostError::OperationError(msg));
+/// Upload `payload` via the Begin/Append/Commit dance (synth-1185), hiding
+/// it from callers who just want to run an oversized payload (e.g. a large
+/// `write_file` `content`) through the host without hand-splitting it
+/// themselves. `commit_op` and `commit_fields` become the `Op::ChunkCommit`
+/// request's `"op"` field and its other fields respectively — the host
+/// hands the assembled buffer to that operation's own handler, so the
+/// commit response is exactly what a direct call to that op would return.
+pub fn call_host_chunked(
+    commit_op: &str,
+    payload: &[u8],
+    mut commit_fields: serde_json::Value,
+) -> Result<serde_json::Value, HostError> {
+    let begin: messages::ChunkBeginResponse =
+        call_host_typed(Op::ChunkBegin, &messages::ChunkBeginRequest {})?;
+
+    for chunk in payload.chunks(CHUNK_APPEND_BYTES) {
+        let chunk_b64 = base64::engine::general_purpose::STANDARD.encode(chunk);
+        let _: messages::ChunkAppendResponse = call_host_typed(
+            Op::ChunkAppend,
+            &messages::ChunkAppendRequest { transfer_id: begin.transfer_id.clone(), chunk_b64 },
+        )?;
+    }
+
+    commit_fields["transfer_id"] = serde_json::Value::String(begin.transfer_id);
+    commit_fields["op"] = serde_json::Value::String(commit_op.to_string());
+    call_host(Op::ChunkCommit, &commit_fields)
+}
+
+/// Drain a spooled download (synth-1185) — e.g. the `transfer_id` an
+/// oversized `Op::ReadFile` response carries in place of `content` — into a
+/// single byte buffer via repeated `Op::ChunkFetch` calls.
+pub fn drain_chunked_transfer(transfer_id: &str) -> Result<Vec<u8>, HostError> {
+    let mut data = Vec::new();
+    let mut offset: u64 = 0;
+    loop {
+        let response: messages::ChunkFetchResponse = call_host_typed(
+            Op::ChunkFetch,
+            &messages::ChunkFetchRequest { transfer_id: transfer_id.to_string(), offset },
+        )?;
+        let chunk = base64::engine::general_purpose::STANDARD
+            .decode(&response.chunk_b64)
+            .map_err(|e| HostError::DeserializeError(e.to_string()))?;
+        offset += chunk.len() as u64;
+        data.extend_from_slice(&chunk);
+        if response.eof {
+            break;
+        }
+    }
+    Ok(data)
+}
+
+/// Request/response structs for each `Op` (synth-1187) — re-exported from
+/// `sentinel-ops` so this crate and `host_functions.rs` share exactly one
+/// definition of each.
+pub use sentinel_ops::messages;
+
+/// Above this, `content` plus its JSON envelope risks not fitting in one
+/// IO_BUFFER round trip, so `write_file` routes through `call_host_chunked`
+/// instead (synth-1185). Public so a tool that builds its own oversized
+/// request can check ahead of time, the way `write_file` itself does.
+pub const INLINE_CONTENT_LIMIT: usize = 512 * 1024;
+
+/// Reads a file as UTF-8 text via `Op::ReadFile`, transparently draining a
+/// spooled transfer (synth-1185) if the file was too large to inline.
+///
+/// One of the high-level wrappers (synth-1189) meant to spare a 30-line tool
+/// from re-deriving the request-building and chunking dance itself — see
+/// also `read_file_bytes`, `write_file`, `http_get`, `http_fetch`, `shell`,
+/// and `get_credential`.
+pub fn read_file(path: &str) -> Result<String, HostError> {
+    let bytes = read_file_bytes(path)?;
+    String::from_utf8(bytes).map_err(|e| HostError::DeserializeError(e.to_string()))
+}
+
+/// Byte-oriented sibling of `read_file`, for files that aren't valid UTF-8.
+pub fn read_file_bytes(path: &str) -> Result<Vec<u8>, HostError> {
+    let request = messages::ReadFileRequest { path: path.to_string(), stream: false };
+    let mut response: messages::ReadFileResponse = call_host_typed(Op::ReadFile, &request)?;
+    match response.transfer_id.take() {
+        Some(transfer_id) => drain_chunked_transfer(&transfer_id),
+        None => Ok(response.content.unwrap_or_default().into_bytes()),
+    }
+}
+
+/// Streams a file via `Op::ReadFile { stream: true }` and successive
+/// growing-offset `Op::ChunkFetch` calls (synth-1204), instead of pulling
+/// the whole file into memory the way [`read_file`]/[`read_file_bytes`] do.
+/// Meant for a tool that wants to bound its own memory use — e.g. `head`ing
+/// or `tail`ing a file far larger than it needs to read in full.
+#[derive(Debug)]
+pub struct FileReader {
+    transfer_id: String,
+    offset: u64,
+    chunk: Vec<u8>,
+    chunk_pos: usize,
+    eof: bool,
+}
+
+impl FileReader {
+    /// Opens `path` for streaming. The host spools the whole file up front
+    /// (synth-1185's transfer mechanism) — only the fetch side is
+    /// incremental, but that's still exactly what a `Read` impl needs: a
+    /// caller controls how much of the file it ever holds in memory at once.
+    pub fn open(path: &str) -> Result<Self, HostError> {
+        let request = messages::ReadFileRequest { path: path.to_string(), stream: true };
+        let response: messages::ReadFileResponse = call_host_typed(Op::ReadFile, &request)?;
+        let transfer_id = response.transfer_id.ok_or_else(|| {
+            HostError::DeserializeError("streaming read_file request did not get a transfer_id back".to_string())
+        })?;
+        Ok(Self { transfer_id, offset: 0, chunk: Vec::new(), chunk_pos: 0, eof: false })
+    }
+
+    fn fill_chunk(&mut self) -> std::io::Result<()> {
+        let response: messages::ChunkFetchResponse = call_host_typed(
+            Op::ChunkFetch,
+            &messages::ChunkFetchRequest { transfer_id: self.transfer_id.clone(), offset: self.offset },
+        )
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+        let chunk = base64::engine::general_purpose::STANDARD
+            .decode(&response.chunk_b64)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
+        self.offset += chunk.len() as u64;
+        self.chunk = chunk;
+        self.chunk_pos = 0;
+        self.eof = response.eof;
+        Ok(())
+    }
+}
+
+impl std::io::Read for FileReader {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        if self.chunk_pos >= self.chunk.len() {
+            if self.eof {
+                return Ok(0);
             }
+            self.fill_chunk()?;
         }
-        return Err(HostError::HostError(resp_len));
+        let available = &self.chunk[self.chunk_pos..];
+        let n = available.len().min(out.len());
+        out[..n].copy_from_slice(&available[..n]);
+        self.chunk_pos += n;
+        Ok(n)
     }
+}
 
-    // Read response from the shared buffer
-    let resp_bytes = unsafe {
-        std::slice::from_raw_parts(buf_ptr as *const u8, resp_len as usize)
-    };
-    serde_json::from_slice(resp_bytes).map_err(|e| HostError::DeserializeError(e.to_string()))
+impl FileReader {
+    /// Convenience for line-by-line consumption — a thin wrapper over
+    /// `std::io::BufRead::lines` since `FileReader` itself only implements
+    /// `Read`.
+    pub fn lines(self) -> std::io::Lines<std::io::BufReader<FileReader>> {
+        std::io::BufRead::lines(std::io::BufReader::new(self))
+    }
+}
+
+/// Writes `content` via `Op::WriteFile`. A thin wrapper over
+/// [`write_file_bytes`] for the common case of UTF-8 text content.
+pub fn write_file(path: &str, content: &str) -> Result<usize, HostError> {
+    write_file_bytes(path, content.as_bytes())
+}
+
+/// Byte-oriented sibling of `write_file`, routing through the chunked
+/// Begin/Append/Commit dance (synth-1185) when `content` is too large to
+/// inline, and through the binary envelope (synth-1203) otherwise — a plain
+/// `content: Vec<u8>` sent as the envelope's raw payload instead of a JSON
+/// string field, so `content` doesn't need to be valid UTF-8 and doesn't pay
+/// JSON string escaping over its whole length. Returns the number of bytes
+/// the host reports as written.
+pub fn write_file_bytes(path: &str, content: &[u8]) -> Result<usize, HostError> {
+    write_file_with_options(path, content, None, None).map(|r| r.written)
+}
+
+/// Sibling of `write_file_bytes` with the full write-mode and
+/// parent-directory controls (synth-1210), returning the host's full
+/// response instead of just the byte count. `mode` of `None` keeps the
+/// host's default (`"overwrite"`); `create_dirs` of `None` keeps its
+/// default (always create missing parents).
+pub fn write_file_with_options(
+    path: &str,
+    content: &[u8],
+    mode: Option<&str>,
+    create_dirs: Option<bool>,
+) -> Result<messages::WriteFileResponse, HostError> {
+    let mut header = serde_json::json!({ "path": path });
+    if let Some(mode) = mode {
+        header["mode"] = serde_json::Value::String(mode.to_string());
+    }
+    if let Some(create_dirs) = create_dirs {
+        header["create_dirs"] = serde_json::Value::Bool(create_dirs);
+    }
+    if content.len() > INLINE_CONTENT_LIMIT {
+        let response = call_host_chunked("write_file", content, header)?;
+        serde_json::from_value(response).map_err(|e| HostError::DeserializeError(e.to_string()))
+    } else {
+        let (resp_header, _) = call_host_binary(Op::WriteFile, &header, content)?;
+        serde_json::from_value(resp_header).map_err(|e| HostError::DeserializeError(e.to_string()))
+    }
+}
+
+/// Shorthand for `http_fetch` with method `GET` and no body or headers.
+pub fn http_get(url: &str) -> Result<messages::HttpFetchResponse, HostError> {
+    http_fetch(messages::HttpFetchRequest {
+        url: url.to_string(),
+        method: "GET".to_string(),
+        headers: Default::default(),
+        body: None,
+        follow_redirects: None,
+        timeout_ms: None,
+        retries: None,
+        response_encoding: None,
+        save_to: None,
+    })
+}
+
+/// Runs an arbitrary HTTP request via `Op::HttpFetch`. A request with a body
+/// sends it as an ordinary JSON string field — see
+/// [`http_fetch_with_raw_body`] for a body that shouldn't pay that escaping
+/// cost.
+pub fn http_fetch(request: messages::HttpFetchRequest) -> Result<messages::HttpFetchResponse, HostError> {
+    call_host_typed(Op::HttpFetch, &request)
+}
+
+/// Sibling of `http_fetch` that sends `body` via the binary envelope
+/// (synth-1203) instead of embedding it as a JSON string field — for a
+/// request body that's large or not valid UTF-8. The response still comes
+/// back as an ordinary `HttpFetchResponse`; `http_client::fetch` only ever
+/// produces a text body, so there's nothing to gain from an enveloped
+/// response here.
+pub fn http_fetch_with_raw_body(
+    url: &str,
+    method: &str,
+    headers: std::collections::HashMap<String, String>,
+    body: &[u8],
+) -> Result<messages::HttpFetchResponse, HostError> {
+    let header = serde_json::json!({ "url": url, "method": method, "headers": headers });
+    let (resp_header, _) = call_host_binary(Op::HttpFetch, &header, body)?;
+    serde_json::from_value(resp_header).map_err(|e| HostError::DeserializeError(e.to_string()))
+}
+
+/// Runs `command` via `Op::ShellExec`. A thin wrapper over [`shell_exec`] for
+/// the common case of a plain shell command with no argv mode, `cwd`, `env`,
+/// `stdin`, or `timeout_ms` override.
+pub fn shell(command: &str) -> Result<messages::ShellExecResponse, HostError> {
+    shell_exec(messages::ShellExecRequest {
+        command: Some(command.to_string()),
+        program: None,
+        args: Vec::new(),
+        cwd: None,
+        env: Default::default(),
+        stdin: None,
+        timeout_ms: None,
+    })
+}
+
+/// Runs an arbitrary shell-exec request via `Op::ShellExec` (synth-1211),
+/// carrying whichever of `command`/`program`+`args`, `cwd`, `env`, `stdin`,
+/// and `timeout_ms` the caller filled in.
+pub fn shell_exec(request: messages::ShellExecRequest) -> Result<messages::ShellExecResponse, HostError> {
+    call_host_typed(Op::ShellExec, &request)
+}
+
+/// Fetches a named credential via `Op::GetCredential`, returning just its
+/// value — a caller that also needs the (echoed-back) name can call
+/// `call_host_typed` directly.
+pub fn get_credential(name: &str) -> Result<String, HostError> {
+    let response: messages::GetCredentialResponse =
+        call_host_typed(Op::GetCredential, &messages::GetCredentialRequest { name: name.to_string() })?;
+    Ok(response.value)
+}
+
+/// Fetches the credential named `name` (synth-1199), hands its value to `f`,
+/// and zeroizes the fetched `String` before returning — so a tool that only
+/// needs the value for one call (building an auth header, say) doesn't have
+/// to remember to scrub its own local copy afterward. `f`'s return value is
+/// unaffected; if it holds onto the value itself, that copy is the caller's
+/// responsibility, the same as with `get_credential`.
+pub fn with_credential<R>(name: &str, f: impl FnOnce(&str) -> R) -> Result<R, HostError> {
+    let mut value = get_credential(name)?;
+    let result = f(&value);
+    value.zeroize();
+    Ok(result)
+}
+
+/// Lists a directory's immediate entries via `Op::ListDir` (synth-1125).
+pub fn list_dir(path: &str) -> Result<Vec<messages::DirEntry>, HostError> {
+    let response: messages::ListDirResponse =
+        call_host_typed(Op::ListDir, &messages::ListDirRequest { path: path.to_string() })?;
+    Ok(response.entries)
+}
+
+/// Deletes a file via `Op::DeleteFile` (synth-1125), returning whether a
+/// file existed at `path` before the call.
+pub fn delete_file(path: &str) -> Result<bool, HostError> {
+    let response: messages::DeleteFileResponse =
+        call_host_typed(Op::DeleteFile, &messages::DeleteFileRequest { path: path.to_string() })?;
+    Ok(response.existed)
+}
+
+/// Reports a path's metadata via `Op::StatFile` (synth-1125), without
+/// reading its content.
+pub fn stat_file(path: &str) -> Result<messages::StatFileResponse, HostError> {
+    call_host_typed(Op::StatFile, &messages::StatFileRequest { path: path.to_string() })
+}
+
+/// Reads one environment variable via `Op::ReadEnv` (synth-1125). `Ok(None)`
+/// means the variable isn't set (or isn't in the host's passthrough
+/// allowlist) — not an error, the same way a missing key in a map isn't.
+pub fn read_env(name: &str) -> Result<Option<String>, HostError> {
+    let response: messages::ReadEnvResponse =
+        call_host_typed(Op::ReadEnv, &messages::ReadEnvRequest { name: name.to_string() })?;
+    Ok(response.value)
+}
+
+/// A `String` that redacts itself on `Debug`/`Display` (synth-1199), so a
+/// stray `{:?}`/`{}` of a credential value — in a log line, a panic message,
+/// an error string — prints `[REDACTED]` instead of the secret. Zeroized on
+/// drop. Use [`expose_secret`](SecretString::expose_secret) to get at the
+/// real value when it's actually needed (e.g. to send as a header).
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(value: String) -> Self {
+        SecretString(value)
+    }
+
+    /// The real value. Named loudly so a call site reads as an intentional
+    /// exception to the redaction, not an accident.
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SecretString").field(&"[REDACTED]").finish()
+    }
+}
+
+impl std::fmt::Display for SecretString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[REDACTED]")
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Blocks the guest for `millis` via `Op::Sleep` (synth-1193) — the backoff
+/// step `call_host_with_retry` uses between attempts. The host clamps how
+/// long it will actually sleep, so this never hangs indefinitely even if a
+/// caller passes an unreasonable value.
+pub fn sleep_ms(millis: u64) -> Result<(), HostError> {
+    let _: messages::SleepResponse = call_host_typed(Op::Sleep, &messages::SleepRequest { millis })?;
+    Ok(())
+}
+
+/// Reports a progress update via `Op::Progress` (synth-1195) — for a
+/// long-running tool (a big download, a multi-step shell pipeline) to
+/// surface incremental status. The host assigns ordering and caps how many
+/// events it retains, so callers can report as often as they like without
+/// worrying about growing the eventual `Response` without bound. `percent`
+/// is `None` when progress isn't naturally expressible as a fraction (e.g.
+/// "step 2 of an unknown-length pipeline").
+pub fn report_progress(message: &str, percent: Option<u8>) -> Result<(), HostError> {
+    let _: messages::ProgressResponse = call_host_typed(
+        Op::Progress,
+        &messages::ProgressRequest { message: message.to_string(), percent, data: None },
+    )?;
+    Ok(())
+}
+
+/// Writes one structured JSON log line to stderr: `{"level": <level>,
+/// "msg": <msg>}`. Called by the `log_debug!`/`log_info!`/`log_warn!`/
+/// `log_error!` macros below — use those instead of calling this directly,
+/// they build `msg` from format arguments the same way `println!` does.
+///
+/// A bare `eprintln!` lands in a 64 KiB stderr pipe nobody reads once
+/// execution finishes; the host parses stderr lines of exactly this shape
+/// back into `tracing` events at the matching level, tagged with the tool
+/// name and request_id (synth-1196).
+pub fn log_line(level: &str, msg: &str) {
+    eprintln!("{}", format_log_line(level, msg));
+}
+
+/// Builds the JSON text `log_line` writes, split out so the macros' output
+/// shape can be asserted on directly instead of capturing process stderr.
+fn format_log_line(level: &str, msg: &str) -> String {
+    serde_json::json!({ "level": level, "msg": msg }).to_string()
+}
+
+/// Emits a `"level": "debug"` structured log line to stderr. See [`log_line`].
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)*) => { $crate::log_line("debug", &format!($($arg)*)) };
+}
+
+/// Emits a `"level": "info"` structured log line to stderr. See [`log_line`].
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)*) => { $crate::log_line("info", &format!($($arg)*)) };
+}
+
+/// Emits a `"level": "warn"` structured log line to stderr. See [`log_line`].
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)*) => { $crate::log_line("warn", &format!($($arg)*)) };
+}
+
+/// Emits a `"level": "error"` structured log line to stderr. See [`log_line`].
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)*) => { $crate::log_line("error", &format!($($arg)*)) };
+}
+
+/// A point in time `millis` in the future, checked with `is_expired` or
+/// `remaining` (synth-1193) — lets `call_host_with_retry` stop retrying
+/// before the caller's own request timeout rather than past it. Built on
+/// `std::time::Instant` since the guest only ever needs a monotonic clock
+/// for its own process lifetime, never a wall-clock timestamp.
+pub struct Deadline {
+    at: std::time::Instant,
+}
+
+impl Deadline {
+    /// A deadline `millis` milliseconds from now.
+    pub fn after_ms(millis: u64) -> Self {
+        Deadline { at: std::time::Instant::now() + std::time::Duration::from_millis(millis) }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        std::time::Instant::now() >= self.at
+    }
+
+    /// Time left before the deadline, or `Duration::ZERO` once it has passed.
+    pub fn remaining(&self) -> std::time::Duration {
+        self.at.saturating_duration_since(std::time::Instant::now())
+    }
+}
+
+/// Controls `call_host_with_retry`'s attempts and backoff (synth-1193).
+///
+/// `retry_on` decides whether a given failure is worth retrying at all —
+/// the default only retries transport-level trouble (a dropped connection,
+/// a malformed response), never `HostError::Operation`, since an
+/// application-level failure (e.g. "file not found") will fail identically
+/// on every attempt. `retry_non_idempotent` guards against silently
+/// resending an op like `Op::WriteFile` that isn't safe to repeat; a caller
+/// that knows better (e.g. the op is a PUT with a stable target) can set it.
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub backoff_ms: u64,
+    pub retry_on: fn(&HostError) -> bool,
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 3,
+            backoff_ms: 200,
+            retry_on: |e| matches!(
+                e,
+                HostError::RequestTooLarge(_)
+                    | HostError::HostError(_)
+                    | HostError::SerializeError(_)
+                    | HostError::DeserializeError(_)
+                    | HostError::InvalidResponseLength(_)
+            ),
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+/// Retries a `call_host_typed` call up to `policy.attempts` times, sleeping
+/// `policy.backoff_ms` (via `Op::Sleep`) between attempts (synth-1193).
+///
+/// Refuses to retry `op` at all — falling back to a single plain attempt —
+/// when `op` isn't idempotent and the policy hasn't explicitly opted in via
+/// `retry_non_idempotent`, so e.g. `Op::WriteFile` isn't resent just because
+/// the host was slow to answer the first time.
+pub fn call_host_with_retry<Req, Resp>(op: Op, req: &Req, policy: &RetryPolicy) -> Result<Resp, HostError>
+where
+    Req: Serialize,
+    Resp: for<'de> Deserialize<'de>,
+{
+    if !op.is_idempotent() && !policy.retry_non_idempotent {
+        return call_host_typed(op, req);
+    }
+
+    let mut last_err = None;
+    for attempt in 1..=policy.attempts.max(1) {
+        match call_host_typed(op, req) {
+            Ok(resp) => return Ok(resp),
+            Err(e) => {
+                let should_retry = attempt < policy.attempts && (policy.retry_on)(&e);
+                last_err = Some(e);
+                if !should_retry {
+                    break;
+                }
+                sleep_ms(policy.backoff_ms)?;
+            }
+        }
+    }
+    Err(last_err.expect("loop runs at least once"))
 }
 
 /// Read tool arguments from the SENTINEL_TOOL_ARGS environment variable.
@@ -131,10 +1069,125 @@ pub fn read_stdin() -> Result<String, std::io::Error> {
     }
 }
 
+/// Default cap on the size of a tool's raw args JSON (synth-1198), used by
+/// [`parse_args`]/[`parse_args_or_usage`]. High enough for any realistic
+/// tool call while still turning "someone piped a whole file in as args" into
+/// a clear error instead of the guest allocating megabytes inside its 64 MiB
+/// memory cap and dying with an opaque OOM trap.
+pub const DEFAULT_MAX_ARGS_BYTES: usize = 4 * 1024 * 1024;
+
+/// Like [`read_stdin`], but rejects input over `max_bytes` with a named error
+/// (synth-1198) and turns the "nothing was passed at all" case — the env var
+/// missing, or set but empty, which is what happens when a tool binary is
+/// run by hand outside the sidecar — into a message that says where args
+/// actually come from instead of leaking the `SENTINEL_TOOL_ARGS` plumbing.
+pub fn read_stdin_bounded(max_bytes: usize) -> Result<String, String> {
+    let input = match read_stdin() {
+        Ok(input) if !input.is_empty() => input,
+        _ => {
+            return Err(
+                "no arguments were provided; this tool expects its arguments as a JSON object \
+                 on stdin (this usually means it was run outside the sidecar, which supplies \
+                 them for you)"
+                    .to_string(),
+            )
+        }
+    };
+    if input.len() > max_bytes {
+        return Err(format!("arguments too large ({} bytes, max {max_bytes})", input.len()));
+    }
+    Ok(input)
+}
+
+/// The host's view of this invocation — allowed filesystem roots, a scratch
+/// dir, the request id, and the effective timeout (synth-1202). Re-exported
+/// from `sentinel-ops` so this crate and `execute_wasm_sync` share exactly
+/// one definition, the same as `Op`/`messages` above.
+pub use sentinel_ops::ExecutionContext;
+
+thread_local! {
+    /// Cache for [`context`], parsed at most once per thread (synth-1202) —
+    /// mirrors `HOST_TRANSPORT`'s thread-local-cache shape above.
+    static CONTEXT_CACHE: std::cell::RefCell<Option<ExecutionContext>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Parses and caches the `ExecutionContext` the host injected via the
+/// `SENTINEL_CONTEXT` env var (synth-1202), the same mechanism
+/// `SENTINEL_TOOL_ARGS` uses and for the same reason — see [`read_stdin`].
+/// A tool calls this once to learn where it's allowed to touch the
+/// filesystem instead of hardcoding a path like `/workspace`.
+pub fn context() -> Result<ExecutionContext, String> {
+    CONTEXT_CACHE.with(|cell| {
+        if let Some(ctx) = cell.borrow().as_ref() {
+            return Ok(ctx.clone());
+        }
+        let raw = std::env::var("SENTINEL_CONTEXT")
+            .map_err(|_| "SENTINEL_CONTEXT env var not set".to_string())?;
+        let ctx: ExecutionContext = parse_args_str(&raw)?;
+        *cell.borrow_mut() = Some(ctx.clone());
+        Ok(ctx)
+    })
+}
+
+/// Resolves `path` against the first entry of the injected `ExecutionContext`'s
+/// `allowed_paths` if it isn't already absolute (synth-1202), instead of
+/// leaving a caller to fail on the host's hard "path must be absolute"
+/// rejection (see `host_functions::validate_path`) just for passing
+/// something like `notes.txt`. An already-absolute path is returned as-is,
+/// so a caller that already knows the right root doesn't pay for a
+/// `context()` call.
+pub fn resolve_path(path: &str) -> Result<String, String> {
+    if path.starts_with('/') {
+        return Ok(path.to_string());
+    }
+    let ctx = context()?;
+    let root = ctx
+        .allowed_paths
+        .first()
+        .ok_or_else(|| "no allowed paths in execution context to resolve a relative path against".to_string())?;
+    Ok(format!("{}/{path}", root.trim_end_matches('/')))
+}
+
 /// Convenience: parse stdin as a JSON value of type T.
+///
+/// Deserializes via `serde_path_to_error` (synth-1192) so a failure names the
+/// exact field it choked on instead of serde's bare "missing field `path` at
+/// line 1 column 2", and echoes back a preview of what was actually received
+/// so the caller can tell a malformed value from a wrong shape. Reads via
+/// [`read_stdin_bounded`] (synth-1198) so oversized or missing args fail with
+/// a clear message instead of an unbounded allocation.
 pub fn parse_args<T: for<'de> Deserialize<'de>>() -> Result<T, String> {
-    let input = read_stdin().map_err(|e| format!("failed to read stdin: {e}"))?;
-    serde_json::from_str(&input).map_err(|e| format!("failed to parse args: {e}"))
+    let input = read_stdin_bounded(DEFAULT_MAX_ARGS_BYTES)?;
+    parse_args_str(&input)
+}
+
+/// The parsing half of [`parse_args`], taking the JSON directly instead of
+/// reading it from `SENTINEL_TOOL_ARGS` — lets a tool crate's own tests feed
+/// it malformed input strings without touching env vars.
+pub fn parse_args_str<T: for<'de> Deserialize<'de>>(input: &str) -> Result<T, String> {
+    let deserializer = &mut serde_json::Deserializer::from_str(input);
+    serde_path_to_error::deserialize(deserializer).map_err(|e| format_parse_error(&e, input))
+}
+
+fn format_parse_error(e: &serde_path_to_error::Error<serde_json::Error>, input: &str) -> String {
+    let preview: String = input.chars().take(200).collect();
+    format!("failed to parse args: {e} (received: {preview:?})")
+}
+
+/// Like [`parse_args`], but on failure also prints the JSON schema `T`
+/// derives via `schemars` — feature-gated (`usage`) since it drags in
+/// `schemars` for every tool crate that doesn't need it (synth-1192).
+#[cfg(feature = "usage")]
+pub fn parse_args_or_usage<T: for<'de> Deserialize<'de> + schemars::JsonSchema>() -> Result<T, String> {
+    let input = read_stdin_bounded(DEFAULT_MAX_ARGS_BYTES)?;
+    parse_args_str(&input).map_err(|e| {
+        let schema = schemars::schema_for!(T);
+        format!(
+            "{e}\nexpected args matching this schema:\n{}",
+            serde_json::to_string_pretty(&schema).unwrap_or_else(|_| "<unavailable>".to_string())
+        )
+    })
 }
 
 /// Convenience: write a JSON result to stdout.
@@ -143,3 +1196,956 @@ pub fn write_result<T: Serialize>(result: &T) -> Result<(), String> {
     print!("{json}");
     Ok(())
 }
+
+/// Outcome of running a tool's body (synth-1194), before `run_tool` turns it
+/// into stdout/stderr writes and a process exit. Split out so tests can
+/// inspect the result without a real `run_tool` call tearing down the test
+/// process via `process::exit`.
+struct RunOutcome {
+    exit_code: i32,
+    /// `Ok` is the tool's own JSON result; `Err` is the message that becomes
+    /// both the stderr line and the `{"error": {"message": ...}}` on stdout.
+    body: Result<String, String>,
+}
+
+/// Shared body of `run_tool` (synth-1194): given already-parsed args (or the
+/// parse failure), runs `f` with panics caught, and serializes the outcome.
+/// `Args` isn't threaded through as a bound here since parsing already
+/// happened by the time this runs — only `Out`/`E` matter to what comes next.
+fn run_tool_inner<Args, Out, E>(args: Result<Args, String>, f: impl FnOnce(Args) -> Result<Out, E>) -> RunOutcome
+where
+    Out: Serialize,
+    E: std::fmt::Display,
+{
+    let args = match args {
+        Ok(a) => a,
+        Err(e) => return RunOutcome { exit_code: 2, body: Err(e) },
+    };
+
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(args))) {
+        Ok(Ok(out)) => match serde_json::to_string(&out) {
+            Ok(json) => RunOutcome { exit_code: 0, body: Ok(json) },
+            Err(e) => RunOutcome { exit_code: 1, body: Err(format!("failed to serialize result: {e}")) },
+        },
+        Ok(Err(e)) => RunOutcome { exit_code: 1, body: Err(e.to_string()) },
+        Err(panic) => RunOutcome { exit_code: 1, body: Err(panic_message(panic)) },
+    }
+}
+
+/// Best-effort extraction of a panic's message — `panic!("...")` and
+/// `.unwrap()`/`.expect("...")` payloads are `&str` or `String`; anything
+/// else (a custom payload type) falls back to a generic message rather than
+/// failing to report the panic at all.
+fn panic_message(panic: Box<dyn std::any::Any + Send>) -> String {
+    panic_payload_message(panic.as_ref())
+}
+
+/// Shared by `panic_message` (owned payload, from `catch_unwind`) and
+/// `install_panic_hook` (borrowed payload, from `PanicHookInfo`) — same
+/// best-effort `&str`/`String` extraction either way.
+fn panic_payload_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        return s.to_string();
+    }
+    if let Some(s) = payload.downcast_ref::<String>() {
+        return s.clone();
+    }
+    "tool panicked".to_string()
+}
+
+/// Installs a panic hook (synth-1205) that writes `{"panic": {"message":
+/// ..., "location": ...}}` to stderr before the default panic behavior runs.
+/// On wasm32 (built `panic = "abort"`), `run_tool_inner`'s `catch_unwind`
+/// never gets a chance to run — the guest just traps, and the sidecar
+/// reports a bare "WASM trap: unreachable" with no message — so this hook is
+/// the only place a panic's payload and location survive to reach the host.
+/// `src/sandbox.rs`'s trap handling scans captured stderr for this line and
+/// folds it into the error `Response` as `error_code: "tool_panic"`.
+fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let message = panic_payload_message(info.payload());
+        let location = info.location().map(|l| l.to_string());
+        eprintln!("{}", format_panic_line(&message, location.as_deref()));
+    }));
+}
+
+/// Builds the JSON line `install_panic_hook`'s hook writes, split out so its
+/// exact shape can be unit-tested without triggering a real panic (see
+/// `format_log_line` for the same split for regular log lines).
+fn format_panic_line(message: &str, location: Option<&str>) -> String {
+    serde_json::json!({"panic": {"message": message, "location": location}}).to_string()
+}
+
+/// Writes `outcome` to stdout/stderr and exits with its code. On failure,
+/// stdout still gets a well-formed `{"error": {"message": ...}}` JSON value
+/// (synth-1194) so a caller that always JSON-parses a tool's stdout doesn't
+/// have to special-case the error path.
+fn finish(outcome: RunOutcome) -> ! {
+    match &outcome.body {
+        Ok(json) => print!("{json}"),
+        Err(message) => {
+            eprintln!("error: {message}");
+            print!("{}", serde_json::json!({"error": {"message": message}}));
+        }
+    }
+    std::process::exit(outcome.exit_code);
+}
+
+/// Standard `main` body for a WASM tool (synth-1194): parses `Args` from
+/// stdin, runs `f`, and writes the JSON result — the ~20 lines of
+/// parse/call/print/exit-code boilerplate every tool's `main.rs` used to
+/// repeat by hand. A panic inside `f` is caught and reported as a structured
+/// error instead of an uncaught wasm trap.
+///
+/// Exit codes: 2 for a bad-args parse failure, 1 for anything else (a tool
+/// error from `f`, a panic, or a result that fails to serialize).
+///
+/// With the `usage` feature, a bad-args failure also gets the expected
+/// schema for `Args` appended to its message, same as `parse_args_or_usage`.
+#[cfg(feature = "usage")]
+pub fn run_tool<Args, Out, E>(f: impl FnOnce(Args) -> Result<Out, E>) -> !
+where
+    Args: for<'de> Deserialize<'de> + schemars::JsonSchema,
+    Out: Serialize,
+    E: std::fmt::Display,
+{
+    install_panic_hook();
+    finish(run_tool_inner(parse_args_or_usage::<Args>(), f))
+}
+
+/// See the `usage`-feature version of [`run_tool`] above for the full doc.
+#[cfg(not(feature = "usage"))]
+pub fn run_tool<Args, Out, E>(f: impl FnOnce(Args) -> Result<Out, E>) -> !
+where
+    Args: for<'de> Deserialize<'de>,
+    Out: Serialize,
+    E: std::fmt::Display,
+{
+    install_panic_hook();
+    finish(run_tool_inner(parse_args::<Args>(), f))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes `bytes` into IO_BUFFER at offset 0, the same place
+    /// `interpret_host_response` reads a payload back from.
+    fn write_io_buffer(bytes: &[u8]) {
+        let ptr = ensure_io_buffer(bytes.len());
+        unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len()) };
+    }
+
+    #[test]
+    fn interpret_host_response_parses_a_structured_error_payload() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let payload = serde_json::to_vec(&sentinel_ops::ErrorPayload {
+            code: "operation_error".to_string(),
+            message: "path '/etc/x' is not under an allowed directory".to_string(),
+        })
+        .unwrap();
+        write_io_buffer(&payload);
+
+        let err = interpret_host_response(-(1000 + payload.len() as i32)).unwrap_err();
+        match err {
+            HostError::Operation { code, message } => {
+                assert_eq!(code, "operation_error");
+                assert_eq!(message, "path '/etc/x' is not under an allowed directory");
+            }
+            other => panic!("expected Operation, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn interpret_host_response_falls_back_to_raw_text_for_a_non_json_payload() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        write_io_buffer(b"not json");
+
+        let err = interpret_host_response(-(1000 + 8)).unwrap_err();
+        match err {
+            HostError::Operation { code, message } => {
+                assert_eq!(code, "operation_error");
+                assert_eq!(message, "not json");
+            }
+            other => panic!("expected Operation, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn interpret_host_response_returns_a_bare_code_for_transport_failures() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let err = interpret_host_response(sentinel_ops::error_codes::UNKNOWN_OP).unwrap_err();
+        assert!(matches!(err, HostError::HostError(-1)));
+    }
+
+    #[test]
+    fn interpret_host_response_returns_response_bytes_on_success() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        write_io_buffer(b"{\"ok\":true}");
+        assert_eq!(interpret_host_response(11).unwrap(), b"{\"ok\":true}");
+    }
+
+    #[test]
+    fn interpret_host_response_rejects_a_length_past_io_buffer_capacity() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let too_long = (io_buffer_capacity() + 1) as i32;
+        let err = interpret_host_response(too_long).unwrap_err();
+        assert!(matches!(err, HostError::InvalidResponseLength(len) if len == too_long));
+    }
+
+    #[test]
+    fn interpret_host_response_rejects_an_unrecognized_negative_code() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let err = interpret_host_response(-6).unwrap_err();
+        assert!(matches!(err, HostError::InvalidResponseLength(-6)));
+    }
+
+    #[test]
+    fn interpret_host_response_rejects_an_error_payload_length_past_io_buffer_capacity() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let bogus = -(1000 + (io_buffer_capacity() + 1) as i32);
+        let err = interpret_host_response(bogus).unwrap_err();
+        assert!(matches!(err, HostError::InvalidResponseLength(len) if len == bogus));
+    }
+
+    /// Simulates a callback-style host that calls back into `call_host_raw`
+    /// (synth-1200) before its own outer call has finished — the re-entrancy
+    /// `call_host_raw` is meant to reject.
+    struct NestedCallTransport;
+
+    impl HostTransport for NestedCallTransport {
+        fn call(&mut self, _op: i32, _len: i32) -> i32 {
+            let nested = call_host_raw(Op::ReadFile, b"{}");
+            assert!(matches!(nested, Err(HostError::BufferBusy)), "expected BufferBusy, got {nested:?}");
+
+            let bytes = serde_json::to_vec(&serde_json::json!({"ok": true})).unwrap();
+            let ptr = ensure_io_buffer(bytes.len());
+            unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len()) };
+            bytes.len() as i32
+        }
+    }
+
+    #[test]
+    fn call_host_raw_rejects_a_nested_call_made_while_one_is_in_flight() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        set_host_transport(NestedCallTransport);
+        let response = call_host_raw(Op::WriteFile, b"{}").unwrap();
+        assert_eq!(response, br#"{"ok":true}"#);
+    }
+
+    #[test]
+    fn call_host_raw_releases_the_guard_after_returning_so_a_later_call_still_works() {
+        let mut mock = MockHost::new();
+        mock.expect(Op::ReadFile, serde_json::json!({}), serde_json::json!({"first": true}));
+        mock.expect(Op::ReadFile, serde_json::json!({}), serde_json::json!({"second": true}));
+        with_mock_host(mock, || {
+            assert_eq!(call_host_raw(Op::ReadFile, b"{}").unwrap(), br#"{"first":true}"#);
+            assert_eq!(call_host_raw(Op::ReadFile, b"{}").unwrap(), br#"{"second":true}"#);
+        });
+    }
+
+    #[test]
+    fn parse_args_str_reports_the_field_path_of_a_missing_field() {
+        #[derive(Debug, Deserialize)]
+        struct Args {
+            #[allow(dead_code)]
+            path: String,
+        }
+        let err = parse_args_str::<Args>("{}").unwrap_err();
+        assert!(err.contains("path"), "expected the field path in: {err}");
+        assert!(err.contains("received: \"{}\""), "expected the input preview in: {err}");
+    }
+
+    #[test]
+    fn parse_args_str_reports_the_field_path_of_a_type_mismatch() {
+        #[derive(Debug, Deserialize)]
+        struct Args {
+            #[allow(dead_code)]
+            count: u32,
+        }
+        let err = parse_args_str::<Args>(r#"{"count": "not a number"}"#).unwrap_err();
+        assert!(err.contains("count"), "expected the field path in: {err}");
+    }
+
+    #[test]
+    fn parse_args_str_truncates_a_long_preview_to_200_chars() {
+        #[derive(Debug, Deserialize)]
+        struct Args {
+            #[allow(dead_code)]
+            path: String,
+        }
+        let input = format!("{{\"junk\": \"{}\"", "x".repeat(500));
+        let err = parse_args_str::<Args>(&input).unwrap_err();
+        assert!(err.contains('x'), "expected part of the input echoed in: {err}");
+        assert!(!err.contains(&"x".repeat(300)), "preview should not include the whole 500-char value: {err}");
+    }
+
+    #[test]
+    fn read_stdin_bounded_rejects_input_over_the_limit() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::set_var("SENTINEL_TOOL_ARGS", "a".repeat(11));
+        let err = read_stdin_bounded(10).unwrap_err();
+        std::env::remove_var("SENTINEL_TOOL_ARGS");
+
+        assert!(err.contains("arguments too large"), "expected a size error in: {err}");
+        assert!(err.contains("11 bytes"), "expected the received size in: {err}");
+        assert!(err.contains("max 10"), "expected the limit in: {err}");
+    }
+
+    #[test]
+    fn read_stdin_bounded_reports_a_friendly_error_when_no_args_were_passed() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::remove_var("SENTINEL_TOOL_ARGS");
+        let err = read_stdin_bounded(DEFAULT_MAX_ARGS_BYTES).unwrap_err();
+
+        assert!(err.contains("run outside the sidecar"), "expected guidance in: {err}");
+    }
+
+    #[test]
+    fn read_stdin_bounded_reports_the_same_friendly_error_when_args_are_empty() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::set_var("SENTINEL_TOOL_ARGS", "");
+        let err = read_stdin_bounded(DEFAULT_MAX_ARGS_BYTES).unwrap_err();
+        std::env::remove_var("SENTINEL_TOOL_ARGS");
+
+        assert!(err.contains("run outside the sidecar"), "expected guidance in: {err}");
+    }
+
+    #[test]
+    fn context_parses_the_env_var_and_caches_it() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::set_var(
+            "SENTINEL_CONTEXT",
+            r#"{"allowed_paths":["/workspace"],"scratch_dir":"/workspace/.scratch","request_id":"req-1","timeout_ms":30000}"#,
+        );
+        let ctx = context().unwrap();
+        std::env::remove_var("SENTINEL_CONTEXT");
+
+        assert_eq!(ctx.allowed_paths, vec!["/workspace".to_string()]);
+        assert_eq!(ctx.scratch_dir, "/workspace/.scratch");
+        assert_eq!(ctx.request_id, "req-1");
+        assert_eq!(ctx.timeout_ms, 30_000);
+
+        // The env var is already gone, so this only succeeds if the first
+        // call cached the parsed value instead of re-reading it.
+        assert_eq!(context().unwrap().request_id, "req-1");
+    }
+
+    #[test]
+    fn context_reports_a_clear_error_when_the_env_var_is_not_set() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::remove_var("SENTINEL_CONTEXT");
+        let err = context().unwrap_err();
+        assert!(err.contains("SENTINEL_CONTEXT"), "expected the env var named in: {err}");
+    }
+
+    #[test]
+    fn resolve_path_leaves_an_absolute_path_untouched() {
+        assert_eq!(resolve_path("/tmp/x").unwrap(), "/tmp/x");
+    }
+
+    #[test]
+    fn resolve_path_joins_a_relative_path_to_the_first_allowed_root() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::set_var(
+            "SENTINEL_CONTEXT",
+            r#"{"allowed_paths":["/workspace"],"scratch_dir":"/workspace","request_id":"req-1","timeout_ms":30000}"#,
+        );
+        let resolved = resolve_path("notes.txt");
+        std::env::remove_var("SENTINEL_CONTEXT");
+
+        assert_eq!(resolved.unwrap(), "/workspace/notes.txt");
+    }
+
+    #[test]
+    fn resolve_path_surfaces_a_missing_context_for_a_relative_path() {
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::remove_var("SENTINEL_CONTEXT");
+        let err = resolve_path("notes.txt").unwrap_err();
+        assert!(err.contains("SENTINEL_CONTEXT"), "expected the env var named in: {err}");
+    }
+
+    #[test]
+    #[should_panic(expected = "MockHost: request mismatch")]
+    fn mock_host_panics_on_a_request_mismatch() {
+        let mut mock = MockHost::new();
+        mock.expect(Op::ReadFile, serde_json::json!({"path": "/tmp/x"}), serde_json::json!({}));
+        let _ = with_mock_host(mock, || read_file("/tmp/other"));
+    }
+
+    #[test]
+    fn read_file_returns_inline_content() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::ReadFile,
+            serde_json::json!({"path": "/tmp/x"}),
+            serde_json::json!({"content": "hi", "bytes": 2, "transfer_id": null}),
+        );
+        assert_eq!(with_mock_host(mock, || read_file("/tmp/x")).unwrap(), "hi");
+    }
+
+    #[test]
+    fn read_file_drains_a_spooled_transfer() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::ReadFile,
+            serde_json::json!({"path": "/tmp/big"}),
+            serde_json::json!({"content": null, "bytes": 5_000_000, "transfer_id": "t1"}),
+        );
+        mock.expect(
+            Op::ChunkFetch,
+            serde_json::json!({"transfer_id": "t1", "offset": 0}),
+            serde_json::json!({
+                "chunk_b64": base64::engine::general_purpose::STANDARD.encode(b"hello"),
+                "eof": true,
+            }),
+        );
+        assert_eq!(with_mock_host(mock, || read_file("/tmp/big")).unwrap(), "hello");
+    }
+
+    #[test]
+    fn file_reader_always_requests_a_stream() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::ReadFile,
+            serde_json::json!({"path": "/tmp/x", "stream": true}),
+            serde_json::json!({"content": null, "bytes": 2, "transfer_id": "t1"}),
+        );
+        mock.expect(
+            Op::ChunkFetch,
+            serde_json::json!({"transfer_id": "t1", "offset": 0}),
+            serde_json::json!({
+                "chunk_b64": base64::engine::general_purpose::STANDARD.encode(b"hi"),
+                "eof": true,
+            }),
+        );
+        let mut reader = with_mock_host(mock, || FileReader::open("/tmp/x")).unwrap();
+        let mut out = Vec::new();
+        std::io::Read::read_to_end(&mut reader, &mut out).unwrap();
+        assert_eq!(out, b"hi");
+    }
+
+    #[test]
+    fn file_reader_reassembles_multiple_chunks_and_stops_at_eof() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::ReadFile,
+            serde_json::json!({"path": "/tmp/big", "stream": true}),
+            serde_json::json!({"content": null, "bytes": 10, "transfer_id": "t1"}),
+        );
+        mock.expect(
+            Op::ChunkFetch,
+            serde_json::json!({"transfer_id": "t1", "offset": 0}),
+            serde_json::json!({
+                "chunk_b64": base64::engine::general_purpose::STANDARD.encode(b"hello "),
+                "eof": false,
+            }),
+        );
+        mock.expect(
+            Op::ChunkFetch,
+            serde_json::json!({"transfer_id": "t1", "offset": 6}),
+            serde_json::json!({
+                "chunk_b64": base64::engine::general_purpose::STANDARD.encode(b"world"),
+                "eof": true,
+            }),
+        );
+        let mut reader = with_mock_host(mock, || FileReader::open("/tmp/big")).unwrap();
+        let mut out = Vec::new();
+        std::io::Read::read_to_end(&mut reader, &mut out).unwrap();
+        assert_eq!(out, b"hello world");
+    }
+
+    #[test]
+    fn file_reader_lines_splits_on_newlines() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::ReadFile,
+            serde_json::json!({"path": "/tmp/lines", "stream": true}),
+            serde_json::json!({"content": null, "bytes": 12, "transfer_id": "t1"}),
+        );
+        mock.expect(
+            Op::ChunkFetch,
+            serde_json::json!({"transfer_id": "t1", "offset": 0}),
+            serde_json::json!({
+                "chunk_b64": base64::engine::general_purpose::STANDARD.encode(b"one\ntwo\nthree"),
+                "eof": true,
+            }),
+        );
+        let lines = with_mock_host(mock, || {
+            let reader = FileReader::open("/tmp/lines").map_err(|e| e.to_string())?;
+            reader.lines().collect::<std::io::Result<Vec<_>>>().map_err(|e| e.to_string())
+        })
+        .unwrap();
+        assert_eq!(lines, vec!["one".to_string(), "two".to_string(), "three".to_string()]);
+    }
+
+    #[test]
+    fn file_reader_surfaces_a_missing_transfer_id_as_an_error() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::ReadFile,
+            serde_json::json!({"path": "/tmp/x", "stream": true}),
+            serde_json::json!({"content": "hi", "bytes": 2, "transfer_id": null}),
+        );
+        let err = with_mock_host(mock, || FileReader::open("/tmp/x")).unwrap_err();
+        assert!(matches!(err, HostError::DeserializeError(_)));
+    }
+
+    #[test]
+    fn write_file_sends_inline_content() {
+        let mut mock = MockHost::new();
+        mock.expect_binary(
+            Op::WriteFile,
+            serde_json::json!({"path": "/tmp/x"}),
+            b"hi",
+            serde_json::json!({"written": 2, "path": "/tmp/x", "existed": false}),
+        );
+        assert_eq!(with_mock_host(mock, || write_file("/tmp/x", "hi")).unwrap(), 2);
+    }
+
+    #[test]
+    fn write_file_chunks_oversized_content() {
+        // One byte past INLINE_CONTENT_LIMIT, which is also CHUNK_APPEND_BYTES
+        // (see the doc comment on that constant) — so this splits into two
+        // ChunkAppend calls: a full chunk, then a 1-byte remainder.
+        let content = "a".repeat(INLINE_CONTENT_LIMIT + 1);
+        let mut mock = MockHost::new();
+        mock.expect(Op::ChunkBegin, serde_json::json!({}), serde_json::json!({"transfer_id": "t1"}));
+        for chunk in content.as_bytes().chunks(INLINE_CONTENT_LIMIT) {
+            mock.expect(
+                Op::ChunkAppend,
+                serde_json::json!({
+                    "transfer_id": "t1",
+                    "chunk_b64": base64::engine::general_purpose::STANDARD.encode(chunk),
+                }),
+                serde_json::json!({"received_bytes": chunk.len()}),
+            );
+        }
+        mock.expect(
+            Op::ChunkCommit,
+            serde_json::json!({"transfer_id": "t1", "op": "write_file", "path": "/tmp/big"}),
+            serde_json::json!({"written": INLINE_CONTENT_LIMIT + 1, "path": "/tmp/big", "existed": false}),
+        );
+        assert_eq!(
+            with_mock_host(mock, || write_file("/tmp/big", &content)).unwrap(),
+            INLINE_CONTENT_LIMIT + 1
+        );
+    }
+
+    #[test]
+    fn write_file_with_options_forwards_mode_and_create_dirs() {
+        let mut mock = MockHost::new();
+        mock.expect_binary(
+            Op::WriteFile,
+            serde_json::json!({"path": "/tmp/x", "mode": "append", "create_dirs": false}),
+            b"hi",
+            serde_json::json!({"written": 2, "path": "/tmp/x", "existed": true}),
+        );
+        let response =
+            with_mock_host(mock, || write_file_with_options("/tmp/x", b"hi", Some("append"), Some(false)))
+                .unwrap();
+        assert_eq!(response, messages::WriteFileResponse { written: 2, path: "/tmp/x".to_string(), existed: true });
+    }
+
+    #[test]
+    fn http_get_defaults_method_and_sends_no_body() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::HttpFetch,
+            serde_json::json!({"url": "https://example.com", "method": "GET", "headers": {}, "body": null}),
+            serde_json::json!({"status": 200, "body": "ok", "headers": {}}),
+        );
+        let response = with_mock_host(mock, || http_get("https://example.com")).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, "ok");
+    }
+
+    #[test]
+    fn http_fetch_sends_the_request_as_given() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::HttpFetch,
+            serde_json::json!({"url": "https://example.com", "method": "POST", "headers": {}, "body": "{}"}),
+            serde_json::json!({"status": 201, "body": "", "headers": {}}),
+        );
+        let request = messages::HttpFetchRequest {
+            url: "https://example.com".to_string(),
+            method: "POST".to_string(),
+            headers: Default::default(),
+            body: Some("{}".to_string()),
+            follow_redirects: None,
+            timeout_ms: None,
+            retries: None,
+            response_encoding: None,
+            save_to: None,
+        };
+        let response = with_mock_host(mock, || http_fetch(request)).unwrap();
+        assert_eq!(response.status, 201);
+    }
+
+    #[test]
+    fn http_fetch_with_raw_body_sends_the_body_as_an_envelope_payload() {
+        let mut mock = MockHost::new();
+        let mut headers = std::collections::HashMap::new();
+        headers.insert("content-type".to_string(), "application/octet-stream".to_string());
+        mock.expect_binary(
+            Op::HttpFetch,
+            serde_json::json!({"url": "https://example.com", "method": "POST", "headers": headers}),
+            &[0xff, 0x00, 0x10],
+            serde_json::json!({"status": 201, "body": "", "headers": {}}),
+        );
+        let response = with_mock_host(mock, || {
+            http_fetch_with_raw_body("https://example.com", "POST", headers.clone(), &[0xff, 0x00, 0x10])
+        })
+        .unwrap();
+        assert_eq!(response.status, 201);
+    }
+
+    #[test]
+    fn call_host_binary_round_trips_a_header_only_response_as_an_empty_payload() {
+        let mut mock = MockHost::new();
+        mock.expect_binary(
+            Op::WriteFile,
+            serde_json::json!({"path": "/tmp/x"}),
+            b"payload bytes",
+            serde_json::json!({"written": 13}),
+        );
+        let (header, payload) = with_mock_host(mock, || {
+            call_host_binary(Op::WriteFile, &serde_json::json!({"path": "/tmp/x"}), b"payload bytes")
+        })
+        .unwrap();
+        assert_eq!(header, serde_json::json!({"written": 13}));
+        assert!(payload.is_empty());
+    }
+
+    #[test]
+    fn shell_runs_the_given_command() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::ShellExec,
+            serde_json::json!({"command": "echo hi"}),
+            serde_json::json!({"stdout": "hi\n", "stderr": "", "exit_code": 0, "timed_out": false, "truncated": false}),
+        );
+        let response = with_mock_host(mock, || shell("echo hi")).unwrap();
+        assert_eq!(response.stdout, "hi\n");
+        assert_eq!(response.exit_code, 0);
+    }
+
+    #[test]
+    fn get_credential_returns_just_the_value() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::GetCredential,
+            serde_json::json!({"name": "api_key"}),
+            serde_json::json!({"name": "api_key", "value": "secret"}),
+        );
+        assert_eq!(with_mock_host(mock, || get_credential("api_key")).unwrap(), "secret");
+    }
+
+    #[test]
+    fn get_credential_surfaces_an_operation_error() {
+        let mut mock = MockHost::new();
+        mock.expect_error(
+            Op::GetCredential,
+            serde_json::json!({"name": "api_key"}),
+            "operation_error",
+            "no credential named 'api_key'",
+        );
+        let err = with_mock_host(mock, || get_credential("api_key")).unwrap_err();
+        match err {
+            HostError::Operation { code, message } => {
+                assert_eq!(code, "operation_error");
+                assert_eq!(message, "no credential named 'api_key'");
+            }
+            other => panic!("expected Operation, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn with_credential_passes_the_real_value_to_the_closure() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::GetCredential,
+            serde_json::json!({"name": "api_key"}),
+            serde_json::json!({"name": "api_key", "value": "secret"}),
+        );
+        let seen = with_mock_host(mock, || with_credential("api_key", |value| value.to_string())).unwrap();
+        assert_eq!(seen, "secret");
+    }
+
+    #[test]
+    fn with_credential_surfaces_a_credential_fetch_failure_without_calling_the_closure() {
+        let mut mock = MockHost::new();
+        mock.expect_error(
+            Op::GetCredential,
+            serde_json::json!({"name": "missing"}),
+            "operation_error",
+            "no credential named 'missing'",
+        );
+        let err = with_mock_host(mock, || with_credential("missing", |_| unreachable!())).unwrap_err();
+        assert!(matches!(err, HostError::Operation { .. }));
+    }
+
+    #[test]
+    fn list_dir_returns_the_entries() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::ListDir,
+            serde_json::json!({"path": "/workspace"}),
+            serde_json::json!({"entries": [
+                {"name": "a.txt", "is_dir": false, "size": 5},
+                {"name": "sub", "is_dir": true, "size": 0},
+            ]}),
+        );
+        let entries = with_mock_host(mock, || list_dir("/workspace")).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "a.txt");
+        assert!(entries[1].is_dir);
+    }
+
+    #[test]
+    fn delete_file_returns_whether_it_existed() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::DeleteFile,
+            serde_json::json!({"path": "/workspace/x.txt"}),
+            serde_json::json!({"path": "/workspace/x.txt", "existed": true}),
+        );
+        assert!(with_mock_host(mock, || delete_file("/workspace/x.txt")).unwrap());
+    }
+
+    #[test]
+    fn stat_file_returns_the_full_response() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::StatFile,
+            serde_json::json!({"path": "/workspace/x.txt"}),
+            serde_json::json!({"path": "/workspace/x.txt", "exists": true, "is_dir": false, "size": 128}),
+        );
+        let response = with_mock_host(mock, || stat_file("/workspace/x.txt")).unwrap();
+        assert!(response.exists);
+        assert_eq!(response.size, 128);
+    }
+
+    #[test]
+    fn read_env_returns_the_value_when_set() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::ReadEnv,
+            serde_json::json!({"name": "PATH"}),
+            serde_json::json!({"name": "PATH", "value": "/usr/bin"}),
+        );
+        assert_eq!(with_mock_host(mock, || read_env("PATH")).unwrap(), Some("/usr/bin".to_string()));
+    }
+
+    #[test]
+    fn read_env_returns_none_when_unset() {
+        let mut mock = MockHost::new();
+        mock.expect(Op::ReadEnv, serde_json::json!({"name": "UNSET_VAR"}), serde_json::json!({"name": "UNSET_VAR"}));
+        assert_eq!(with_mock_host(mock, || read_env("UNSET_VAR")).unwrap(), None);
+    }
+
+    #[test]
+    fn secret_string_debug_and_display_are_redacted() {
+        let secret = SecretString::new("hunter2".to_string());
+        assert_eq!(format!("{secret:?}"), "SecretString(\"[REDACTED]\")");
+        assert_eq!(format!("{secret}"), "[REDACTED]");
+        assert_eq!(secret.expose_secret(), "hunter2");
+    }
+
+    #[test]
+    fn report_progress_sends_message_and_percent() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::Progress,
+            serde_json::json!({"message": "halfway there", "percent": 50, "data": null}),
+            serde_json::json!({}),
+        );
+        assert!(with_mock_host(mock, || report_progress("halfway there", Some(50))).is_ok());
+    }
+
+    #[test]
+    fn report_progress_omits_percent_when_not_given() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::Progress,
+            serde_json::json!({"message": "still working", "percent": null, "data": null}),
+            serde_json::json!({}),
+        );
+        assert!(with_mock_host(mock, || report_progress("still working", None)).is_ok());
+    }
+
+    #[test]
+    fn format_log_line_emits_the_shape_the_host_parser_expects() {
+        let line = format_log_line("info", "starting fetch");
+        let parsed: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed["level"], "info");
+        assert_eq!(parsed["msg"], "starting fetch");
+    }
+
+    #[test]
+    fn log_macros_format_their_arguments_like_println() {
+        assert_eq!(format_log_line("debug", &format!("{} of {}", 2, 5)), format_log_line("debug", "2 of 5"));
+        assert_eq!(format_log_line("warn", &format!("disk at {}%", 90)), format_log_line("warn", "disk at 90%"));
+    }
+
+    #[test]
+    fn format_panic_line_emits_the_shape_the_host_parser_expects() {
+        let line = format_panic_line("kaboom", Some("src/main.rs:12:5"));
+        let parsed: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed["panic"]["message"], "kaboom");
+        assert_eq!(parsed["panic"]["location"], "src/main.rs:12:5");
+    }
+
+    #[test]
+    fn format_panic_line_reports_a_missing_location_as_null() {
+        let line = format_panic_line("kaboom", None);
+        let parsed: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert!(parsed["panic"]["location"].is_null());
+    }
+
+    #[test]
+    fn install_panic_hook_writes_the_structured_line_before_unwinding() {
+        // A stub tool whose logic panics: with the hook installed, the panic
+        // still unwinds into run_tool_inner's catch_unwind as before (this
+        // is what makes native tests of a panicking tool possible at all),
+        // but the hook itself has already run and would have put the same
+        // message on the guest's real stderr, which is all `execute_wasm_sync`
+        // captures on wasm32 where `catch_unwind` never fires.
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(None));
+        let seen_in_hook = seen.clone();
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            *seen_in_hook.lock().unwrap() = Some(format_panic_line(&panic_payload_message(info.payload()), None));
+        }));
+        let outcome = run_tool_inner(Ok(()), |_: ()| -> Result<(), String> { panic!("kaboom") });
+        std::panic::set_hook(previous);
+
+        assert_eq!(outcome.exit_code, 1);
+        let line = seen.lock().unwrap().clone().expect("hook should have run");
+        let parsed: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed["panic"]["message"], "kaboom");
+    }
+
+    #[test]
+    fn call_host_with_retry_retries_an_idempotent_op_until_it_succeeds() {
+        let mut mock = MockHost::new();
+        let request = serde_json::json!({"path": "/tmp/x"});
+        mock.expect_error(Op::ReadFile, request.clone(), "operation_error", "try again");
+        mock.expect(Op::Sleep, serde_json::json!({"millis": 10}), serde_json::json!({}));
+        mock.expect_error(Op::ReadFile, request.clone(), "operation_error", "try again");
+        mock.expect(Op::Sleep, serde_json::json!({"millis": 10}), serde_json::json!({}));
+        mock.expect(
+            Op::ReadFile,
+            request,
+            serde_json::json!({"content": "hi", "bytes": 2, "transfer_id": null}),
+        );
+
+        let policy = RetryPolicy {
+            attempts: 3,
+            backoff_ms: 10,
+            retry_on: |_| true,
+            retry_non_idempotent: false,
+        };
+        let response: messages::ReadFileResponse = with_mock_host(mock, || {
+            call_host_with_retry(
+                Op::ReadFile,
+                &messages::ReadFileRequest { path: "/tmp/x".to_string(), stream: false },
+                &policy,
+            )
+        })
+        .unwrap();
+        assert_eq!(response.content.as_deref(), Some("hi"));
+    }
+
+    #[test]
+    fn call_host_with_retry_does_not_retry_a_non_idempotent_op_by_default() {
+        let mut mock = MockHost::new();
+        mock.expect_error(
+            Op::WriteFile,
+            serde_json::json!({"path": "/tmp/x", "content": "data"}),
+            "operation_error",
+            "disk full",
+        );
+
+        let err = with_mock_host(mock, || {
+            call_host_with_retry::<_, messages::WriteFileResponse>(
+                Op::WriteFile,
+                &messages::WriteFileRequest {
+                    path: "/tmp/x".to_string(),
+                    content: "data".to_string(),
+                    mode: None,
+                    create_dirs: None,
+                },
+                &RetryPolicy::default(),
+            )
+        })
+        .unwrap_err();
+        match err {
+            HostError::Operation { message, .. } => assert_eq!(message, "disk full"),
+            other => panic!("expected Operation, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn run_tool_inner_writes_the_result_json_on_success() {
+        struct Args {
+            name: String,
+        }
+
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::GetCredential,
+            serde_json::json!({"name": "api_key"}),
+            serde_json::json!({"name": "api_key", "value": "secret"}),
+        );
+        let outcome = with_mock_host(mock, || {
+            run_tool_inner(Ok(Args { name: "api_key".to_string() }), |args: Args| get_credential(&args.name))
+        });
+        assert_eq!(outcome.exit_code, 0);
+        assert_eq!(outcome.body.unwrap(), "\"secret\"");
+    }
+
+    #[test]
+    fn run_tool_inner_reports_a_tool_error_with_exit_code_one() {
+        let outcome =
+            run_tool_inner(Ok(()), |_: ()| -> Result<(), String> { Err("disk full".to_string()) });
+        assert_eq!(outcome.exit_code, 1);
+        assert_eq!(outcome.body.unwrap_err(), "disk full");
+    }
+
+    #[test]
+    fn run_tool_inner_reports_a_bad_args_parse_failure_with_exit_code_two() {
+        let outcome =
+            run_tool_inner::<(), (), String>(Err("missing field `path`".to_string()), |_| Ok(()));
+        assert_eq!(outcome.exit_code, 2);
+        assert!(outcome.body.unwrap_err().contains("path"));
+    }
+
+    #[test]
+    fn run_tool_inner_catches_a_panic_and_reports_it_as_a_tool_error() {
+        let outcome = run_tool_inner(Ok(()), |_: ()| -> Result<(), String> { panic!("kaboom") });
+        assert_eq!(outcome.exit_code, 1);
+        assert_eq!(outcome.body.unwrap_err(), "kaboom");
+    }
+
+    #[cfg(feature = "usage")]
+    #[test]
+    fn parse_args_or_usage_prints_the_schema_on_failure() {
+        #[derive(Debug, Deserialize, schemars::JsonSchema)]
+        struct Args {
+            #[allow(dead_code)]
+            path: String,
+        }
+
+        let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::set_var("SENTINEL_TOOL_ARGS", "{}");
+        let err = parse_args_or_usage::<Args>().unwrap_err();
+        std::env::remove_var("SENTINEL_TOOL_ARGS");
+
+        assert!(err.contains("path"), "expected the field path in: {err}");
+        assert!(err.contains("expected args matching this schema"), "expected a schema dump in: {err}");
+        assert!(err.contains("\"path\""), "expected the schema to mention the path field: {err}");
+    }
+}
+
@@ -0,0 +1,470 @@
+//! Library entry point for the file-read WASM tool.
+//!
+//! Splitting `read_file_response` out of `main` (synth-1190) lets it run
+//! natively against `tool_common::MockHost` instead of only being testable
+//! by compiling to wasm and running under the real sidecar.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "usage", derive(schemars::JsonSchema))]
+pub struct Args {
+    /// A single file to read. Mutually exclusive with `paths` (synth-1209).
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Several files to read in one invocation (synth-1209), one ReadFile
+    /// host call per path. Mutually exclusive with `path`. `max_bytes`,
+    /// `head`, and `tail` only apply to the single-`path` form.
+    #[serde(default)]
+    pub paths: Option<Vec<String>>,
+    /// Read at most this many bytes from the start of the file (synth-1204),
+    /// streaming via `tool_common::FileReader` instead of pulling the whole
+    /// file inline. Mutually exclusive with `head`/`tail`.
+    #[serde(default)]
+    pub max_bytes: Option<usize>,
+    /// Read only the first this-many lines (synth-1204). Mutually exclusive
+    /// with `max_bytes`/`tail`.
+    #[serde(default)]
+    pub head: Option<usize>,
+    /// Read only the last this-many lines (synth-1204). Streams the whole
+    /// file — there's no way to know where the last N lines start without
+    /// reading up to them — but never holds more than N lines in memory.
+    /// Mutually exclusive with `max_bytes`/`head`.
+    #[serde(default)]
+    pub tail: Option<usize>,
+    /// When reading `paths` (synth-1209), abort on the first file that
+    /// fails instead of recording its error and continuing with the rest.
+    #[serde(default)]
+    pub fail_fast: bool,
+}
+
+/// A single file's outcome within a `paths` response (synth-1209).
+/// `content`/`bytes` are set on success, `error` on failure; exactly one of
+/// the two is present.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct FileResult {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// `Single` is the original, pre-synth-1209 shape returned for `args.path`,
+/// kept byte-for-byte compatible with callers that only know about it.
+/// `Multi` is returned for `args.paths`. Untagged so the wire shape depends
+/// only on which of `path`/`paths` was given, not on an extra tag field.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum Response {
+    Single { content: String, bytes: usize },
+    Multi { files: Vec<FileResult>, truncated: bool },
+}
+
+/// Dispatches on whether `args.path` or `args.paths` was given (synth-1209);
+/// exactly one of the two is required. See `read_single`/`read_many` for the
+/// respective implementations.
+pub fn read_file_response(args: Args) -> Result<Response, String> {
+    match (args.path, args.paths) {
+        (Some(_), Some(_)) => Err("only one of path or paths may be given".to_string()),
+        (None, None) => Err("path or paths is required".to_string()),
+        (Some(path), None) => read_single(path, args.max_bytes, args.head, args.tail),
+        (None, Some(paths)) => read_many(paths, args.fail_fast),
+    }
+}
+
+/// Reads `path`, in full via `tool_common::read_file` (which handles the
+/// Op::ReadFile round trip and the chunked Op::ChunkFetch drain for a
+/// spooled file transparently, synth-1185/synth-1189) unless `max_bytes`,
+/// `head`, or `tail` narrows it to a streamed slice (synth-1204). A relative
+/// `path` is resolved against the injected `ExecutionContext`'s allowed
+/// roots first (synth-1202), since the host itself rejects a non-absolute
+/// path outright.
+fn read_single(
+    path: String,
+    max_bytes: Option<usize>,
+    head: Option<usize>,
+    tail: Option<usize>,
+) -> Result<Response, String> {
+    let path = tool_common::resolve_path(&path)?;
+    let given = [max_bytes.is_some(), head.is_some(), tail.is_some()];
+    if given.iter().filter(|g| **g).count() > 1 {
+        return Err("only one of max_bytes, head, or tail may be given".to_string());
+    }
+
+    let content = if let Some(max_bytes) = max_bytes {
+        read_max_bytes(&path, max_bytes)?
+    } else if let Some(head) = head {
+        read_head(&path, head)?
+    } else if let Some(tail) = tail {
+        read_tail(&path, tail)?
+    } else {
+        tool_common::read_file(&path).map_err(|e| format!("host call failed: {e}"))?
+    };
+    Ok(Response::Single { bytes: content.len(), content })
+}
+
+/// Reads each of `paths` in full, one ReadFile host call apiece (synth-1209).
+/// A missing/denied file becomes that entry's `error` rather than failing
+/// the whole invocation, unless `fail_fast` is set. The running total is
+/// capped at `tool_common::INLINE_CONTENT_LIMIT` — the same threshold
+/// `write_file` uses to stay clear of the IO-buffer round trip size — after
+/// which remaining files are marked skipped and `truncated` is set.
+fn read_many(paths: Vec<String>, fail_fast: bool) -> Result<Response, String> {
+    let mut files = Vec::with_capacity(paths.len());
+    let mut aggregate_bytes = 0usize;
+    let mut truncated = false;
+    for path in paths {
+        if truncated {
+            files.push(FileResult {
+                path,
+                content: None,
+                bytes: None,
+                error: Some("skipped: aggregate size limit reached".to_string()),
+            });
+            continue;
+        }
+        match read_one(&path) {
+            Ok((_content, bytes)) if aggregate_bytes + bytes > tool_common::INLINE_CONTENT_LIMIT => {
+                truncated = true;
+                files.push(FileResult {
+                    path,
+                    content: None,
+                    bytes: None,
+                    error: Some("skipped: aggregate size limit reached".to_string()),
+                });
+            }
+            Ok((content, bytes)) => {
+                aggregate_bytes += bytes;
+                files.push(FileResult { path, content: Some(content), bytes: Some(bytes), error: None });
+            }
+            Err(e) if fail_fast => return Err(e),
+            Err(e) => files.push(FileResult { path, content: None, bytes: None, error: Some(e) }),
+        }
+    }
+    Ok(Response::Multi { files, truncated })
+}
+
+fn read_one(path: &str) -> Result<(String, usize), String> {
+    let path = tool_common::resolve_path(path)?;
+    let content = tool_common::read_file(&path).map_err(|e| format!("host call failed: {e}"))?;
+    let bytes = content.len();
+    Ok((content, bytes))
+}
+
+fn read_max_bytes(path: &str, max_bytes: usize) -> Result<String, String> {
+    use std::io::Read;
+    let reader = tool_common::FileReader::open(path).map_err(|e| format!("host call failed: {e}"))?;
+    let mut buf = Vec::new();
+    reader.take(max_bytes as u64).read_to_end(&mut buf).map_err(|e| format!("read failed: {e}"))?;
+    String::from_utf8(buf).map_err(|e| format!("file is not valid UTF-8: {e}"))
+}
+
+fn read_head(path: &str, lines: usize) -> Result<String, String> {
+    let reader = tool_common::FileReader::open(path).map_err(|e| format!("host call failed: {e}"))?;
+    let lines: Vec<String> =
+        reader.lines().take(lines).collect::<std::io::Result<Vec<_>>>().map_err(|e| format!("read failed: {e}"))?;
+    Ok(lines.join("\n"))
+}
+
+fn read_tail(path: &str, lines: usize) -> Result<String, String> {
+    if lines == 0 {
+        return Ok(String::new());
+    }
+    let reader = tool_common::FileReader::open(path).map_err(|e| format!("host call failed: {e}"))?;
+    let mut ring: std::collections::VecDeque<String> = std::collections::VecDeque::with_capacity(lines);
+    for line in reader.lines() {
+        let line = line.map_err(|e| format!("read failed: {e}"))?;
+        if ring.len() == lines {
+            ring.pop_front();
+        }
+        ring.push_back(line);
+    }
+    Ok(Vec::from(ring).join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use tool_common::{MockHost, Op};
+
+    fn single_path_args(path: &str) -> Args {
+        Args {
+            path: Some(path.to_string()),
+            paths: None,
+            max_bytes: None,
+            head: None,
+            tail: None,
+            fail_fast: false,
+        }
+    }
+
+    #[test]
+    fn reads_inline_content() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::ReadFile,
+            serde_json::json!({"path": "/tmp/x"}),
+            serde_json::json!({"content": "hi", "bytes": 2, "transfer_id": null}),
+        );
+        let response =
+            tool_common::with_mock_host(mock, || read_file_response(single_path_args("/tmp/x"))).unwrap();
+        assert_eq!(response, Response::Single { content: "hi".to_string(), bytes: 2 });
+    }
+
+    #[test]
+    fn drains_a_spooled_transfer() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::ReadFile,
+            serde_json::json!({"path": "/tmp/big"}),
+            serde_json::json!({"content": null, "bytes": 5_000_000, "transfer_id": "t1"}),
+        );
+        mock.expect(
+            Op::ChunkFetch,
+            serde_json::json!({"transfer_id": "t1", "offset": 0}),
+            serde_json::json!({"chunk_b64": "aGVsbG8=", "eof": true}),
+        );
+        let response =
+            tool_common::with_mock_host(mock, || read_file_response(single_path_args("/tmp/big"))).unwrap();
+        assert_eq!(response, Response::Single { content: "hello".to_string(), bytes: 5 });
+    }
+
+    #[test]
+    fn surfaces_a_host_error() {
+        let mut mock = MockHost::new();
+        mock.expect_error(
+            Op::ReadFile,
+            serde_json::json!({"path": "/tmp/missing"}),
+            "operation_error",
+            "no such file",
+        );
+        let err = tool_common::with_mock_host(mock, || read_file_response(single_path_args("/tmp/missing")))
+            .unwrap_err();
+        assert!(err.contains("no such file"));
+    }
+
+    #[test]
+    fn requires_either_path_or_paths() {
+        let err = read_file_response(Args {
+            path: None,
+            paths: None,
+            max_bytes: None,
+            head: None,
+            tail: None,
+            fail_fast: false,
+        })
+        .unwrap_err();
+        assert!(err.contains("path"), "expected the field path in: {err}");
+    }
+
+    #[test]
+    fn rejects_both_path_and_paths_at_once() {
+        let err = read_file_response(Args {
+            path: Some("/tmp/x".to_string()),
+            paths: Some(vec!["/tmp/y".to_string()]),
+            max_bytes: None,
+            head: None,
+            tail: None,
+            fail_fast: false,
+        })
+        .unwrap_err();
+        assert!(err.contains("only one of"));
+    }
+
+    #[test]
+    fn resolves_a_relative_path_against_the_first_allowed_root() {
+        std::env::set_var(
+            "SENTINEL_CONTEXT",
+            r#"{"allowed_paths":["/workspace"],"scratch_dir":"/workspace","request_id":"req-1","timeout_ms":30000}"#,
+        );
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::ReadFile,
+            serde_json::json!({"path": "/workspace/notes.txt"}),
+            serde_json::json!({"content": "hi", "bytes": 2, "transfer_id": null}),
+        );
+        let response = tool_common::with_mock_host(mock, || read_file_response(single_path_args("notes.txt")));
+        std::env::remove_var("SENTINEL_CONTEXT");
+        assert_eq!(response.unwrap(), Response::Single { content: "hi".to_string(), bytes: 2 });
+    }
+
+    fn stream_mock(path: &str, content: &[u8]) -> MockHost {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::ReadFile,
+            serde_json::json!({"path": path, "stream": true}),
+            serde_json::json!({"content": null, "bytes": content.len(), "transfer_id": "t1"}),
+        );
+        mock.expect(
+            Op::ChunkFetch,
+            serde_json::json!({"transfer_id": "t1", "offset": 0}),
+            serde_json::json!({
+                "chunk_b64": base64::engine::general_purpose::STANDARD.encode(content),
+                "eof": true,
+            }),
+        );
+        mock
+    }
+
+    #[test]
+    fn max_bytes_truncates_to_the_given_byte_count() {
+        let mock = stream_mock("/tmp/x", b"hello world");
+        let response = tool_common::with_mock_host(mock, || {
+            read_file_response(Args { max_bytes: Some(5), ..single_path_args("/tmp/x") })
+        })
+        .unwrap();
+        assert_eq!(response, Response::Single { content: "hello".to_string(), bytes: 5 });
+    }
+
+    #[test]
+    fn head_returns_only_the_first_n_lines() {
+        let mock = stream_mock("/tmp/x", b"one\ntwo\nthree\nfour");
+        let response = tool_common::with_mock_host(mock, || {
+            read_file_response(Args { head: Some(2), ..single_path_args("/tmp/x") })
+        })
+        .unwrap();
+        let Response::Single { content, .. } = response else { panic!("expected Response::Single") };
+        assert_eq!(content, "one\ntwo");
+    }
+
+    #[test]
+    fn tail_returns_only_the_last_n_lines() {
+        let mock = stream_mock("/tmp/x", b"one\ntwo\nthree\nfour");
+        let response = tool_common::with_mock_host(mock, || {
+            read_file_response(Args { tail: Some(2), ..single_path_args("/tmp/x") })
+        })
+        .unwrap();
+        let Response::Single { content, .. } = response else { panic!("expected Response::Single") };
+        assert_eq!(content, "three\nfour");
+    }
+
+    #[test]
+    fn tail_of_a_file_shorter_than_n_returns_the_whole_file() {
+        let mock = stream_mock("/tmp/x", b"one\ntwo");
+        let response = tool_common::with_mock_host(mock, || {
+            read_file_response(Args { tail: Some(5), ..single_path_args("/tmp/x") })
+        })
+        .unwrap();
+        let Response::Single { content, .. } = response else { panic!("expected Response::Single") };
+        assert_eq!(content, "one\ntwo");
+    }
+
+    #[test]
+    fn rejects_more_than_one_mode_at_once() {
+        let err =
+            read_file_response(Args { max_bytes: Some(1), head: Some(1), ..single_path_args("/tmp/x") })
+                .unwrap_err();
+        assert!(err.contains("only one of"));
+    }
+
+    fn multi_path_args(paths: &[&str], fail_fast: bool) -> Args {
+        Args {
+            path: None,
+            paths: Some(paths.iter().map(|p| p.to_string()).collect()),
+            max_bytes: None,
+            head: None,
+            tail: None,
+            fail_fast,
+        }
+    }
+
+    #[test]
+    fn reads_multiple_files_and_reports_a_mixed_success_and_failure_list() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::ReadFile,
+            serde_json::json!({"path": "/tmp/a"}),
+            serde_json::json!({"content": "aaa", "bytes": 3, "transfer_id": null}),
+        );
+        mock.expect_error(
+            Op::ReadFile,
+            serde_json::json!({"path": "/tmp/missing"}),
+            "operation_error",
+            "no such file",
+        );
+        mock.expect(
+            Op::ReadFile,
+            serde_json::json!({"path": "/tmp/b"}),
+            serde_json::json!({"content": "bbb", "bytes": 3, "transfer_id": null}),
+        );
+        let response = tool_common::with_mock_host(mock, || {
+            read_file_response(multi_path_args(&["/tmp/a", "/tmp/missing", "/tmp/b"], false))
+        })
+        .unwrap();
+        assert_eq!(
+            response,
+            Response::Multi {
+                files: vec![
+                    FileResult {
+                        path: "/tmp/a".to_string(),
+                        content: Some("aaa".to_string()),
+                        bytes: Some(3),
+                        error: None,
+                    },
+                    FileResult {
+                        path: "/tmp/missing".to_string(),
+                        content: None,
+                        bytes: None,
+                        error: Some("host call failed: operation_error: no such file".to_string()),
+                    },
+                    FileResult {
+                        path: "/tmp/b".to_string(),
+                        content: Some("bbb".to_string()),
+                        bytes: Some(3),
+                        error: None,
+                    },
+                ],
+                truncated: false,
+            }
+        );
+    }
+
+    #[test]
+    fn fail_fast_aborts_the_whole_invocation_on_the_first_error() {
+        let mut mock = MockHost::new();
+        mock.expect_error(
+            Op::ReadFile,
+            serde_json::json!({"path": "/tmp/missing"}),
+            "operation_error",
+            "no such file",
+        );
+        let err = tool_common::with_mock_host(mock, || {
+            read_file_response(multi_path_args(&["/tmp/missing", "/tmp/b"], true))
+        })
+        .unwrap_err();
+        assert!(err.contains("no such file"));
+    }
+
+    #[test]
+    fn truncates_the_aggregate_once_the_inline_content_limit_is_exceeded() {
+        let almost_all_of_it = "a".repeat(tool_common::INLINE_CONTENT_LIMIT - 10);
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::ReadFile,
+            serde_json::json!({"path": "/tmp/big"}),
+            serde_json::json!({
+                "content": almost_all_of_it,
+                "bytes": tool_common::INLINE_CONTENT_LIMIT - 10,
+                "transfer_id": null,
+            }),
+        );
+        mock.expect(
+            Op::ReadFile,
+            serde_json::json!({"path": "/tmp/tips-it-over"}),
+            serde_json::json!({"content": "0123456789012345", "bytes": 16, "transfer_id": null}),
+        );
+        let response = tool_common::with_mock_host(mock, || {
+            read_file_response(multi_path_args(&["/tmp/big", "/tmp/tips-it-over", "/tmp/never-fetched"], false))
+        })
+        .unwrap();
+        let Response::Multi { files, truncated } = response else { panic!("expected Response::Multi") };
+        assert!(truncated);
+        assert_eq!(files[0].bytes, Some(tool_common::INLINE_CONTENT_LIMIT - 10));
+        assert_eq!(files[1].error.as_deref(), Some("skipped: aggregate size limit reached"));
+        assert_eq!(files[2].error.as_deref(), Some("skipped: aggregate size limit reached"));
+    }
+}
@@ -0,0 +1,278 @@
+//! Library entry point for the file-write WASM tool.
+//!
+//! Splitting `write_file_response` out of `main` (synth-1190) lets it run
+//! natively against `tool_common::MockHost` instead of only being testable
+//! by compiling to wasm and running under the real sidecar.
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "usage", derive(schemars::JsonSchema))]
+pub struct Args {
+    pub path: String,
+    /// UTF-8 text to write. Mutually exclusive with `content_base64`
+    /// (synth-1210); exactly one is required.
+    #[serde(default)]
+    pub content: Option<String>,
+    /// Base64-encoded bytes to write, for content that isn't valid UTF-8
+    /// (synth-1210). Mutually exclusive with `content`.
+    #[serde(default)]
+    pub content_base64: Option<String>,
+    /// `overwrite` (default) truncates the file before writing; `append`
+    /// appends to it, creating the file if it doesn't exist (synth-1210).
+    #[serde(default)]
+    pub mode: WriteMode,
+    /// Create missing parent directories before writing (synth-1210).
+    #[serde(default = "default_create_dirs")]
+    pub create_dirs: bool,
+}
+
+fn default_create_dirs() -> bool {
+    true
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[cfg_attr(feature = "usage", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum WriteMode {
+    #[default]
+    Overwrite,
+    Append,
+}
+
+impl WriteMode {
+    fn as_wire_str(&self) -> &'static str {
+        match self {
+            WriteMode::Overwrite => "overwrite",
+            WriteMode::Append => "append",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Response {
+    pub written: usize,
+    /// The absolute path the host actually wrote to (synth-1210).
+    pub path: String,
+    /// Whether a file already existed at `path` before this write
+    /// (synth-1210).
+    pub existed: bool,
+}
+
+/// Writes `args.content`/`args.content_base64` to `args.path` via
+/// `tool_common::write_file_with_options`, which picks Op::WriteFile or the
+/// chunked Begin/Append/Commit dance (synth-1185) depending on size
+/// (synth-1189), now also carrying `mode` and `create_dirs` (synth-1210). A
+/// relative `args.path` is resolved against the injected `ExecutionContext`'s
+/// allowed roots first (synth-1202), since the host itself rejects a
+/// non-absolute path outright.
+pub fn write_file_response(args: Args) -> Result<Response, String> {
+    let content = match (args.content, args.content_base64) {
+        (Some(_), Some(_)) => return Err("only one of content or content_base64 may be given".to_string()),
+        (None, None) => return Err("content or content_base64 is required".to_string()),
+        (Some(text), None) => text.into_bytes(),
+        (None, Some(encoded)) => base64::engine::general_purpose::STANDARD
+            .decode(&encoded)
+            .map_err(|e| format!("content_base64 is not valid base64: {e}"))?,
+    };
+
+    let path = tool_common::resolve_path(&args.path)?;
+    // Only sent when they differ from the host's own default, so a plain
+    // write without these new args looks exactly like it did before
+    // synth-1210 on the wire.
+    let mode = (args.mode != WriteMode::Overwrite).then(|| args.mode.as_wire_str());
+    let create_dirs = (!args.create_dirs).then_some(false);
+    let response = tool_common::write_file_with_options(&path, &content, mode, create_dirs)
+        .map_err(|e| format!("host call failed: {e}"))?;
+    Ok(Response { written: response.written, path: response.path, existed: response.existed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tool_common::{MockHost, Op};
+
+    fn text_args(path: &str, content: &str) -> Args {
+        Args {
+            path: path.to_string(),
+            content: Some(content.to_string()),
+            content_base64: None,
+            mode: WriteMode::Overwrite,
+            create_dirs: true,
+        }
+    }
+
+    #[test]
+    fn writes_inline_content() {
+        let mut mock = MockHost::new();
+        mock.expect_binary(
+            Op::WriteFile,
+            serde_json::json!({"path": "/tmp/x"}),
+            b"hi",
+            serde_json::json!({"written": 2, "path": "/tmp/x", "existed": false}),
+        );
+        let response =
+            tool_common::with_mock_host(mock, || write_file_response(text_args("/tmp/x", "hi"))).unwrap();
+        assert_eq!(response, Response { written: 2, path: "/tmp/x".to_string(), existed: false });
+    }
+
+    #[test]
+    fn chunks_oversized_content() {
+        let content = "a".repeat(tool_common::INLINE_CONTENT_LIMIT + 1);
+        let mut mock = MockHost::new();
+        mock.expect(Op::ChunkBegin, serde_json::json!({}), serde_json::json!({"transfer_id": "t1"}));
+        for chunk in content.as_bytes().chunks(tool_common::INLINE_CONTENT_LIMIT) {
+            mock.expect(
+                Op::ChunkAppend,
+                serde_json::json!({
+                    "transfer_id": "t1",
+                    "chunk_b64": base64::engine::general_purpose::STANDARD.encode(chunk),
+                }),
+                serde_json::json!({"received_bytes": chunk.len()}),
+            );
+        }
+        mock.expect(
+            Op::ChunkCommit,
+            serde_json::json!({"transfer_id": "t1", "op": "write_file", "path": "/tmp/big"}),
+            serde_json::json!({"written": content.len(), "path": "/tmp/big", "existed": false}),
+        );
+        let response =
+            tool_common::with_mock_host(mock, || write_file_response(text_args("/tmp/big", &content))).unwrap();
+        assert_eq!(response, Response { written: content.len(), path: "/tmp/big".to_string(), existed: false });
+    }
+
+    #[test]
+    fn surfaces_a_host_error() {
+        let mut mock = MockHost::new();
+        mock.expect_binary_error(
+            Op::WriteFile,
+            serde_json::json!({"path": "/etc/x"}),
+            b"hi",
+            "operation_error",
+            "path '/etc/x' is not under an allowed directory",
+        );
+        let err = tool_common::with_mock_host(mock, || write_file_response(text_args("/etc/x", "hi"))).unwrap_err();
+        assert!(err.contains("not under an allowed directory"));
+    }
+
+    #[test]
+    fn args_parse_error_names_the_missing_field() {
+        let err = tool_common::parse_args_str::<Args>(r#"{"content": "hi"}"#).unwrap_err();
+        assert!(err.contains("path"), "expected the field path in: {err}");
+    }
+
+    #[test]
+    fn resolves_a_relative_path_against_the_first_allowed_root() {
+        std::env::set_var(
+            "SENTINEL_CONTEXT",
+            r#"{"allowed_paths":["/workspace"],"scratch_dir":"/workspace","request_id":"req-1","timeout_ms":30000}"#,
+        );
+        let mut mock = MockHost::new();
+        mock.expect_binary(
+            Op::WriteFile,
+            serde_json::json!({"path": "/workspace/notes.txt"}),
+            b"hi",
+            serde_json::json!({"written": 2, "path": "/workspace/notes.txt", "existed": false}),
+        );
+        let response = tool_common::with_mock_host(mock, || write_file_response(text_args("notes.txt", "hi")));
+        std::env::remove_var("SENTINEL_CONTEXT");
+        assert_eq!(
+            response.unwrap(),
+            Response { written: 2, path: "/workspace/notes.txt".to_string(), existed: false }
+        );
+    }
+
+    #[test]
+    fn requires_either_content_or_content_base64() {
+        let err = write_file_response(Args {
+            path: "/tmp/x".to_string(),
+            content: None,
+            content_base64: None,
+            mode: WriteMode::Overwrite,
+            create_dirs: true,
+        })
+        .unwrap_err();
+        assert!(err.contains("content or content_base64 is required"));
+    }
+
+    #[test]
+    fn rejects_both_content_and_content_base64_at_once() {
+        let err = write_file_response(Args {
+            path: "/tmp/x".to_string(),
+            content: Some("hi".to_string()),
+            content_base64: Some("aGk=".to_string()),
+            mode: WriteMode::Overwrite,
+            create_dirs: true,
+        })
+        .unwrap_err();
+        assert!(err.contains("only one of"));
+    }
+
+    #[test]
+    fn content_base64_decodes_before_writing() {
+        let mut mock = MockHost::new();
+        mock.expect_binary(
+            Op::WriteFile,
+            serde_json::json!({"path": "/tmp/x"}),
+            &[0xff, 0x00, 0x10],
+            serde_json::json!({"written": 3, "path": "/tmp/x", "existed": false}),
+        );
+        let response = tool_common::with_mock_host(mock, || {
+            write_file_response(Args {
+                path: "/tmp/x".to_string(),
+                content: None,
+                content_base64: Some(base64::engine::general_purpose::STANDARD.encode([0xff, 0x00, 0x10])),
+                mode: WriteMode::Overwrite,
+                create_dirs: true,
+            })
+        })
+        .unwrap();
+        assert_eq!(response, Response { written: 3, path: "/tmp/x".to_string(), existed: false });
+    }
+
+    #[test]
+    fn rejects_invalid_content_base64() {
+        let err = write_file_response(Args {
+            path: "/tmp/x".to_string(),
+            content: None,
+            content_base64: Some("not valid base64!!".to_string()),
+            mode: WriteMode::Overwrite,
+            create_dirs: true,
+        })
+        .unwrap_err();
+        assert!(err.contains("not valid base64"));
+    }
+
+    #[test]
+    fn append_mode_is_forwarded_to_the_host() {
+        let mut mock = MockHost::new();
+        mock.expect_binary(
+            Op::WriteFile,
+            serde_json::json!({"path": "/tmp/x", "mode": "append"}),
+            b"more",
+            serde_json::json!({"written": 4, "path": "/tmp/x", "existed": true}),
+        );
+        let response = tool_common::with_mock_host(mock, || {
+            write_file_response(Args { mode: WriteMode::Append, ..text_args("/tmp/x", "more") })
+        })
+        .unwrap();
+        assert_eq!(response, Response { written: 4, path: "/tmp/x".to_string(), existed: true });
+    }
+
+    #[test]
+    fn create_dirs_false_is_forwarded_to_the_host() {
+        let mut mock = MockHost::new();
+        mock.expect_binary(
+            Op::WriteFile,
+            serde_json::json!({"path": "/tmp/nested/x", "create_dirs": false}),
+            b"hi",
+            serde_json::json!({"written": 2, "path": "/tmp/nested/x", "existed": false}),
+        );
+        let response = tool_common::with_mock_host(mock, || {
+            write_file_response(Args { create_dirs: false, ..text_args("/tmp/nested/x", "hi") })
+        })
+        .unwrap();
+        assert_eq!(response, Response { written: 2, path: "/tmp/nested/x".to_string(), existed: false });
+    }
+}
@@ -0,0 +1,170 @@
+//! Library entry point for the http-download WASM tool.
+//!
+//! Splitting `http_download_response` out of `main` (synth-1190 pattern)
+//! lets it run natively against `tool_common::MockHost` instead of only
+//! being testable by compiling to wasm and running under the real sidecar.
+//!
+//! This tool is a thin wrapper around `Op::HttpFetch`'s `save_to` option
+//! (synth-1213): it asks the host to write the response body straight to
+//! `path` instead of returning it inline, then checks the host-computed
+//! sha256 against `sha256_expected` when the caller supplied one.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use tool_common::messages::HttpFetchRequest;
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "usage", derive(schemars::JsonSchema))]
+pub struct Args {
+    pub url: String,
+    /// Where to save the response body — forwarded to the host as
+    /// `HttpFetchRequest::save_to`.
+    pub path: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Optional credential to send as `Authorization: <value>`.
+    #[serde(default)]
+    pub auth_credential: Option<String>,
+    /// Reject the download once the response exceeds this many bytes.
+    /// Accepted for forward compatibility, but not enforced yet — nothing
+    /// in `http_client::fetch` caps response size below its own
+    /// `max_response_bytes` today (mirrors the `follow_redirects`/
+    /// `timeout_ms`/`retries` fields on `http-fetch`, which were also
+    /// wired through before the host acted on them).
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+    /// If given, compared (case-insensitively) against the sha256 the host
+    /// reports for the saved file; the mismatch is reported via
+    /// `Response::verified` rather than failing the call, since the file
+    /// is on disk either way and the caller may still want it.
+    #[serde(default)]
+    pub sha256_expected: Option<String>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+#[derive(Debug, serde::Serialize)]
+#[cfg_attr(feature = "usage", derive(schemars::JsonSchema))]
+pub struct Response {
+    pub status: u16,
+    pub saved_path: String,
+    pub bytes: u64,
+    pub sha256: String,
+    pub verified: bool,
+}
+
+/// Fetches `args.url` and saves the body to `args.path` via the host's
+/// `save_to` support, then checks the result against `args.sha256_expected`
+/// when given.
+pub fn http_download_response(mut args: Args) -> Result<Response, String> {
+    if let Some(name) = args.auth_credential.take() {
+        tool_common::with_credential(&name, |value| {
+            args.headers.insert("Authorization".to_string(), value.to_string());
+        })
+        .map_err(|e| format!("credential fetch failed: {e}"))?;
+    }
+
+    let request = HttpFetchRequest {
+        url: args.url,
+        method: args.method,
+        headers: args.headers,
+        body: None,
+        follow_redirects: None,
+        timeout_ms: None,
+        retries: None,
+        response_encoding: None,
+        save_to: Some(args.path),
+    };
+    let response = tool_common::http_fetch(request).map_err(|e| format!("host call failed: {e}"))?;
+
+    let saved_path = response.saved_path.ok_or("host did not report a saved_path for save_to")?;
+    let sha256 = response.sha256.ok_or("host did not report a sha256 for save_to")?;
+    let bytes = response.bytes.ok_or("host did not report a byte count for save_to")?;
+
+    let verified = match &args.sha256_expected {
+        Some(expected) => expected.eq_ignore_ascii_case(&sha256),
+        None => true,
+    };
+
+    Ok(Response { status: response.status, saved_path, bytes, sha256, verified })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tool_common::{MockHost, Op};
+
+    fn args(url: &str, path: &str) -> Args {
+        Args {
+            url: url.to_string(),
+            path: path.to_string(),
+            method: default_method(),
+            headers: HashMap::new(),
+            auth_credential: None,
+            max_bytes: None,
+            sha256_expected: None,
+        }
+    }
+
+    #[test]
+    fn saves_the_response_and_reports_the_host_computed_fields() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::HttpFetch,
+            serde_json::json!({"url": "https://example.com/a.txt", "method": "GET", "headers": {}, "body": null, "save_to": "/workspace/a.txt"}),
+            serde_json::json!({
+                "status": 200,
+                "body": "",
+                "headers": {},
+                "saved_path": "/workspace/a.txt",
+                "sha256": "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b85",
+                "bytes": 0,
+            }),
+        );
+        let response = tool_common::with_mock_host(mock, || http_download_response(args("https://example.com/a.txt", "/workspace/a.txt"))).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.saved_path, "/workspace/a.txt");
+        assert_eq!(response.bytes, 0);
+        assert_eq!(response.sha256, "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b85");
+        assert!(response.verified);
+    }
+
+    #[test]
+    fn reports_verified_false_on_a_checksum_mismatch_without_failing_the_call() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::HttpFetch,
+            serde_json::json!({"url": "https://example.com/a.txt", "method": "GET", "headers": {}, "body": null, "save_to": "/workspace/a.txt"}),
+            serde_json::json!({
+                "status": 200,
+                "body": "",
+                "headers": {},
+                "saved_path": "/workspace/a.txt",
+                "sha256": "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b85",
+                "bytes": 0,
+            }),
+        );
+        let mut a = args("https://example.com/a.txt", "/workspace/a.txt");
+        a.sha256_expected = Some("deadbeef".to_string());
+        let response = tool_common::with_mock_host(mock, || http_download_response(a)).unwrap();
+        assert!(!response.verified);
+        assert_eq!(response.saved_path, "/workspace/a.txt");
+    }
+
+    #[test]
+    fn surfaces_a_missing_write_capability_error_from_the_host() {
+        let mut mock = MockHost::new();
+        mock.expect_error(
+            Op::HttpFetch,
+            serde_json::json!({"url": "https://example.com/a.txt", "method": "GET", "headers": {}, "body": null, "save_to": "/workspace/a.txt"}),
+            "operation_error",
+            "capability denied: WriteFile",
+        );
+        let result = tool_common::with_mock_host(mock, || http_download_response(args("https://example.com/a.txt", "/workspace/a.txt")));
+        assert!(result.unwrap_err().contains("capability denied: WriteFile"));
+    }
+}
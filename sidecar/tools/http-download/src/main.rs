@@ -0,0 +1,18 @@
+//! WASM tool: fetch a URL and save the response body to the workspace.
+//!
+//! Reads JSON args from stdin: {"url": "...", "path": "...", "method": "...",
+//! "headers": {...}, "auth_credential": "...", "max_bytes": N,
+//! "sha256_expected": "..."}.
+//! Delegates to `tool_http_download::http_download_response` (synth-1213)
+//! for the actual logic — see that module's tests for native coverage
+//! against a mocked host, including the checksum-mismatch and missing
+//! write-capability cases.
+//! Writes JSON result to stdout: {"status": N, "saved_path": "...",
+//! "bytes": N, "sha256": "...", "verified": bool}
+//!
+//! `main` itself is just `tool_common::run_tool` (synth-1194), which handles
+//! args parsing, error formatting, exit codes, and panic catching.
+
+fn main() {
+    tool_common::run_tool(tool_http_download::http_download_response);
+}
@@ -0,0 +1,468 @@
+//! Library entry point for the http-fetch WASM tool.
+//!
+//! Splitting `http_fetch_response` out of `main` (synth-1190) lets it run
+//! natively against `tool_common::MockHost` instead of only being testable
+//! by compiling to wasm and running under the real sidecar.
+
+use base64::Engine;
+use serde::Deserialize;
+use std::collections::HashMap;
+use tool_common::messages::{HttpFetchRequest, HttpFetchResponse};
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "usage", derive(schemars::JsonSchema))]
+pub struct Args {
+    pub url: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Optional credential to inject into the request (synth-1207): either
+    /// a bare credential name (shorthand for header placement as
+    /// `Authorization: <value>`, the original behavior), or an object
+    /// naming where and how the value goes. See [`CredentialPlacement`].
+    #[serde(default)]
+    pub auth_credential: Option<AuthCredential>,
+    /// Follow HTTP redirects (synth-1206). Forwarded to the host as-is;
+    /// `None` defers to the host's own default.
+    #[serde(default)]
+    pub follow_redirects: Option<bool>,
+    /// Per-request timeout override in milliseconds (synth-1206).
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Number of retries on a failed request (synth-1206).
+    #[serde(default)]
+    pub retries: Option<u32>,
+    /// How the response body should come back — `"utf8"` (default) or
+    /// `"base64"` (synth-1206). See `HttpFetchResponse::body_base64`.
+    #[serde(default)]
+    pub response_encoding: Option<String>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+/// Either a bare credential name (header placement, matching the
+/// pre-synth-1207 behavior) or a full [`CredentialPlacement`] (synth-1207).
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "usage", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+pub enum AuthCredential {
+    Name(String),
+    Placement(CredentialPlacement),
+}
+
+impl AuthCredential {
+    fn into_placement(self) -> CredentialPlacement {
+        match self {
+            AuthCredential::Name(name) => CredentialPlacement { name, ..Default::default() },
+            AuthCredential::Placement(placement) => placement,
+        }
+    }
+}
+
+/// Where and how a resolved credential value is added to the request
+/// (synth-1207) — the tool does the placement itself today; forwarding it
+/// to the host to do instead (so the raw value never enters guest memory)
+/// is future work once the host grows that capability.
+#[derive(Debug, Default, Deserialize)]
+#[cfg_attr(feature = "usage", derive(schemars::JsonSchema))]
+pub struct CredentialPlacement {
+    pub name: String,
+    #[serde(default)]
+    pub placement: Placement,
+    /// Header name for `placement: "header"` or `"basic"`. Defaults to
+    /// `Authorization`.
+    #[serde(default)]
+    pub header_name: Option<String>,
+    /// Query parameter name for `placement: "query"`. Defaults to
+    /// `api_key`.
+    #[serde(default)]
+    pub query_param: Option<String>,
+    /// A template for `placement: "header"` or `"basic"`, with `{value}`
+    /// replaced by the credential value — e.g. `"Bearer {value}"` for an
+    /// API that wants a scheme prefix. Defaults to `"{value}"` (the raw
+    /// value, unchanged). Not used for `"query"`, where the value is
+    /// percent-encoded and used as-is.
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[cfg_attr(feature = "usage", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Placement {
+    #[default]
+    Header,
+    Query,
+    Basic,
+}
+
+/// Applies a resolved credential `value` to `url`/`headers` per `placement`
+/// (synth-1207). Called from inside `with_credential`'s closure, so `value`
+/// never outlives this call.
+fn apply_credential(placement: &CredentialPlacement, value: &str, url: &mut String, headers: &mut HashMap<String, String>) {
+    let rendered = match &placement.template {
+        Some(template) => template.replace("{value}", value),
+        None => value.to_string(),
+    };
+    match placement.placement {
+        Placement::Header => {
+            let header_name = placement.header_name.clone().unwrap_or_else(|| "Authorization".to_string());
+            headers.insert(header_name, rendered);
+        }
+        Placement::Basic => {
+            let header_name = placement.header_name.clone().unwrap_or_else(|| "Authorization".to_string());
+            let encoded = base64::engine::general_purpose::STANDARD.encode(rendered);
+            headers.insert(header_name, format!("Basic {encoded}"));
+        }
+        Placement::Query => {
+            let query_param = placement.query_param.clone().unwrap_or_else(|| "api_key".to_string());
+            let separator = if url.contains('?') { '&' } else { '?' };
+            url.push(separator);
+            url.push_str(&query_param);
+            url.push('=');
+            url.push_str(&percent_encode_query_value(&rendered));
+        }
+    }
+}
+
+/// Percent-encodes `value` for safe inclusion in a URL query string
+/// (synth-1207): unreserved characters (letters, digits, `-`, `_`, `.`,
+/// `~`) pass through unchanged, everything else becomes a `%XX` escape of
+/// its UTF-8 bytes.
+fn percent_encode_query_value(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// If `args.auth_credential` is set, resolves it via `tool_common::with_credential`
+/// and adds it to `headers` as `Authorization`, then runs the fetch via
+/// `tool_common::http_fetch`. Using `with_credential` (synth-1199) instead of
+/// `get_credential` means the fetched value never sits in a local `String`
+/// of this tool's own past what's needed to copy it into `headers` — it's
+/// zeroized the moment the closure returns.
+///
+/// P-002: No format validation on the credential value (e.g. "Bearer "
+/// prefix) because the credential store is host-controlled, not
+/// guest-supplied. A compromised store would be a larger issue than header
+/// format validation can solve.
+///
+/// `follow_redirects`/`timeout_ms`/`retries`/`response_encoding` (synth-1206)
+/// pass straight through to the host — this tool just forwards whatever the
+/// caller asked for and returns whatever the host sent back, including the
+/// new `final_url`/`attempts`/`body_base64` fields on the response.
+pub fn http_fetch_response(mut args: Args) -> Result<HttpFetchResponse, String> {
+    let mut url = args.url;
+    if let Some(cred) = args.auth_credential.take() {
+        let placement = cred.into_placement();
+        tool_common::with_credential(&placement.name, |value| {
+            apply_credential(&placement, value, &mut url, &mut args.headers)
+        })
+        .map_err(|e| format!("credential fetch failed: {e}"))?;
+    }
+
+    // synth-1195: a stub progress report ahead of the actual request — a
+    // real chunked-download tool would report again as bytes arrive, but
+    // http_fetch reads its whole response in one host call, so there's only
+    // one meaningful point to report before the fetch itself resolves.
+    tool_common::report_progress(&format!("fetching {url}"), None)
+        .map_err(|e| format!("progress report failed: {e}"))?;
+
+    let request = HttpFetchRequest {
+        url,
+        method: args.method,
+        headers: args.headers,
+        body: args.body,
+        follow_redirects: args.follow_redirects,
+        timeout_ms: args.timeout_ms,
+        retries: args.retries,
+        response_encoding: args.response_encoding,
+        save_to: None,
+    };
+    tool_common::http_fetch(request).map_err(|e| format!("host call failed: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tool_common::{MockHost, Op};
+
+    fn args(url: &str) -> Args {
+        Args {
+            url: url.to_string(),
+            method: default_method(),
+            headers: HashMap::new(),
+            body: None,
+            auth_credential: None,
+            follow_redirects: None,
+            timeout_ms: None,
+            retries: None,
+            response_encoding: None,
+        }
+    }
+
+    #[test]
+    fn fetches_without_a_credential() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::Progress,
+            serde_json::json!({"message": "fetching https://example.com", "percent": null, "data": null}),
+            serde_json::json!({}),
+        );
+        mock.expect(
+            Op::HttpFetch,
+            serde_json::json!({"url": "https://example.com", "method": "GET", "headers": {}, "body": null}),
+            serde_json::json!({"status": 200, "body": "ok", "headers": {}}),
+        );
+        let response =
+            tool_common::with_mock_host(mock, || http_fetch_response(args("https://example.com"))).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, "ok");
+    }
+
+    #[test]
+    fn forwards_redirect_timeout_retry_and_encoding_args_and_returns_the_new_response_fields() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::Progress,
+            serde_json::json!({"message": "fetching https://example.com", "percent": null, "data": null}),
+            serde_json::json!({}),
+        );
+        mock.expect(
+            Op::HttpFetch,
+            serde_json::json!({
+                "url": "https://example.com",
+                "method": "GET",
+                "headers": {},
+                "body": null,
+                "follow_redirects": false,
+                "timeout_ms": 5000,
+                "retries": 2,
+                "response_encoding": "base64",
+            }),
+            serde_json::json!({
+                "status": 200,
+                "body": "",
+                "headers": {},
+                "final_url": "https://example.com/",
+                "attempts": 2,
+                "body_base64": "aGVsbG8=",
+            }),
+        );
+        let response = tool_common::with_mock_host(mock, || {
+            http_fetch_response(Args {
+                follow_redirects: Some(false),
+                timeout_ms: Some(5_000),
+                retries: Some(2),
+                response_encoding: Some("base64".to_string()),
+                ..args("https://example.com")
+            })
+        })
+        .unwrap();
+        assert_eq!(response.final_url, Some("https://example.com/".to_string()));
+        assert_eq!(response.attempts, Some(2));
+        assert_eq!(response.body_base64, Some("aGVsbG8=".to_string()));
+    }
+
+    #[test]
+    fn injects_a_resolved_credential_as_an_authorization_header() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::GetCredential,
+            serde_json::json!({"name": "api_key"}),
+            serde_json::json!({"name": "api_key", "value": "secret"}),
+        );
+        mock.expect(
+            Op::Progress,
+            serde_json::json!({"message": "fetching https://example.com", "percent": null, "data": null}),
+            serde_json::json!({}),
+        );
+        mock.expect(
+            Op::HttpFetch,
+            serde_json::json!({
+                "url": "https://example.com",
+                "method": "GET",
+                "headers": {"Authorization": "secret"},
+                "body": null,
+            }),
+            serde_json::json!({"status": 200, "body": "ok", "headers": {}}),
+        );
+        let response = tool_common::with_mock_host(mock, || {
+            http_fetch_response(Args {
+                auth_credential: Some(AuthCredential::Name("api_key".to_string())),
+                ..args("https://example.com")
+            })
+        })
+        .unwrap();
+        assert_eq!(response.status, 200);
+    }
+
+    #[test]
+    fn header_placement_with_a_custom_name_and_template() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::GetCredential,
+            serde_json::json!({"name": "api_key"}),
+            serde_json::json!({"name": "api_key", "value": "secret"}),
+        );
+        mock.expect(
+            Op::Progress,
+            serde_json::json!({"message": "fetching https://example.com", "percent": null, "data": null}),
+            serde_json::json!({}),
+        );
+        mock.expect(
+            Op::HttpFetch,
+            serde_json::json!({
+                "url": "https://example.com",
+                "method": "GET",
+                "headers": {"X-Api-Key": "Bearer secret"},
+                "body": null,
+            }),
+            serde_json::json!({"status": 200, "body": "ok", "headers": {}}),
+        );
+        let response = tool_common::with_mock_host(mock, || {
+            http_fetch_response(Args {
+                auth_credential: Some(AuthCredential::Placement(CredentialPlacement {
+                    name: "api_key".to_string(),
+                    placement: Placement::Header,
+                    header_name: Some("X-Api-Key".to_string()),
+                    query_param: None,
+                    template: Some("Bearer {value}".to_string()),
+                })),
+                ..args("https://example.com")
+            })
+        })
+        .unwrap();
+        assert_eq!(response.status, 200);
+        let output = serde_json::to_string(&response).unwrap();
+        assert!(!output.contains("secret"), "raw credential value must not reach the tool's stdout: {output}");
+    }
+
+    #[test]
+    fn basic_placement_base64_encodes_the_templated_value() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::GetCredential,
+            serde_json::json!({"name": "creds"}),
+            serde_json::json!({"name": "creds", "value": "s3cret"}),
+        );
+        mock.expect(
+            Op::Progress,
+            serde_json::json!({"message": "fetching https://example.com", "percent": null, "data": null}),
+            serde_json::json!({}),
+        );
+        let expected = base64::engine::general_purpose::STANDARD.encode("admin:s3cret");
+        mock.expect(
+            Op::HttpFetch,
+            serde_json::json!({
+                "url": "https://example.com",
+                "method": "GET",
+                "headers": {"Authorization": format!("Basic {expected}")},
+                "body": null,
+            }),
+            serde_json::json!({"status": 200, "body": "ok", "headers": {}}),
+        );
+        let response = tool_common::with_mock_host(mock, || {
+            http_fetch_response(Args {
+                auth_credential: Some(AuthCredential::Placement(CredentialPlacement {
+                    name: "creds".to_string(),
+                    placement: Placement::Basic,
+                    header_name: None,
+                    query_param: None,
+                    template: Some("admin:{value}".to_string()),
+                })),
+                ..args("https://example.com")
+            })
+        })
+        .unwrap();
+        assert_eq!(response.status, 200);
+        let output = serde_json::to_string(&response).unwrap();
+        assert!(!output.contains("s3cret"), "raw credential value must not reach the tool's stdout: {output}");
+    }
+
+    #[test]
+    fn query_placement_percent_encodes_the_value_and_appends_to_an_existing_query_string() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::GetCredential,
+            serde_json::json!({"name": "api_key"}),
+            serde_json::json!({"name": "api_key", "value": "a b&c"}),
+        );
+        mock.expect(
+            Op::Progress,
+            serde_json::json!({
+                "message": "fetching https://example.com/search?q=rust&key=a%20b%26c",
+                "percent": null,
+                "data": null,
+            }),
+            serde_json::json!({}),
+        );
+        mock.expect(
+            Op::HttpFetch,
+            serde_json::json!({
+                "url": "https://example.com/search?q=rust&key=a%20b%26c",
+                "method": "GET",
+                "headers": {},
+                "body": null,
+            }),
+            serde_json::json!({"status": 200, "body": "ok", "headers": {}}),
+        );
+        let response = tool_common::with_mock_host(mock, || {
+            http_fetch_response(Args {
+                auth_credential: Some(AuthCredential::Placement(CredentialPlacement {
+                    name: "api_key".to_string(),
+                    placement: Placement::Query,
+                    header_name: None,
+                    query_param: Some("key".to_string()),
+                    template: None,
+                })),
+                ..args("https://example.com/search?q=rust")
+            })
+        })
+        .unwrap();
+        assert_eq!(response.status, 200);
+    }
+
+    #[test]
+    fn auth_credential_accepts_a_bare_string_as_shorthand_for_header_placement() {
+        let cred: AuthCredential = serde_json::from_str(r#""api_key""#).unwrap();
+        let placement = cred.into_placement();
+        assert_eq!(placement.name, "api_key");
+        assert_eq!(placement.placement, Placement::Header);
+    }
+
+    #[test]
+    fn surfaces_a_credential_fetch_failure_without_calling_http_fetch() {
+        let mut mock = MockHost::new();
+        mock.expect_error(
+            Op::GetCredential,
+            serde_json::json!({"name": "missing"}),
+            "operation_error",
+            "no credential named 'missing'",
+        );
+        let err = tool_common::with_mock_host(mock, || {
+            http_fetch_response(Args {
+                auth_credential: Some(AuthCredential::Name("missing".to_string())),
+                ..args("https://example.com")
+            })
+        })
+        .unwrap_err();
+        assert!(err.contains("credential fetch failed"));
+    }
+
+    #[test]
+    fn args_parse_error_names_the_missing_field() {
+        let err = tool_common::parse_args_str::<Args>("{}").unwrap_err();
+        assert!(err.contains("url"), "expected the field path in: {err}");
+    }
+}
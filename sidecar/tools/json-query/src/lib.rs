@@ -0,0 +1,502 @@
+//! Library entry point for the json-query WASM tool.
+//!
+//! Splitting `json_query_response` out of `main` (synth-1190 convention) lets
+//! it run as a plain native unit test instead of only being testable by
+//! compiling to wasm. Unlike every other tool in this workspace, this one
+//! makes no host calls at all (synth-1212) — it just parses `args.query`
+//! into a small pipeline and evaluates it against `args.json`/`args.json_string`
+//! in-process, so its `tool.toml` declares zero capabilities.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "usage", derive(schemars::JsonSchema))]
+pub struct Args {
+    /// The document to query, given inline. Mutually exclusive with
+    /// `json_string`; exactly one is required.
+    #[serde(default)]
+    pub json: Option<Value>,
+    /// The document to query, given as a JSON-encoded string — for a caller
+    /// that already has it serialized and would rather not pay to
+    /// re-parse-then-re-serialize it as an inline `json` value.
+    #[serde(default)]
+    pub json_string: Option<String>,
+    /// A jq-like query: dotted field access, `[N]` indexing, `[]`
+    /// iteration, `select(cond)`, and `|` to pipe stages together, e.g.
+    /// `.items[] | select(.active) | .name`.
+    pub query: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Response {
+    pub results: Vec<Value>,
+    pub count: usize,
+}
+
+/// Parses `args.query` and evaluates it against `args.json`/`args.json_string`.
+pub fn json_query_response(args: Args) -> Result<Response, String> {
+    let document = match (args.json, args.json_string) {
+        (Some(_), Some(_)) => return Err("only one of json or json_string may be given".to_string()),
+        (None, None) => return Err("json or json_string is required".to_string()),
+        (Some(value), None) => value,
+        (None, Some(text)) => {
+            serde_json::from_str(&text).map_err(|e| format!("json_string is not valid JSON: {e}"))?
+        }
+    };
+
+    let pipeline = query::parse(&args.query)?;
+    let results = query::eval(&pipeline, document)?;
+    let count = results.len();
+    Ok(Response { results, count })
+}
+
+/// A small jq subset: field access, array indexing/iteration, `select` with
+/// simple comparisons, and `|` to pipe stages together (synth-1212).
+mod query {
+    use serde_json::Value;
+
+    #[derive(Debug, PartialEq)]
+    pub enum Segment {
+        Field(String),
+        Index(i64),
+        Iterate,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum CmpOp {
+        Eq,
+        Ne,
+        Lt,
+        Le,
+        Gt,
+        Ge,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum Literal {
+        Str(String),
+        Num(f64),
+        Bool(bool),
+        Null,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum Cond {
+        Truthy(Vec<Segment>),
+        Compare(Vec<Segment>, CmpOp, Literal),
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum Stage {
+        Identity,
+        Path(Vec<Segment>),
+        Select(Cond),
+    }
+
+    pub type Pipeline = Vec<Stage>;
+
+    /// Splits `query` into `|`-separated stages (ignoring `|` inside `(...)`
+    /// or `[...]`), then parses each one, reporting the failing segment's own
+    /// text on error rather than an offset into the whole query — every
+    /// stage is short enough that quoting it back is more useful than a
+    /// column number.
+    pub fn parse(query: &str) -> Result<Pipeline, String> {
+        split_top_level(query, '|')
+            .into_iter()
+            .map(|segment| parse_stage(segment.trim()))
+            .collect()
+    }
+
+    fn split_top_level(input: &str, sep: char) -> Vec<&str> {
+        let mut parts = Vec::new();
+        let mut depth = 0i32;
+        let mut start = 0usize;
+        for (i, c) in input.char_indices() {
+            match c {
+                '(' | '[' => depth += 1,
+                ')' | ']' => depth -= 1,
+                c if c == sep && depth == 0 => {
+                    parts.push(&input[start..i]);
+                    start = i + c.len_utf8();
+                }
+                _ => {}
+            }
+        }
+        parts.push(&input[start..]);
+        parts
+    }
+
+    fn parse_stage(stage: &str) -> Result<Stage, String> {
+        if stage.is_empty() {
+            return Err("empty query segment".to_string());
+        }
+        if stage == "." {
+            return Ok(Stage::Identity);
+        }
+        if let Some(inner) = stage.strip_prefix("select(").and_then(|s| s.strip_suffix(')')) {
+            return Ok(Stage::Select(parse_cond(inner)?));
+        }
+        Ok(Stage::Path(parse_path(stage)?))
+    }
+
+    fn parse_cond(cond: &str) -> Result<Cond, String> {
+        let cond = cond.trim();
+        for op in [" == ", " != ", " <= ", " >= ", " < ", " > "] {
+            if let Some((lhs, rhs)) = cond.split_once(op) {
+                let segments = parse_path(lhs.trim())?;
+                let literal = parse_literal(rhs.trim())
+                    .ok_or_else(|| format!("invalid comparison value in 'select({cond})': {rhs}"))?;
+                let cmp = match op.trim() {
+                    "==" => CmpOp::Eq,
+                    "!=" => CmpOp::Ne,
+                    "<" => CmpOp::Lt,
+                    "<=" => CmpOp::Le,
+                    ">" => CmpOp::Gt,
+                    ">=" => CmpOp::Ge,
+                    _ => unreachable!(),
+                };
+                return Ok(Cond::Compare(segments, cmp, literal));
+            }
+        }
+        Ok(Cond::Truthy(parse_path(cond)?))
+    }
+
+    fn parse_literal(text: &str) -> Option<Literal> {
+        if let Some(inner) = text.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+            return Some(Literal::Str(inner.to_string()));
+        }
+        match text {
+            "true" => return Some(Literal::Bool(true)),
+            "false" => return Some(Literal::Bool(false)),
+            "null" => return Some(Literal::Null),
+            _ => {}
+        }
+        text.parse::<f64>().ok().map(Literal::Num)
+    }
+
+    /// Parses a single path expression like `.items[0].name[]`. Every path
+    /// must start with `.`, mirroring jq.
+    fn parse_path(path: &str) -> Result<Vec<Segment>, String> {
+        let bytes = path.as_bytes();
+        if bytes.first() != Some(&b'.') {
+            return Err(format!("expected a path starting with '.', got: '{path}'"));
+        }
+        let mut segments = Vec::new();
+        let mut i = 1usize;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'.' => {
+                    i += 1;
+                }
+                b'[' => {
+                    let close = path[i..]
+                        .find(']')
+                        .map(|offset| i + offset)
+                        .ok_or_else(|| format!("unterminated '[' in path segment: '{path}'"))?;
+                    let inner = &path[i + 1..close];
+                    if inner.is_empty() {
+                        segments.push(Segment::Iterate);
+                    } else {
+                        let index = inner
+                            .parse::<i64>()
+                            .map_err(|_| format!("invalid array index in path segment: '{path}[{inner}]'"))?;
+                        segments.push(Segment::Index(index));
+                    }
+                    i = close + 1;
+                }
+                _ => {
+                    let start = i;
+                    while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                        i += 1;
+                    }
+                    if i == start {
+                        return Err(format!("invalid character '{}' in path segment: '{path}'", bytes[i] as char));
+                    }
+                    segments.push(Segment::Field(path[start..i].to_string()));
+                }
+            }
+        }
+        Ok(segments)
+    }
+
+    pub fn eval(pipeline: &Pipeline, root: Value) -> Result<Vec<Value>, String> {
+        let mut values = vec![root];
+        for stage in pipeline {
+            let mut next = Vec::new();
+            for value in values {
+                next.extend(eval_stage(stage, value)?);
+            }
+            values = next;
+        }
+        Ok(values)
+    }
+
+    fn eval_stage(stage: &Stage, value: Value) -> Result<Vec<Value>, String> {
+        match stage {
+            Stage::Identity => Ok(vec![value]),
+            Stage::Path(segments) => eval_path(segments, value),
+            Stage::Select(cond) => {
+                if eval_cond(cond, &value)? {
+                    Ok(vec![value])
+                } else {
+                    Ok(vec![])
+                }
+            }
+        }
+    }
+
+    fn eval_path(segments: &[Segment], value: Value) -> Result<Vec<Value>, String> {
+        let Some((segment, rest)) = segments.split_first() else {
+            return Ok(vec![value]);
+        };
+        let heads = eval_segment(segment, value)?;
+        let mut results = Vec::new();
+        for head in heads {
+            results.extend(eval_path(rest, head)?);
+        }
+        Ok(results)
+    }
+
+    fn eval_segment(segment: &Segment, value: Value) -> Result<Vec<Value>, String> {
+        match segment {
+            Segment::Field(name) => match value {
+                Value::Null => Ok(vec![Value::Null]),
+                Value::Object(mut map) => Ok(vec![map.remove(name).unwrap_or(Value::Null)]),
+                other => Err(format!("cannot index {} with field '{name}'", type_name(&other))),
+            },
+            Segment::Index(index) => match value {
+                Value::Null => Ok(vec![Value::Null]),
+                Value::Array(mut items) => {
+                    let len = items.len() as i64;
+                    let resolved = if *index < 0 { index + len } else { *index };
+                    if resolved < 0 || resolved >= len {
+                        Ok(vec![Value::Null])
+                    } else {
+                        Ok(vec![items.swap_remove(resolved as usize)])
+                    }
+                }
+                other => Err(format!("cannot index {} with number", type_name(&other))),
+            },
+            Segment::Iterate => match value {
+                Value::Array(items) => Ok(items),
+                Value::Object(map) => Ok(map.into_values().collect()),
+                other => Err(format!("cannot iterate over {}", type_name(&other))),
+            },
+        }
+    }
+
+    fn eval_cond(cond: &Cond, value: &Value) -> Result<bool, String> {
+        match cond {
+            Cond::Truthy(segments) => {
+                let heads = eval_path(segments, value.clone())?;
+                Ok(heads.into_iter().all(|v| is_truthy(&v)))
+            }
+            Cond::Compare(segments, op, literal) => {
+                let heads = eval_path(segments, value.clone())?;
+                heads.into_iter().try_fold(true, |acc, head| Ok(acc && compare(&head, op, literal)?))
+            }
+        }
+    }
+
+    fn is_truthy(value: &Value) -> bool {
+        !matches!(value, Value::Null | Value::Bool(false))
+    }
+
+    fn compare(value: &Value, op: &CmpOp, literal: &Literal) -> Result<bool, String> {
+        match (value, literal) {
+            (Value::Null, Literal::Null) => Ok(matches!(op, CmpOp::Eq | CmpOp::Le | CmpOp::Ge)),
+            (Value::Bool(a), Literal::Bool(b)) => compare_ord(a, b, op),
+            (Value::String(a), Literal::Str(b)) => compare_ord(&a.as_str(), &b.as_str(), op),
+            (Value::Number(a), Literal::Num(b)) => {
+                let a = a.as_f64().ok_or_else(|| "number is not representable as f64".to_string())?;
+                compare_ord(&a, b, op)
+            }
+            _ => match op {
+                CmpOp::Eq => Ok(false),
+                CmpOp::Ne => Ok(true),
+                _ => Err(format!("cannot order-compare {} and the given literal", type_name(value))),
+            },
+        }
+    }
+
+    fn compare_ord<T: PartialOrd>(a: &T, b: &T, op: &CmpOp) -> Result<bool, String> {
+        Ok(match op {
+            CmpOp::Eq => a == b,
+            CmpOp::Ne => a != b,
+            CmpOp::Lt => a < b,
+            CmpOp::Le => a <= b,
+            CmpOp::Gt => a > b,
+            CmpOp::Ge => a >= b,
+        })
+    }
+
+    fn type_name(value: &Value) -> &'static str {
+        match value {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(json: Value, query: &str) -> Result<Response, String> {
+        json_query_response(Args { json: Some(json), json_string: None, query: query.to_string() })
+    }
+
+    #[test]
+    fn identity_returns_the_whole_document() {
+        let response = query(serde_json::json!({"a": 1}), ".").unwrap();
+        assert_eq!(response, Response { results: vec![serde_json::json!({"a": 1})], count: 1 });
+    }
+
+    #[test]
+    fn field_access_extracts_a_top_level_field() {
+        let response = query(serde_json::json!({"name": "ada"}), ".name").unwrap();
+        assert_eq!(response, Response { results: vec![Value::String("ada".to_string())], count: 1 });
+    }
+
+    #[test]
+    fn field_access_on_a_missing_field_returns_null() {
+        let response = query(serde_json::json!({"name": "ada"}), ".missing").unwrap();
+        assert_eq!(response, Response { results: vec![Value::Null], count: 1 });
+    }
+
+    #[test]
+    fn chained_field_access_walks_nested_objects() {
+        let response = query(serde_json::json!({"a": {"b": {"c": 42}}}), ".a.b.c").unwrap();
+        assert_eq!(response, Response { results: vec![serde_json::json!(42)], count: 1 });
+    }
+
+    #[test]
+    fn array_indexing_returns_the_given_element() {
+        let response = query(serde_json::json!({"items": ["x", "y", "z"]}), ".items[1]").unwrap();
+        assert_eq!(response, Response { results: vec![Value::String("y".to_string())], count: 1 });
+    }
+
+    #[test]
+    fn negative_array_index_counts_from_the_end() {
+        let response = query(serde_json::json!({"items": ["x", "y", "z"]}), ".items[-1]").unwrap();
+        assert_eq!(response, Response { results: vec![Value::String("z".to_string())], count: 1 });
+    }
+
+    #[test]
+    fn out_of_range_index_returns_null() {
+        let response = query(serde_json::json!({"items": ["x"]}), ".items[5]").unwrap();
+        assert_eq!(response, Response { results: vec![Value::Null], count: 1 });
+    }
+
+    #[test]
+    fn array_iteration_fans_out_into_multiple_results() {
+        let response = query(serde_json::json!({"items": [1, 2, 3]}), ".items[]").unwrap();
+        assert_eq!(response, Response { results: vec![serde_json::json!(1), serde_json::json!(2), serde_json::json!(3)], count: 3 });
+    }
+
+    #[test]
+    fn object_iteration_yields_the_values() {
+        let response = query(serde_json::json!({"a": 1, "b": 2}), ".[]").unwrap();
+        assert_eq!(response.count, 2);
+    }
+
+    #[test]
+    fn select_keeps_only_truthy_matches() {
+        let response = query(
+            serde_json::json!({"items": [{"active": true, "name": "a"}, {"active": false, "name": "b"}]}),
+            ".items[] | select(.active) | .name",
+        )
+        .unwrap();
+        assert_eq!(response, Response { results: vec![Value::String("a".to_string())], count: 1 });
+    }
+
+    #[test]
+    fn select_with_equality_comparison() {
+        let response = query(
+            serde_json::json!({"items": [{"kind": "a"}, {"kind": "b"}]}),
+            r#".items[] | select(.kind == "b")"#,
+        )
+        .unwrap();
+        assert_eq!(response.count, 1);
+        assert_eq!(response.results[0], serde_json::json!({"kind": "b"}));
+    }
+
+    #[test]
+    fn select_with_numeric_comparison() {
+        let response = query(
+            serde_json::json!({"items": [{"count": 1}, {"count": 5}, {"count": 10}]}),
+            ".items[] | select(.count > 3)",
+        )
+        .unwrap();
+        assert_eq!(response.count, 2);
+    }
+
+    #[test]
+    fn json_string_is_parsed_before_querying() {
+        let response = json_query_response(Args {
+            json: None,
+            json_string: Some(r#"{"a": 1}"#.to_string()),
+            query: ".a".to_string(),
+        })
+        .unwrap();
+        assert_eq!(response, Response { results: vec![serde_json::json!(1)], count: 1 });
+    }
+
+    #[test]
+    fn requires_either_json_or_json_string() {
+        let err =
+            json_query_response(Args { json: None, json_string: None, query: ".".to_string() }).unwrap_err();
+        assert!(err.contains("json or json_string is required"));
+    }
+
+    #[test]
+    fn rejects_both_json_and_json_string_at_once() {
+        let err = json_query_response(Args {
+            json: Some(serde_json::json!({})),
+            json_string: Some("{}".to_string()),
+            query: ".".to_string(),
+        })
+        .unwrap_err();
+        assert!(err.contains("only one of"));
+    }
+
+    #[test]
+    fn rejects_invalid_json_string() {
+        let err = json_query_response(Args {
+            json: None,
+            json_string: Some("not json".to_string()),
+            query: ".".to_string(),
+        })
+        .unwrap_err();
+        assert!(err.contains("not valid JSON"));
+    }
+
+    #[test]
+    fn error_names_the_failing_segment_for_a_bad_path() {
+        let err = query(serde_json::json!({}), "items[]").unwrap_err();
+        assert!(err.contains("items[]"), "expected the offending segment in: {err}");
+    }
+
+    #[test]
+    fn error_names_the_failing_segment_for_an_unterminated_bracket() {
+        let err = query(serde_json::json!({}), ".items[0").unwrap_err();
+        assert!(err.contains(".items[0"), "expected the offending segment in: {err}");
+    }
+
+    #[test]
+    fn field_access_on_a_non_object_is_an_error() {
+        let err = query(serde_json::json!([1, 2]), ".name").unwrap_err();
+        assert!(err.contains("cannot index array"));
+    }
+
+    #[test]
+    fn args_parse_error_names_the_missing_field() {
+        let err = tool_common::parse_args_str::<Args>(r#"{"json": {}}"#).unwrap_err();
+        assert!(err.contains("query"), "expected the field query in: {err}");
+    }
+}
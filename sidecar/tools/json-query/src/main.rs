@@ -0,0 +1,17 @@
+//! WASM tool: extract values from a JSON document with a jq-like query.
+//!
+//! Reads JSON args from stdin: {"json": {...}, "query": "..."} or
+//! {"json_string": "...", "query": "..."}.
+//! Delegates to `tool_json_query::json_query_response` (synth-1212) for the
+//! actual logic — see that module's tests for native coverage of the
+//! supported query subset. Unlike every other tool in this workspace, this
+//! one makes no host calls at all, so its `tool.toml` grants zero
+//! capabilities.
+//! Writes JSON result to stdout: {"results": [...], "count": N}
+//!
+//! `main` itself is just `tool_common::run_tool` (synth-1194), which handles
+//! args parsing, error formatting, exit codes, and panic catching.
+
+fn main() {
+    tool_common::run_tool(tool_json_query::json_query_response);
+}
@@ -0,0 +1,171 @@
+//! Library entry point for the shell-exec WASM tool.
+//!
+//! Splitting `shell_exec_response` out of `main` (synth-1190) lets it run
+//! natively against `tool_common::MockHost` instead of only being testable
+//! by compiling to wasm and running under the real sidecar.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use tool_common::messages::{ShellExecRequest, ShellExecResponse};
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "usage", derive(schemars::JsonSchema))]
+pub struct Args {
+    /// Shell command string, run via `sh -c` (or split into argv if the host
+    /// disallows a shell). Mutually exclusive with `program` (synth-1211);
+    /// exactly one of the two is required.
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Argv-mode program name, run directly with no shell involved
+    /// (synth-1211). Mutually exclusive with `command`.
+    #[serde(default)]
+    pub program: Option<String>,
+    /// Arguments passed to `program` (synth-1211). Ignored in `command` mode.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Working directory override (synth-1211).
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Extra environment variables set on the child (synth-1211).
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Bytes written to the child's stdin before it's asked to exit
+    /// (synth-1211).
+    #[serde(default)]
+    pub stdin: Option<String>,
+    /// Per-request timeout override in milliseconds (synth-1211).
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Runs `args.command` (or `args.program`/`args.args` in argv mode) via
+/// `tool_common::shell_exec`.
+pub fn shell_exec_response(args: Args) -> Result<ShellExecResponse, String> {
+    match (&args.command, &args.program) {
+        (Some(_), Some(_)) => return Err("only one of command or program may be given".to_string()),
+        (None, None) => return Err("command or program is required".to_string()),
+        _ => {}
+    }
+    tool_common::shell_exec(ShellExecRequest {
+        command: args.command,
+        program: args.program,
+        args: args.args,
+        cwd: args.cwd,
+        env: args.env,
+        stdin: args.stdin,
+        timeout_ms: args.timeout_ms,
+    })
+    .map_err(|e| format!("host call failed: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tool_common::{MockHost, Op};
+
+    fn command_args(command: &str) -> Args {
+        Args {
+            command: Some(command.to_string()),
+            program: None,
+            args: Vec::new(),
+            cwd: None,
+            env: HashMap::new(),
+            stdin: None,
+            timeout_ms: None,
+        }
+    }
+
+    #[test]
+    fn runs_the_given_command() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::ShellExec,
+            serde_json::json!({"command": "echo hi"}),
+            serde_json::json!({"stdout": "hi\n", "stderr": "", "exit_code": 0, "timed_out": false, "truncated": false}),
+        );
+        let response = tool_common::with_mock_host(mock, || shell_exec_response(command_args("echo hi"))).unwrap();
+        assert_eq!(response.stdout, "hi\n");
+        assert_eq!(response.exit_code, 0);
+        assert!(!response.timed_out);
+        assert!(!response.truncated);
+    }
+
+    #[test]
+    fn runs_argv_mode_with_cwd_env_stdin_and_timeout() {
+        let mut mock = MockHost::new();
+        mock.expect(
+            Op::ShellExec,
+            serde_json::json!({
+                "program": "cat",
+                "cwd": "/tmp",
+                "env": {"FOO": "bar"},
+                "stdin": "hello",
+                "timeout_ms": 5000,
+            }),
+            serde_json::json!({"stdout": "hello", "stderr": "", "exit_code": 0, "timed_out": false, "truncated": false}),
+        );
+        let response = tool_common::with_mock_host(mock, || {
+            shell_exec_response(Args {
+                command: None,
+                program: Some("cat".to_string()),
+                args: Vec::new(),
+                cwd: Some("/tmp".to_string()),
+                env: HashMap::from([("FOO".to_string(), "bar".to_string())]),
+                stdin: Some("hello".to_string()),
+                timeout_ms: Some(5000),
+            })
+        })
+        .unwrap();
+        assert_eq!(response.stdout, "hello");
+    }
+
+    #[test]
+    fn surfaces_a_host_error() {
+        let mut mock = MockHost::new();
+        mock.expect_error(
+            Op::ShellExec,
+            serde_json::json!({"command": "rm -rf /"}),
+            "operation_error",
+            "shell command 'rm' is on the shell denylist",
+        );
+        let err = tool_common::with_mock_host(mock, || shell_exec_response(command_args("rm -rf /"))).unwrap_err();
+        assert!(err.contains("denylist"));
+    }
+
+    #[test]
+    fn requires_either_command_or_program() {
+        let err = shell_exec_response(Args {
+            command: None,
+            program: None,
+            args: Vec::new(),
+            cwd: None,
+            env: HashMap::new(),
+            stdin: None,
+            timeout_ms: None,
+        })
+        .unwrap_err();
+        assert!(err.contains("command or program is required"));
+    }
+
+    #[test]
+    fn rejects_both_command_and_program_at_once() {
+        let err = shell_exec_response(Args {
+            command: Some("echo hi".to_string()),
+            program: Some("echo".to_string()),
+            args: Vec::new(),
+            cwd: None,
+            env: HashMap::new(),
+            stdin: None,
+            timeout_ms: None,
+        })
+        .unwrap_err();
+        assert!(err.contains("only one of"));
+    }
+
+    #[test]
+    fn args_parse_error_names_the_field_with_the_wrong_type() {
+        let err = tool_common::parse_args_str::<Args>(r#"{"program": 5}"#).unwrap_err();
+        assert!(err.contains("program"), "expected the field program in: {err}");
+    }
+}